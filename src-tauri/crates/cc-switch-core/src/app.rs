@@ -0,0 +1,42 @@
+//! 应用类型与 live 配置写入方式
+
+use serde::{Deserialize, Serialize};
+
+/// 应用类型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppType {
+    Claude,
+    Codex,
+}
+
+impl AppType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AppType::Claude => "claude",
+            AppType::Codex => "codex",
+        }
+    }
+}
+
+impl From<&str> for AppType {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "codex" => AppType::Codex,
+            _ => AppType::Claude, // 默认为 Claude
+        }
+    }
+}
+
+/// 切换供应商时写入 live 配置的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LiveWriteMode {
+    /// 以供应商配置整体替换 live 文件
+    #[default]
+    Replace,
+    /// 仅替换供应商相关字段，保留用户在 live 文件中维护的其他配置
+    Merge,
+    /// 仅 Codex：config.toml 以 `[profiles.*]` 保存全部供应商，切换时只更新 profile 与 auth.json
+    Profiles,
+}
@@ -0,0 +1,240 @@
+//! Codex config.toml 的生成、解析与检查
+
+use crate::error::CoreError;
+
+/// 生成第三方供应商的 config.toml（与前端 generateThirdPartyConfig 保持一致）
+pub fn generate_third_party_config(provider_name: &str, base_url: &str, model: &str) -> String {
+    // 清理供应商名称，确保符合 TOML 键名规范
+    let cleaned: String = provider_name
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches('_');
+    let name = if cleaned.is_empty() {
+        "custom"
+    } else {
+        cleaned
+    };
+
+    format!(
+        r#"model_provider = "{name}"
+model = "{model}"
+model_reasoning_effort = "high"
+disable_response_storage = true
+
+[model_providers.{name}]
+name = "{name}"
+base_url = "{base_url}"
+wire_api = "responses"
+requires_openai_auth = true"#
+    )
+}
+
+/// 从 config.toml 文本中提取当前 model_provider 的 base_url
+pub fn extract_base_url(config_text: &str) -> Option<String> {
+    let table = toml::from_str::<toml::Table>(config_text).ok()?;
+    let provider = table.get("model_provider")?.as_str()?;
+    table
+        .get("model_providers")?
+        .get(provider)?
+        .get("base_url")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+/// 将字节偏移换算为 1 起始的行列号
+fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let prefix = &text[..offset.min(text.len())];
+    let line = prefix.matches('\n').count() + 1;
+    let col = prefix
+        .rsplit('\n')
+        .next()
+        .map(|l| l.chars().count())
+        .unwrap_or(0)
+        + 1;
+    (line, col)
+}
+
+pub(crate) fn parse_config_table(text: &str) -> Result<toml::Table, CoreError> {
+    toml::from_str::<toml::Table>(text).map_err(|e| {
+        let (line, col) = e
+            .span()
+            .map(|span| line_col(text, span.start))
+            .map_or((None, None), |(line, col)| (Some(line), Some(col)));
+        CoreError::TomlSyntax {
+            line,
+            col,
+            reason: e.message().to_string(),
+        }
+    })
+}
+
+/// 对非空的 TOML 文本进行语法校验
+pub fn validate_config_toml(text: &str) -> Result<(), CoreError> {
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+    parse_config_table(text).map(|_| ())
+}
+
+/// Codex 内置的供应商，无需在 model_providers 中声明
+const BUILTIN_MODEL_PROVIDERS: &[&str] = &["openai", "oss"];
+const WIRE_APIS: &[&str] = &["chat", "responses"];
+
+/// 检查供应商 config.toml 的常见错误（语法、model_provider 指向、wire_api 取值、base_url）
+pub fn lint_config_toml(text: &str) -> Result<(), CoreError> {
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+    let table = parse_config_table(text)?;
+    let mut problems = Vec::new();
+
+    let providers = table.get("model_providers").and_then(|v| v.as_table());
+    match table.get("model_provider") {
+        Some(toml::Value::String(name)) => {
+            let declared = providers.map(|p| p.contains_key(name)).unwrap_or(false);
+            if !declared && !BUILTIN_MODEL_PROVIDERS.contains(&name.as_str()) {
+                problems.push(format!(
+                    "model_provider = \"{}\" 未在 [model_providers.{}] 中定义",
+                    name, name
+                ));
+            }
+        }
+        Some(_) => problems.push("model_provider 必须是字符串".to_string()),
+        None if providers.map(|p| !p.is_empty()).unwrap_or(false) => {
+            problems.push("定义了 model_providers 但缺少 model_provider".to_string())
+        }
+        None => {}
+    }
+
+    for (name, def) in providers.into_iter().flatten() {
+        let Some(def) = def.as_table() else {
+            problems.push(format!("[model_providers.{}] 必须是表", name));
+            continue;
+        };
+        match def.get("base_url").and_then(|v| v.as_str()) {
+            Some(url) if url.starts_with("http://") || url.starts_with("https://") => {}
+            Some(url) => problems.push(format!(
+                "[model_providers.{}] base_url 必须以 http:// 或 https:// 开头: {}",
+                name, url
+            )),
+            None => problems.push(format!("[model_providers.{}] 缺少 base_url", name)),
+        }
+        if let Some(wire_api) = def.get("wire_api") {
+            let valid = wire_api
+                .as_str()
+                .map(|w| WIRE_APIS.contains(&w))
+                .unwrap_or(false);
+            if !valid {
+                problems.push(format!(
+                    "[model_providers.{}] wire_api 取值无效: {}（可选: {}）",
+                    name,
+                    wire_api,
+                    WIRE_APIS.join(", ")
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(CoreError::InvalidCodexConfig(problems))
+    }
+}
+
+/// 将 toml_edit 的解析错误转换为语法错误（toml_edit 不提供行列号）
+pub(crate) fn parse_document(text: &str) -> Result<toml_edit::Document, CoreError> {
+    text.parse::<toml_edit::Document>()
+        .map_err(|e| CoreError::TomlSyntax {
+            line: None,
+            col: None,
+            reason: e.message().to_string(),
+        })
+}
+
+/// 设置 config.toml 顶层的 model，保留其余内容与格式
+pub fn set_model(text: &str, model: &str) -> Result<String, CoreError> {
+    let mut doc = parse_document(text)?;
+    doc["model"] = toml_edit::value(model);
+    Ok(doc.to_string())
+}
+
+/// 读取 config.toml 顶层的 model
+pub fn model_of(text: &str) -> Option<String> {
+    let doc = text.parse::<toml_edit::Document>().ok()?;
+    doc.get("model")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+/// 顶层 model 仍为 `injected` 时恢复为 `original`（为空则移除），保留其余内容与格式
+pub fn restore_model(
+    text: &str,
+    injected: &str,
+    original: Option<&str>,
+) -> Result<String, CoreError> {
+    let mut doc = parse_document(text)?;
+    if doc.get("model").and_then(|v| v.as_str()) != Some(injected) {
+        return Ok(text.to_string());
+    }
+    match original {
+        Some(model) => doc["model"] = toml_edit::value(model),
+        None => {
+            doc.remove("model");
+        }
+    }
+    Ok(doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_config_round_trips_base_url() {
+        let text = generate_third_party_config("My Relay!", "https://relay.example/v1", "gpt-5");
+        assert!(text.contains("model_provider = \"my_relay\""));
+        assert_eq!(
+            extract_base_url(&text).as_deref(),
+            Some("https://relay.example/v1")
+        );
+        assert!(lint_config_toml(&text).is_ok());
+    }
+
+    #[test]
+    fn syntax_errors_report_line_and_column() {
+        let err = validate_config_toml("model = \"a\"\nbroken =\n").unwrap_err();
+        match err {
+            CoreError::TomlSyntax { line, col, .. } => {
+                assert_eq!(line, Some(2));
+                assert!(col.is_some());
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert!(validate_config_toml("   ").is_ok());
+    }
+
+    #[test]
+    fn lint_reports_common_mistakes() {
+        let text = r#"
+model_provider = "missing"
+
+[model_providers.relay]
+base_url = "relay.example"
+wire_api = "grpc"
+"#;
+        let Err(CoreError::InvalidCodexConfig(problems)) = lint_config_toml(text) else {
+            panic!("lint should fail");
+        };
+        assert_eq!(problems.len(), 3);
+        assert!(lint_config_toml("model_provider = \"openai\"").is_ok());
+    }
+}
@@ -0,0 +1,30 @@
+/// 核心逻辑错误；GUI 侧转换为带错误码的 AppError
+#[derive(Debug, thiserror::Error)]
+pub enum CoreError {
+    /// 供应商 ID 不合法
+    #[error("{reason}")]
+    InvalidProviderId { id: String, reason: String },
+    /// 供应商名称不合法
+    #[error("{reason}")]
+    InvalidProviderName { reason: String },
+    /// config.toml 语法错误（只保留行列号与 toml 的错误描述，不含出错行原文）
+    #[error("config.toml 语法错误{}: {reason}", toml_location(.line, .col))]
+    TomlSyntax {
+        line: Option<usize>,
+        col: Option<usize>,
+        reason: String,
+    },
+    /// config.toml 检查未通过
+    #[error("config.toml 检查未通过: {}", .0.join("; "))]
+    InvalidCodexConfig(Vec<String>),
+    /// 合并后的 config.toml 无法序列化
+    #[error("序列化 config.toml 失败: {0}")]
+    TomlSerialize(String),
+}
+
+pub(crate) fn toml_location(line: &Option<usize>, col: &Option<usize>) -> String {
+    match (line, col) {
+        (Some(line), Some(col)) => format!("（第 {} 行，第 {} 列）", line, col),
+        _ => String::new(),
+    }
+}
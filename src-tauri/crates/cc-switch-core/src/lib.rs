@@ -0,0 +1,20 @@
+//! cc-switch 的核心逻辑，不依赖 Tauri 与全局状态：
+//! 路径由调用方注入（GUI 按设置与运行环境解析，CLI 与测试可使用临时主目录），
+//! live 写入方式等设置也由调用方传入
+//!
+//! 范围：配置路径、供应商命名、Codex 配置（检查、模型、profile）、live 合并与回填。
+//! `MultiAppConfig` 的读写（加密、迁移、存储锁）、keychain 与 live 文件的实际读写
+//! 依赖应用的全局设置与运行环境，仍留在 GUI crate 中
+
+pub mod app;
+pub mod codex;
+pub mod error;
+pub mod live;
+pub mod merge;
+pub mod naming;
+pub mod paths;
+pub mod profiles;
+
+pub use app::{AppType, LiveWriteMode};
+pub use error::CoreError;
+pub use paths::{ClaudePaths, CodexPaths, Paths};
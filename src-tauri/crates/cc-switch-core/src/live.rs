@@ -0,0 +1,257 @@
+//! 切换时写入 live 与回填供应商的纯逻辑：模型注入/还原、按写入方式回填与漂移比较视图
+
+use serde_json::{Map, Value};
+
+use crate::app::{AppType, LiveWriteMode};
+use crate::codex::parse_config_table;
+use crate::merge::{merge_claude, merge_codex_config, project_claude, project_codex};
+
+/// 合并模式下回填 Codex 配置：auth 整体采用 live，config 仅更新供应商相关部分
+fn backfill_codex(stored: &Value, mut live: Value) -> Value {
+    let stored_config = stored.get("config").and_then(|v| v.as_str()).unwrap_or("");
+    let live_config = live.get("config").and_then(|v| v.as_str()).unwrap_or("");
+    match merge_codex_config(stored_config, live_config) {
+        Ok(text) => {
+            if let Some(obj) = live.as_object_mut() {
+                obj.insert("config".to_string(), Value::String(text));
+            }
+            live
+        }
+        Err(e) => {
+            log::warn!("合并回填 config.toml 失败，改为整体回填: {}", e);
+            live
+        }
+    }
+}
+
+/// profile 模式下回填 Codex 配置：auth 整体采用 live，config 取生效 profile 的内容
+fn backfill_codex_profile(stored: &Value, mut live: Value) -> Value {
+    let stored_config = stored.get("config").and_then(|v| v.as_str()).unwrap_or("");
+    let live_config = live.get("config").and_then(|v| v.as_str()).unwrap_or("");
+    let config = match crate::profiles::backfill_config(stored_config, live_config) {
+        Ok(text) => text,
+        Err(e) => {
+            log::warn!("回填 profile 失败，保留供应商原有 config.toml: {}", e);
+            stored_config.to_string()
+        }
+    };
+    if let Some(obj) = live.as_object_mut() {
+        obj.insert("config".to_string(), Value::String(config));
+    }
+    live
+}
+
+/// 写入供应商指定的模型：Claude 为 env.ANTHROPIC_MODEL（已有顶层 model 时一并替换），
+/// Codex 为 config.toml 的 model；未指定模型时原样返回
+pub fn with_model(app: &AppType, model: Option<&str>, mut settings: Value) -> Value {
+    let Some(model) = model.map(str::trim).filter(|m| !m.is_empty()) else {
+        return settings;
+    };
+    let Some(obj) = settings.as_object_mut() else {
+        return settings;
+    };
+    match app {
+        AppType::Claude => {
+            if obj.contains_key("model") {
+                obj.insert("model".to_string(), Value::String(model.to_string()));
+            }
+            let env = obj
+                .entry("env")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(env) = env.as_object_mut() {
+                env.insert(
+                    "ANTHROPIC_MODEL".to_string(),
+                    Value::String(model.to_string()),
+                );
+            }
+        }
+        AppType::Codex => {
+            let text = obj.get("config").and_then(|v| v.as_str()).unwrap_or("");
+            match crate::codex::set_model(text, model) {
+                Ok(text) => {
+                    obj.insert("config".to_string(), Value::String(text));
+                }
+                Err(e) => log::warn!("写入供应商模型失败: {}", e),
+            }
+        }
+    }
+    settings
+}
+
+/// 撤销 `with_model` 写入 live 的模型：回读内容中的模型仍为供应商模型时恢复为存储中的原值
+/// （原先没有则移除），避免切换后模型被永久回填进 settings_config
+pub fn strip_model(app: &AppType, model: Option<&str>, stored: &Value, mut live: Value) -> Value {
+    let Some(model) = model.map(str::trim).filter(|m| !m.is_empty()) else {
+        return live;
+    };
+    let Some(obj) = live.as_object_mut() else {
+        return live;
+    };
+    match app {
+        AppType::Claude => {
+            if obj.get("model").and_then(|v| v.as_str()) == Some(model) {
+                match stored.get("model") {
+                    Some(original) => {
+                        obj.insert("model".to_string(), original.clone());
+                    }
+                    None => {
+                        obj.remove("model");
+                    }
+                }
+            }
+            if let Some(env) = obj.get_mut("env").and_then(|v| v.as_object_mut()) {
+                if env.get("ANTHROPIC_MODEL").and_then(|v| v.as_str()) == Some(model) {
+                    match stored.get("env").and_then(|e| e.get("ANTHROPIC_MODEL")) {
+                        Some(original) => {
+                            env.insert("ANTHROPIC_MODEL".to_string(), original.clone());
+                        }
+                        None => {
+                            env.remove("ANTHROPIC_MODEL");
+                        }
+                    }
+                }
+            }
+        }
+        AppType::Codex => {
+            let stored_config = stored.get("config").and_then(|v| v.as_str()).unwrap_or("");
+            let text = obj.get("config").and_then(|v| v.as_str()).unwrap_or("");
+            let original = crate::codex::model_of(stored_config);
+            match crate::codex::restore_model(text, model, original.as_deref()) {
+                Ok(text) => {
+                    obj.insert("config".to_string(), Value::String(text));
+                }
+                Err(e) => log::warn!("还原供应商模型失败: {}", e),
+            }
+        }
+    }
+    live
+}
+
+/// 计算回填到供应商的配置（`mode` 为该应用的写入方式）：合并模式下只更新供应商相关字段，
+/// 避免把用户的 hooks 等写入供应商
+pub fn backfill(app: &AppType, mode: LiveWriteMode, stored: &Value, live: Value) -> Value {
+    match (app, mode) {
+        (AppType::Claude, LiveWriteMode::Merge) => merge_claude(stored, &live),
+        (AppType::Codex, LiveWriteMode::Merge) => backfill_codex(stored, live),
+        (AppType::Codex, LiveWriteMode::Profiles) => backfill_codex_profile(stored, live),
+        _ => live,
+    }
+}
+
+/// 替换 settings 中的 config 文本；settings 不是对象或转换失败时原样返回
+fn map_config<E>(settings: &Value, f: impl FnOnce(&str) -> Result<String, E>) -> Value {
+    let mut projected = settings.clone();
+    let config = settings
+        .get("config")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    if let (Ok(text), Some(obj)) = (f(config), projected.as_object_mut()) {
+        obj.insert("config".to_string(), Value::String(text));
+    }
+    projected
+}
+
+/// 漂移检测时参与比较的部分：合并模式下仅比较供应商相关字段
+pub fn comparable(app: &AppType, mode: LiveWriteMode, settings: &Value) -> Value {
+    match (app, mode) {
+        (AppType::Claude, LiveWriteMode::Merge) => project_claude(settings),
+        (AppType::Codex, LiveWriteMode::Merge) => map_config(settings, |config| {
+            parse_config_table(config)
+                .map(|table| toml::to_string(&project_codex(&table)).unwrap_or_default())
+        }),
+        (AppType::Codex, LiveWriteMode::Profiles) => {
+            map_config(settings, crate::profiles::comparable_config)
+        }
+        _ => settings.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn strip_model_removes_injected_claude_model() {
+        let stored = json!({ "env": { "ANTHROPIC_BASE_URL": "https://a.example" } });
+        let live = with_model(&AppType::Claude, Some("opus"), stored.clone());
+        assert_eq!(live["env"]["ANTHROPIC_MODEL"], "opus");
+        assert_eq!(
+            strip_model(&AppType::Claude, Some("opus"), &stored, live),
+            stored
+        );
+    }
+
+    #[test]
+    fn strip_model_restores_original_claude_model() {
+        let stored = json!({ "model": "sonnet", "env": { "ANTHROPIC_MODEL": "sonnet" } });
+        let live = with_model(&AppType::Claude, Some("opus"), stored.clone());
+        assert_eq!(live["model"], "opus");
+        assert_eq!(
+            strip_model(&AppType::Claude, Some("opus"), &stored, live),
+            stored
+        );
+    }
+
+    #[test]
+    fn strip_model_keeps_live_edits() {
+        let stored = json!({ "env": {} });
+        let live = json!({ "env": { "ANTHROPIC_MODEL": "edited-in-live" } });
+        let stripped = strip_model(&AppType::Claude, Some("opus"), &stored, live.clone());
+        assert_eq!(stripped, live);
+    }
+
+    #[test]
+    fn strip_model_restores_codex_config() {
+        let stored = json!({ "auth": {}, "config": "model_provider = \"x\"\n" });
+        let live = with_model(&AppType::Codex, Some("gpt-5"), stored.clone());
+        assert!(live["config"].as_str().unwrap().contains("gpt-5"));
+        let stripped = strip_model(&AppType::Codex, Some("gpt-5"), &stored, live);
+        assert_eq!(stripped, stored);
+
+        let stored = json!({ "auth": {}, "config": "model = \"o3\"\n" });
+        let live = with_model(&AppType::Codex, Some("gpt-5"), stored.clone());
+        let stripped = strip_model(&AppType::Codex, Some("gpt-5"), &stored, live);
+        assert_eq!(stripped, stored);
+    }
+
+    #[test]
+    fn merge_backfill_keeps_user_fields_out_of_provider() {
+        let stored = json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "old" } });
+        let live = json!({
+            "env": { "ANTHROPIC_AUTH_TOKEN": "new", "HTTP_PROXY": "http://proxy" },
+            "hooks": {}
+        });
+        let merged = backfill(
+            &AppType::Claude,
+            LiveWriteMode::Merge,
+            &stored,
+            live.clone(),
+        );
+        assert_eq!(merged["env"]["ANTHROPIC_AUTH_TOKEN"], "new");
+        assert!(merged.get("hooks").is_none());
+        assert_eq!(
+            backfill(
+                &AppType::Claude,
+                LiveWriteMode::Replace,
+                &stored,
+                live.clone()
+            ),
+            live
+        );
+    }
+
+    #[test]
+    fn comparable_ignores_user_sections_in_merge_mode() {
+        let a = json!({ "config": "model = \"gpt-5\"\n\n[mcp_servers.x]\ncommand = \"a\"\n" });
+        let b = json!({ "config": "model = \"gpt-5\"\n" });
+        assert_eq!(
+            comparable(&AppType::Codex, LiveWriteMode::Merge, &a),
+            comparable(&AppType::Codex, LiveWriteMode::Merge, &b)
+        );
+        assert_ne!(
+            comparable(&AppType::Codex, LiveWriteMode::Replace, &a),
+            comparable(&AppType::Codex, LiveWriteMode::Replace, &b)
+        );
+    }
+}
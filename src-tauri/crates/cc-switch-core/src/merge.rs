@@ -0,0 +1,212 @@
+//! 合并写入 live 配置时的字段划分：只替换与供应商相关的部分，保留用户自行维护的配置
+
+use serde_json::{Map, Value};
+
+use crate::codex::parse_config_table;
+use crate::error::CoreError;
+
+/// Claude settings.json 中与供应商相关的顶层字段（env 下另含 `ANTHROPIC_*`）
+const CLAUDE_PROVIDER_KEYS: &[&str] = &["apiKeyHelper", "model"];
+
+fn is_claude_provider_env(key: &str) -> bool {
+    key.starts_with("ANTHROPIC_")
+}
+
+/// Codex config.toml 中与供应商相关的顶层键（另含当前 `[model_providers.<name>]` 表）
+const CODEX_PROVIDER_KEYS: &[&str] = &["model_provider", "model"];
+
+/// 仅保留 Claude 配置中与供应商相关的字段
+pub fn project_claude(settings: &Value) -> Value {
+    let mut out = Map::new();
+    for key in CLAUDE_PROVIDER_KEYS {
+        if let Some(v) = settings.get(*key) {
+            out.insert(key.to_string(), v.clone());
+        }
+    }
+    let env: Map<String, Value> = settings
+        .get("env")
+        .and_then(|v| v.as_object())
+        .map(|env| {
+            env.iter()
+                .filter(|(k, _)| is_claude_provider_env(k))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !env.is_empty() {
+        out.insert("env".to_string(), Value::Object(env));
+    }
+    Value::Object(out)
+}
+
+/// 以 `base` 为底，将其中供应商相关字段替换为 `source` 中的对应值
+pub fn merge_claude(base: &Value, source: &Value) -> Value {
+    let mut merged = base.as_object().cloned().unwrap_or_default();
+    for key in CLAUDE_PROVIDER_KEYS {
+        merged.remove(*key);
+        if let Some(v) = source.get(*key) {
+            merged.insert(key.to_string(), v.clone());
+        }
+    }
+
+    let had_env = merged.contains_key("env");
+    let mut env = merged
+        .remove("env")
+        .and_then(|v| v.as_object().cloned())
+        .unwrap_or_default();
+    env.retain(|k, _| !is_claude_provider_env(k));
+    if let Some(source_env) = source.get("env").and_then(|v| v.as_object()) {
+        for (k, v) in source_env {
+            if is_claude_provider_env(k) {
+                env.insert(k.clone(), v.clone());
+            }
+        }
+    }
+    if had_env || !env.is_empty() {
+        merged.insert("env".to_string(), Value::Object(env));
+    }
+    Value::Object(merged)
+}
+
+fn active_model_provider(table: &toml::Table) -> Option<String> {
+    table
+        .get("model_provider")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+/// 仅保留 Codex 配置中与供应商相关的部分
+pub fn project_codex(table: &toml::Table) -> toml::Table {
+    let mut out = toml::Table::new();
+    for key in CODEX_PROVIDER_KEYS {
+        if let Some(v) = table.get(*key) {
+            out.insert(key.to_string(), v.clone());
+        }
+    }
+    if let Some(name) = active_model_provider(table) {
+        if let Some(section) = table
+            .get("model_providers")
+            .and_then(|v| v.as_table())
+            .and_then(|t| t.get(&name))
+        {
+            let mut providers = toml::Table::new();
+            providers.insert(name, section.clone());
+            out.insert("model_providers".to_string(), toml::Value::Table(providers));
+        }
+    }
+    out
+}
+
+/// 以 `base` 为底，将 model_provider / model 及当前 `[model_providers.*]` 表替换为 `source` 中的内容，
+/// 其余段落（mcp_servers、profiles 等）保持不变
+pub fn merge_codex_config(base: &str, source: &str) -> Result<String, CoreError> {
+    let mut merged = parse_config_table(base)?;
+    let source = parse_config_table(source)?;
+
+    // 移除 base 中原先生效的供应商表，避免切换后残留旧供应商（及其令牌）
+    if let Some(old) = active_model_provider(&merged) {
+        let now_empty = match merged.get_mut("model_providers") {
+            Some(toml::Value::Table(providers)) => {
+                providers.remove(&old);
+                providers.is_empty()
+            }
+            _ => false,
+        };
+        if now_empty {
+            merged.remove("model_providers");
+        }
+    }
+    for key in CODEX_PROVIDER_KEYS {
+        merged.remove(*key);
+    }
+
+    for (key, value) in project_codex(&source) {
+        match (key.as_str(), value) {
+            ("model_providers", toml::Value::Table(incoming)) => {
+                let entry = merged
+                    .entry("model_providers".to_string())
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+                if let toml::Value::Table(providers) = entry {
+                    providers.extend(incoming);
+                }
+            }
+            (_, value) => {
+                merged.insert(key, value);
+            }
+        }
+    }
+    toml::to_string(&merged).map_err(|e| CoreError::TomlSerialize(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_claude_replaces_only_provider_fields() {
+        let live = json!({
+            "model": "old-model",
+            "hooks": { "PreToolUse": [] },
+            "env": { "ANTHROPIC_AUTH_TOKEN": "sk-old", "ANTHROPIC_BASE_URL": "https://old", "DEBUG": "1" },
+        });
+        let provider = json!({
+            "env": { "ANTHROPIC_AUTH_TOKEN": "sk-new", "OTHER": "ignored" },
+        });
+
+        let merged = merge_claude(&live, &provider);
+        assert_eq!(
+            merged,
+            json!({
+                "hooks": { "PreToolUse": [] },
+                "env": { "ANTHROPIC_AUTH_TOKEN": "sk-new", "DEBUG": "1" },
+            })
+        );
+        assert_eq!(
+            project_claude(&merged),
+            json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-new" } })
+        );
+    }
+
+    #[test]
+    fn merge_codex_swaps_active_provider_and_keeps_user_sections() {
+        let live = r#"
+model_provider = "old"
+model = "gpt-4"
+
+[model_providers.old]
+base_url = "https://old.example"
+
+[mcp_servers.fs]
+command = "npx"
+"#;
+        let provider = r#"
+model_provider = "new"
+model = "gpt-5"
+
+[model_providers.new]
+base_url = "https://new.example"
+"#;
+        let merged: toml::Table =
+            toml::from_str(&merge_codex_config(live, provider).unwrap()).unwrap();
+        assert_eq!(merged["model_provider"].as_str(), Some("new"));
+        assert_eq!(merged["model"].as_str(), Some("gpt-5"));
+        let providers = merged["model_providers"].as_table().unwrap();
+        assert!(!providers.contains_key("old"));
+        assert_eq!(
+            providers["new"]["base_url"].as_str(),
+            Some("https://new.example")
+        );
+        assert_eq!(merged["mcp_servers"]["fs"]["command"].as_str(), Some("npx"));
+    }
+
+    #[test]
+    fn merge_codex_accepts_empty_base_and_rejects_invalid_toml() {
+        let merged = merge_codex_config("", "model = \"gpt-5\"").unwrap();
+        assert_eq!(merged.trim(), "model = \"gpt-5\"");
+        assert!(matches!(
+            merge_codex_config("model = ", ""),
+            Err(CoreError::TomlSyntax { .. })
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+//! 供应商 ID 与名称的校验规则，以及由名称派生文件名的规则
+
+use crate::error::CoreError;
+
+/// 供应商 ID / 名称的最大长度（字符数）
+const MAX_PROVIDER_FIELD_LEN: usize = 128;
+/// 派生文件名的最大长度（字符数）
+const MAX_FILE_STEM_LEN: usize = 64;
+
+/// Windows 保留设备名（不区分大小写，忽略扩展名）
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or("").to_ascii_uppercase();
+    matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || ((stem.starts_with("COM") || stem.starts_with("LPT"))
+            && stem.len() == 4
+            && stem.as_bytes()[3].is_ascii_digit())
+}
+
+/// 清理供应商名称，确保文件名安全
+pub fn sanitize_provider_name(name: &str) -> String {
+    let cleaned = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '-',
+            c if c.is_control() => '-',
+            _ => c,
+        })
+        .collect::<String>()
+        .to_lowercase();
+    // 去掉首尾的点与空白（避免 `..` 及 Windows 下被静默截断的文件名）
+    let mut cleaned: String = cleaned
+        .trim_matches(|c: char| c == '.' || c.is_whitespace())
+        .chars()
+        .take(MAX_FILE_STEM_LEN)
+        .collect();
+    if cleaned.is_empty() {
+        cleaned = "provider".to_string();
+    }
+    if is_reserved_name(&cleaned) {
+        cleaned.insert(0, '_');
+    }
+    cleaned
+}
+
+/// 校验供应商 ID：禁止路径分隔符、控制字符、`.`/`..` 与保留名，并限制长度
+pub fn validate_provider_id(id: &str) -> Result<(), CoreError> {
+    let invalid = |reason: String| CoreError::InvalidProviderId {
+        id: id.to_string(),
+        reason,
+    };
+    if id.trim().is_empty() {
+        return Err(invalid("供应商 ID 不能为空".to_string()));
+    }
+    if id.chars().count() > MAX_PROVIDER_FIELD_LEN {
+        return Err(invalid(format!(
+            "供应商 ID 过长（最多 {} 个字符）",
+            MAX_PROVIDER_FIELD_LEN
+        )));
+    }
+    if id.contains(['/', '\\']) || id.chars().any(|c| c.is_control()) {
+        return Err(invalid(format!(
+            "供应商 ID 包含非法字符: {}",
+            id.escape_debug()
+        )));
+    }
+    if id == "." || id == ".." || is_reserved_name(id) {
+        return Err(invalid(format!("供应商 ID 为保留名称: {}", id)));
+    }
+    Ok(())
+}
+
+/// 规范化供应商名称：去掉首尾空白，拒绝空名称、控制字符与超长名称
+pub fn normalize_provider_name(name: &str) -> Result<String, CoreError> {
+    let invalid = |reason: String| CoreError::InvalidProviderName { reason };
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(invalid("供应商名称不能为空".to_string()));
+    }
+    if name.chars().count() > MAX_PROVIDER_FIELD_LEN {
+        return Err(invalid(format!(
+            "供应商名称过长（最多 {} 个字符）",
+            MAX_PROVIDER_FIELD_LEN
+        )));
+    }
+    if name.chars().any(|c| c.is_control()) {
+        return Err(invalid("供应商名称包含控制字符".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_produces_safe_file_stems() {
+        assert_eq!(sanitize_provider_name("My/Provider:1"), "my-provider-1");
+        assert_eq!(sanitize_provider_name("  ..  "), "provider");
+        assert_eq!(sanitize_provider_name("con"), "_con");
+        assert_eq!(sanitize_provider_name("COM1.json"), "_com1.json");
+        assert_eq!(
+            sanitize_provider_name(&"x".repeat(100)).chars().count(),
+            MAX_FILE_STEM_LEN
+        );
+    }
+
+    #[test]
+    fn validate_provider_id_rejects_paths_and_reserved_names() {
+        assert!(validate_provider_id("work-1").is_ok());
+        for id in ["", " ", "a/b", "a\\b", ".", "..", "nul", "a\nb"] {
+            assert!(validate_provider_id(id).is_err(), "{:?}", id);
+        }
+        assert!(validate_provider_id(&"x".repeat(MAX_PROVIDER_FIELD_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn normalize_provider_name_trims_and_validates() {
+        assert_eq!(normalize_provider_name("  Work  ").unwrap(), "Work");
+        assert!(normalize_provider_name("   ").is_err());
+        assert!(normalize_provider_name("a\u{7}b").is_err());
+    }
+}
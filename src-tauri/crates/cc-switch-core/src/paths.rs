@@ -0,0 +1,148 @@
+//! 各工具的配置文件布局；目录由调用方注入，本模块只负责在目录下拼出文件路径
+
+use std::path::{Path, PathBuf};
+
+use crate::naming::sanitize_provider_name;
+
+/// 由供应商名称（缺省时用 ID）派生文件名
+fn file_stem(provider_id: &str, provider_name: Option<&str>) -> String {
+    sanitize_provider_name(provider_name.unwrap_or(provider_id))
+}
+
+/// Claude Code 配置目录（默认 ~/.claude）下的文件
+#[derive(Debug, Clone)]
+pub struct ClaudePaths {
+    pub dir: PathBuf,
+}
+
+impl ClaudePaths {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 主配置文件：优先 settings.json，仅存在旧版 claude.json 时沿用旧文件
+    pub fn settings_path(&self) -> PathBuf {
+        let settings = self.dir.join("settings.json");
+        if settings.exists() {
+            return settings;
+        }
+        let legacy = self.dir.join("claude.json");
+        if legacy.exists() {
+            return legacy;
+        }
+        settings
+    }
+
+    /// 供应商配置副本 `settings-<名称>.json`
+    pub fn provider_config_path(&self, provider_id: &str, provider_name: Option<&str>) -> PathBuf {
+        self.dir.join(format!(
+            "settings-{}.json",
+            file_stem(provider_id, provider_name)
+        ))
+    }
+}
+
+/// Codex 配置目录（默认 ~/.codex）下的文件
+#[derive(Debug, Clone)]
+pub struct CodexPaths {
+    pub dir: PathBuf,
+}
+
+impl CodexPaths {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn auth_path(&self) -> PathBuf {
+        self.dir.join("auth.json")
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.dir.join("config.toml")
+    }
+
+    /// 供应商配置副本 `auth-<名称>.json` 与 `config-<名称>.toml`
+    pub fn provider_paths(
+        &self,
+        provider_id: &str,
+        provider_name: Option<&str>,
+    ) -> (PathBuf, PathBuf) {
+        let stem = file_stem(provider_id, provider_name);
+        (
+            self.dir.join(format!("auth-{}.json", stem)),
+            self.dir.join(format!("config-{}.toml", stem)),
+        )
+    }
+}
+
+/// 一组完整的配置目录
+#[derive(Debug, Clone)]
+pub struct Paths {
+    pub claude: ClaudePaths,
+    pub codex: CodexPaths,
+    /// cc-switch 自身的配置目录（config.json 所在目录）
+    pub app_config_dir: PathBuf,
+}
+
+impl Paths {
+    /// 以 `home` 为主目录的默认布局：~/.claude、~/.codex、~/.cc-switch
+    pub fn from_home(home: &Path) -> Self {
+        Self {
+            claude: ClaudePaths::new(home.join(".claude")),
+            codex: CodexPaths::new(home.join(".codex")),
+            app_config_dir: home.join(".cc-switch"),
+        }
+    }
+
+    pub fn app_config_path(&self) -> PathBuf {
+        self.app_config_dir.join("config.json")
+    }
+
+    /// 归档根目录 `<app_config_dir>/archive`
+    pub fn archive_root(&self) -> PathBuf {
+        self.app_config_dir.join("archive")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_is_rooted_at_injected_home() {
+        let home = tempfile::tempdir().unwrap();
+        let paths = Paths::from_home(home.path());
+        assert_eq!(
+            paths.app_config_path(),
+            home.path().join(".cc-switch").join("config.json")
+        );
+        assert_eq!(
+            paths.codex.auth_path(),
+            home.path().join(".codex/auth.json")
+        );
+        assert_eq!(
+            paths.codex.provider_paths("id", Some("My Relay")),
+            (
+                home.path().join(".codex/auth-my relay.json"),
+                home.path().join(".codex/config-my relay.toml"),
+            )
+        );
+        assert_eq!(
+            paths.claude.provider_config_path("work", None),
+            home.path().join(".claude/settings-work.json")
+        );
+    }
+
+    #[test]
+    fn claude_settings_prefers_settings_json_over_legacy_file() {
+        let home = tempfile::tempdir().unwrap();
+        let claude = ClaudePaths::new(home.path());
+        assert_eq!(claude.settings_path(), home.path().join("settings.json"));
+
+        std::fs::write(home.path().join("claude.json"), "{}").unwrap();
+        assert_eq!(claude.settings_path(), home.path().join("claude.json"));
+
+        std::fs::write(home.path().join("settings.json"), "{}").unwrap();
+        assert_eq!(claude.settings_path(), home.path().join("settings.json"));
+    }
+}
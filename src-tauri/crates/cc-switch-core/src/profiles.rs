@@ -0,0 +1,202 @@
+//! Codex profile 模式：config.toml 以 `[profiles.*]` 保存全部供应商，切换时只更新顶层 `profile`
+
+use toml_edit::{Array, Document, InlineTable, Item, Table};
+
+use crate::codex::{parse_config_table, parse_document};
+use crate::error::CoreError;
+
+/// cc-switch 维护的 profile 与 `[model_providers.*]` 键名前缀，其余条目视为用户配置，不做改动
+const KEY_PREFIX: &str = "cc-switch-";
+
+/// Codex 内置的默认供应商
+const BUILTIN_PROVIDER: &str = "openai";
+
+/// 供应商对应的 profile 键名（同时作为其 `[model_providers.*]` 键名）
+pub fn profile_key(provider_id: &str) -> String {
+    let id: String = provider_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}", KEY_PREFIX, id)
+}
+
+fn str_of<'a>(table: &'a toml::Table, key: &str) -> Option<&'a str> {
+    table.get(key).and_then(|v| v.as_str())
+}
+
+fn provider_table<'a>(table: &'a toml::Table, name: &str) -> Option<&'a toml::Table> {
+    table
+        .get("model_providers")?
+        .as_table()?
+        .get(name)?
+        .as_table()
+}
+
+/// 可放入 profile 的设置：顶层非表值（model、model_reasoning_effort 等），不含 model_provider / profile
+fn profile_settings(table: &toml::Table) -> toml::Table {
+    table
+        .iter()
+        .filter(|(k, v)| !v.is_table() && k.as_str() != "model_provider" && k.as_str() != "profile")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// 由供应商的独立 config.toml 生成 (profile, 供应商表)
+fn build_profile(key: &str, config: &str) -> Result<(toml::Table, Option<toml::Table>), CoreError> {
+    let table = parse_config_table(config)?;
+    let name = str_of(&table, "model_provider").unwrap_or(BUILTIN_PROVIDER);
+    let provider = provider_table(&table, name).cloned();
+    let mut profile = profile_settings(&table);
+    // 未指定 model_provider 时显式使用内置 openai，避免沿用顶层设置
+    let reference = if provider.is_some() { key } else { name };
+    profile.insert(
+        "model_provider".to_string(),
+        toml::Value::String(reference.to_string()),
+    );
+    Ok((profile, provider))
+}
+
+fn to_edit_value(value: &toml::Value) -> toml_edit::Value {
+    match value {
+        toml::Value::String(s) => s.as_str().into(),
+        toml::Value::Integer(i) => (*i).into(),
+        toml::Value::Float(f) => (*f).into(),
+        toml::Value::Boolean(b) => (*b).into(),
+        toml::Value::Datetime(d) => (*d).into(),
+        toml::Value::Array(items) => items.iter().map(to_edit_value).collect::<Array>().into(),
+        toml::Value::Table(table) => table
+            .iter()
+            .map(|(k, v)| (k.as_str(), to_edit_value(v)))
+            .collect::<InlineTable>()
+            .into(),
+    }
+}
+
+fn to_edit_table(table: &toml::Table) -> Item {
+    let mut out = Table::new();
+    for (k, v) in table {
+        out.insert(k, Item::Value(to_edit_value(v)));
+    }
+    Item::Table(out)
+}
+
+fn section<'a>(doc: &'a mut Document, name: &str) -> Result<&'a mut Table, CoreError> {
+    doc.entry(name)
+        .or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        })
+        .as_table_mut()
+        .ok_or_else(|| {
+            CoreError::InvalidCodexConfig(vec![format!("{} 不是表，无法写入 profile", name)])
+        })
+}
+
+/// 在现有 config.toml 上更新所有供应商的 profile 并切换顶层 `profile`
+///
+/// 只改动 cc-switch 维护的条目（键名以 `cc-switch-` 开头），用户的其他配置与注释保持不变
+pub fn render(
+    live: &str,
+    providers: &[(String, String)],
+    active_id: &str,
+) -> Result<String, CoreError> {
+    let mut doc = parse_document(live)?;
+
+    let mut profiles = Vec::with_capacity(providers.len());
+    for (id, config) in providers {
+        let key = profile_key(id);
+        let (profile, provider) = build_profile(&key, config)
+            .map_err(|e| CoreError::InvalidCodexConfig(vec![format!("供应商 {} 的 {}", id, e)]))?;
+        profiles.push((key, profile, provider));
+    }
+    let keys: Vec<&str> = profiles.iter().map(|(k, _, _)| k.as_str()).collect();
+    let keep = |k: &str, _: &mut Item| !k.starts_with(KEY_PREFIX) || keys.contains(&k);
+
+    let model_providers = section(&mut doc, "model_providers")?;
+    model_providers.retain(keep);
+    for (key, _, provider) in &profiles {
+        match provider {
+            Some(table) => model_providers.insert(key, to_edit_table(table)),
+            None => model_providers.remove(key),
+        };
+    }
+    if model_providers.is_empty() {
+        doc.remove("model_providers");
+    }
+
+    let profile_section = section(&mut doc, "profiles")?;
+    profile_section.retain(keep);
+    for (key, profile, _) in &profiles {
+        profile_section.insert(key, to_edit_table(profile));
+    }
+
+    doc.insert("profile", toml_edit::value(profile_key(active_id)));
+    Ok(doc.to_string())
+}
+
+/// 当前生效的 cc-switch profile
+fn active_profile(table: &toml::Table) -> Option<&toml::Table> {
+    let key = str_of(table, "profile").filter(|k| k.starts_with(KEY_PREFIX))?;
+    table.get("profiles")?.as_table()?.get(key)?.as_table()
+}
+
+/// 参与漂移比较的供应商视图：生效 profile 的设置及其供应商表；未使用 cc-switch profile 时取顶层设置
+pub fn comparable_config(config: &str) -> Result<String, CoreError> {
+    let table = parse_config_table(config)?;
+    let (mut view, name) = match active_profile(&table) {
+        Some(profile) => (
+            profile_settings(profile),
+            str_of(profile, "model_provider").unwrap_or(BUILTIN_PROVIDER),
+        ),
+        None => (
+            profile_settings(&table),
+            str_of(&table, "model_provider").unwrap_or(BUILTIN_PROVIDER),
+        ),
+    };
+    if let Some(provider) = provider_table(&table, name) {
+        view.insert("provider".to_string(), toml::Value::Table(provider.clone()));
+    }
+    toml::to_string(&view).map_err(|e| CoreError::TomlSerialize(e.to_string()))
+}
+
+/// 将 live 中生效 profile 的内容回填到供应商的独立 config.toml（沿用供应商原有的 model_provider 名称）
+pub fn backfill_config(stored: &str, live: &str) -> Result<String, CoreError> {
+    let live = parse_config_table(live)?;
+    let Some(profile) = active_profile(&live) else {
+        return Ok(stored.to_string());
+    };
+    let mut merged = parse_config_table(stored)?;
+    merged.retain(|k, v| v.is_table() || k == "model_provider");
+    merged.extend(profile_settings(profile));
+
+    let reference = str_of(profile, "model_provider").unwrap_or(BUILTIN_PROVIDER);
+    match provider_table(&live, reference) {
+        Some(provider) => {
+            let name = str_of(&merged, "model_provider")
+                .unwrap_or(reference)
+                .to_string();
+            let entry = merged
+                .entry("model_providers".to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if let toml::Value::Table(providers) = entry {
+                providers.insert(name.clone(), toml::Value::Table(provider.clone()));
+            }
+            merged.insert("model_provider".to_string(), toml::Value::String(name));
+        }
+        None if reference != BUILTIN_PROVIDER || merged.contains_key("model_provider") => {
+            merged.insert(
+                "model_provider".to_string(),
+                toml::Value::String(reference.to_string()),
+            );
+        }
+        None => {}
+    }
+    toml::to_string(&merged).map_err(|e| CoreError::TomlSerialize(e.to_string()))
+}
@@ -18,34 +18,11 @@ pub struct McpRoot {
     pub codex: McpConfig,
 }
 
-use crate::config::{copy_file, get_app_config_dir, get_app_config_path, write_json_file};
-use crate::provider::ProviderManager;
+use crate::config::{copy_file, get_app_config_dir, get_app_config_path};
+use crate::error::AppError;
+use crate::provider::{Provider, ProviderManager};
 
-/// 应用类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum AppType {
-    Claude,
-    Codex,
-}
-
-impl AppType {
-    pub fn as_str(&self) -> &str {
-        match self {
-            AppType::Claude => "claude",
-            AppType::Codex => "codex",
-        }
-    }
-}
-
-impl From<&str> for AppType {
-    fn from(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "codex" => AppType::Codex,
-            _ => AppType::Claude, // 默认为 Claude
-        }
-    }
-}
+pub use cc_switch_core::AppType;
 
 /// 多应用配置结构（向后兼容）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,10 +35,44 @@ pub struct MultiAppConfig {
     /// MCP 配置（按客户端分治）
     #[serde(default)]
     pub mcp: McpRoot,
+    /// 回收站：已删除的供应商（可恢复，超期自动清理）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trash: Vec<TrashEntry>,
+    /// Codex 配置片段：多个供应商共用的 config.toml 内容
+    #[serde(
+        default,
+        rename = "codexFragments",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub codex_fragments: Vec<ConfigFragment>,
+}
+
+/// 配置片段：供应商按 ID 引用，切换时与供应商自身的配置合并
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFragment {
+    pub id: String,
+    pub name: String,
+    /// TOML 文本
+    pub content: String,
+}
+
+/// 回收站条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    pub app_type: String,
+    pub provider: Provider,
+    /// 删除时间（毫秒时间戳）
+    pub deleted_at: i64,
 }
 
 fn default_version() -> u32 {
-    2
+    crate::config_migration::CURRENT_CONFIG_VERSION
+}
+
+fn parse_json(content: &str) -> Result<serde_json::Value, String> {
+    serde_json::from_str(content).map_err(|e| format!("解析配置文件失败: {}", e))
 }
 
 impl Default for MultiAppConfig {
@@ -74,6 +85,8 @@ impl Default for MultiAppConfig {
             version: 2,
             apps,
             mcp: McpRoot::default(),
+            trash: Vec::new(),
+            codex_fragments: Vec::new(),
         }
     }
 }
@@ -91,51 +104,49 @@ impl MultiAppConfig {
         // 尝试读取文件
         let content = std::fs::read_to_string(&config_path)
             .map_err(|e| format!("读取配置文件失败: {}", e))?;
+        // 已加密的配置需先解锁；锁定时直接返回错误，不进入损坏恢复流程
+        let content = crate::store_crypto::decode_content(&content)?;
 
-        // 检查是否是旧版本格式（v1）
-        if let Ok(v1_config) = serde_json::from_str::<ProviderManager>(&content) {
-            log::info!("检测到v1配置，自动迁移到v2");
-
-            // 迁移到新格式
-            let mut apps = HashMap::new();
-            apps.insert("claude".to_string(), v1_config);
-            apps.insert("codex".to_string(), ProviderManager::default());
-
-            let config = Self {
-                version: 2,
-                apps,
-                mcp: McpRoot::default(),
-            };
-
-            // 迁移前备份旧版(v1)配置文件
-            let backup_dir = get_app_config_dir();
-            let ts = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
-            let backup_path = backup_dir.join(format!("config.v1.backup.{}.json", ts));
-
-            match copy_file(&config_path, &backup_path) {
-                Ok(()) => log::info!(
-                    "已备份旧版配置文件: {} -> {}",
-                    config_path.display(),
-                    backup_path.display()
-                ),
-                Err(e) => log::warn!("备份旧版配置文件失败: {}", e),
-            }
+        // 解析失败视为文件损坏：隔离并从备份恢复
+        let value = match parse_json(&content) {
+            Ok(value) => value,
+            Err(e) => return crate::store_health::recover(&config_path, &e),
+        };
+        let mut outcome = crate::config_migration::run(value, false)?;
+        let config = match Self::from_migrated(&outcome) {
+            Ok(config) => config,
+            Err(e) => return crate::store_health::recover(&config_path, &e),
+        };
+        if outcome.migrated() {
+            // 每一步迁移前备份，失败时不覆盖原文件
+            crate::config_migration::write_step_backups(&mut outcome)?;
 
             // 保存迁移后的配置
             config.save()?;
-            return Ok(config);
         }
+        crate::store_health::mark_healthy();
+        Ok(config)
+    }
+
+    /// 解析配置文本，必要时按迁移流水线升级到当前版本；返回 (配置, 是否发生迁移)
+    pub fn parse_with_migration(content: &str) -> Result<(Self, bool), String> {
+        let content = crate::store_crypto::decode_content(content)?;
+        let outcome = crate::config_migration::run(parse_json(&content)?, false)?;
+        Ok((Self::from_migrated(&outcome)?, outcome.migrated()))
+    }
 
-        // 尝试读取v2格式
-        serde_json::from_str::<Self>(&content).map_err(|e| format!("解析配置文件失败: {}", e))
+    fn from_migrated(outcome: &crate::config_migration::MigrationOutcome) -> Result<Self, String> {
+        serde_json::from_value::<Self>(outcome.value.clone())
+            .map_err(|e| format!("解析配置文件失败: {}", e))
     }
 
     /// 保存配置到文件
-    pub fn save(&self) -> Result<(), String> {
+    pub fn save(&self) -> Result<(), AppError> {
         let config_path = get_app_config_path();
+        let json = serde_json::to_string_pretty(self).map_err(AppError::SerializeJson)?;
+        // 启用加密时以密文写入；锁定状态下拒绝写入
+        let bytes = crate::store_crypto::encode_content(json.as_bytes())?;
+
         // 先备份旧版（若存在）到 ~/.cc-switch/config.json.bak，再写入新内容
         if config_path.exists() {
             let backup_path = get_app_config_dir().join("config.json.bak");
@@ -144,10 +155,70 @@ impl MultiAppConfig {
             }
         }
 
-        write_json_file(&config_path, self)?;
+        crate::config::atomic_write(&config_path, &bytes)?;
+
+        // 记录历史版本，便于追溯变更
+        match std::fs::read(&config_path) {
+            Ok(content) => {
+                if let Err(e) = crate::history::record_version(&content) {
+                    log::warn!("记录 config.json 历史版本失败: {}", e);
+                }
+            }
+            Err(e) => log::warn!("读取 config.json 以记录历史失败: {}", e),
+        }
         Ok(())
     }
 
+    /// 将供应商移入回收站
+    pub fn move_to_trash(&mut self, app: &AppType, provider: Provider) {
+        self.trash.push(TrashEntry {
+            app_type: app.as_str().to_string(),
+            provider,
+            deleted_at: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+
+    /// 从回收站取出指定应用下该 ID 最近删除的供应商
+    pub fn take_from_trash(&mut self, app: &AppType, id: &str) -> Option<TrashEntry> {
+        let idx = self
+            .trash
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.app_type == app.as_str() && e.provider.id == id)
+            .max_by_key(|(_, e)| e.deleted_at)
+            .map(|(i, _)| i)?;
+        Some(self.trash.remove(idx))
+    }
+
+    /// 清理回收站中超过 max_age_days 天的条目（0 表示不自动清理），返回清理数量
+    pub fn purge_expired_trash(&mut self, max_age_days: u64) -> usize {
+        if max_age_days == 0 {
+            return 0;
+        }
+        let cutoff = chrono::Utc::now().timestamp_millis()
+            - (max_age_days as i64).saturating_mul(24 * 60 * 60 * 1000);
+        let (kept, expired): (Vec<_>, Vec<_>) = std::mem::take(&mut self.trash)
+            .into_iter()
+            .partition(|e| e.deleted_at >= cutoff);
+        self.trash = kept;
+        self.forget_trashed_secrets(&expired);
+        expired.len()
+    }
+
+    /// 永久删除回收站条目后清理其钥匙串密钥（同 ID 供应商仍存在时保留）
+    pub fn forget_trashed_secrets(&self, entries: &[TrashEntry]) {
+        for entry in entries {
+            let app = AppType::from(entry.app_type.as_str());
+            let in_use = self
+                .get_manager(&app)
+                .map(|m| m.providers.contains_key(&entry.provider.id))
+                .unwrap_or(false);
+            if !in_use {
+                crate::keychain::forget(&app, &entry.provider.id);
+            }
+        }
+    }
+
     /// 获取指定应用的管理器
     pub fn get_manager(&self, app: &AppType) -> Option<&ProviderManager> {
         self.apps.get(app.as_str())
@@ -182,3 +253,77 @@ impl MultiAppConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_home;
+    use serde_json::json;
+
+    fn provider(id: &str, token: &str) -> Provider {
+        Provider::with_id(
+            id.to_string(),
+            id.to_string(),
+            json!({ "env": { "ANTHROPIC_AUTH_TOKEN": token } }),
+            None,
+        )
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let _home = test_home::enter();
+        let mut config = MultiAppConfig::default();
+        let manager = config.get_manager_mut(&AppType::Claude).unwrap();
+        manager
+            .providers
+            .insert("work".into(), provider("work", "sk-work"));
+        manager.current = "work".into();
+        config.save().unwrap();
+
+        let loaded = MultiAppConfig::load().unwrap();
+        let manager = loaded.get_manager(&AppType::Claude).unwrap();
+        assert_eq!(manager.current, "work");
+        assert_eq!(
+            manager.providers["work"].settings_config,
+            json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-work" } })
+        );
+        // 首次保存即记录历史版本
+        assert_eq!(crate::history::list_versions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn load_migrates_v1_config_and_keeps_backup() {
+        let _home = test_home::enter();
+        let v1 = json!({
+            "providers": { "old": provider("old", "sk-old") },
+            "current": "old",
+        });
+        let path = get_app_config_path();
+        std::fs::create_dir_all(get_app_config_dir()).unwrap();
+        std::fs::write(&path, serde_json::to_string(&v1).unwrap()).unwrap();
+
+        let config = MultiAppConfig::load().unwrap();
+        assert_eq!(config.version, 2);
+        assert_eq!(config.get_manager(&AppType::Claude).unwrap().current, "old");
+        assert!(config
+            .get_manager(&AppType::Codex)
+            .unwrap()
+            .providers
+            .is_empty());
+
+        // 迁移后的配置已写回，且保留了 v1 备份
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], 2);
+        let backups: Vec<_> = std::fs::read_dir(get_app_config_dir())
+            .unwrap()
+            .flatten()
+            .filter(|e| {
+                e.file_name()
+                    .to_string_lossy()
+                    .starts_with("config.v1.backup.")
+            })
+            .collect();
+        assert_eq!(backups.len(), 1);
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+
+use crate::settings::LogLevel;
+
+/// 日志文件名（不含扩展名，插件自动追加 .log）
+const LOG_FILE_NAME: &str = "cc-switch";
+/// 单个日志文件上限，超过后轮转
+const MAX_FILE_SIZE: u128 = 2 * 1024 * 1024;
+/// 轮转后保留的历史日志份数
+const KEEP_FILES: usize = 5;
+
+/// 日志目录：~/.cc-switch/logs
+pub fn log_dir() -> PathBuf {
+    crate::config::get_app_config_dir().join("logs")
+}
+
+/// 当前日志文件
+pub fn log_file_path() -> PathBuf {
+    log_dir().join(format!("{}.log", LOG_FILE_NAME))
+}
+
+fn level_filter(level: LogLevel) -> log::LevelFilter {
+    match level {
+        LogLevel::Error => log::LevelFilter::Error,
+        LogLevel::Warn => log::LevelFilter::Warn,
+        LogLevel::Info => log::LevelFilter::Info,
+        LogLevel::Debug => log::LevelFilter::Debug,
+        LogLevel::Trace => log::LevelFilter::Trace,
+    }
+}
+
+/// 构建日志插件：输出到终端与带轮转的日志文件，内容统一脱敏
+pub fn plugin<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_log::Builder::default()
+        // 插件内部不做级别过滤，实际级别由 log::set_max_level 控制，便于运行时调整
+        .level(log::LevelFilter::Trace)
+        .targets([
+            // JSON-RPC / MCP 模式下 stdout 用于响应，日志改写到 stderr
+            Target::new(if crate::rpc::requested() {
+                TargetKind::Stderr
+            } else {
+                TargetKind::Stdout
+            }),
+            Target::new(TargetKind::Folder {
+                path: log_dir(),
+                file_name: Some(LOG_FILE_NAME.to_string()),
+            }),
+        ])
+        .max_file_size(MAX_FILE_SIZE)
+        .rotation_strategy(RotationStrategy::KeepSome(KEEP_FILES))
+        // 统一对日志内容脱敏，避免密钥写入日志文件
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "{}[{}][{}] {}",
+                chrono::Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                record.target(),
+                record.level(),
+                crate::redact::redact_text(&message.to_string())
+            ))
+        })
+        .build()
+}
+
+/// 按设置应用日志级别（立即生效）
+pub fn apply_level(level: LogLevel) {
+    log::set_max_level(level_filter(level));
+}
+
+/// 读取当前日志文件末尾的若干行
+pub fn recent_lines(limit: usize) -> Result<Vec<String>, String> {
+    let path = log_file_path();
+    let content = match std::fs::read(&path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("读取日志文件失败: {}: {}", path.display(), e)),
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(limit);
+    Ok(lines[start..].iter().map(|l| l.to_string()).collect())
+}
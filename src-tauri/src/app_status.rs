@@ -0,0 +1,103 @@
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::config::ConfigStatus;
+use crate::error::AppError;
+use crate::wsl_env::{EnvironmentStatus, PlatformInfo};
+
+/// 单个应用的状态汇总
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStatus {
+    pub app_type: String,
+    pub config: ConfigStatus,
+    /// 当前生效的配置目录
+    pub config_dir: String,
+    /// 切换时写入的 live 文件
+    pub live_files: Vec<String>,
+    pub current_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_name: Option<String>,
+    /// live 配置是否偏离当前供应商的存储
+    pub drifted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift_changes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift_error: Option<String>,
+    pub environments: Vec<EnvironmentStatus>,
+}
+
+/// 所有应用的状态，供界面加载时一次获取
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllStatus {
+    pub platform: PlatformInfo,
+    pub apps: Vec<AppStatus>,
+}
+
+fn app_status(config: &MultiAppConfig, app: &AppType) -> AppStatus {
+    let config_status = match app {
+        AppType::Claude => crate::config::get_claude_config_status(),
+        AppType::Codex => crate::codex_config::get_codex_config_status(),
+    };
+    let config_dir = match app {
+        AppType::Claude => crate::config::get_claude_config_dir(),
+        AppType::Codex => crate::codex_config::get_codex_config_dir(),
+    };
+    let (current_id, current_name) = match config.get_manager(app) {
+        Some(manager) => (
+            manager.current.clone(),
+            manager
+                .providers
+                .get(&manager.current)
+                .map(|p| p.name.clone()),
+        ),
+        None => (String::new(), None),
+    };
+    let (drift_changes, drift_error) = match crate::drift::detect(config, app) {
+        Ok(report) => (report.map(|r| r.changes.len()), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    AppStatus {
+        app_type: app.as_str().to_string(),
+        config: config_status,
+        config_dir: config_dir.to_string_lossy().to_string(),
+        live_files: crate::backup::live_files(app)
+            .into_iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+        current_id,
+        current_name,
+        drifted: drift_changes.is_some(),
+        drift_changes,
+        drift_error,
+        environments: crate::wsl_env::environment_status(app),
+    }
+}
+
+/// 并发收集各应用状态（涉及文件读取与 WSL 命令，均在阻塞线程中执行）
+pub async fn collect(config: MultiAppConfig) -> Result<AllStatus, AppError> {
+    let config = Arc::new(config);
+    let handles: Vec<_> = [AppType::Claude, AppType::Codex]
+        .into_iter()
+        .map(|app| {
+            let config = config.clone();
+            tauri::async_runtime::spawn_blocking(move || app_status(&config, &app))
+        })
+        .collect();
+
+    let mut apps = Vec::with_capacity(handles.len());
+    for handle in handles {
+        apps.push(
+            handle
+                .await
+                .map_err(|e| format!("获取应用状态失败: {}", e))?,
+        );
+    }
+    Ok(AllStatus {
+        platform: crate::wsl_env::platform_info(),
+        apps,
+    })
+}
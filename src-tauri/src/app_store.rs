@@ -5,6 +5,7 @@ use tauri_plugin_store::StoreExt;
 
 /// Store 中的键名
 const STORE_KEY_APP_CONFIG_DIR: &str = "app_config_dir_override";
+const STORE_KEY_INSTANCE_ID: &str = "instance_id";
 
 /// 全局缓存的 AppHandle (在应用启动时设置)
 static APP_HANDLE: OnceLock<RwLock<Option<tauri::AppHandle>>> = OnceLock::new();
@@ -18,7 +19,7 @@ pub fn set_app_handle(handle: tauri::AppHandle) {
 }
 
 /// 获取全局 AppHandle
-fn get_app_handle() -> Option<tauri::AppHandle> {
+pub fn get_app_handle() -> Option<tauri::AppHandle> {
     let store = APP_HANDLE.get()?;
     let guard = store.read().ok()?;
     guard.as_ref().cloned()
@@ -105,18 +106,48 @@ pub fn set_app_config_dir_to_store(
     Ok(())
 }
 
+/// 获取本机实例 ID（首次调用时生成并写入 Store，用于区分共享目录中的写入者）
+pub fn get_instance_id() -> String {
+    static INSTANCE_ID: OnceLock<String> = OnceLock::new();
+    INSTANCE_ID
+        .get_or_init(|| {
+            let store = get_app_handle()
+                .and_then(|app| app.store_builder("app_paths.json").build().ok());
+            if let Some(store) = &store {
+                if let Some(Value::String(id)) = store.get(STORE_KEY_INSTANCE_ID) {
+                    if !id.trim().is_empty() {
+                        return id;
+                    }
+                }
+            }
+
+            let id: String = crate::crypto::random_salt()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            if let Some(store) = store {
+                store.set(STORE_KEY_INSTANCE_ID, Value::String(id.clone()));
+                if let Err(e) = store.save() {
+                    log::warn!("保存实例 ID 失败: {}", e);
+                }
+            }
+            id
+        })
+        .clone()
+}
+
 /// 解析路径，支持 ~ 开头的相对路径
 fn resolve_path(raw: &str) -> PathBuf {
     if raw == "~" {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::config::home_dir() {
             return home;
         }
     } else if let Some(stripped) = raw.strip_prefix("~/") {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::config::home_dir() {
             return home.join(stripped);
         }
     } else if let Some(stripped) = raw.strip_prefix("~\\") {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::config::home_dir() {
             return home.join(stripped);
         }
     }
@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use tauri_plugin_updater::{Updater, UpdaterExt};
+
+/// GitHub 最新发布信息接口
+const LATEST_RELEASE_API: &str =
+    "https://api.github.com/repos/farion1231/cc-switch/releases/latest";
+
+/// 启动后首次后台检查前的等待时间，避免与启动流程争抢资源
+const INITIAL_DELAY: Duration = Duration::from_secs(30);
+
+/// 更新检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCheck {
+    pub available: bool,
+    pub current_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+/// 发布附件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub url: String,
+    pub size: u64,
+}
+
+/// GitHub 最新发布与当前版本的比较结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestRelease {
+    pub version: String,
+    pub current_version: String,
+    pub update_available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    pub html_url: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+    #[serde(default)]
+    size: u64,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    body: Option<String>,
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+fn parse_version(raw: &str) -> Result<semver::Version, String> {
+    let trimmed = raw.trim();
+    semver::Version::parse(trimmed.strip_prefix('v').unwrap_or(trimmed))
+        .map_err(|e| format!("无法解析版本号 {}: {}", raw, e))
+}
+
+/// 查询 GitHub 最新发布并与当前版本比较（不依赖 updater 签名清单，便携版同样可用）
+pub async fn latest_release(handle: &tauri::AppHandle) -> Result<LatestRelease, String> {
+    let current_version = handle.package_info().version.to_string();
+    let client = crate::http_client::builder()?
+        .timeout(Duration::from_secs(15))
+        .user_agent(format!("cc-switch/{}", current_version))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let resp = client
+        .get(LATEST_RELEASE_API)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("查询最新版本失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("查询最新版本失败: HTTP {}", resp.status()));
+    }
+    let release: GithubRelease = resp
+        .json()
+        .await
+        .map_err(|e| format!("解析发布信息失败: {}", e))?;
+
+    let latest = parse_version(&release.tag_name)?;
+    let update_available = latest > parse_version(&current_version)?;
+    Ok(LatestRelease {
+        version: latest.to_string(),
+        current_version,
+        update_available,
+        notes: release.body.filter(|b| !b.trim().is_empty()),
+        published_at: release.published_at,
+        html_url: release.html_url,
+        assets: release
+            .assets
+            .into_iter()
+            .map(|a| ReleaseAsset {
+                name: a.name,
+                url: a.browser_download_url,
+                size: a.size,
+            })
+            .collect(),
+    })
+}
+
+/// 下载进度事件（update-progress）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateProgress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// 按代理设置构建 Updater（签名由插件使用 tauri.conf.json 中的公钥校验）
+fn updater(handle: &tauri::AppHandle) -> Result<Updater, String> {
+    let mut builder = handle.updater_builder();
+    let proxy = crate::settings::get_settings().proxy;
+    if proxy.mode == crate::settings::ProxyMode::Custom {
+        let url = tauri::Url::parse(proxy.url.trim())
+            .map_err(|e| crate::redact::redact_text(&format!("代理地址无效: {}", e)))?;
+        builder = builder.proxy(url);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("初始化更新器失败: {}", e))
+}
+
+/// 检查最新版本
+pub async fn check(handle: &tauri::AppHandle) -> Result<UpdateCheck, String> {
+    let current_version = handle.package_info().version.to_string();
+    let update = updater(handle)?
+        .check()
+        .await
+        .map_err(|e| format!("检查更新失败: {}", e))?;
+    Ok(match update {
+        Some(update) => UpdateCheck {
+            available: true,
+            current_version,
+            version: Some(update.version),
+            notes: update.body,
+            date: update.date.map(|d| d.to_string()),
+        },
+        None => UpdateCheck {
+            available: false,
+            current_version,
+            version: None,
+            notes: None,
+            date: None,
+        },
+    })
+}
+
+/// 下载、校验签名并安装更新（需由用户确认后调用）；成功后重启应用
+pub async fn install(handle: &tauri::AppHandle) -> Result<(), String> {
+    if crate::config::portable_data_dir().is_some() {
+        return Err("便携版不支持自动更新，请手动下载新版本".to_string());
+    }
+    let update = updater(handle)?
+        .check()
+        .await
+        .map_err(|e| format!("检查更新失败: {}", e))?
+        .ok_or_else(|| "当前已是最新版本".to_string())?;
+
+    log::info!("开始下载更新: {}", update.version);
+    let mut downloaded: u64 = 0;
+    update
+        .download_and_install(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = handle.emit("update-progress", UpdateProgress { downloaded, total });
+            },
+            || log::info!("更新下载完成，开始安装"),
+        )
+        .await
+        .map_err(|e| format!("安装更新失败: {}", e))?;
+
+    crate::audit::record(
+        "install_update",
+        None,
+        None,
+        serde_json::json!({ "version": update.version }),
+    );
+    if let Some(state) = handle.try_state::<crate::store::AppState>() {
+        state.shutdown().await;
+    }
+    handle.restart()
+}
+
+/// 后台定期检查更新（按设置开关与间隔），发现新版本时通知前端与系统
+pub fn start_periodic_check(handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(INITIAL_DELAY).await;
+        let mut notified: Option<String> = None;
+        loop {
+            let settings = crate::settings::get_settings();
+            if settings.auto_check_updates {
+                match check(&handle).await {
+                    Ok(result) if result.available => {
+                        // 同一版本只提醒一次
+                        if notified != result.version {
+                            notified = result.version.clone();
+                            let _ = handle.emit("update-available", &result);
+                            crate::notify::send(
+                                "发现新版本",
+                                &format!(
+                                    "CC Switch {} 可用",
+                                    result.version.as_deref().unwrap_or_default()
+                                ),
+                            );
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::warn!("后台检查更新失败: {}", e),
+                }
+            }
+            let hours = settings.update_check_interval_hours.max(1);
+            tokio::time::sleep(Duration::from_secs(hours * 3600)).await;
+        }
+    });
+}
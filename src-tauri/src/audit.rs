@@ -0,0 +1,185 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::app_config::AppType;
+use crate::config::get_app_config_dir;
+
+const AUDIT_FILE: &str = "audit.jsonl";
+/// 单个日志文件超过该大小后轮转
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// 最多保留的轮转文件数
+const MAX_ROTATED_FILES: usize = 5;
+
+/// 串行化写入，避免并发追加交错
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 审计记录（每行一条 JSON）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub ts: i64,
+    /// 操作者：系统用户名@实例 ID
+    pub actor: String,
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// 操作详情（敏感字段已脱敏）
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub details: Value,
+}
+
+fn audit_dir() -> PathBuf {
+    get_app_config_dir().join("audit")
+}
+
+fn actor() -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string());
+    let instance = crate::app_store::get_instance_id();
+    format!("{}@{}", user, &instance[..instance.len().min(8)])
+}
+
+pub(crate) fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    [
+        "key",
+        "token",
+        "secret",
+        "password",
+        "passphrase",
+        "credential",
+        "authorization",
+    ]
+    .iter()
+    .any(|k| key.contains(k))
+}
+
+/// 脱敏：敏感字段的字符串值替换为带摘要前缀的占位符，便于判断是否发生变化
+pub fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut out = Map::new();
+            for (k, v) in map {
+                let redacted = match v {
+                    Value::String(s) if is_secret_key(k) && !s.is_empty() => {
+                        let digest = crate::crypto::sha256_hex(s.as_bytes());
+                        Value::String(format!("<redacted:{}>", &digest[..8]))
+                    }
+                    _ => redact(v),
+                };
+                out.insert(k.clone(), redacted);
+            }
+            Value::Object(out)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// 列出轮转后的日志文件（按时间倒序）
+fn rotated_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(audit_dir()) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.starts_with("audit-") && s.ends_with(".jsonl"))
+                    .unwrap_or(false)
+            })
+            .collect(),
+        Err(_) => return vec![],
+    };
+    files.sort();
+    files.reverse();
+    files
+}
+
+fn rotate_if_needed(path: &PathBuf) -> Result<(), String> {
+    let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if size < MAX_FILE_BYTES {
+        return Ok(());
+    }
+    let ts = Utc::now().format("%Y%m%d_%H%M%S_%3f");
+    let rotated = audit_dir().join(format!("audit-{}.jsonl", ts));
+    fs::rename(path, &rotated).map_err(|e| format!("轮转审计日志失败: {}", e))?;
+    for old in rotated_files().into_iter().skip(MAX_ROTATED_FILES) {
+        if let Err(e) = fs::remove_file(&old) {
+            log::warn!("删除旧审计日志失败: {}: {}", old.display(), e);
+        }
+    }
+    Ok(())
+}
+
+fn append(record: &AuditRecord) -> Result<(), String> {
+    let _guard = WRITE_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let dir = audit_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("创建审计目录失败: {}", e))?;
+    let path = dir.join(AUDIT_FILE);
+    rotate_if_needed(&path)?;
+
+    let mut line =
+        serde_json::to_string(record).map_err(|e| format!("序列化审计记录失败: {}", e))?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("打开审计日志失败: {}", e))?;
+    file.write_all(line.as_bytes())
+        .map_err(|e| format!("写入审计日志失败: {}", e))
+}
+
+/// 记录一次变更；details 会先脱敏。写入失败只记日志，不影响业务操作
+pub fn record(action: &str, app: Option<&AppType>, target: Option<&str>, details: Value) {
+    let record = AuditRecord {
+        ts: Utc::now().timestamp_millis(),
+        actor: actor(),
+        action: action.to_string(),
+        app_type: app.map(|a| a.as_str().to_string()),
+        target: target.map(|t| t.to_string()),
+        details: redact(&details),
+    };
+    if let Err(e) = append(&record) {
+        log::warn!("写入审计日志失败: {}", e);
+    }
+}
+
+/// 读取审计日志（按时间倒序），包含轮转文件
+pub fn read_log(limit: usize) -> Result<Vec<AuditRecord>, String> {
+    let mut files = vec![audit_dir().join(AUDIT_FILE)];
+    files.extend(rotated_files());
+
+    let mut result = Vec::new();
+    for path in files {
+        if !path.exists() {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("读取审计日志失败: {}: {}", path.display(), e))?;
+        for line in content.lines().rev() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<AuditRecord>(line) {
+                Ok(record) => result.push(record),
+                Err(e) => log::warn!("跳过无法解析的审计记录: {}", e),
+            }
+            if limit > 0 && result.len() >= limit {
+                return Ok(result);
+            }
+        }
+    }
+    Ok(result)
+}
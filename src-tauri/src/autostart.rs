@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+/// 登录项名称（Windows 注册表值名 / Linux desktop 文件名 / macOS LaunchAgent 标签）
+#[cfg(target_os = "windows")]
+const ENTRY_NAME: &str = "CC Switch";
+#[cfg(target_os = "macos")]
+const LAUNCH_AGENT_LABEL: &str = "com.ccswitch.desktop";
+
+fn current_exe() -> Result<PathBuf, String> {
+    std::env::current_exe().map_err(|e| format!("获取可执行路径失败: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn desktop_entry_path() -> Result<PathBuf, String> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| crate::config::home_dir().map(|h| h.join(".config")))
+        .ok_or_else(|| "无法获取用户主目录".to_string())?;
+    Ok(config.join("autostart").join("cc-switch.desktop"))
+}
+
+/// 当前是否已注册为登录启动项
+#[cfg(target_os = "linux")]
+pub fn is_enabled() -> Result<bool, String> {
+    Ok(desktop_entry_path()?.exists())
+}
+
+/// 注册或移除登录启动项（XDG autostart desktop 文件）
+#[cfg(target_os = "linux")]
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let path = desktop_entry_path()?;
+    if !enabled {
+        return crate::config::delete_file(&path).map_err(Into::into);
+    }
+    // AppImage 运行时 current_exe 指向临时挂载点，需使用 APPIMAGE 指向的文件
+    let exe = std::env::var_os("APPIMAGE")
+        .map(PathBuf::from)
+        .map_or_else(current_exe, Ok)?;
+    let content = format!(
+        "[Desktop Entry]\nType=Application\nName=CC Switch\nExec=\"{}\"\nX-GNOME-Autostart-enabled=true\nNoDisplay=true\n",
+        exe.display()
+    );
+    crate::config::write_text_file(&path, &content).map_err(Into::into)
+}
+
+#[cfg(target_os = "macos")]
+fn launch_agent_path() -> Result<PathBuf, String> {
+    Ok(crate::config::home_dir()
+        .ok_or_else(|| "无法获取用户主目录".to_string())?
+        .join("Library")
+        .join("LaunchAgents")
+        .join(format!("{}.plist", LAUNCH_AGENT_LABEL)))
+}
+
+#[cfg(target_os = "macos")]
+pub fn is_enabled() -> Result<bool, String> {
+    Ok(launch_agent_path()?.exists())
+}
+
+/// 注册或移除登录启动项（~/Library/LaunchAgents 下的 plist）
+#[cfg(target_os = "macos")]
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let path = launch_agent_path()?;
+    if !enabled {
+        return crate::config::delete_file(&path).map_err(Into::into);
+    }
+    let exe = current_exe()?;
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let content = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        LAUNCH_AGENT_LABEL,
+        escape(&exe.to_string_lossy())
+    );
+    crate::config::write_text_file(&path, &content).map_err(Into::into)
+}
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<std::process::Output, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    std::process::Command::new("reg.exe")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("启动 reg.exe 失败: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+pub fn is_enabled() -> Result<bool, String> {
+    Ok(reg(&["query", RUN_KEY, "/v", ENTRY_NAME])?.status.success())
+}
+
+/// 注册或移除登录启动项（HKCU\...\Run 注册表值）
+#[cfg(target_os = "windows")]
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let output = if enabled {
+        let exe = format!("\"{}\"", current_exe()?.display());
+        reg(&[
+            "add", RUN_KEY, "/v", ENTRY_NAME, "/t", "REG_SZ", "/d", &exe, "/f",
+        ])?
+    } else {
+        if !is_enabled()? {
+            return Ok(());
+        }
+        reg(&["delete", RUN_KEY, "/v", ENTRY_NAME, "/f"])?
+    };
+    if !output.status.success() {
+        return Err(format!(
+            "修改登录启动项失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// 启动时按设置同步登录启动项（程序移动位置后刷新路径）
+pub fn sync_with_settings() {
+    let enabled = crate::settings::get_settings().launch_at_login;
+    if !enabled && !is_enabled().unwrap_or(false) {
+        return;
+    }
+    if let Err(e) = set_enabled(enabled) {
+        log::warn!("同步登录启动项失败: {}", e);
+    }
+}
@@ -0,0 +1,68 @@
+use serde::Serialize;
+
+use crate::app_config::AppType;
+
+/// 命令接口版本：命令参数或返回结构发生不兼容变化时递增
+pub const COMMAND_SCHEMA_VERSION: u32 = 1;
+
+/// 后端能力信息，供前端与外部集成按功能探测
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendInfo {
+    pub version: String,
+    pub schema_version: u32,
+    /// windows / macos / linux
+    pub os: String,
+    pub app_types: Vec<String>,
+    pub features: Features,
+}
+
+/// 当前构建与平台可用的功能
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Features {
+    /// WSL 目标环境（仅 Windows）
+    pub wsl: bool,
+    /// SSH / WSL exec 远程环境（需要本机 ssh 或 WSL）
+    pub remote_env: bool,
+    /// 系统钥匙串保存密钥（探测钥匙串服务是否可访问）
+    pub keychain: bool,
+    /// config.json 加密
+    pub encryption: bool,
+    /// WebDAV 同步
+    pub webdav_sync: bool,
+    /// S3 备份
+    pub s3_backup: bool,
+    /// 应用内自动更新（便携版不可用）
+    pub auto_update: bool,
+    /// 后台任务列表与取消
+    pub tasks: bool,
+    /// 中断切换的恢复
+    pub switch_recovery: bool,
+}
+
+/// 收集后端信息；会探测系统钥匙串，可能阻塞，需在阻塞线程中调用
+pub fn info(handle: &tauri::AppHandle) -> BackendInfo {
+    let platform = crate::wsl_env::platform_info();
+    BackendInfo {
+        version: handle.package_info().version.to_string(),
+        schema_version: COMMAND_SCHEMA_VERSION,
+        os: platform.os,
+        app_types: [AppType::Claude, AppType::Codex]
+            .iter()
+            .map(|app| app.as_str().to_string())
+            .collect(),
+        features: Features {
+            wsl: platform.supports_wsl,
+            remote_env: platform.supports_wsl
+                || crate::cli_detect::find_native_binary("ssh").is_some(),
+            keychain: crate::keychain::available(),
+            encryption: true,
+            webdav_sync: true,
+            s3_backup: true,
+            auto_update: cfg!(desktop) && crate::config::portable_data_dir().is_none(),
+            tasks: true,
+            switch_recovery: true,
+        },
+    }
+}
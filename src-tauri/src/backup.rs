@@ -0,0 +1,281 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app_config::AppType;
+use crate::config::{copy_file, get_app_config_dir, write_json_file};
+use crate::settings::TargetEnv;
+
+const MANIFEST_FILE: &str = "manifest.json";
+/// WSL 发行版内的备份目录（相对发行版用户主目录）
+const WSL_BACKUPS_DIR: &str = ".cc-switch-backups";
+
+/// 单个备份文件记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    /// 备份目录中的文件名
+    pub name: String,
+    /// 备份时 live 文件所在的原始路径
+    pub original_path: String,
+}
+
+/// 备份清单（写入每个备份目录的 manifest.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub id: String,
+    pub app_type: String,
+    pub created_at: i64,
+    pub files: Vec<BackupFile>,
+}
+
+/// live 配置备份根目录 ~/.cc-switch/backups
+pub fn get_backups_root() -> PathBuf {
+    get_app_config_dir().join("backups")
+}
+
+fn app_backup_dir(app: &AppType) -> PathBuf {
+    get_backups_root().join(app.as_str())
+}
+
+/// 各应用需要备份的 live 文件
+pub(crate) fn live_files(app: &AppType) -> Vec<PathBuf> {
+    match app {
+        AppType::Claude => vec![crate::config::get_claude_settings_path()],
+        AppType::Codex => vec![
+            crate::codex_config::get_codex_auth_path(),
+            crate::codex_config::get_codex_config_path(),
+        ],
+    }
+}
+
+fn read_manifest(dir: &Path) -> Option<BackupInfo> {
+    let path = dir.join(MANIFEST_FILE);
+    crate::config::read_json_file::<BackupInfo>(&path).ok()
+}
+
+/// 在写入 live 配置前备份现有文件到 `~/.cc-switch/backups/<app>/<timestamp>/`
+/// 返回备份 ID；若没有任何 live 文件存在则返回 Ok(None)
+pub fn backup_live_configs(app: &AppType) -> Result<Option<String>, String> {
+    let sources: Vec<PathBuf> = live_files(app).into_iter().filter(|p| p.exists()).collect();
+    if sources.is_empty() {
+        return Ok(None);
+    }
+
+    let now = Utc::now();
+    let base_id = now.format("%Y%m%d_%H%M%S_%3f").to_string();
+    let root = app_backup_dir(app);
+    let mut id = base_id.clone();
+    let mut suffix = 2;
+    while root.join(&id).exists() {
+        id = format!("{}-{}", base_id, suffix);
+        suffix += 1;
+    }
+
+    let dir = root.join(&id);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {}: {}", dir.display(), e))?;
+
+    let mut files = Vec::new();
+    for src in sources {
+        let name = src
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".into());
+        copy_file(&src, &dir.join(&name))?;
+        files.push(BackupFile {
+            name,
+            original_path: src.to_string_lossy().to_string(),
+        });
+    }
+
+    let info = BackupInfo {
+        id: id.clone(),
+        app_type: app.as_str().to_string(),
+        created_at: now.timestamp_millis(),
+        files,
+    };
+    write_json_file(&dir.join(MANIFEST_FILE), &info)?;
+    // 切换、恢复前的自动备份不发系统通知，仅用户发起的备份（如 S3）提示完成
+    log::info!("已备份 {} live 配置: {}", app.as_str(), dir.display());
+
+    if let Err(e) = cleanup_backups(app) {
+        log::warn!("清理旧备份失败: {}", e);
+    }
+    if let Err(e) = mirror_to_wsl(app, &info, &dir) {
+        log::warn!("同步备份到 WSL 失败: {}", e);
+    }
+
+    Ok(Some(id))
+}
+
+/// 目标环境为 WSL 时，将备份同时保存到发行版的 `~/.cc-switch-backups/<app>/<id>/`，
+/// 即使 Windows 用户目录被清空，Linux 侧仍保留可恢复的历史
+fn mirror_to_wsl(app: &AppType, info: &BackupInfo, dir: &Path) -> Result<(), String> {
+    let env = crate::wsl_env::active_environment(&crate::settings::get_settings(), app);
+    if env.target_env != TargetEnv::Wsl {
+        return Ok(());
+    }
+    let names: Vec<&str> = info
+        .files
+        .iter()
+        .map(|f| f.name.as_str())
+        .chain(std::iter::once(MANIFEST_FILE))
+        .collect();
+
+    if crate::remote_env::uses_mirror(&env) {
+        // exec 模式：经 wsl.exe 写入发行版，仅按份数清理
+        let rel = format!("{}/{}", WSL_BACKUPS_DIR, app.as_str());
+        for name in names {
+            let src = dir.join(name);
+            let data = fs::read(&src)
+                .map_err(|e| format!("读取备份文件失败: {}: {}", src.display(), e))?;
+            crate::remote_env::push_file(&env, &format!("{}/{}/{}", rel, info.id, name), &data)?;
+        }
+        let max_count = crate::settings::get_settings().backup_max_count;
+        if max_count > 0 {
+            crate::remote_env::prune_dir(&env, &rel, max_count)?;
+        }
+        return Ok(());
+    }
+
+    let root = crate::wsl_env::env_home(&env)?
+        .join(WSL_BACKUPS_DIR)
+        .join(app.as_str());
+    let target = root.join(&info.id);
+    fs::create_dir_all(&target)
+        .map_err(|e| format!("创建备份目录失败: {}: {}", target.display(), e))?;
+    for name in names {
+        copy_file(&dir.join(name), &target.join(name))?;
+    }
+    cleanup_dir(&root)?;
+    Ok(())
+}
+
+/// 列出指定应用的备份（按时间倒序）
+pub fn list_backups(app: &AppType) -> Result<Vec<BackupInfo>, String> {
+    list_dir(&app_backup_dir(app))
+}
+
+fn list_dir(root: &Path) -> Result<Vec<BackupInfo>, String> {
+    if !root.exists() {
+        return Ok(vec![]);
+    }
+
+    let entries =
+        fs::read_dir(root).map_err(|e| format!("读取备份目录失败: {}: {}", root.display(), e))?;
+    let mut result: Vec<BackupInfo> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| read_manifest(&e.path()))
+        .collect();
+    result.sort_by_key(|info| std::cmp::Reverse(info.created_at));
+    Ok(result)
+}
+
+/// 按设置中的保留策略（最大份数 / 最长天数）清理旧备份
+pub fn cleanup_backups(app: &AppType) -> Result<usize, String> {
+    cleanup_dir(&app_backup_dir(app))
+}
+
+fn cleanup_dir(root: &Path) -> Result<usize, String> {
+    let settings = crate::settings::get_settings();
+    let max_count = settings.backup_max_count;
+    let max_age_days = settings.backup_max_age_days;
+
+    let backups = list_dir(root)?;
+    let now = Utc::now().timestamp_millis();
+    let max_age_ms = (max_age_days as i64).saturating_mul(24 * 60 * 60 * 1000);
+
+    let mut removed = 0;
+    for (idx, info) in backups.iter().enumerate() {
+        // 始终保留最新一份，避免策略配置异常时清空所有备份
+        if idx == 0 {
+            continue;
+        }
+        let over_count = max_count > 0 && idx >= max_count;
+        let too_old = max_age_days > 0 && now - info.created_at > max_age_ms;
+        if !(over_count || too_old) {
+            continue;
+        }
+        let dir = root.join(&info.id);
+        match fs::remove_dir_all(&dir) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("删除旧备份失败: {}: {}", dir.display(), e),
+        }
+    }
+    Ok(removed)
+}
+
+/// 备份文件对应的当前 live 路径（按文件名映射到当前生效的配置目录）
+pub(crate) fn live_target_for(app: &AppType, name: &str) -> PathBuf {
+    match app {
+        AppType::Claude => crate::config::get_claude_config_dir().join(name),
+        AppType::Codex => crate::codex_config::get_codex_config_dir().join(name),
+    }
+}
+
+/// 将指定备份恢复到 live 位置；任一文件写入失败则回滚已写入的文件
+pub fn restore_backup(app: &AppType, id: &str) -> Result<BackupInfo, String> {
+    if id.trim().is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("无效的备份 ID: {}", id));
+    }
+    let dir = app_backup_dir(app).join(id);
+    let info = read_manifest(&dir).ok_or_else(|| format!("备份不存在: {}", id))?;
+
+    // 先读取全部备份内容，避免写到一半才发现文件缺失
+    let mut payloads = Vec::new();
+    for file in info.files.iter() {
+        let src = dir.join(&file.name);
+        let bytes =
+            fs::read(&src).map_err(|e| format!("读取备份文件失败: {}: {}", src.display(), e))?;
+        payloads.push((live_target_for(app, &file.name), Some(bytes)));
+    }
+
+    // 恢复前再备份一次当前 live，确保恢复操作本身可撤销
+    if let Err(e) = backup_live_configs(app) {
+        log::warn!("恢复前备份 live 配置失败: {}", e);
+    }
+
+    crate::config::write_files_atomically(&payloads)?;
+
+    log::info!("已恢复 {} 备份: {}", app.as_str(), id);
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_home;
+
+    #[test]
+    fn backup_then_restore_live_configs() {
+        let home = test_home::enter();
+        let dir = home.path().join(".claude");
+        fs::create_dir_all(&dir).unwrap();
+        let settings = dir.join("settings.json");
+        fs::write(&settings, r#"{"env":{"A":"old"}}"#).unwrap();
+
+        let id = backup_live_configs(&AppType::Claude)
+            .unwrap()
+            .expect("存在 live 文件时应生成备份");
+        fs::write(&settings, r#"{"env":{"A":"new"}}"#).unwrap();
+
+        let info = restore_backup(&AppType::Claude, &id).unwrap();
+        assert_eq!(info.id, id);
+        assert_eq!(
+            fs::read_to_string(&settings).unwrap(),
+            r#"{"env":{"A":"old"}}"#
+        );
+        // 恢复前的 live 也已备份，恢复操作本身可撤销
+        assert_eq!(list_backups(&AppType::Claude).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn backup_skips_missing_live_files() {
+        let _home = test_home::enter();
+        assert_eq!(backup_live_configs(&AppType::Codex).unwrap(), None);
+        assert!(restore_backup(&AppType::Codex, "../escape").is_err());
+    }
+}
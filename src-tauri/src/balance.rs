@@ -0,0 +1,248 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::provider::{UsageData, UsageResult};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+/// new-api / one-api 的额度单位：500000 = 1 USD
+const NEW_API_QUOTA_PER_UNIT: f64 = 500_000.0;
+
+/// 余额接口类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BalanceKind {
+    /// OpenAI 兼容：`/v1/dashboard/billing/subscription` + `/v1/dashboard/billing/usage`
+    OpenAiBilling,
+    /// new-api / one-api：`/api/user/self`
+    NewApi,
+    /// 自定义 GET 接口 + JSON 字段路径
+    Custom,
+}
+
+/// 供应商余额查询配置（存于 meta.balance）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceConfig {
+    pub enabled: bool,
+    pub kind: BalanceKind,
+    /// 自定义接口地址，可使用 `{baseUrl}` 占位；为空时按类型使用默认路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 查询使用的令牌（为空时使用供应商 API Key）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// new-api 需要的用户 ID（New-Api-User 请求头）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// 自定义接口中剩余额度 / 总额度 / 已用额度的字段路径（如 `data.balance`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub used_path: Option<String>,
+    /// 数值换算除数（原始值 / divisor）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub divisor: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// 按 `a.b[0].c` 形式的路径读取 JSON 数值（兼容数字字符串）
+fn number_at(value: &Value, path: &str) -> Option<f64> {
+    let mut current = value;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let (key, index) = match segment.split_once('[') {
+            Some((key, rest)) => (key, rest.trim_end_matches(']').parse::<usize>().ok()),
+            None => (segment, None),
+        };
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+        if let Some(i) = index {
+            current = current.get(i)?;
+        }
+    }
+    current
+        .as_f64()
+        .or_else(|| current.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+/// 去掉末尾的 `/v1`，得到站点根地址
+fn site_root(base_url: &str) -> String {
+    let trimmed = base_url.trim().trim_end_matches('/');
+    trimmed.strip_suffix("/v1").unwrap_or(trimmed).to_string()
+}
+
+async fn get_json(
+    client: &Client,
+    url: &str,
+    token: &str,
+    user_id: Option<&str>,
+) -> Result<Value, String> {
+    let mut req = client.get(url).bearer_auth(token);
+    if let Some(uid) = user_id {
+        req = req.header("New-Api-User", uid);
+    }
+    let resp = req
+        .send()
+        .await
+        .map_err(|e| format!("请求余额接口失败: {}", e))?;
+    let status = resp.status();
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| format!("读取余额接口响应失败: {}", e))?;
+    if !status.is_success() {
+        let snippet: String = text.chars().take(200).collect();
+        return Err(format!("余额接口返回 {}: {}", status.as_u16(), snippet));
+    }
+    serde_json::from_str(&text).map_err(|e| format!("余额接口返回的不是 JSON: {}", e))
+}
+
+/// 查询余额并转换为用量数据
+pub async fn query_balance(
+    config: &BalanceConfig,
+    api_key: &str,
+    base_url: &str,
+) -> Result<UsageData, String> {
+    let timeout = config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(2, 30);
+    let client = crate::http_client::builder()?
+        .timeout(Duration::from_secs(timeout))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let token = config
+        .token
+        .as_deref()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(api_key);
+    let root = site_root(base_url);
+    let url_or = |default: String| {
+        config
+            .url
+            .as_deref()
+            .filter(|u| !u.trim().is_empty())
+            .map(|u| u.replace("{baseUrl}", &root))
+            .unwrap_or(default)
+    };
+
+    let (total, used, remaining, default_unit) = match config.kind {
+        BalanceKind::OpenAiBilling => {
+            let sub = get_json(
+                &client,
+                &url_or(format!("{}/v1/dashboard/billing/subscription", root)),
+                token,
+                None,
+            )
+            .await?;
+            let total = number_at(&sub, "hard_limit_usd")
+                .or_else(|| number_at(&sub, "system_hard_limit_usd"));
+
+            let end = chrono::Utc::now().date_naive() + chrono::Duration::days(1);
+            let start = end - chrono::Duration::days(100);
+            let usage = get_json(
+                &client,
+                &format!(
+                    "{}/v1/dashboard/billing/usage?start_date={}&end_date={}",
+                    root,
+                    start.format("%Y-%m-%d"),
+                    end.format("%Y-%m-%d")
+                ),
+                token,
+                None,
+            )
+            .await?;
+            // total_usage 单位为美分
+            let used = number_at(&usage, "total_usage").map(|v| v / 100.0);
+            let remaining = match (total, used) {
+                (Some(t), Some(u)) => Some(t - u),
+                _ => None,
+            };
+            (total, used, remaining, "USD")
+        }
+        BalanceKind::NewApi => {
+            let body = get_json(
+                &client,
+                &url_or(format!("{}/api/user/self", root)),
+                token,
+                config.user_id.as_deref(),
+            )
+            .await?;
+            if body.get("success").and_then(|v| v.as_bool()) == Some(false) {
+                let msg = body
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("未知错误");
+                return Err(format!("余额接口返回失败: {}", msg));
+            }
+            let divisor = config.divisor.unwrap_or(NEW_API_QUOTA_PER_UNIT);
+            let remaining = number_at(&body, "data.quota").map(|v| v / divisor);
+            let used = number_at(&body, "data.used_quota").map(|v| v / divisor);
+            let total = match (remaining, used) {
+                (Some(r), Some(u)) => Some(r + u),
+                _ => None,
+            };
+            (total, used, remaining, "USD")
+        }
+        BalanceKind::Custom => {
+            let url = config
+                .url
+                .as_deref()
+                .filter(|u| !u.trim().is_empty())
+                .ok_or("自定义余额查询缺少接口地址")?
+                .replace("{baseUrl}", &root);
+            let body = get_json(&client, &url, token, config.user_id.as_deref()).await?;
+            let divisor = config.divisor.filter(|d| *d != 0.0).unwrap_or(1.0);
+            let pick = |path: &Option<String>| {
+                path.as_deref()
+                    .and_then(|p| number_at(&body, p))
+                    .map(|v| v / divisor)
+            };
+            let total = pick(&config.total_path);
+            let used = pick(&config.used_path);
+            let remaining = pick(&config.remaining_path).or(match (total, used) {
+                (Some(t), Some(u)) => Some(t - u),
+                _ => None,
+            });
+            if remaining.is_none() && total.is_none() && used.is_none() {
+                return Err("未能从响应中读取余额字段，请检查字段路径".to_string());
+            }
+            (total, used, remaining, "")
+        }
+    };
+
+    let unit = config
+        .unit
+        .clone()
+        .or_else(|| (!default_unit.is_empty()).then(|| default_unit.to_string()));
+    Ok(UsageData {
+        plan_name: None,
+        extra: None,
+        is_valid: Some(true),
+        invalid_message: None,
+        total,
+        used,
+        remaining,
+        unit,
+    })
+}
+
+/// 将查询结果包装为 UsageResult（失败时不返回 Err，便于列表中逐项展示）
+pub fn to_usage_result(result: Result<UsageData, String>) -> UsageResult {
+    match result {
+        Ok(data) => UsageResult {
+            success: true,
+            data: Some(vec![data]),
+            error: None,
+        },
+        Err(e) => UsageResult {
+            success: false,
+            data: None,
+            error: Some(crate::redact::redact_text(&e)),
+        },
+    }
+}
@@ -0,0 +1,99 @@
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::app_config::AppType;
+use crate::provider::BenchmarkResult;
+
+/// 单个供应商的测试结果（用于返回与进度事件）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderBenchmark {
+    pub provider_id: String,
+    pub name: String,
+    pub result: BenchmarkResult,
+}
+
+/// 进度事件 `provider-benchmark-progress` 的载荷
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchmarkProgress<'a> {
+    app_type: &'a str,
+    completed: usize,
+    total: usize,
+    entry: &'a ProviderBenchmark,
+}
+
+/// 待测试的供应商
+pub struct BenchmarkTarget {
+    pub provider_id: String,
+    pub name: String,
+    pub url: String,
+}
+
+/// 并发测试所有供应商的延迟，每完成一个即发送进度事件；结果按成功优先、延迟升序排列
+pub async fn run(
+    handle: &tauri::AppHandle,
+    app: &AppType,
+    targets: Vec<BenchmarkTarget>,
+    timeout_secs: Option<u64>,
+    task: &crate::tasks::TaskContext,
+) -> Result<Vec<ProviderBenchmark>, String> {
+    let timeout = crate::speedtest::sanitize_timeout(timeout_secs);
+    let client = crate::speedtest::build_client(timeout)?;
+    let total = targets.len();
+
+    let mut tasks: FuturesUnordered<_> = targets
+        .into_iter()
+        .map(|target| {
+            let client = client.clone();
+            async move {
+                let probe = crate::speedtest::probe_endpoint(&client, target.url.clone()).await;
+                // 能收到任意非 5xx 响应即视为端点可用（根路径返回 404 属正常）
+                let success = probe.status.map(|s| s < 500).unwrap_or(false);
+                ProviderBenchmark {
+                    provider_id: target.provider_id,
+                    name: target.name,
+                    result: BenchmarkResult {
+                        url: target.url,
+                        success,
+                        latency_ms: probe.latency.map(|l| l as u64),
+                        status: probe.status,
+                        error: probe.error,
+                        tested_at: chrono::Utc::now().timestamp_millis(),
+                    },
+                }
+            }
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(total);
+    while let Some(entry) = tasks.next().await {
+        let progress = BenchmarkProgress {
+            app_type: app.as_str(),
+            completed: results.len() + 1,
+            total,
+            entry: &entry,
+        };
+        if let Err(e) = handle.emit("provider-benchmark-progress", &progress) {
+            log::warn!("发送测速进度事件失败: {}", e);
+        }
+        task.progress(progress.completed, total, Some(entry.name.clone()));
+        results.push(entry);
+    }
+
+    results.sort_by_key(|r| (!r.result.success, r.result.latency_ms.unwrap_or(u64::MAX)));
+
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|r| !r.result.success)
+        .map(|r| r.name.as_str())
+        .collect();
+    if !failed.is_empty() {
+        crate::notify::send(
+            "供应商健康检查失败",
+            &format!("{}: {}", app.as_str(), failed.join(", ")),
+        );
+    }
+    Ok(results)
+}
@@ -16,9 +16,7 @@ pub struct McpStatus {
 
 fn user_config_path() -> PathBuf {
     // 用户级 MCP 配置文件：~/.claude.json
-    dirs::home_dir()
-        .expect("无法获取用户主目录")
-        .join(".claude.json")
+    crate::wsl_env::home_dir(&crate::app_config::AppType::Claude).join(".claude.json")
 }
 
 fn read_json_value(path: &Path) -> Result<Value, String> {
@@ -39,7 +37,7 @@ fn write_json_value(path: &Path, value: &Value) -> Result<(), String> {
     }
     let json =
         serde_json::to_string_pretty(value).map_err(|e| format!("序列化 JSON 失败: {}", e))?;
-    atomic_write(path, json.as_bytes())
+    atomic_write(path, json.as_bytes()).map_err(Into::into)
 }
 
 pub fn get_mcp_status() -> Result<McpStatus, String> {
@@ -153,8 +151,8 @@ pub fn validate_command_in_path(cmd: &str) -> Result<bool, String> {
     if cmd.trim().is_empty() {
         return Ok(false);
     }
-    // 如果包含路径分隔符，直接判断是否存在可执行文件
-    if cmd.contains('/') || cmd.contains('\\') {
+    // 如果包含路径分隔符，直接判断是否存在可执行文件（`\` 仅在 Windows 下为分隔符）
+    if cmd.contains('/') || cmd.contains(std::path::MAIN_SEPARATOR) {
         return Ok(Path::new(cmd).exists());
     }
 
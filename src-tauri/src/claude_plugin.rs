@@ -9,7 +9,7 @@ fn claude_dir() -> Result<PathBuf, String> {
     if let Some(dir) = crate::settings::get_claude_override_dir() {
         return Ok(dir);
     }
-    let home = dirs::home_dir().ok_or_else(|| "无法获取用户主目录".to_string())?;
+    let home = crate::wsl_env::home_dir(&crate::app_config::AppType::Claude);
     Ok(home.join(CLAUDE_DIR))
 }
 
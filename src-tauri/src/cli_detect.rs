@@ -0,0 +1,221 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::settings::{NamedEnvironment, TargetEnv};
+
+/// 单个 CLI 的安装与配置情况（用于首次运行引导）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledApp {
+    pub app_type: String,
+    /// 检测所在的目标环境名称
+    pub environment: String,
+    pub installed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<String>,
+    pub config_exists: bool,
+    pub provider_count: usize,
+    /// 已有 live 配置但尚无供应商，可通过 import_default_config 导入
+    pub can_import: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn binary_name(app: &AppType) -> &'static str {
+    match app {
+        AppType::Claude => "claude",
+        AppType::Codex => "codex",
+    }
+}
+
+/// PATH 之外的常见安装位置（图形界面启动时通常不继承 shell 的 PATH）
+fn extra_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = crate::config::home_dir() {
+        dirs.push(home.join(".claude").join("local"));
+        dirs.push(home.join(".local").join("bin"));
+        dirs.push(home.join(".npm-global").join("bin"));
+        dirs.push(home.join(".bun").join("bin"));
+    }
+    if cfg!(windows) {
+        if let Some(appdata) = std::env::var_os("APPDATA") {
+            dirs.push(PathBuf::from(appdata).join("npm"));
+        }
+    } else {
+        dirs.push(PathBuf::from("/usr/local/bin"));
+        dirs.push(PathBuf::from("/opt/homebrew/bin"));
+    }
+    dirs
+}
+
+/// 在本机 PATH 与常见安装目录中查找可执行文件
+pub fn find_native_binary(name: &str) -> Option<PathBuf> {
+    let exts: Vec<String> = if cfg!(windows) {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+            .split(';')
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_lowercase())
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+    let path_dirs = std::env::var_os("PATH")
+        .map(|p| std::env::split_paths(&p).collect::<Vec<_>>())
+        .unwrap_or_default();
+    path_dirs
+        .into_iter()
+        .chain(extra_dirs())
+        .flat_map(|dir| {
+            exts.iter()
+                .map(move |ext| dir.join(format!("{}{}", name, ext)))
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+/// 在远程环境（WSL / SSH）中执行的命令前缀：加载登录配置以获得完整 PATH
+const REMOTE_PROFILE: &str =
+    "[ -f \"$HOME/.profile\" ] && . \"$HOME/.profile\" >/dev/null 2>&1; PATH=\"$HOME/.local/bin:$HOME/.claude/local:$HOME/.npm-global/bin:$PATH\";";
+
+fn find_remote_binary(env: &NamedEnvironment, name: &str) -> Result<Option<String>, String> {
+    let output =
+        crate::remote_env::run_shell(env, &format!("{} command -v {}", REMOTE_PROFILE, name))?;
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((output.status.success() && !path.is_empty()).then_some(path))
+}
+
+fn detect(app: &AppType, config: &MultiAppConfig) -> InstalledApp {
+    let env = crate::wsl_env::active_environment(&crate::settings::get_settings(), app);
+    let provider_count = config
+        .get_manager(app)
+        .map(|m| m.providers.len())
+        .unwrap_or(0);
+    let mut report = InstalledApp {
+        app_type: app.as_str().to_string(),
+        environment: env.name.clone(),
+        installed: false,
+        binary_path: None,
+        config_exists: false,
+        provider_count,
+        can_import: false,
+        error: None,
+    };
+
+    let binary = if env.target_env == TargetEnv::Native {
+        Ok(find_native_binary(binary_name(app)).map(|p| p.to_string_lossy().to_string()))
+    } else {
+        crate::wsl_env::ensure_ready(app, true)
+            .and_then(|_| find_remote_binary(&env, binary_name(app)))
+    };
+    let config_exists = if crate::remote_env::uses_mirror(&env) {
+        crate::remote_env::live_exists(&env, app)
+    } else {
+        Ok(match app {
+            AppType::Claude => crate::config::get_claude_settings_path().exists(),
+            AppType::Codex => crate::codex_config::get_codex_auth_path().exists(),
+        })
+    };
+
+    match binary {
+        Ok(path) => {
+            report.installed = path.is_some();
+            report.binary_path = path;
+        }
+        Err(e) => report.error = Some(e),
+    }
+    match config_exists {
+        Ok(exists) => report.config_exists = exists,
+        Err(e) => report.error = report.error.take().or(Some(e)),
+    }
+    report.can_import = report.config_exists && provider_count == 0;
+    report
+}
+
+/// 检测 Claude Code 与 Codex 的安装位置、配置文件与供应商情况
+pub fn detect_installed(config: &MultiAppConfig) -> Vec<InstalledApp> {
+    [AppType::Claude, AppType::Codex]
+        .iter()
+        .map(|app| detect(app, config))
+        .collect()
+}
+
+/// CLI 版本信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVersion {
+    pub app_type: String,
+    pub environment: String,
+    /// 解析出的语义化版本号，如 `1.0.88`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// `--version` 的原始输出
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn parse_version(output: &str) -> Option<String> {
+    static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    RE.get_or_init(|| regex::Regex::new(r"\d+\.\d+\.\d+(?:-[0-9A-Za-z.]+)?").unwrap())
+        .find(output)
+        .map(|m| m.as_str().to_string())
+}
+
+fn native_version_output(name: &str) -> Result<std::process::Output, String> {
+    let binary = find_native_binary(name).ok_or_else(|| format!("未找到 {} 命令", name))?;
+    let mut cmd = std::process::Command::new(&binary);
+    cmd.arg("--version");
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x0800_0000);
+    }
+    cmd.output()
+        .map_err(|e| format!("执行 {} 失败: {}", binary.display(), e))
+}
+
+fn cli_version(app: &AppType) -> CliVersion {
+    let env = crate::wsl_env::active_environment(&crate::settings::get_settings(), app);
+    let name = binary_name(app);
+    let output = if env.target_env == TargetEnv::Native {
+        native_version_output(name)
+    } else {
+        crate::wsl_env::ensure_ready(app, true).and_then(|_| {
+            crate::remote_env::run_shell(&env, &format!("{} {} --version", REMOTE_PROFILE, name))
+        })
+    };
+
+    let mut result = CliVersion {
+        app_type: app.as_str().to_string(),
+        environment: env.name,
+        version: None,
+        raw: None,
+        error: None,
+    };
+    match output {
+        Ok(output) if output.status.success() => {
+            let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            result.version = parse_version(&raw);
+            result.raw = Some(raw);
+        }
+        Ok(output) => {
+            result.error = Some(format!(
+                "{} --version 执行失败: {}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Err(e) => result.error = Some(e),
+    }
+    result
+}
+
+/// 在各应用所选目标环境中查询 CLI 版本
+pub fn cli_versions() -> Vec<CliVersion> {
+    [AppType::Claude, AppType::Codex]
+        .iter()
+        .map(cli_version)
+        .collect()
+}
@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::app_config::AppType;
+use crate::settings::{NamedEnvironment, TargetEnv};
+
+/// POSIX shell 单引号转义
+#[cfg(not(target_os = "windows"))]
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+fn binary_name(app: &AppType) -> &'static str {
+    match app {
+        AppType::Claude => "claude",
+        AppType::Codex => "codex",
+    }
+}
+
+/// 在目标环境中启动 CLI 的命令行（尚未包裹终端）
+fn cli_argv(env: &NamedEnvironment, app: &AppType) -> Result<Vec<String>, String> {
+    let name = binary_name(app).to_string();
+    match env.target_env {
+        TargetEnv::Native => {
+            let binary = crate::cli_detect::find_native_binary(&name)
+                .ok_or_else(|| format!("未找到 {} 命令，请先安装", name))?;
+            Ok(vec![binary.to_string_lossy().to_string()])
+        }
+        TargetEnv::Wsl => {
+            let distro = crate::wsl_env::resolve_distro(env)?;
+            Ok(vec![
+                "wsl.exe".to_string(),
+                "-d".to_string(),
+                distro,
+                "--cd".to_string(),
+                "~".to_string(),
+                "-e".to_string(),
+                "bash".to_string(),
+                "-lic".to_string(),
+                name,
+            ])
+        }
+        TargetEnv::Ssh => {
+            let target = env
+                .ssh
+                .as_ref()
+                .filter(|t| !t.host.trim().is_empty())
+                .ok_or_else(|| format!("SSH 环境 {} 未配置主机", env.name))?;
+            let mut argv = vec!["ssh".to_string(), "-t".to_string()];
+            if let Some(port) = target.port {
+                argv.extend(["-p".to_string(), port.to_string()]);
+            }
+            if let Some(key) = target.key_path.as_deref().filter(|k| !k.trim().is_empty()) {
+                argv.extend(["-i".to_string(), key.trim().to_string()]);
+            }
+            let host = target.host.trim();
+            argv.push(
+                match target
+                    .user
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|u| !u.is_empty())
+                {
+                    Some(user) => format!("{}@{}", user, host),
+                    None => host.to_string(),
+                },
+            );
+            argv.push(format!("bash -lic {}", name));
+            Ok(argv)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn terminal_command(argv: &[String], cwd: &Path) -> Result<Command, String> {
+    // Windows Terminal 优先，否则新开控制台窗口；/k 保证 CLI 退出后窗口保留
+    let mut cmd = match crate::cli_detect::find_native_binary("wt") {
+        Some(wt) => {
+            let mut cmd = Command::new(wt);
+            cmd.arg("-d").arg(cwd).args(["cmd", "/k"]);
+            cmd
+        }
+        None => {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/c", "start", "CC Switch", "/D"])
+                .arg(cwd)
+                .args(["cmd", "/k"]);
+            cmd
+        }
+    };
+    cmd.args(argv);
+    Ok(cmd)
+}
+
+#[cfg(target_os = "macos")]
+fn terminal_command(argv: &[String], cwd: &Path) -> Result<Command, String> {
+    let script = format!(
+        "cd {} && {}",
+        sh_quote(&cwd.to_string_lossy()),
+        argv.iter()
+            .map(|a| sh_quote(a))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let escaped = script.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut cmd = Command::new("osascript");
+    cmd.args([
+        "-e",
+        "tell application \"Terminal\"",
+        "-e",
+        "activate",
+        "-e",
+        &format!("do script \"{}\"", escaped),
+        "-e",
+        "end tell",
+    ]);
+    Ok(cmd)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn terminal_command(argv: &[String], cwd: &Path) -> Result<Command, String> {
+    // CLI 退出后回到交互式 shell，避免终端窗口直接关闭
+    let script = format!(
+        "{}; exec \"${{SHELL:-sh}}\"",
+        argv.iter()
+            .map(|a| sh_quote(a))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let candidates: [(&str, &[&str]); 7] = [
+        ("x-terminal-emulator", &["-e"]),
+        ("gnome-terminal", &["--"]),
+        ("konsole", &["-e"]),
+        ("xfce4-terminal", &["-x"]),
+        ("kitty", &[]),
+        ("alacritty", &["-e"]),
+        ("xterm", &["-e"]),
+    ];
+    let (terminal, prefix) = candidates
+        .iter()
+        .find_map(|(name, prefix)| {
+            crate::cli_detect::find_native_binary(name).map(|path| (path, *prefix))
+        })
+        .ok_or_else(|| "未找到可用的终端程序".to_string())?;
+    let mut cmd = Command::new(terminal);
+    cmd.current_dir(cwd)
+        .args(prefix)
+        .args(["sh", "-c", script.as_str()]);
+    Ok(cmd)
+}
+
+/// 在新终端窗口中启动应用 CLI（按该应用选中的目标环境）
+pub fn launch(app: &AppType, cwd: Option<&str>) -> Result<(), String> {
+    let env = crate::wsl_env::active_environment(&crate::settings::get_settings(), app);
+    let argv = cli_argv(&env, app)?;
+    let cwd: PathBuf = match cwd.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => crate::config::home_dir().ok_or_else(|| "无法获取用户主目录".to_string())?,
+    };
+    if !cwd.is_dir() {
+        return Err(format!("工作目录不存在: {}", cwd.display()));
+    }
+    terminal_command(&argv, &cwd)?
+        .spawn()
+        .map_err(|e| format!("启动终端失败: {}", e))?;
+    log::info!("已在终端中启动 {}（环境: {}）", binary_name(app), env.name);
+    Ok(())
+}
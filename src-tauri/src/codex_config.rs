@@ -1,30 +1,55 @@
 // unused imports removed
 use std::path::PathBuf;
 
-use crate::config::{
-    atomic_write, delete_file, sanitize_provider_name, write_json_file, write_text_file,
-};
+use crate::app_config::MultiAppConfig;
+use crate::config::{atomic_write, delete_file, write_json_file, write_text_file};
+use crate::error::AppError;
+use crate::provider::{CodexAuthMode, Provider};
 use serde_json::Value;
 use std::fs;
 use std::path::Path;
 
 /// 获取 Codex 配置目录路径
 pub fn get_codex_config_dir() -> PathBuf {
-    if let Some(custom) = crate::settings::get_codex_override_dir() {
-        return custom;
-    }
+    crate::wsl_env::config_dir(&crate::app_config::AppType::Codex)
+}
 
-    dirs::home_dir().expect("无法获取用户主目录").join(".codex")
+/// 当前生效的 Codex 配置目录布局
+pub fn codex_paths() -> cc_switch_core::CodexPaths {
+    cc_switch_core::CodexPaths::new(get_codex_config_dir())
 }
 
 /// 获取 Codex auth.json 路径
 pub fn get_codex_auth_path() -> PathBuf {
-    get_codex_config_dir().join("auth.json")
+    codex_paths().auth_path()
 }
 
 /// 获取 Codex config.toml 路径
 pub fn get_codex_config_path() -> PathBuf {
-    get_codex_config_dir().join("config.toml")
+    codex_paths().config_path()
+}
+
+/// 获取 Codex 配置状态：auth.json 存在即视为已配置（config.toml 允许为空）
+pub fn get_codex_config_status() -> crate::config::ConfigStatus {
+    let app = crate::app_config::AppType::Codex;
+    let auth_path = get_codex_auth_path();
+    let exists = auth_path.exists();
+    let auth = if exists {
+        crate::config::read_json_file::<Value>(&auth_path).ok()
+    } else {
+        None
+    };
+    // ChatGPT 登录：检查令牌是否过期，便于前端提示重新登录
+    let codex_login = auth.as_ref().and_then(crate::codex_oauth::login_status);
+
+    crate::config::ConfigStatus {
+        exists,
+        path: get_codex_config_dir().to_string_lossy().to_string(),
+        codex_login,
+        codex_auth_mode: auth.as_ref().and_then(CodexAuthMode::detect),
+        env_error: crate::wsl_env::ensure_ready(&app, false).err(),
+        sandbox: crate::wsl_env::sandbox_label(&app),
+    }
 }
 
 /// 获取 Codex 供应商配置文件路径
@@ -32,18 +57,14 @@ pub fn get_codex_provider_paths(
     provider_id: &str,
     provider_name: Option<&str>,
 ) -> (PathBuf, PathBuf) {
-    let base_name = provider_name
-        .map(sanitize_provider_name)
-        .unwrap_or_else(|| sanitize_provider_name(provider_id));
-
-    let auth_path = get_codex_config_dir().join(format!("auth-{}.json", base_name));
-    let config_path = get_codex_config_dir().join(format!("config-{}.toml", base_name));
-
-    (auth_path, config_path)
+    codex_paths().provider_paths(provider_id, provider_name)
 }
 
 /// 删除 Codex 供应商配置文件
-pub fn delete_codex_provider_config(provider_id: &str, provider_name: &str) -> Result<(), String> {
+pub fn delete_codex_provider_config(
+    provider_id: &str,
+    provider_name: &str,
+) -> Result<(), AppError> {
     let (auth_path, config_path) = get_codex_provider_paths(provider_id, Some(provider_name));
 
     delete_file(&auth_path).ok();
@@ -54,48 +75,76 @@ pub fn delete_codex_provider_config(provider_id: &str, provider_name: &str) -> R
 
 //（移除未使用的备份/保存/恢复/导入函数，避免 dead_code 告警）
 
+fn non_empty_str(value: Option<&Value>) -> Option<&str> {
+    value
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
+}
+
+/// 按认证方式校验 auth.json 的结构
+pub fn validate_auth(mode: CodexAuthMode, auth: &Value) -> Result<(), String> {
+    let api_key = non_empty_str(auth.get("OPENAI_API_KEY"));
+    let tokens = auth.get("tokens").filter(|t| !t.is_null());
+    match mode {
+        CodexAuthMode::ApiKey => {
+            if api_key.is_none() {
+                return Err("API Key 认证需要在 auth.json 中填写 OPENAI_API_KEY".to_string());
+            }
+            if tokens.is_some() {
+                return Err(
+                    "API Key 认证的 auth.json 不应包含 tokens（ChatGPT 登录令牌）".to_string(),
+                );
+            }
+        }
+        CodexAuthMode::Chatgpt => {
+            let tokens = tokens.ok_or_else(|| {
+                "ChatGPT 登录认证需要 auth.json 中的 tokens，请先运行 codex login 后导入"
+                    .to_string()
+            })?;
+            for name in ["access_token", "refresh_token"] {
+                if non_empty_str(tokens.get(name)).is_none() {
+                    return Err(format!("ChatGPT 登录认证的 auth.json 缺少 tokens.{}", name));
+                }
+            }
+            if api_key.is_some() {
+                return Err("ChatGPT 登录认证的 auth.json 不应同时填写 OPENAI_API_KEY".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
 /// 原子写 Codex 的 `auth.json` 与 `config.toml`，在第二步失败时回滚第一步
-pub fn write_codex_live_atomic(auth: &Value, config_text_opt: Option<&str>) -> Result<(), String> {
+pub fn write_codex_live_atomic(
+    auth: &Value,
+    config_text_opt: Option<&str>,
+) -> Result<(), AppError> {
     let auth_path = get_codex_auth_path();
     let config_path = get_codex_config_path();
 
     if let Some(parent) = auth_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("创建 Codex 目录失败: {}: {}", parent.display(), e))?;
+        std::fs::create_dir_all(parent).map_err(|e| AppError::create_dir(parent, e))?;
     }
 
     // 读取旧内容用于回滚
     let old_auth = if auth_path.exists() {
-        Some(
-            fs::read(&auth_path)
-                .map_err(|e| format!("读取旧 auth.json 失败: {}: {}", auth_path.display(), e))?,
-        )
+        Some(fs::read(&auth_path).map_err(|e| AppError::read_file(&auth_path, e))?)
+    } else {
+        None
+    };
+    let _old_config = if config_path.exists() {
+        Some(fs::read(&config_path).map_err(|e| AppError::read_file(&config_path, e))?)
     } else {
         None
     };
-    let _old_config =
-        if config_path.exists() {
-            Some(fs::read(&config_path).map_err(|e| {
-                format!("读取旧 config.toml 失败: {}: {}", config_path.display(), e)
-            })?)
-        } else {
-            None
-        };
 
     // 准备写入内容
     let cfg_text = match config_text_opt {
         Some(s) => s.to_string(),
         None => String::new(),
     };
-    if !cfg_text.trim().is_empty() {
-        toml::from_str::<toml::Table>(&cfg_text).map_err(|e| {
-            format!(
-                "config.toml 语法错误: {} (路径: {})",
-                e,
-                config_path.display()
-            )
-        })?;
-    }
+    // toml 的 Display 会附带出错行原文，可能包含密钥，因此只使用行列号与错误描述
+    validate_config_toml(&cfg_text)?;
 
     // 第一步：写 auth.json
     write_json_file(&auth_path, auth)?;
@@ -115,43 +164,227 @@ pub fn write_codex_live_atomic(auth: &Value, config_text_opt: Option<&str>) -> R
 }
 
 /// 读取 `~/.codex/config.toml`，若不存在返回空字符串
-pub fn read_codex_config_text() -> Result<String, String> {
+pub fn read_codex_config_text() -> Result<String, AppError> {
     let path = get_codex_config_path();
     if path.exists() {
-        std::fs::read_to_string(&path).map_err(|e| format!("读取 config.toml 失败: {}", e))
+        std::fs::read_to_string(&path).map_err(|e| AppError::read_file(&path, e))
     } else {
         Ok(String::new())
     }
 }
 
 /// 从给定路径读取 config.toml 文本（路径存在时）；路径不存在则返回空字符串
-pub fn read_config_text_from_path(path: &Path) -> Result<String, String> {
+pub fn read_config_text_from_path(path: &Path) -> Result<String, AppError> {
     if path.exists() {
-        std::fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))
+        std::fs::read_to_string(path).map_err(|e| AppError::read_file(path, e))
     } else {
         Ok(String::new())
     }
 }
 
-/// 对非空的 TOML 文本进行语法校验
-pub fn validate_config_toml(text: &str) -> Result<(), String> {
+pub use cc_switch_core::codex::{extract_base_url, generate_third_party_config};
+
+/// 由供应商决定的顶层表；其余表（mcp_servers、tui 等）视为用户维护
+const PROVIDER_SECTIONS: &[&str] = &["model_providers"];
+
+fn max_position(table: &toml_edit::Table) -> usize {
+    let mut max = table.position().unwrap_or(0);
+    for (_, item) in table.iter() {
+        match item {
+            toml_edit::Item::Table(t) => max = max.max(max_position(t)),
+            toml_edit::Item::ArrayOfTables(tables) => {
+                for t in tables.iter() {
+                    max = max.max(max_position(t));
+                }
+            }
+            _ => {}
+        }
+    }
+    max
+}
+
+fn shift_positions(table: &mut toml_edit::Table, offset: usize) {
+    if let Some(pos) = table.position() {
+        table.set_position(pos + offset);
+    }
+    for (_, item) in table.iter_mut() {
+        match item {
+            toml_edit::Item::Table(t) => shift_positions(t, offset),
+            toml_edit::Item::ArrayOfTables(tables) => {
+                for t in tables.iter_mut() {
+                    shift_positions(t, offset);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 以供应商的 config.toml 为准，补上 live 中由用户维护、而供应商配置未包含的顶层表
+///
+/// 补入的表保留原有注释与格式，排在供应商配置之后
+pub fn preserve_user_sections(live: &str, provider: &str) -> Result<String, AppError> {
+    let parse = |text: &str, label: &str| {
+        text.parse::<toml_edit::Document>()
+            .map_err(|e| AppError::Message(format!("解析{}失败: {}", label, e.message())))
+    };
+    let live = parse(live, " live config.toml ")?;
+    let mut merged = parse(provider, "供应商 config.toml ")?;
+
+    let offset = max_position(merged.as_table()) + 1;
+    for (key, item) in live.iter() {
+        if PROVIDER_SECTIONS.contains(&key) || merged.contains_key(key) || !item.is_table_like() {
+            continue;
+        }
+        let mut item = item.clone();
+        if let toml_edit::Item::Table(table) = &mut item {
+            shift_positions(table, offset);
+        }
+        merged.insert(key, item);
+    }
+    Ok(merged.to_string())
+}
+
+fn parse_fragment_table(text: &str) -> Result<toml::Table, AppError> {
     if text.trim().is_empty() {
-        return Ok(());
+        return Ok(toml::Table::new());
     }
+    // 只使用错误描述，避免错误信息附带出错行原文（可能包含密钥）
     toml::from_str::<toml::Table>(text)
-        .map(|_| ())
-        .map_err(|e| format!("config.toml 语法错误: {}", e))
+        .map_err(|e| AppError::Message(format!("解析 config.toml 失败: {}", e.message())))
+}
+
+fn deep_merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(incoming)) => {
+                deep_merge(existing, incoming)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn merge_all<'a>(texts: impl IntoIterator<Item = &'a str>) -> Result<toml::Table, AppError> {
+    let mut merged = toml::Table::new();
+    for text in texts {
+        deep_merge(&mut merged, parse_fragment_table(text)?);
+    }
+    Ok(merged)
+}
+
+fn strip_same(table: &mut toml::Table, base: &toml::Table) {
+    table.retain(|key, value| match (value, base.get(key)) {
+        (toml::Value::Table(inner), Some(toml::Value::Table(base_inner))) => {
+            strip_same(inner, base_inner);
+            !inner.is_empty()
+        }
+        (value, Some(base_value)) => value != base_value,
+        (_, None) => true,
+    });
+}
+
+/// 按 ID 取出配置片段内容（保持引用顺序）
+pub fn fragment_texts<'a>(
+    config: &'a MultiAppConfig,
+    ids: &[String],
+) -> Result<Vec<&'a str>, AppError> {
+    ids.iter()
+        .map(|id| {
+            config
+                .codex_fragments
+                .iter()
+                .find(|f| &f.id == id)
+                .map(|f| f.content.as_str())
+                .ok_or_else(|| AppError::Message(format!("配置片段不存在: {}", id)))
+        })
+        .collect()
+}
+
+/// 依次合并配置片段与供应商自身的 config.toml：后者优先，表按键递归合并；无片段时原样返回
+pub fn compose_config(fragments: &[&str], provider: &str) -> Result<String, AppError> {
+    if fragments.is_empty() {
+        return Ok(provider.to_string());
+    }
+    let merged = merge_all(fragments.iter().copied().chain(std::iter::once(provider)))?;
+    toml::to_string(&merged)
+        .map_err(|e| AppError::Message(format!("序列化 config.toml 失败: {}", e)))
+}
+
+/// 从完整的 config.toml 中去掉与配置片段相同的内容，避免回填时把片段固化到供应商配置中
+pub fn strip_fragments(config: &str, fragments: &[&str]) -> Result<String, AppError> {
+    if fragments.is_empty() {
+        return Ok(config.to_string());
+    }
+    let base = merge_all(fragments.iter().copied())?;
+    let mut table = parse_fragment_table(config)?;
+    strip_same(&mut table, &base);
+    toml::to_string(&table)
+        .map_err(|e| AppError::Message(format!("序列化 config.toml 失败: {}", e)))
+}
+
+fn map_config(
+    mut settings: Value,
+    f: impl FnOnce(&str) -> Result<String, AppError>,
+) -> Result<Value, AppError> {
+    let text = settings
+        .get("config")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let mapped = f(text)?;
+    if let Some(obj) = settings.as_object_mut() {
+        obj.insert("config".to_string(), Value::String(mapped));
+    }
+    Ok(settings)
+}
+
+/// 将供应商 settings 中的 config 替换为合并配置片段后的完整内容
+pub fn compose_settings(
+    config: &MultiAppConfig,
+    provider: &Provider,
+    settings: Value,
+) -> Result<Value, AppError> {
+    if provider.config_fragments.is_empty() {
+        return Ok(settings);
+    }
+    let fragments = fragment_texts(config, &provider.config_fragments)?;
+    map_config(settings, |text| compose_config(&fragments, text))
+}
+
+/// 回填前去掉 settings 的 config 中来自配置片段的内容
+pub fn strip_settings(
+    config: &MultiAppConfig,
+    provider: &Provider,
+    settings: Value,
+) -> Result<Value, AppError> {
+    if provider.config_fragments.is_empty() {
+        return Ok(settings);
+    }
+    let fragments = fragment_texts(config, &provider.config_fragments)?;
+    map_config(settings, |text| strip_fragments(text, &fragments))
+}
+
+/// 对非空的 TOML 文本进行语法校验
+pub fn validate_config_toml(text: &str) -> Result<(), AppError> {
+    Ok(cc_switch_core::codex::validate_config_toml(text)?)
+}
+
+/// 检查供应商 config.toml 的常见错误（语法、model_provider 指向、wire_api 取值、base_url）
+pub fn lint_config_toml(text: &str) -> Result<(), AppError> {
+    Ok(cc_switch_core::codex::lint_config_toml(text)?)
 }
 
 /// 读取并校验 `~/.codex/config.toml`，返回文本（可能为空）
-pub fn read_and_validate_codex_config_text() -> Result<String, String> {
+pub fn read_and_validate_codex_config_text() -> Result<String, AppError> {
     let s = read_codex_config_text()?;
     validate_config_toml(&s)?;
     Ok(s)
 }
 
 /// 从指定路径读取并校验 config.toml，返回文本（可能为空）
-pub fn read_and_validate_config_from_path(path: &Path) -> Result<String, String> {
+pub fn read_and_validate_config_from_path(path: &Path) -> Result<String, AppError> {
     let s = read_config_text_from_path(path)?;
     validate_config_toml(&s)?;
     Ok(s)
@@ -0,0 +1,121 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Codex CLI 使用的 OAuth 刷新端点与客户端 ID
+const TOKEN_URL: &str = "https://auth.openai.com/oauth/token";
+const CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
+/// 距离过期不足该秒数即视为已过期，避免切换后立即失效
+const EXPIRY_MARGIN_SECS: i64 = 60;
+const REQUEST_TIMEOUT_SECS: u64 = 20;
+
+/// ChatGPT 登录令牌状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LoginState {
+    Valid,
+    Expired,
+    /// 令牌无法解析出过期时间
+    Unknown,
+}
+
+/// auth.json 中 ChatGPT 登录的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexLoginStatus {
+    pub state: LoginState,
+    /// access_token 过期时间（Unix 秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_refresh: Option<String>,
+    /// 是否可用 refresh_token 自动刷新
+    pub refreshable: bool,
+}
+
+fn token<'a>(auth: &'a Value, name: &str) -> Option<&'a str> {
+    auth.get("tokens")
+        .and_then(|t| t.get(name))
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+}
+
+/// 读取 JWT 载荷中的 exp（不校验签名）
+fn jwt_exp(jwt: &str) -> Option<i64> {
+    let payload = jwt.split('.').nth(1)?;
+    let bytes = BASE64_URL.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: Value = serde_json::from_slice(&bytes).ok()?;
+    claims.get("exp").and_then(|v| v.as_i64())
+}
+
+/// 解析 ChatGPT 登录状态；使用 API Key（无 tokens）时返回 None
+pub fn login_status(auth: &Value) -> Option<CodexLoginStatus> {
+    auth.get("tokens").filter(|t| t.is_object())?;
+    let expires_at = token(auth, "access_token").and_then(jwt_exp);
+    let state = match expires_at {
+        Some(exp) if exp - EXPIRY_MARGIN_SECS <= chrono::Utc::now().timestamp() => {
+            LoginState::Expired
+        }
+        Some(_) => LoginState::Valid,
+        None => LoginState::Unknown,
+    };
+    Some(CodexLoginStatus {
+        state,
+        expires_at,
+        last_refresh: auth
+            .get("last_refresh")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        refreshable: token(auth, "refresh_token").is_some(),
+    })
+}
+
+/// 使用 refresh_token 换取新令牌，返回更新后的 auth.json 内容
+pub async fn refresh(auth: &Value) -> Result<Value, String> {
+    let refresh_token =
+        token(auth, "refresh_token").ok_or_else(|| "缺少 refresh_token".to_string())?;
+
+    let client = crate::http_client::builder()?
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let resp = client
+        .post(TOKEN_URL)
+        .json(&json!({
+            "client_id": CLIENT_ID,
+            "grant_type": "refresh_token",
+            "refresh_token": refresh_token,
+            "scope": "openid profile email",
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("请求令牌刷新失败: {}", e))?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(format!("令牌刷新被拒绝: HTTP {}", status.as_u16()));
+    }
+    let body: Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("解析令牌刷新响应失败: {}", e))?;
+
+    let mut updated = auth.clone();
+    let tokens = updated
+        .get_mut("tokens")
+        .and_then(|t| t.as_object_mut())
+        .ok_or_else(|| "auth.json 缺少 tokens".to_string())?;
+    for name in ["id_token", "access_token", "refresh_token"] {
+        if let Some(value) = body.get(name).and_then(|v| v.as_str()) {
+            tokens.insert(name.to_string(), json!(value));
+        }
+    }
+    if let Some(obj) = updated.as_object_mut() {
+        obj.insert(
+            "last_refresh".to_string(),
+            json!(chrono::Utc::now().to_rfc3339()),
+        );
+    }
+    Ok(updated)
+}
@@ -0,0 +1,32 @@
+use crate::app_config::{AppType, MultiAppConfig};
+
+/// 计算 profile 模式下写入 live 的 config.toml：包含全部供应商（已合并配置片段），
+/// 目标供应商使用传入的最新配置
+pub fn live_config(
+    app_config: &MultiAppConfig,
+    target_id: &str,
+    target_config: &str,
+) -> Result<String, String> {
+    let mut providers: Vec<(String, String)> = Vec::new();
+    if let Some(manager) = app_config.get_manager(&AppType::Codex) {
+        for p in manager.providers.values().filter(|p| p.id != target_id) {
+            let settings = crate::live_merge::with_model(
+                &AppType::Codex,
+                p.model.as_deref(),
+                p.settings_config.clone(),
+            );
+            let settings = crate::codex_config::compose_settings(app_config, p, settings)?;
+            let config = settings
+                .get("config")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            providers.push((p.id.clone(), config));
+        }
+    }
+    providers.push((target_id.to_string(), target_config.to_string()));
+
+    let live = crate::codex_config::read_codex_config_text().map_err(|e| e.detail())?;
+    cc_switch_core::profiles::render(&live, &providers, target_id)
+        .map_err(|e| crate::error::AppError::from(e).into())
+}
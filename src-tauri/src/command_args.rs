@@ -0,0 +1,164 @@
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use tauri::ipc::{CommandArg, CommandItem, InvokeBody, InvokeError};
+use tauri::Runtime;
+
+use crate::app_config::AppType;
+
+/// 前端历史上使用过的应用类型参数名，按优先级排列
+const APP_TYPE_KEYS: [&str; 3] = ["app_type", "app", "appType"];
+
+/// 从参数对象中解析应用类型：仅接受 claude / codex，其他取值返回错误
+pub fn app_type_from_args(args: &Map<String, Value>) -> Result<Option<AppType>, String> {
+    for key in APP_TYPE_KEYS {
+        match args.get(key) {
+            None | Some(Value::Null) => continue,
+            Some(value) => {
+                return serde_json::from_value::<AppType>(value.clone())
+                    .map(Some)
+                    .map_err(|e| format!("参数 {} 无效: {}", key, e));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn parse_app_type<R: Runtime>(
+    command: &CommandItem<'_, R>,
+) -> Result<Option<AppType>, InvokeError> {
+    let InvokeBody::Json(Value::Object(args)) = command.message.payload() else {
+        return Ok(None);
+    };
+    app_type_from_args(args)
+        .map_err(|e| InvokeError::from(format!("命令 {} 的{}", command.name, e)))
+}
+
+/// 命令的应用类型参数：统一从 `app_type` / `app` / `appType` 解析，均未提供时为 Claude
+///
+/// 作为命令参数使用时参数名不影响解析，新命令只需声明一个该类型的参数
+pub struct AppTarget(AppType);
+
+impl AppTarget {
+    pub fn app_type(self) -> AppType {
+        self.0
+    }
+}
+
+/// 供内部直接调用命令函数时构造
+impl From<AppType> for AppTarget {
+    fn from(app_type: AppType) -> Self {
+        Self(app_type)
+    }
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for AppTarget {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        Ok(Self(parse_app_type(&command)?.unwrap_or(AppType::Claude)))
+    }
+}
+
+/// 可选的应用类型参数（未提供时表示全部应用），解析规则与 [`AppTarget`] 相同
+pub struct OptionalApp(Option<AppType>);
+
+impl OptionalApp {
+    pub fn app_type(self) -> Option<AppType> {
+        self.0
+    }
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for OptionalApp {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        Ok(Self(parse_app_type(&command)?))
+    }
+}
+
+/// camelCase 参数名转换为 snake_case（`driftPolicy` -> `drift_policy`）
+fn snake_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 可选命令参数：同时接受 camelCase（Tauri 默认）与 snake_case 参数名，均提供时以 camelCase 为准
+///
+/// 替代以两个参数分别接收 `fooBar` / `foo_bar` 的写法
+pub struct OptionalArg<T>(Option<T>);
+
+impl<T> OptionalArg<T> {
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+/// 供内部直接调用命令函数时构造
+impl<T> From<Option<T>> for OptionalArg<T> {
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<'de, R: Runtime, T: DeserializeOwned> CommandArg<'de, R> for OptionalArg<T> {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        let InvokeBody::Json(Value::Object(args)) = command.message.payload() else {
+            return Ok(Self(None));
+        };
+        let snake = snake_case(command.key);
+        for key in [command.key, snake.as_str()] {
+            match args.get(key) {
+                None | Some(Value::Null) => continue,
+                Some(value) => {
+                    return serde_json::from_value(value.clone())
+                        .map(|v| Self(Some(v)))
+                        .map_err(|e| {
+                            InvokeError::from(format!(
+                                "命令 {} 的参数 {} 无效: {}",
+                                command.name, key, e
+                            ))
+                        });
+                }
+            }
+        }
+        Ok(Self(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn app_type_accepts_every_key() {
+        for key in APP_TYPE_KEYS {
+            let parsed = app_type_from_args(&args(json!({ key: "codex" }))).unwrap();
+            assert!(matches!(parsed, Some(AppType::Codex)));
+        }
+        assert!(app_type_from_args(&args(json!({}))).unwrap().is_none());
+    }
+
+    #[test]
+    fn unknown_app_type_is_rejected() {
+        for key in APP_TYPE_KEYS {
+            assert!(app_type_from_args(&args(json!({ key: "gemini" }))).is_err());
+        }
+        assert!(app_type_from_args(&args(json!({ "app": 1 }))).is_err());
+    }
+
+    #[test]
+    fn snake_case_converts_camel_keys() {
+        assert_eq!(snake_case("driftPolicy"), "drift_policy");
+        assert_eq!(snake_case("oldPassphrase"), "old_passphrase");
+        assert_eq!(snake_case("id"), "id");
+    }
+}
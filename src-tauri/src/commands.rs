@@ -1,25 +1,56 @@
 #![allow(non_snake_case)]
 
-use std::collections::HashMap;
-use tauri::State;
+use indexmap::IndexMap;
+use tauri::{Emitter, Manager, State};
 use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_opener::OpenerExt;
 
-use crate::app_config::AppType;
+use crate::app_config::{AppType, MultiAppConfig};
 use crate::claude_mcp;
 use crate::claude_plugin;
 use crate::codex_config;
+use crate::command_args::{AppTarget, OptionalApp, OptionalArg};
 use crate::config::{self, get_claude_settings_path, ConfigStatus};
+use crate::error::{AppError, ResultExt};
 use crate::provider::{Provider, ProviderMeta};
 use crate::speedtest;
 use crate::store::AppState;
 
+/// 密钥格式可疑时记录日志并通知前端（不阻止保存）
+fn warn_key_format(handle: &tauri::AppHandle, app_type: &AppType, provider: &Provider) {
+    let messages = crate::key_format::check(app_type, provider);
+    if messages.is_empty() {
+        return;
+    }
+    log::warn!(
+        "供应商 {} 的 API Key 格式可疑: {}",
+        provider.id,
+        messages.join("; ")
+    );
+    let warning = crate::key_format::KeyFormatWarning {
+        app_type: app_type.as_str().to_string(),
+        provider_id: provider.id.clone(),
+        provider_name: provider.name.clone(),
+        messages,
+    };
+    if let Err(e) = handle.emit("provider-key-warning", &warning) {
+        log::error!("发送密钥格式提示事件失败: {}", e);
+    }
+}
+
 fn validate_provider_settings(app_type: &AppType, provider: &Provider) -> Result<(), String> {
     match app_type {
         AppType::Claude => {
             if !provider.settings_config.is_object() {
                 return Err("Claude 配置必须是 JSON 对象".to_string());
             }
+            if provider.auth_mode.is_some() {
+                return Err("认证方式仅适用于 Codex 供应商".to_string());
+            }
+            if !provider.config_fragments.is_empty() {
+                return Err("配置片段仅适用于 Codex 供应商".to_string());
+            }
+            crate::settings_schema::ensure_valid_claude_settings(&provider.settings_config)?;
         }
         AppType::Codex => {
             let settings = provider
@@ -32,12 +63,15 @@ fn validate_provider_settings(app_type: &AppType, provider: &Provider) -> Result
             if !auth.is_object() {
                 return Err("Codex auth 配置必须是 JSON 对象".to_string());
             }
+            if let Some(mode) = provider.auth_mode {
+                codex_config::validate_auth(mode, auth)?;
+            }
             if let Some(config_value) = settings.get("config") {
                 if !(config_value.is_string() || config_value.is_null()) {
                     return Err("Codex config 字段必须是字符串".to_string());
                 }
                 if let Some(cfg_text) = config_value.as_str() {
-                    codex_config::validate_config_toml(cfg_text)?;
+                    codex_config::lint_config_toml(cfg_text)?;
                 }
             }
         }
@@ -49,48 +83,58 @@ fn validate_provider_settings(app_type: &AppType, provider: &Provider) -> Result
 #[tauri::command]
 pub async fn get_providers(
     state: State<'_, AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-) -> Result<HashMap<String, Provider>, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
-
-    let config = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    app: AppTarget,
+) -> Result<IndexMap<String, Provider>, AppError> {
+    let app_type = app.app_type();
+
+    let config = state.read().await;
 
     let manager = config
         .get_manager(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+
+    crate::store_crypto::touch();
+
+    // 默认返回掩码后的密钥，明文需通过 reveal_provider_secret 显式获取
+    Ok(manager
+        .get_all_providers()
+        .iter()
+        .map(|(id, p)| {
+            let mut masked = p.clone();
+            masked.settings_config =
+                crate::secret_mask::mask_settings(&app_type, &p.settings_config);
+            (id.clone(), masked)
+        })
+        .collect())
+}
 
-    Ok(manager.get_all_providers().clone())
+/// 按条件查询供应商摘要（不含 settingsConfig 与密钥），供大量供应商时的列表刷新使用
+#[tauri::command]
+pub async fn query_providers(
+    state: State<'_, AppState>,
+    query: Option<crate::provider_query::ProviderQuery>,
+) -> Result<Vec<crate::provider_query::ProviderSummary>, AppError> {
+    let config = state.read().await;
+    crate::store_crypto::touch();
+    Ok(crate::provider_query::query(
+        &config,
+        &query.unwrap_or_default(),
+    ))
 }
 
 /// 获取当前供应商ID
 #[tauri::command]
 pub async fn get_current_provider(
     state: State<'_, AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-) -> Result<String, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
-
-    let config = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    app: AppTarget,
+) -> Result<String, AppError> {
+    let app_type = app.app_type();
+
+    let config = state.read().await;
 
     let manager = config
         .get_manager(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
     Ok(manager.current.clone())
 }
@@ -98,66 +142,65 @@ pub async fn get_current_provider(
 /// 添加供应商
 #[tauri::command]
 pub async fn add_provider(
+    handle: tauri::AppHandle,
     state: State<'_, AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-    provider: Provider,
-) -> Result<bool, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
-
+    app: AppTarget,
+    mut provider: Provider,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
+
+    // ID / 名称会参与派生文件路径，需先校验
+    crate::config::validate_provider_id(&provider.id)?;
+    provider.name = crate::config::normalize_provider_name(&provider.name)?;
+    crate::secret_mask::ensure_unmasked(&app_type, &provider.settings_config)?;
     validate_provider_settings(&app_type, &provider)?;
+    warn_key_format(&handle, &app_type, &provider);
+
+    // 是否当前供应商的判断与写 live 需与切换互斥
+    let _switch = state.lock_switch(&app_type).await;
 
     // 读取当前是否是激活供应商（短锁）
     let is_current = {
-        let config = state
-            .config
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+        let config = state.read().await;
+        crate::codex_config::fragment_texts(&config, &provider.config_fragments)?;
         let manager = config
             .get_manager(&app_type)
-            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
         manager.current == provider.id
     };
 
-    // 若目标为当前供应商，则先写 live，成功后再落盘配置
+    // 若目标为当前供应商，则先写 live，成功后再更新配置
     if is_current {
-        match app_type {
-            AppType::Claude => {
-                let settings_path = crate::config::get_claude_settings_path();
-                crate::config::write_json_file(&settings_path, &provider.settings_config)?;
-            }
-            AppType::Codex => {
-                let auth = provider
-                    .settings_config
-                    .get("auth")
-                    .ok_or_else(|| "目标供应商缺少 auth 配置".to_string())?;
-                let cfg_text = provider
-                    .settings_config
-                    .get("config")
-                    .and_then(|v| v.as_str());
-                crate::codex_config::write_codex_live_atomic(auth, cfg_text)?;
-            }
-        }
+        let config = state.snapshot().await;
+        crate::live_write::write_provider_live(&config, &app_type, &provider)?;
     }
 
-    // 更新内存并保存配置
+    // 更新内存并保存配置（钥匙串写入在锁外完成）
+    let mut stored = provider.clone();
+    stored.settings_config =
+        crate::keychain::protect(&app_type, &stored.id, stored.settings_config)?;
     {
-        let mut config = state
-            .config
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+        let mut config = state.write().await;
         let manager = config
             .get_manager_mut(&app_type)
-            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-        manager
-            .providers
-            .insert(provider.id.clone(), provider.clone());
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+        manager.providers.insert(stored.id.clone(), stored);
     }
-    state.save()?;
+    state.mark_dirty();
+
+    crate::events::publish(crate::events::StateEvent::provider_added(
+        &app_type,
+        &provider.id,
+    ));
+    crate::audit::record(
+        "add_provider",
+        Some(&app_type),
+        Some(&provider.id),
+        serde_json::json!({
+            "name": provider.name,
+            "settingsConfig": provider.settings_config,
+        }),
+    );
 
     Ok(true)
 }
@@ -165,67 +208,59 @@ pub async fn add_provider(
 /// 更新供应商
 #[tauri::command]
 pub async fn update_provider(
+    handle: tauri::AppHandle,
     state: State<'_, AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-    provider: Provider,
-) -> Result<bool, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+    app: AppTarget,
+    mut provider: Provider,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
 
-    validate_provider_settings(&app_type, &provider)?;
+    // ID / 名称会参与派生文件路径，需先校验
+    crate::config::validate_provider_id(&provider.id)?;
+    provider.name = crate::config::normalize_provider_name(&provider.name)?;
 
-    // 读取校验 & 是否当前（短锁）
+    // 是否当前供应商的判断与写 live 需与切换互斥
+    let _switch = state.lock_switch(&app_type).await;
+
+    // 读取校验 & 是否当前（短锁）；前端回传的掩码密钥还原为已存值
     let (exists, is_current) = {
-        let config = state
-            .config
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+        let config = state.read().await;
+        crate::codex_config::fragment_texts(&config, &provider.config_fragments)?;
         let manager = config
             .get_manager(&app_type)
-            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+        if let Some(stored) = manager.providers.get(&provider.id) {
+            crate::secret_mask::unmask_settings(
+                &app_type,
+                &mut provider.settings_config,
+                &stored.settings_config,
+            );
+        }
         (
             manager.providers.contains_key(&provider.id),
             manager.current == provider.id,
         )
     };
     if !exists {
-        return Err(format!("供应商不存在: {}", provider.id));
+        return Err(AppError::ProviderNotFound(provider.id.clone()));
     }
 
+    crate::secret_mask::ensure_unmasked(&app_type, &provider.settings_config)?;
+    validate_provider_settings(&app_type, &provider)?;
+    warn_key_format(&handle, &app_type, &provider);
+
     // 若更新的是当前供应商，先写 live 成功再保存
     if is_current {
-        match app_type {
-            AppType::Claude => {
-                let settings_path = crate::config::get_claude_settings_path();
-                crate::config::write_json_file(&settings_path, &provider.settings_config)?;
-            }
-            AppType::Codex => {
-                let auth = provider
-                    .settings_config
-                    .get("auth")
-                    .ok_or_else(|| "目标供应商缺少 auth 配置".to_string())?;
-                let cfg_text = provider
-                    .settings_config
-                    .get("config")
-                    .and_then(|v| v.as_str());
-                crate::codex_config::write_codex_live_atomic(auth, cfg_text)?;
-            }
-        }
+        let config = state.snapshot().await;
+        crate::live_write::write_provider_live(&config, &app_type, &provider)?;
     }
 
     // 更新内存并保存（保留/合并已有的 meta.custom_endpoints，避免丢失在编辑流程中新增的自定义端点）
-    {
-        let mut config = state
-            .config
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+    let (merged_provider, previous) = {
+        let config = state.read().await;
         let manager = config
-            .get_manager_mut(&app_type)
-            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+            .get_manager(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
         // 若已存在旧供应商，合并其 meta（尤其是 custom_endpoints）到新对象
         let merged_provider = if let Some(existing) = manager.providers.get(&provider.id) {
@@ -247,6 +282,8 @@ pub async fn update_provider(
                     updated.meta = Some(crate::provider::ProviderMeta {
                         custom_endpoints: merged_map,
                         usage_script: new_meta.usage_script.clone(),
+                        balance: new_meta.balance.clone(),
+                        benchmark: new_meta.benchmark.or_else(|| old_meta.benchmark.clone()),
                     });
                 }
                 // 旧 meta 不存在：使用入参（可能为 None）
@@ -261,51 +298,117 @@ pub async fn update_provider(
             provider.clone()
         };
 
+        let previous = manager
+            .providers
+            .get(&merged_provider.id)
+            .map(|old| old.settings_config.clone());
+        (merged_provider, previous)
+    };
+
+    // 审计需比较明文（钥匙串引用在密钥变化时保持不变），须在写入钥匙串前解析旧值
+    let previous_settings = previous.map(|old| {
+        crate::keychain::resolve(&app_type, &merged_provider.id, &old).unwrap_or_default()
+    });
+    let mut merged_provider = merged_provider;
+    merged_provider.settings_config = crate::keychain::protect(
+        &app_type,
+        &merged_provider.id,
+        merged_provider.settings_config,
+    )?;
+    {
+        let mut config = state.write().await;
+        let manager = config
+            .get_manager_mut(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
         manager
             .providers
             .insert(merged_provider.id.clone(), merged_provider);
     }
-    state.save()?;
+    state.mark_dirty();
+
+    // 仅记录脱敏后的字段差异，密钥变化以摘要体现
+    let changes = previous_settings
+        .map(|old| {
+            crate::history::diff_json(
+                &crate::audit::redact(&old),
+                &crate::audit::redact(
+                    &crate::keychain::resolve(&app_type, &provider.id, &provider.settings_config)
+                        .unwrap_or_default(),
+                ),
+            )
+        })
+        .unwrap_or_default();
+    crate::events::publish(crate::events::StateEvent::provider_updated(
+        &app_type,
+        &provider.id,
+    ));
+    crate::audit::record(
+        "update_provider",
+        Some(&app_type),
+        Some(&provider.id),
+        serde_json::json!({ "name": provider.name, "changes": changes }),
+    );
 
     Ok(true)
 }
 
+/// 设置供应商的模型（为空表示不指定）；若为当前供应商则立即写入 live 配置
+#[tauri::command]
+pub async fn set_provider_model(
+    handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    app: AppTarget,
+    id: String,
+    model: Option<String>,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
+    let mut provider = {
+        let config = state.read().await;
+        config
+            .get_manager(&app_type)
+            .and_then(|m| m.providers.get(&id))
+            .cloned()
+            .ok_or_else(|| AppError::ProviderNotFound(id.clone()))?
+    };
+    let model = model
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty());
+    if provider.model == model {
+        return Ok(true);
+    }
+    provider.model = model;
+    update_provider(handle, state, app_type.into(), provider).await
+}
+
 /// 删除供应商
 #[tauri::command]
 pub async fn delete_provider(
     state: State<'_, AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
+    app: AppTarget,
     id: String,
-) -> Result<bool, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
 
-    let mut config = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-
-    let manager = config
-        .get_manager_mut(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+    // 校验并获取供应商信息（短锁）
+    let provider = {
+        let config = state.read().await;
+        let manager = config
+            .get_manager(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
-    // 检查是否为当前供应商
-    if manager.current == id {
-        return Err("不能删除当前正在使用的供应商".to_string());
-    }
+        // 检查是否为当前供应商
+        if manager.current == id {
+            return Err(AppError::DeleteCurrentProvider);
+        }
 
-    // 获取供应商信息
-    let provider = manager
-        .providers
-        .get(&id)
-        .ok_or_else(|| format!("供应商不存在: {}", id))?
-        .clone();
+        manager
+            .providers
+            .get(&id)
+            .ok_or_else(|| AppError::ProviderNotFound(id.clone()))?
+            .clone()
+    };
 
-    // 删除配置文件
+    // 删除配置文件（锁外进行）
     match app_type {
         AppType::Codex => {
             codex_config::delete_codex_provider_config(&id, &provider.name)?;
@@ -320,62 +423,183 @@ pub async fn delete_provider(
         }
     }
 
-    // 从管理器删除
-    manager.providers.remove(&id);
+    // 从管理器移除并放入回收站
+    {
+        let mut config = state.write().await;
+        let manager = config
+            .get_manager_mut(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+        manager.providers.shift_remove(&id);
+        config.move_to_trash(&app_type, provider.clone());
+    }
 
     // 保存配置
-    drop(config); // 释放锁
-    state.save()?;
+    state.mark_dirty();
+
+    crate::events::publish(crate::events::StateEvent::provider_deleted(&app_type, &id));
+    crate::audit::record(
+        "delete_provider",
+        Some(&app_type),
+        Some(&id),
+        serde_json::json!({ "name": provider.name }),
+    );
 
     Ok(true)
 }
 
+/// Codex 供应商存储的 ChatGPT 令牌已过期时尝试刷新并保存；无法刷新则提示重新登录
+async fn refresh_codex_login_if_expired(state: &AppState, id: &str) -> Result<(), String> {
+    let settings = {
+        let config = state.read().await;
+        config
+            .get_manager(&AppType::Codex)
+            .and_then(|m| m.providers.get(id))
+            .map(|p| p.settings_config.clone())
+    };
+    let Some(settings) = settings else {
+        return Ok(());
+    };
+    let mut settings = crate::keychain::resolve(&AppType::Codex, id, &settings)?;
+    let Some(auth) = settings.get("auth") else {
+        return Ok(());
+    };
+    let Some(status) = crate::codex_oauth::login_status(auth) else {
+        return Ok(());
+    };
+    if status.state != crate::codex_oauth::LoginState::Expired {
+        return Ok(());
+    }
+    if !status.refreshable {
+        return Err("该供应商的 ChatGPT 登录已过期，请运行 codex login 重新登录后再回填".to_string());
+    }
+
+    let refreshed = crate::codex_oauth::refresh(auth).await.map_err(|e| {
+        format!(
+            "该供应商的 ChatGPT 登录已过期且自动刷新失败，请运行 codex login 重新登录: {}",
+            e
+        )
+    })?;
+    if let Some(obj) = settings.as_object_mut() {
+        obj.insert("auth".to_string(), refreshed);
+    }
+    let settings = crate::keychain::protect(&AppType::Codex, id, settings)?;
+    {
+        let mut config = state.write().await;
+        if let Some(provider) = config
+            .get_manager_mut(&AppType::Codex)
+            .and_then(|m| m.providers.get_mut(id))
+        {
+            provider.settings_config = settings;
+        }
+    }
+    state.mark_dirty();
+    log::info!("已刷新 Codex 供应商 {} 的 ChatGPT 登录令牌", id);
+    Ok(())
+}
+
+/// 计算回填到供应商的 settings_config：按写入方式合并 live，去掉切换时注入的模型与来自配置片段的内容
+/// （二者修改后仍对该供应商生效），最后将密钥存入钥匙串。只处理传入的 live，不持有配置锁
+fn backfill_settings(
+    config: &MultiAppConfig,
+    app_type: &AppType,
+    provider: &Provider,
+    live: serde_json::Value,
+) -> Result<serde_json::Value, AppError> {
+    let live = crate::live_merge::backfill(app_type, &provider.settings_config, live);
+    let live = crate::live_merge::strip_model(
+        app_type,
+        provider.model.as_deref(),
+        &provider.settings_config,
+        live,
+    );
+    let live = match app_type {
+        AppType::Codex => crate::codex_config::strip_settings(config, provider, live)?,
+        AppType::Claude => live,
+    };
+    crate::keychain::protect(app_type, &provider.id, live).map_err(Into::into)
+}
+
 /// 切换供应商
 #[tauri::command]
 pub async fn switch_provider(
     state: State<'_, AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
+    app: AppTarget,
     id: String,
-) -> Result<bool, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
-
-    let mut config = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-
-    // 为避免长期可变借用，尽快获取必要数据并缩小借用范围
-    let provider = {
-        let manager = config
-            .get_manager_mut(&app_type)
-            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+    drift_policy: OptionalArg<crate::drift::DriftPolicy>,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
+    let drift_policy = drift_policy.into_inner();
+    // 退出流程会等待切换完成，避免 live 与 config.json 只写入一半
+    let _operation = state.begin_operation()?;
+    // 同一应用的切换串行执行：从落盘、写 live 到保存配置与结束日志期间持有
+    let _switch = state.lock_switch(&app_type).await;
+
+    // ChatGPT 登录的 Codex 供应商：令牌已过期时先刷新，避免切换到不可用的登录态
+    if let AppType::Codex = app_type {
+        refresh_codex_login_if_expired(&state, &id).await?;
+    }
 
-        // 检查供应商是否存在
-        let provider = manager
-            .providers
-            .get(&id)
-            .ok_or_else(|| format!("供应商不存在: {}", id))?
-            .clone();
-        provider
+    // 先写入尚未落盘的编辑，切换基于已持久化的配置进行
+    state.flush().await?;
+
+    // SSH / WSL exec 环境：先拉取远程 live 到本地镜像，保证回填与漂移检查基于最新内容
+    crate::remote_env::pull_live(&app_type)?;
+
+    // 读锁内仅复制配置；漂移检查、备份与 live 读写均基于快照在锁外进行，
+    // 回填结果最后在写锁内一次性应用
+    let config = state.snapshot().await;
+
+    // live 被外部修改（如 `claude login`）时不再静默回填，需调用方明确选择处理方式
+    if drift_policy.is_none() {
+        if let Some(report) = crate::drift::detect(&config, &app_type)? {
+            return Err(AppError::LiveDrift {
+                report: Box::new(report),
+            });
+        }
+    }
+    let backfill = !matches!(drift_policy, Some(crate::drift::DriftPolicy::Force));
+
+    let manager = config
+        .get_manager(&app_type)
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+    // 检查供应商是否存在
+    let provider = manager
+        .providers
+        .get(&id)
+        .ok_or_else(|| AppError::ProviderNotFound(id.clone()))?
+        .clone();
+    let previous_current = manager.current.clone();
+
+    // 校验目标配置，避免写出无法被客户端解析的 live 文件
+    validate_provider_settings(&app_type, &provider)?;
+
+    // 写入前备份现有 live 配置
+    if let Err(e) = crate::backup::backup_live_configs(&app_type) {
+        log::warn!("备份 live 配置失败: {}", e);
+    }
+
+    // 记录切换前快照，供 undo_switch 一键撤销
+    let snapshot_id = match crate::snapshot::take_snapshot(&app_type, &previous_current, &id) {
+        Ok(snapshot_id) => Some(snapshot_id),
+        Err(e) => {
+            log::warn!("记录切换快照失败: {}", e);
+            None
+        }
     };
 
+    // 写入 live 前记录切换日志；进程中途退出时，下次启动据此回滚或完成切换
+    let journal = crate::switch_journal::begin(&app_type, &previous_current, &id, snapshot_id)?;
+
+    // 待回写到 SSOT 的供应商配置：(供应商 ID, 已保护的 settings_config)
+    let mut backfilled: Vec<(String, serde_json::Value)> = Vec::new();
+
     // SSOT 切换：先回填 live 配置到当前供应商，然后从内存写入目标主配置
     match app_type {
         AppType::Codex => {
             use serde_json::Value;
 
             // 回填：读取 live（auth.json + config.toml）写回当前供应商 settings_config
-            if !{
-                let cur = config
-                    .get_manager_mut(&app_type)
-                    .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-                cur.current.is_empty()
-            } {
+            if backfill && !previous_current.is_empty() {
                 let auth_path = codex_config::get_codex_auth_path();
                 let config_path = codex_config::get_codex_config_path();
                 if auth_path.exists() {
@@ -393,31 +617,34 @@ pub async fn switch_provider(
                         "config": config_str,
                     });
 
-                    let cur_id2 = {
-                        let m = config
-                            .get_manager(&app_type)
-                            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-                        m.current.clone()
-                    };
-                    let m = config
-                        .get_manager_mut(&app_type)
-                        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-                    if let Some(cur) = m.providers.get_mut(&cur_id2) {
-                        cur.settings_config = live;
+                    if let Some(cur) = manager.providers.get(&previous_current) {
+                        backfilled.push((
+                            previous_current.clone(),
+                            backfill_settings(&config, &app_type, cur, live)?,
+                        ));
                     }
                 }
             }
 
             // 切换：从目标供应商 settings_config 写入主配置（Codex 双文件原子+回滚）
-            let auth = provider
-                .settings_config
+            let live_settings = crate::live_merge::with_model(
+                &app_type,
+                provider.model.as_deref(),
+                crate::keychain::resolve(&app_type, &provider.id, &provider.settings_config)?,
+            );
+            let auth = live_settings
                 .get("auth")
                 .ok_or_else(|| "目标供应商缺少 auth 配置".to_string())?;
-            let cfg_text = provider
-                .settings_config
-                .get("config")
-                .and_then(|v| v.as_str());
-            crate::codex_config::write_codex_live_atomic(auth, cfg_text)?;
+            if let Some(mode) = provider.auth_mode {
+                codex_config::validate_auth(mode, auth)?;
+            }
+            let cfg_text = crate::live_merge::codex_live_config(
+                &config,
+                &provider,
+                live_settings.get("config").and_then(|v| v.as_str()),
+            )?;
+            crate::codex_config::write_codex_live_atomic(auth, cfg_text.as_deref())
+                .context("写入 Codex live 配置失败")?;
         }
         AppType::Claude => {
             use crate::config::{read_json_file, write_json_file};
@@ -425,88 +652,138 @@ pub async fn switch_provider(
             let settings_path = get_claude_settings_path();
 
             // 回填：读取 live settings.json 写回当前供应商 settings_config
-            if settings_path.exists() {
-                let cur_id = {
-                    let m = config
-                        .get_manager(&app_type)
-                        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-                    m.current.clone()
-                };
-                if !cur_id.is_empty() {
-                    if let Ok(live) = read_json_file::<serde_json::Value>(&settings_path) {
-                        let m = config
-                            .get_manager_mut(&app_type)
-                            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-                        if let Some(cur) = m.providers.get_mut(&cur_id) {
-                            cur.settings_config = live;
-                        }
+            if backfill && settings_path.exists() && !previous_current.is_empty() {
+                if let Ok(live) = read_json_file::<serde_json::Value>(&settings_path) {
+                    if let Some(cur) = manager.providers.get(&previous_current) {
+                        backfilled.push((
+                            previous_current.clone(),
+                            backfill_settings(&config, &app_type, cur, live)?,
+                        ));
                     }
                 }
             }
 
             // 切换：从目标供应商 settings_config 写入主配置
             if let Some(parent) = settings_path.parent() {
-                std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+                std::fs::create_dir_all(parent).map_err(|e| AppError::create_dir(parent, e))?;
             }
 
-            // 不做归档，直接写入
-            write_json_file(&settings_path, &provider.settings_config)?;
+            // 不做归档，直接写入（解析钥匙串引用为明文；合并模式下保留非供应商字段）
+            let live_settings = crate::live_merge::with_model(
+                &app_type,
+                provider.model.as_deref(),
+                crate::keychain::resolve(&app_type, &provider.id, &provider.settings_config)?,
+            );
+            write_json_file(
+                &settings_path,
+                &crate::live_merge::claude_live_content(&live_settings)?,
+            )
+            .context("写入 Claude live 配置失败")?;
 
             // 写入后回读 live，并回填到目标供应商的 SSOT，保证一致
             if settings_path.exists() {
                 if let Ok(live_after) = read_json_file::<serde_json::Value>(&settings_path) {
-                    let m = config
-                        .get_manager_mut(&app_type)
-                        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-                    if let Some(target) = m.providers.get_mut(&id) {
-                        target.settings_config = live_after;
-                    }
+                    backfilled.push((
+                        id.clone(),
+                        backfill_settings(&config, &app_type, &provider, live_after)?,
+                    ));
                 }
             }
         }
     }
 
-    // 更新当前供应商（短借用范围）
-    {
-        let manager = config
-            .get_manager_mut(&app_type)
-            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-        manager.current = id;
+    crate::remote_env::push_live(&app_type)?;
+    if let Err(e) = crate::wsl_env::mirror_live(&app_type) {
+        log::warn!("同步镜像环境失败: {}", e);
     }
 
-    // 对 Codex：切换完成后，同步 MCP 到 config.toml，并将最新的 config.toml 回填到当前供应商 settings_config.config
-    if let AppType::Codex = app_type {
+    // 对 Codex：切换完成后，同步 MCP 到 config.toml，并读取投影后的 config.toml 以回填到目标供应商
+    let codex_config_after = if let AppType::Codex = app_type {
         // 1) 依据 SSOT 将启用的 MCP 投影到 ~/.codex/config.toml
         crate::mcp::sync_enabled_to_codex(&config)?;
 
-        // 2) 读取投影后的 live config.toml 文本
-        let cfg_text_after = crate::codex_config::read_and_validate_codex_config_text()?;
+        // 2) 读取投影后的 live config.toml 文本（profile 模式下 live 含全部供应商，不回填）
+        if crate::settings::get_settings().codex_live_mode
+            == crate::settings::LiveWriteMode::Profiles
+        {
+            None
+        } else {
+            let text = crate::codex_config::read_and_validate_codex_config_text()?;
+            let fragments =
+                crate::codex_config::fragment_texts(&config, &provider.config_fragments)?;
+            let text = crate::codex_config::strip_fragments(&text, &fragments)?;
+            // 注入的模型同样不回填，保持供应商原有 config.toml 中的 model
+            let model = provider.model.as_deref().map(str::trim);
+            match model.filter(|m| !m.is_empty()) {
+                Some(model) => {
+                    let stored_config = provider
+                        .settings_config
+                        .get("config")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("");
+                    let original = cc_switch_core::codex::model_of(stored_config);
+                    Some(cc_switch_core::codex::restore_model(
+                        &text,
+                        model,
+                        original.as_deref(),
+                    )?)
+                }
+                None => Some(text),
+            }
+        }
+    } else {
+        None
+    };
 
-        // 3) 回填到当前（目标）供应商的 settings_config.config，确保编辑面板读取到最新 MCP
-        let cur_id = {
-            let m = config
-                .get_manager(&app_type)
-                .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-            m.current.clone()
-        };
-        let m = config
+    // 应用回填结果并更新当前供应商（短写锁）
+    {
+        let mut config = state.write().await;
+        let manager = config
             .get_manager_mut(&app_type)
-            .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
-        if let Some(p) = m.providers.get_mut(&cur_id) {
-            if let Some(obj) = p.settings_config.as_object_mut() {
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+        for (provider_id, settings) in backfilled {
+            if let Some(p) = manager.providers.get_mut(&provider_id) {
+                p.settings_config = settings;
+            }
+        }
+        // 3) 回填到目标供应商的 settings_config.config，确保编辑面板读取到最新 MCP
+        if let Some(cfg_text_after) = codex_config_after {
+            if let Some(obj) = manager
+                .providers
+                .get_mut(&id)
+                .and_then(|p| p.settings_config.as_object_mut())
+            {
                 obj.insert(
                     "config".to_string(),
                     serde_json::Value::String(cfg_text_after),
                 );
             }
         }
+        manager.record_recent(&id);
+        manager.current = id;
     }
 
     log::info!("成功切换到供应商: {}", provider.name);
 
     // 保存配置
-    drop(config); // 释放锁
-    state.save()?;
+    state.save().await?;
+    journal.finish();
+    crate::events::publish(crate::events::StateEvent::provider_switched(
+        &app_type,
+        &provider.id,
+        &previous_current,
+    ));
+
+    crate::audit::record(
+        "switch_provider",
+        Some(&app_type),
+        Some(&provider.id),
+        serde_json::json!({
+            "from": previous_current,
+            "name": provider.name,
+            "driftPolicy": drift_policy,
+        }),
+    );
 
     Ok(true)
 }
@@ -515,21 +792,13 @@ pub async fn switch_provider(
 #[tauri::command]
 pub async fn import_default_config(
     state: State<'_, AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-) -> Result<bool, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+    app: AppTarget,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
 
     // 仅当 providers 为空时才从 live 导入一条默认项
     {
-        let config = state
-            .config
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+        let config = state.read().await;
 
         if let Some(manager) = config.get_manager(&app_type) {
             if !manager.get_all_providers().is_empty() {
@@ -544,7 +813,7 @@ pub async fn import_default_config(
         AppType::Codex => {
             let auth_path = codex_config::get_codex_auth_path();
             if !auth_path.exists() {
-                return Err("Codex 配置文件不存在".to_string());
+                return Err(AppError::FileNotFound(auth_path.clone()));
             }
             let auth: serde_json::Value =
                 crate::config::read_json_file::<serde_json::Value>(&auth_path)?;
@@ -557,13 +826,14 @@ pub async fn import_default_config(
         AppType::Claude => {
             let settings_path = get_claude_settings_path();
             if !settings_path.exists() {
-                return Err("Claude Code 配置文件不存在".to_string());
+                return Err(AppError::FileNotFound(settings_path.clone()));
             }
             crate::config::read_json_file::<serde_json::Value>(&settings_path)?
         }
     };
 
     // 创建默认供应商（仅首次初始化）
+    let settings_config = crate::keychain::protect(&app_type, "default", settings_config)?;
     let provider = Provider::with_id(
         "default".to_string(),
         "default".to_string(),
@@ -572,14 +842,11 @@ pub async fn import_default_config(
     );
 
     // 添加到管理器
-    let mut config = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut config = state.write().await;
 
     let manager = config
         .get_manager_mut(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
     manager.providers.insert(provider.id.clone(), provider);
     // 设置当前供应商为默认项
@@ -587,62 +854,55 @@ pub async fn import_default_config(
 
     // 保存配置
     drop(config); // 释放锁
-    state.save()?;
+    state.mark_dirty();
+
+    crate::audit::record(
+        "import_default_config",
+        Some(&app_type),
+        Some("default"),
+        serde_json::Value::Null,
+    );
 
     Ok(true)
 }
 
 /// 获取 Claude Code 配置状态
 #[tauri::command]
-pub async fn get_claude_config_status() -> Result<ConfigStatus, String> {
+pub async fn get_claude_config_status() -> Result<ConfigStatus, AppError> {
     Ok(crate::config::get_claude_config_status())
 }
 
 /// 获取应用配置状态（通用）
 /// 兼容两种参数：`app_type`（推荐）或 `app`（字符串）
 #[tauri::command]
-pub async fn get_config_status(
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-) -> Result<ConfigStatus, String> {
-    let app = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+pub async fn get_config_status(app: AppTarget) -> Result<ConfigStatus, AppError> {
+    let app = app.app_type();
 
     match app {
         AppType::Claude => Ok(crate::config::get_claude_config_status()),
-        AppType::Codex => {
-            use crate::codex_config::{get_codex_auth_path, get_codex_config_dir};
-            let auth_path = get_codex_auth_path();
-
-            // 放宽：只要 auth.json 存在即可认为已配置；config.toml 允许为空
-            let exists = auth_path.exists();
-            let path = get_codex_config_dir().to_string_lossy().to_string();
-
-            Ok(ConfigStatus { exists, path })
-        }
+        AppType::Codex => Ok(crate::codex_config::get_codex_config_status()),
     }
 }
 
+/// 一次获取所有应用的配置状态、路径、当前供应商、漂移与环境信息
+#[tauri::command]
+pub async fn get_all_status(
+    state: State<'_, AppState>,
+) -> Result<crate::app_status::AllStatus, AppError> {
+    let config = state.snapshot().await;
+    crate::app_status::collect(config).await
+}
+
 /// 获取 Claude Code 配置文件路径
 #[tauri::command]
-pub async fn get_claude_code_config_path() -> Result<String, String> {
+pub async fn get_claude_code_config_path() -> Result<String, AppError> {
     Ok(get_claude_settings_path().to_string_lossy().to_string())
 }
 
 /// 获取当前生效的配置目录
 #[tauri::command]
-pub async fn get_config_dir(
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-) -> Result<String, String> {
-    let app = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+pub async fn get_config_dir(app: AppTarget) -> Result<String, AppError> {
+    let app = app.app_type();
 
     let dir = match app {
         AppType::Claude => config::get_claude_config_dir(),
@@ -657,14 +917,9 @@ pub async fn get_config_dir(
 #[tauri::command]
 pub async fn open_config_folder(
     handle: tauri::AppHandle,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-) -> Result<bool, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+    app: AppTarget,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
 
     let config_dir = match app_type {
         AppType::Claude => crate::config::get_claude_config_dir(),
@@ -673,7 +928,7 @@ pub async fn open_config_folder(
 
     // 确保目录存在
     if !config_dir.exists() {
-        std::fs::create_dir_all(&config_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+        std::fs::create_dir_all(&config_dir).map_err(|e| AppError::create_dir(&config_dir, e))?;
     }
 
     // 使用 opener 插件打开文件夹
@@ -690,7 +945,7 @@ pub async fn open_config_folder(
 pub async fn pick_directory(
     app: tauri::AppHandle,
     default_path: Option<String>,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, AppError> {
     let initial = default_path
         .map(|p| p.trim().to_string())
         .filter(|p| !p.is_empty());
@@ -719,7 +974,7 @@ pub async fn pick_directory(
 
 /// 打开外部链接
 #[tauri::command]
-pub async fn open_external(app: tauri::AppHandle, url: String) -> Result<bool, String> {
+pub async fn open_external(app: tauri::AppHandle, url: String) -> Result<bool, AppError> {
     // 规范化 URL，缺少协议时默认加 https://
     let url = if url.starts_with("http://") || url.starts_with("https://") {
         url
@@ -737,7 +992,7 @@ pub async fn open_external(app: tauri::AppHandle, url: String) -> Result<bool, S
 
 /// 获取应用配置文件路径
 #[tauri::command]
-pub async fn get_app_config_path() -> Result<String, String> {
+pub async fn get_app_config_path() -> Result<String, AppError> {
     use crate::config::get_app_config_path;
 
     let config_path = get_app_config_path();
@@ -746,14 +1001,14 @@ pub async fn get_app_config_path() -> Result<String, String> {
 
 /// 打开应用配置文件夹
 #[tauri::command]
-pub async fn open_app_config_folder(handle: tauri::AppHandle) -> Result<bool, String> {
+pub async fn open_app_config_folder(handle: tauri::AppHandle) -> Result<bool, AppError> {
     use crate::config::get_app_config_dir;
 
     let config_dir = get_app_config_dir();
 
     // 确保目录存在
     if !config_dir.exists() {
-        std::fs::create_dir_all(&config_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+        std::fs::create_dir_all(&config_dir).map_err(|e| AppError::create_dir(&config_dir, e))?;
     }
 
     // 使用 opener 插件打开文件夹
@@ -771,32 +1026,35 @@ pub async fn open_app_config_folder(handle: tauri::AppHandle) -> Result<bool, St
 
 /// 获取 Claude MCP 状态（settings.local.json 与 mcp.json）
 #[tauri::command]
-pub async fn get_claude_mcp_status() -> Result<crate::claude_mcp::McpStatus, String> {
-    claude_mcp::get_mcp_status()
+pub async fn get_claude_mcp_status() -> Result<crate::claude_mcp::McpStatus, AppError> {
+    claude_mcp::get_mcp_status().map_err(Into::into)
 }
 
 /// 读取 mcp.json 文本内容（不存在则返回 Ok(None)）
 #[tauri::command]
-pub async fn read_claude_mcp_config() -> Result<Option<String>, String> {
-    claude_mcp::read_mcp_json()
+pub async fn read_claude_mcp_config() -> Result<Option<String>, AppError> {
+    claude_mcp::read_mcp_json().map_err(Into::into)
 }
 
 /// 新增或更新一个 MCP 服务器条目
 #[tauri::command]
-pub async fn upsert_claude_mcp_server(id: String, spec: serde_json::Value) -> Result<bool, String> {
-    claude_mcp::upsert_mcp_server(&id, spec)
+pub async fn upsert_claude_mcp_server(
+    id: String,
+    spec: serde_json::Value,
+) -> Result<bool, AppError> {
+    claude_mcp::upsert_mcp_server(&id, spec).map_err(Into::into)
 }
 
 /// 删除一个 MCP 服务器条目
 #[tauri::command]
-pub async fn delete_claude_mcp_server(id: String) -> Result<bool, String> {
-    claude_mcp::delete_mcp_server(&id)
+pub async fn delete_claude_mcp_server(id: String) -> Result<bool, AppError> {
+    claude_mcp::delete_mcp_server(&id).map_err(Into::into)
 }
 
 /// 校验命令是否在 PATH 中可用（不执行）
 #[tauri::command]
-pub async fn validate_mcp_command(cmd: String) -> Result<bool, String> {
-    claude_mcp::validate_command_in_path(&cmd)
+pub async fn validate_mcp_command(cmd: String) -> Result<bool, AppError> {
+    claude_mcp::validate_command_in_path(&cmd).map_err(Into::into)
 }
 
 // =====================
@@ -807,30 +1065,19 @@ pub async fn validate_mcp_command(cmd: String) -> Result<bool, String> {
 #[tauri::command]
 pub async fn query_provider_usage(
     state: State<'_, AppState>,
-    provider_id: Option<String>,
-    providerId: Option<String>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-) -> Result<crate::provider::UsageResult, String> {
+    provider_id: OptionalArg<String>,
+    app: AppTarget,
+) -> Result<crate::provider::UsageResult, AppError> {
     use crate::provider::{UsageData, UsageResult};
 
     // 解析参数
-    let provider_id = provider_id
-        .or(providerId)
-        .ok_or("缺少 providerId 参数")?;
+    let provider_id = provider_id.into_inner().ok_or("缺少 providerId 参数")?;
 
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+    let app_type = app.app_type();
 
     // 1. 获取供应商配置并克隆所需数据
     let (api_key, base_url, usage_script_code, timeout) = {
-        let config = state
-            .config
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+        let config = state.read().await;
 
         let manager = config
             .get_manager(&app_type)
@@ -849,7 +1096,7 @@ pub async fn query_provider_usage(
             .ok_or("未配置用量查询脚本")?;
 
         if !usage_script.enabled {
-            return Err("用量查询未启用".to_string());
+            return Err("用量查询未启用".into());
         }
 
         // 3. 提取凭证和脚本配置
@@ -897,7 +1144,7 @@ pub async fn query_provider_usage(
             Ok(UsageResult {
                 success: false,
                 data: None,
-                error: Some(e),
+                error: Some(crate::redact::redact_text(&e)),
             })
         }
     }
@@ -919,8 +1166,8 @@ fn extract_credentials(
             let api_key = env
                 .get("ANTHROPIC_AUTH_TOKEN")
                 .and_then(|v| v.as_str())
-                .ok_or("缺少 API Key")?
-                .to_string();
+                .ok_or("缺少 API Key")?;
+            let api_key = crate::keychain::resolve_str(app_type, &provider.id, api_key)?;
 
             let base_url = env
                 .get("ANTHROPIC_BASE_URL")
@@ -940,8 +1187,8 @@ fn extract_credentials(
             let api_key = auth
                 .get("OPENAI_API_KEY")
                 .and_then(|v| v.as_str())
-                .ok_or("缺少 API Key")?
-                .to_string();
+                .ok_or("缺少 API Key")?;
+            let api_key = crate::keychain::resolve_str(app_type, &provider.id, api_key)?;
 
             // 从 config TOML 中提取 base_url
             let config_toml = provider
@@ -979,21 +1226,18 @@ pub struct McpConfigResponse {
 #[tauri::command]
 pub async fn get_mcp_config(
     state: State<'_, AppState>,
-    app: Option<String>,
-) -> Result<McpConfigResponse, String> {
+    app: AppTarget,
+) -> Result<McpConfigResponse, AppError> {
     let config_path = crate::config::get_app_config_path()
         .to_string_lossy()
         .to_string();
-    let mut cfg = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    let app_ty = crate::app_config::AppType::from(app.as_deref().unwrap_or("claude"));
+    let mut cfg = state.write().await;
+    let app_ty = app.app_type();
     let (servers, normalized) = crate::mcp::get_servers_snapshot_for(&mut cfg, &app_ty);
     let need_save = normalized > 0;
     drop(cfg);
     if need_save {
-        state.save()?;
+        state.mark_dirty();
     }
     Ok(McpConfigResponse {
         config_path,
@@ -1005,16 +1249,13 @@ pub async fn get_mcp_config(
 #[tauri::command]
 pub async fn upsert_mcp_server_in_config(
     state: State<'_, AppState>,
-    app: Option<String>,
+    app: AppTarget,
     id: String,
     spec: serde_json::Value,
-    sync_other_side: Option<bool>,
-) -> Result<bool, String> {
-    let mut cfg = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    let app_ty = crate::app_config::AppType::from(app.as_deref().unwrap_or("claude"));
+    sync_other_side: OptionalArg<bool>,
+) -> Result<bool, AppError> {
+    let mut cfg = state.write().await;
+    let app_ty = app.app_type();
     let mut sync_targets: Vec<crate::app_config::AppType> = Vec::new();
 
     let changed = crate::mcp::upsert_in_config_for(&mut cfg, &app_ty, &id, spec.clone())?;
@@ -1030,7 +1271,7 @@ pub async fn upsert_mcp_server_in_config(
         sync_targets.push(app_ty.clone());
     }
 
-    if sync_other_side.unwrap_or(false) {
+    if sync_other_side.into_inner().unwrap_or(false) {
         let other_app = match app_ty.clone() {
             crate::app_config::AppType::Claude => crate::app_config::AppType::Codex,
             crate::app_config::AppType::Codex => crate::app_config::AppType::Claude,
@@ -1049,17 +1290,11 @@ pub async fn upsert_mcp_server_in_config(
         }
     }
     drop(cfg);
-    state.save()?;
+    state.mark_dirty();
 
-    let cfg2 = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let cfg2 = state.snapshot().await;
     for app_ty_to_sync in sync_targets {
-        match app_ty_to_sync {
-            crate::app_config::AppType::Claude => crate::mcp::sync_enabled_to_claude(&cfg2)?,
-            crate::app_config::AppType::Codex => crate::mcp::sync_enabled_to_codex(&cfg2)?,
-        };
+        crate::mcp::sync_enabled_for(&cfg2, &app_ty_to_sync)?;
     }
     Ok(changed)
 }
@@ -1068,26 +1303,17 @@ pub async fn upsert_mcp_server_in_config(
 #[tauri::command]
 pub async fn delete_mcp_server_in_config(
     state: State<'_, AppState>,
-    app: Option<String>,
+    app: AppTarget,
     id: String,
-) -> Result<bool, String> {
-    let mut cfg = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    let app_ty = crate::app_config::AppType::from(app.as_deref().unwrap_or("claude"));
+) -> Result<bool, AppError> {
+    let mut cfg = state.write().await;
+    let app_ty = app.app_type();
     let existed = crate::mcp::delete_in_config_for(&mut cfg, &app_ty, &id)?;
     drop(cfg);
-    state.save()?;
+    state.mark_dirty();
     // 若删除的是 Claude/Codex 客户端的条目，则同步一次，确保启用项从对应 live 配置中移除
-    let cfg2 = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    match app_ty {
-        crate::app_config::AppType::Claude => crate::mcp::sync_enabled_to_claude(&cfg2)?,
-        crate::app_config::AppType::Codex => crate::mcp::sync_enabled_to_codex(&cfg2)?,
-    }
+    let cfg2 = state.snapshot().await;
+    crate::mcp::sync_enabled_for(&cfg2, &app_ty)?;
     Ok(existed)
 }
 
@@ -1095,81 +1321,80 @@ pub async fn delete_mcp_server_in_config(
 #[tauri::command]
 pub async fn set_mcp_enabled(
     state: State<'_, AppState>,
-    app: Option<String>,
+    app: AppTarget,
     id: String,
     enabled: bool,
-) -> Result<bool, String> {
-    let mut cfg = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    let app_ty = crate::app_config::AppType::from(app.as_deref().unwrap_or("claude"));
-    let changed = crate::mcp::set_enabled_and_sync_for(&mut cfg, &app_ty, &id, enabled)?;
+) -> Result<bool, AppError> {
+    let mut cfg = state.write().await;
+    let app_ty = app.app_type();
+    let changed = crate::mcp::set_enabled_for(&mut cfg, &app_ty, &id, enabled)?;
+    if !changed {
+        return Ok(false);
+    }
+    // 投影到 live 配置基于快照进行，不占用配置锁
+    let snapshot = cfg.clone();
     drop(cfg);
-    state.save()?;
+    crate::mcp::sync_enabled_for(&snapshot, &app_ty)?;
+    state.mark_dirty();
     Ok(changed)
 }
 
 /// 手动同步：将启用的 MCP 投影到 ~/.claude.json（不更改 config.json）
 #[tauri::command]
-pub async fn sync_enabled_mcp_to_claude(state: State<'_, AppState>) -> Result<bool, String> {
-    let mut cfg = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    let normalized = crate::mcp::normalize_servers_for(&mut cfg, &AppType::Claude);
-    crate::mcp::sync_enabled_to_claude(&cfg)?;
+pub async fn sync_enabled_mcp_to_claude(state: State<'_, AppState>) -> Result<bool, AppError> {
+    let (normalized, snapshot) = {
+        let mut cfg = state.write().await;
+        let normalized = crate::mcp::normalize_servers_for(&mut cfg, &AppType::Claude);
+        (normalized, cfg.clone())
+    };
+    crate::mcp::sync_enabled_to_claude(&snapshot)?;
     let need_save = normalized > 0;
-    drop(cfg);
     if need_save {
-        state.save()?;
+        state.mark_dirty();
     }
     Ok(true)
 }
 
 /// 手动同步：将启用的 MCP 投影到 ~/.codex/config.toml（不更改 config.json）
 #[tauri::command]
-pub async fn sync_enabled_mcp_to_codex(state: State<'_, AppState>) -> Result<bool, String> {
-    let mut cfg = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    let normalized = crate::mcp::normalize_servers_for(&mut cfg, &AppType::Codex);
-    crate::mcp::sync_enabled_to_codex(&cfg)?;
+pub async fn sync_enabled_mcp_to_codex(state: State<'_, AppState>) -> Result<bool, AppError> {
+    let (normalized, snapshot) = {
+        let mut cfg = state.write().await;
+        let normalized = crate::mcp::normalize_servers_for(&mut cfg, &AppType::Codex);
+        (normalized, cfg.clone())
+    };
+    crate::mcp::sync_enabled_to_codex(&snapshot)?;
     let need_save = normalized > 0;
-    drop(cfg);
     if need_save {
-        state.save()?;
+        state.mark_dirty();
     }
     Ok(true)
 }
 
 /// 从 ~/.claude.json 导入 MCP 定义到 config.json，返回变更数量
 #[tauri::command]
-pub async fn import_mcp_from_claude(state: State<'_, AppState>) -> Result<usize, String> {
-    let mut cfg = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    let changed = crate::mcp::import_from_claude(&mut cfg)?;
+pub async fn import_mcp_from_claude(state: State<'_, AppState>) -> Result<usize, AppError> {
+    let Some(text) = crate::claude_mcp::read_mcp_json()? else {
+        return Ok(0);
+    };
+    let mut cfg = state.write().await;
+    let changed = crate::mcp::import_from_claude(&mut cfg, &text)?;
     drop(cfg);
     if changed > 0 {
-        state.save()?;
+        state.mark_dirty();
     }
     Ok(changed)
 }
 
 /// 从 ~/.codex/config.toml 导入 MCP 定义到 config.json（Codex 作用域），返回变更数量
 #[tauri::command]
-pub async fn import_mcp_from_codex(state: State<'_, AppState>) -> Result<usize, String> {
-    let mut cfg = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-    let changed = crate::mcp::import_from_codex(&mut cfg)?;
+pub async fn import_mcp_from_codex(state: State<'_, AppState>) -> Result<usize, AppError> {
+    let text = crate::codex_config::read_and_validate_codex_config_text()?;
+    let mut cfg = state.write().await;
+    let changed = crate::mcp::import_from_codex(&mut cfg, &text)?;
     drop(cfg);
     if changed > 0 {
-        state.save()?;
+        state.mark_dirty();
     }
     Ok(changed)
 }
@@ -1178,21 +1403,14 @@ pub async fn import_mcp_from_codex(state: State<'_, AppState>) -> Result<usize,
 /// - Codex: 返回 { auth: JSON, config: string }
 /// - Claude: 返回 settings.json 的 JSON 内容
 #[tauri::command]
-pub async fn read_live_provider_settings(
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-) -> Result<serde_json::Value, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+pub async fn read_live_provider_settings(app: AppTarget) -> Result<serde_json::Value, AppError> {
+    let app_type = app.app_type();
 
     match app_type {
         AppType::Codex => {
             let auth_path = crate::codex_config::get_codex_auth_path();
             if !auth_path.exists() {
-                return Err("Codex 配置文件不存在：缺少 auth.json".to_string());
+                return Err(AppError::FileNotFound(auth_path.clone()));
             }
             let auth: serde_json::Value = crate::config::read_json_file(&auth_path)?;
             let cfg_text = crate::codex_config::read_and_validate_codex_config_text()?;
@@ -1201,7 +1419,7 @@ pub async fn read_live_provider_settings(
         AppType::Claude => {
             let path = crate::config::get_claude_settings_path();
             if !path.exists() {
-                return Err("Claude Code 配置文件不存在".to_string());
+                return Err(AppError::FileNotFound(path.clone()));
             }
             let v: serde_json::Value = crate::config::read_json_file(&path)?;
             Ok(v)
@@ -1209,173 +1427,1389 @@ pub async fn read_live_provider_settings(
     }
 }
 
-/// 获取设置
+/// 列出指定应用的 live 配置备份（按时间倒序）
 #[tauri::command]
-pub async fn get_settings() -> Result<crate::settings::AppSettings, String> {
-    Ok(crate::settings::get_settings())
+pub async fn list_backups(app: AppTarget) -> Result<Vec<crate::backup::BackupInfo>, AppError> {
+    let app_type = app.app_type();
+
+    crate::backup::list_backups(&app_type).map_err(Into::into)
 }
 
-/// 保存设置
+/// 将备份恢复到 live 配置；`sync_current` 为 true 时将恢复后的 live 回填到当前供应商
 #[tauri::command]
-pub async fn save_settings(settings: crate::settings::AppSettings) -> Result<bool, String> {
-    crate::settings::update_settings(settings)?;
+pub async fn restore_backup(
+    state: State<'_, AppState>,
+    app: AppTarget,
+    id: String,
+    sync_current: OptionalArg<bool>,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
+    let _operation = state.begin_operation()?;
+    let _switch = state.lock_switch(&app_type).await;
+
+    crate::backup::restore_backup(&app_type, &id)?;
+    crate::audit::record("restore_backup", Some(&app_type), Some(&id), serde_json::Value::Null);
+
+    if !sync_current.into_inner().unwrap_or(false) {
+        return Ok(true);
+    }
+
+    // 与切换相同的回填流程；读 live 与钥匙串写入基于快照在锁外完成，写锁内仅应用结果
+    let live = read_live_provider_settings(app_type.clone().into()).await?;
+    let config = state.snapshot().await;
+    let manager = config
+        .get_manager(&app_type)
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+    let cur_id = manager.current.clone();
+    let Some(cur) = manager.providers.get(&cur_id) else {
+        return Ok(true);
+    };
+    let settings = backfill_settings(&config, &app_type, cur, live)?;
+    {
+        let mut config = state.write().await;
+        if let Some(cur) = config
+            .get_manager_mut(&app_type)
+            .and_then(|m| m.providers.get_mut(&cur_id))
+        {
+            cur.settings_config = settings;
+        }
+    }
+    state.save().await?;
+
     Ok(true)
 }
 
-/// 重启应用程序（当 app_config_dir 变更后使用）
+/// 立即执行一次 WebDAV 同步；冲突时可通过 `keep` 指定保留本地或远端
 #[tauri::command]
-pub async fn restart_app(app: tauri::AppHandle) -> Result<bool, String> {
-    // 使用 tauri-plugin-process 重启应用
-    app.restart();
+pub async fn sync_now(
+    handle: tauri::AppHandle,
+    keep: Option<crate::webdav_sync::ConflictChoice>,
+) -> Result<crate::webdav_sync::SyncResult, AppError> {
+    crate::tasks::run("webdav_sync", "WebDAV 同步", move |_| async move {
+        let state = handle.state::<AppState>();
+        crate::webdav_sync::sync_now(state.inner(), keep)
+            .await
+            .map_err(Into::into)
+    })
+    .await
 }
 
-/// 检查更新
+/// 将完整状态加密备份到 S3 兼容对象存储
 #[tauri::command]
-pub async fn check_for_updates(handle: tauri::AppHandle) -> Result<bool, String> {
-    // 打开 GitHub releases 页面
-    handle
-        .opener()
-        .open_url(
-            "https://github.com/farion1231/cc-switch/releases/latest",
-            None::<String>,
-        )
-        .map_err(|e| format!("打开更新页面失败: {}", e))?;
-
-    Ok(true)
+pub async fn s3_backup_now(
+    handle: tauri::AppHandle,
+) -> Result<crate::s3_backup::S3BackupObject, AppError> {
+    crate::tasks::run("s3_backup", "S3 备份", move |_| async move {
+        let state = handle.state::<AppState>();
+        crate::s3_backup::backup_now(state.inner())
+            .await
+            .map_err(Into::into)
+    })
+    .await
 }
 
-/// 判断是否为便携版（绿色版）运行
+/// 列出后台任务（运行中与最近结束的任务）
 #[tauri::command]
-pub async fn is_portable_mode() -> Result<bool, String> {
-    let exe_path = std::env::current_exe().map_err(|e| format!("获取可执行路径失败: {}", e))?;
-    if let Some(dir) = exe_path.parent() {
-        Ok(dir.join("portable.ini").is_file())
-    } else {
-        Ok(false)
-    }
+pub async fn list_tasks() -> Result<Vec<crate::tasks::TaskInfo>, AppError> {
+    Ok(crate::tasks::list())
 }
 
-/// Claude 插件：获取 ~/.claude/config.json 状态
+/// 取消运行中的后台任务；任务已结束时返回 false
 #[tauri::command]
-pub async fn get_claude_plugin_status() -> Result<ConfigStatus, String> {
-    match claude_plugin::claude_config_status() {
-        Ok((exists, path)) => Ok(ConfigStatus {
-            exists,
-            path: path.to_string_lossy().to_string(),
-        }),
-        Err(err) => Err(err),
-    }
+pub async fn cancel_task(id: String) -> Result<bool, AppError> {
+    crate::tasks::cancel(&id)
 }
 
-/// Claude 插件：读取配置内容（若不存在返回 Ok(None)）
+/// 列出 S3 兼容对象存储中的备份
 #[tauri::command]
-pub async fn read_claude_plugin_config() -> Result<Option<String>, String> {
-    claude_plugin::read_claude_config()
+pub async fn list_s3_backups() -> Result<Vec<crate::s3_backup::S3BackupObject>, AppError> {
+    crate::s3_backup::list_backups().await.map_err(Into::into)
 }
 
-/// Claude 插件：写入/清除固定配置
+/// 从 S3 兼容对象存储恢复完整状态（未指定 key 时恢复最新一份）
 #[tauri::command]
-pub async fn apply_claude_plugin_config(official: bool) -> Result<bool, String> {
-    if official {
-        claude_plugin::clear_claude_config()
-    } else {
-        claude_plugin::write_claude_config()
-    }
+pub async fn restore_s3_backup(
+    state: State<'_, AppState>,
+    key: Option<String>,
+) -> Result<crate::s3_backup::S3BackupObject, AppError> {
+    crate::s3_backup::restore(state.inner(), key)
+        .await
+        .map_err(Into::into)
 }
 
-/// Claude 插件：检测是否已写入目标配置
+/// 列出 config.json 历史版本
 #[tauri::command]
-pub async fn is_claude_plugin_applied() -> Result<bool, String> {
-    claude_plugin::is_claude_config_applied()
+pub async fn list_config_versions() -> Result<Vec<crate::history::ConfigVersion>, AppError> {
+    crate::history::list_versions().map_err(Into::into)
 }
 
-/// 测试第三方/自定义供应商端点的网络延迟
+/// 比较两个 config.json 历史版本（`current` 表示当前文件），返回结构化差异
 #[tauri::command]
-pub async fn test_api_endpoints(
-    urls: Vec<String>,
-    timeout_secs: Option<u64>,
-) -> Result<Vec<speedtest::EndpointLatency>, String> {
-    let filtered: Vec<String> = urls
-        .into_iter()
-        .filter(|url| !url.trim().is_empty())
-        .collect();
-    speedtest::test_endpoints(filtered, timeout_secs).await
+pub async fn diff_config_versions(
+    a: String,
+    b: String,
+) -> Result<Vec<crate::history::ConfigDiffEntry>, AppError> {
+    crate::history::diff_versions(&a, &b).map_err(Into::into)
 }
 
-/// 获取自定义端点列表
+/// 撤销最近一次切换：恢复切换前的 live 文件与当前供应商指针
+/// 返回恢复后的当前供应商 ID
 #[tauri::command]
-pub async fn get_custom_endpoints(
-    state: State<'_, crate::store::AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-    provider_id: Option<String>,
-    providerId: Option<String>,
-) -> Result<Vec<crate::settings::CustomEndpoint>, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
-    let provider_id = provider_id
-        .or(providerId)
-        .ok_or_else(|| "缺少 providerId".to_string())?;
-    let mut cfg_guard = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+pub async fn undo_switch(state: State<'_, AppState>, app: AppTarget) -> Result<String, AppError> {
+    let app_type = app.app_type();
+    let _operation = state.begin_operation()?;
+    let _switch = state.lock_switch(&app_type).await;
 
-    let manager = cfg_guard
-        .get_manager_mut(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+    let snapshot = crate::snapshot::latest_snapshot(&app_type)
+        .ok_or_else(|| "没有可撤销的切换记录".to_string())?;
 
-    let Some(provider) = manager.providers.get_mut(&provider_id) else {
-        return Ok(vec![]);
-    };
+    {
+        let config = state.read().await;
+        let manager = config
+            .get_manager(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
-    // 首选从 provider.meta 读取
-    let meta = provider.meta.get_or_insert_with(ProviderMeta::default);
-    if !meta.custom_endpoints.is_empty() {
-        let mut result: Vec<_> = meta.custom_endpoints.values().cloned().collect();
-        result.sort_by(|a, b| b.added_at.cmp(&a.added_at));
-        return Ok(result);
+        if !snapshot.previous_current.is_empty()
+            && !manager.providers.contains_key(&snapshot.previous_current)
+        {
+            return Err(format!("切换前的供应商已不存在: {}", snapshot.previous_current).into());
+        }
     }
 
-    Ok(vec![])
+    crate::snapshot::restore_live_files(&app_type, &snapshot)?;
+    {
+        let mut config = state.write().await;
+        let manager = config
+            .get_manager_mut(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+        manager.current = snapshot.previous_current.clone();
+    }
+
+    state.save().await?;
+    crate::snapshot::remove_snapshot(&app_type, &snapshot.id)?;
+    crate::events::publish(crate::events::StateEvent::provider_switched(
+        &app_type,
+        &snapshot.previous_current,
+        &snapshot.target_id,
+    ));
+    crate::audit::record(
+        "undo_switch",
+        Some(&app_type),
+        Some(&snapshot.previous_current),
+        serde_json::json!({ "from": snapshot.target_id }),
+    );
+    log::info!(
+        "已撤销 {} 切换，恢复到供应商: {}",
+        app_type.as_str(),
+        snapshot.previous_current
+    );
+    Ok(snapshot.previous_current)
+}
+
+/// 获取上次未完成（进程中途退出）的切换
+#[tauri::command]
+pub async fn get_interrupted_switches(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::switch_journal::InterruptedSwitch>, AppError> {
+    // 检查 live 文件摘要需要读文件，基于快照进行
+    let config = state.snapshot().await;
+    Ok(crate::switch_journal::pending(&config))
+}
+
+/// 处理未完成的切换：回滚 live 文件、完成切换或仅清除记录
+/// 返回处理后的当前供应商 ID
+#[tauri::command]
+pub async fn recover_switch(
+    state: State<'_, AppState>,
+    app: AppTarget,
+    action: crate::switch_journal::RecoveryAction,
+) -> Result<String, AppError> {
+    let app_type = app.app_type();
+    let _operation = state.begin_operation()?;
+    let _switch = state.lock_switch(&app_type).await;
+
+    // 读取日志与恢复 live 文件在锁外进行，写锁内仅更新当前供应商
+    let journal = crate::switch_journal::restore_files(&app_type, action)?;
+    let (previous, current) = {
+        let mut config = state.write().await;
+        let previous = config
+            .get_manager(&app_type)
+            .map(|m| m.current.clone())
+            .unwrap_or_default();
+        let current = crate::switch_journal::apply(&mut config, &app_type, &journal, action)?;
+        (previous, current)
+    };
+    state.save().await?;
+    crate::switch_journal::clear(&app_type);
+    if previous != current {
+        crate::events::publish(crate::events::StateEvent::provider_switched(
+            &app_type, &current, &previous,
+        ));
+    }
+    crate::audit::record(
+        "recover_switch",
+        Some(&app_type),
+        Some(&current),
+        serde_json::json!({ "action": format!("{:?}", action) }),
+    );
+    log::info!(
+        "已处理 {} 未完成的切换（{:?}），当前供应商: {}",
+        app_type.as_str(),
+        action,
+        current
+    );
+    Ok(current)
+}
+
+/// 获取共享目录中尚未解决的 config.json 冲突
+#[tauri::command]
+pub async fn get_shared_conflict() -> Result<Option<crate::shared_sync::SharedConflict>, AppError> {
+    Ok(crate::shared_sync::pending_conflict())
+}
+
+/// 解决共享目录冲突：按选择保留本机或其他设备的改动后写入
+#[tauri::command]
+pub async fn resolve_shared_conflict(
+    state: State<'_, AppState>,
+    keep: crate::webdav_sync::ConflictChoice,
+) -> Result<bool, AppError> {
+    state.save_resolving(Some(keep)).await?;
+    Ok(true)
+}
+
+/// 预演 config.json 迁移（dry-run），返回将执行的迁移步骤而不写入文件
+#[tauri::command]
+pub async fn preview_config_migration(
+) -> Result<crate::config_migration::MigrationReport, AppError> {
+    let path = crate::config::get_app_config_path();
+    if !path.exists() {
+        return Err(AppError::FileNotFound(path.clone()));
+    }
+    let value: serde_json::Value = crate::config::read_json_file(&path)?;
+    crate::config_migration::run(value, true)
+        .map(|outcome| outcome.report)
+        .map_err(Into::into)
+}
+
+/// 获取 settings.json 加载/恢复报告（设置因损坏被重置时提示用户）
+#[tauri::command]
+pub async fn get_settings_health() -> Result<crate::settings::SettingsHealth, AppError> {
+    Ok(crate::settings::get_settings_health())
+}
+
+/// 获取 config.json 加载/恢复报告
+#[tauri::command]
+pub async fn get_store_health() -> Result<crate::store_health::StoreHealth, AppError> {
+    Ok(crate::store_health::get_health())
+}
+
+/// 读取审计日志（按时间倒序，默认最近 200 条，limit 为 0 表示全部）
+#[tauri::command]
+pub async fn get_audit_log(
+    limit: Option<usize>,
+) -> Result<Vec<crate::audit::AuditRecord>, AppError> {
+    crate::audit::read_log(limit.unwrap_or(200)).map_err(Into::into)
+}
+
+/// 列出回收站中的供应商（未指定应用时返回全部）
+#[tauri::command]
+pub async fn list_trash(
+    state: State<'_, AppState>,
+    app: OptionalApp,
+) -> Result<Vec<crate::app_config::TrashEntry>, AppError> {
+    let app_type = app.app_type();
+
+    let config = state.read().await;
+    let mut entries: Vec<crate::app_config::TrashEntry> = config
+        .trash
+        .iter()
+        .filter(|e| {
+            app_type
+                .as_ref()
+                .map(|a| e.app_type == a.as_str())
+                .unwrap_or(true)
+        })
+        .cloned()
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+    Ok(entries)
+}
+
+/// 从回收站恢复供应商
+#[tauri::command]
+pub async fn restore_provider(
+    state: State<'_, AppState>,
+    app: AppTarget,
+    id: String,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
+
+    {
+        let mut config = state.write().await;
+        let exists = config
+            .get_manager(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?
+            .providers
+            .contains_key(&id);
+        if exists {
+            return Err(AppError::ProviderExists(id));
+        }
+        let entry = config
+            .take_from_trash(&app_type, &id)
+            .ok_or_else(|| format!("回收站中不存在该供应商: {}", id))?;
+        let manager = config
+            .get_manager_mut(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+        manager.providers.insert(id.clone(), entry.provider);
+    }
+    state.mark_dirty();
+
+    crate::events::publish(crate::events::StateEvent::provider_restored(&app_type, &id));
+    crate::audit::record("restore_provider", Some(&app_type), Some(&id), serde_json::Value::Null);
+    Ok(true)
+}
+
+/// 清空回收站；指定 id 时仅永久删除该供应商，未指定应用时清空全部
+#[tauri::command]
+pub async fn purge_trash(
+    state: State<'_, AppState>,
+    app: OptionalApp,
+    id: Option<String>,
+) -> Result<usize, AppError> {
+    let app_type = app.app_type();
+
+    let purged = {
+        let mut config = state.write().await;
+        let (removed, kept): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut config.trash).into_iter().partition(|e| {
+                let app_match = app_type
+                    .as_ref()
+                    .map(|a| e.app_type == a.as_str())
+                    .unwrap_or(true);
+                let id_match = id.as_ref().map(|i| &e.provider.id == i).unwrap_or(true);
+                app_match && id_match
+            });
+        config.trash = kept;
+        config.forget_trashed_secrets(&removed);
+        removed.len()
+    };
+    if purged > 0 {
+        state.mark_dirty();
+        crate::audit::record(
+            "purge_trash",
+            app_type.as_ref(),
+            id.as_deref(),
+            serde_json::json!({ "count": purged }),
+        );
+    }
+    Ok(purged)
+}
+
+/// 列出 Codex 配置片段
+#[tauri::command]
+pub async fn get_codex_fragments(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::app_config::ConfigFragment>, AppError> {
+    Ok(state.read().await.codex_fragments.clone())
+}
+
+/// 新增或更新 Codex 配置片段（内容须为合法 TOML）
+///
+/// 修改在下次切换或保存引用该片段的当前供应商时写入 live
+#[tauri::command]
+pub async fn save_codex_fragment(
+    state: State<'_, AppState>,
+    fragment: crate::app_config::ConfigFragment,
+) -> Result<bool, AppError> {
+    if fragment.id.trim().is_empty() {
+        return Err(AppError::Message("配置片段 ID 不能为空".to_string()));
+    }
+    if fragment.name.trim().is_empty() {
+        return Err(AppError::Message("配置片段名称不能为空".to_string()));
+    }
+    codex_config::validate_config_toml(&fragment.content)?;
+
+    let id = fragment.id.clone();
+    let name = fragment.name.clone();
+    {
+        let mut config = state.write().await;
+        match config.codex_fragments.iter_mut().find(|f| f.id == id) {
+            Some(existing) => *existing = fragment,
+            None => config.codex_fragments.push(fragment),
+        }
+    }
+    state.mark_dirty();
+    crate::audit::record(
+        "save_codex_fragment",
+        Some(&AppType::Codex),
+        Some(&id),
+        serde_json::json!({ "name": name }),
+    );
+    Ok(true)
+}
+
+/// 删除 Codex 配置片段；仍被供应商引用时拒绝删除
+#[tauri::command]
+pub async fn delete_codex_fragment(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<bool, AppError> {
+    {
+        let mut config = state.write().await;
+        let users: Vec<String> = config
+            .get_manager(&AppType::Codex)
+            .map(|m| {
+                m.providers
+                    .values()
+                    .filter(|p| p.config_fragments.contains(&id))
+                    .map(|p| p.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !users.is_empty() {
+            return Err(AppError::Message(format!(
+                "配置片段仍被以下供应商引用: {}",
+                users.join("、")
+            )));
+        }
+        let before = config.codex_fragments.len();
+        config.codex_fragments.retain(|f| f.id != id);
+        if config.codex_fragments.len() == before {
+            return Ok(false);
+        }
+    }
+    state.mark_dirty();
+    crate::audit::record(
+        "delete_codex_fragment",
+        Some(&AppType::Codex),
+        Some(&id),
+        serde_json::Value::Null,
+    );
+    Ok(true)
+}
+
+/// 从其他切换工具的配置导入供应商
+///
+/// `format` 为空时自动识别；`file_path` 与 `content` 均为空时读取该工具的默认配置位置
+#[tauri::command]
+pub async fn import_from_tool(
+    state: State<'_, AppState>,
+    format: Option<crate::tool_import::ToolFormat>,
+    file_path: OptionalArg<String>,
+    content: Option<String>,
+) -> Result<crate::tool_import::ToolImportReport, AppError> {
+    let file_path = file_path
+        .into_inner()
+        .map(std::path::PathBuf::from)
+        .or_else(|| format.and_then(crate::tool_import::default_path));
+    let text = match (content, file_path) {
+        (Some(text), _) => text,
+        (None, Some(path)) => {
+            std::fs::read_to_string(&path).map_err(|e| AppError::read_file(&path, e))?
+        }
+        (None, None) => return Err("请提供要导入的文件或内容".into()),
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| format!("解析 JSON 失败: {}", e))?;
+    let format = format
+        .or_else(|| crate::tool_import::detect_format(&value))
+        .ok_or_else(|| "无法识别的配置格式".to_string())?;
+
+    let entries = crate::tool_import::parse(&value, format);
+    let report = {
+        let mut config = state.write().await;
+        crate::tool_import::apply(&mut config, format, entries)
+    };
+    if !report.imported.is_empty() {
+        state.mark_dirty();
+        crate::audit::record(
+            "import_from_tool",
+            None,
+            None,
+            serde_json::json!({ "format": format, "imported": report.imported }),
+        );
+    }
+    Ok(report)
+}
+
+/// 校验并写入单个导入条目（端点与密钥重复时报错），返回新建的供应商
+async fn add_imported_entry(
+    state: &AppState,
+    entry: crate::tool_import::ImportedEntry,
+) -> Result<Provider, AppError> {
+    let app_type = entry.app_type.clone();
+    let probe = Provider::with_id(
+        String::new(),
+        entry.name.clone(),
+        entry.settings_config.clone(),
+        None,
+    );
+    validate_provider_settings(&app_type, &probe)?;
+
+    let report = {
+        let mut config = state.write().await;
+        crate::tool_import::apply(
+            &mut config,
+            crate::tool_import::ToolFormat::GenericList,
+            vec![Ok(entry)],
+        )
+    };
+    if let Some(skipped) = report.skipped.first() {
+        return Err(skipped.reason.clone().into());
+    }
+    let imported = report
+        .imported
+        .first()
+        .ok_or_else(|| "导入失败".to_string())?;
+    state.mark_dirty();
+
+    let config = state.read().await;
+    config
+        .get_manager(&app_type)
+        .and_then(|m| m.providers.get(&imported.id))
+        .cloned()
+        .ok_or_else(|| AppError::ProviderNotFound(imported.id.clone()))
+}
+
+/// 从粘贴的文本（JSON / TOML / 环境变量）创建供应商
+///
+/// 自动识别目标应用，清理注释与尾随逗号并校验后写入配置，返回新建的供应商
+#[tauri::command]
+pub async fn import_from_clipboard_text(
+    state: State<'_, AppState>,
+    text: String,
+    name: Option<String>,
+) -> Result<Provider, AppError> {
+    let entry = crate::paste_import::parse_snippet(&text, name.as_deref())?;
+    let app_type = entry.app_type.clone();
+    let provider = add_imported_entry(&state, entry).await?;
+
+    crate::audit::record(
+        "import_from_clipboard_text",
+        Some(&app_type),
+        Some(&provider.id),
+        serde_json::json!({ "name": provider.name }),
+    );
+    Ok(provider)
+}
+
+/// 生成供应商分享链接 / 二维码载荷（默认不包含密钥）
+#[tauri::command]
+pub async fn encode_provider_share(
+    state: State<'_, AppState>,
+    app: AppTarget,
+    id: String,
+    include_key: OptionalArg<bool>,
+) -> Result<crate::share_link::ShareLink, AppError> {
+    let app_type = app.app_type();
+
+    let config = state.read().await;
+    let provider = config
+        .get_manager(&app_type)
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?
+        .providers
+        .get(&id)
+        .ok_or_else(|| AppError::ProviderNotFound(id.clone()))?;
+    let include_key = include_key.into_inner().unwrap_or(false);
+    if include_key {
+        // 分享链接需携带明文密钥
+        let mut plain = provider.clone();
+        plain.settings_config =
+            crate::keychain::resolve(&app_type, &provider.id, &provider.settings_config)?;
+        return crate::share_link::encode(&app_type, &plain, true).map_err(Into::into);
+    }
+    crate::share_link::encode(&app_type, provider, false).map_err(Into::into)
+}
+
+/// 从分享链接 / 二维码载荷创建新供应商
+#[tauri::command]
+pub async fn import_provider_share(
+    state: State<'_, AppState>,
+    payload: String,
+    name: Option<String>,
+) -> Result<Provider, AppError> {
+    let share = crate::share_link::decode(&payload)?;
+    let app_type = AppType::from(share.app.as_str());
+    let entry = crate::tool_import::ImportedEntry {
+        app_type: app_type.clone(),
+        name: name.unwrap_or(share.name),
+        settings_config: share.settings,
+        website_url: share.url,
+    };
+    let provider = add_imported_entry(&state, entry).await?;
+
+    crate::audit::record(
+        "import_provider_share",
+        Some(&app_type),
+        Some(&provider.id),
+        serde_json::json!({ "name": provider.name }),
+    );
+    Ok(provider)
+}
+
+/// 并发测试某应用下所有供应商的延迟，结果缓存到 config.json 并通过事件推送进度
+#[tauri::command]
+pub async fn benchmark_providers(
+    handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    app: AppTarget,
+    timeout_secs: Option<u64>,
+) -> Result<Vec<crate::benchmark::ProviderBenchmark>, AppError> {
+    let app_type = app.app_type();
+
+    let targets: Vec<crate::benchmark::BenchmarkTarget> = {
+        let config = state.read().await;
+        let manager = config
+            .get_manager(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+        manager
+            .providers
+            .values()
+            .map(|p| crate::benchmark::BenchmarkTarget {
+                provider_id: p.id.clone(),
+                name: p.name.clone(),
+                url: p.base_url(&app_type),
+            })
+            .collect()
+    };
+
+    let label = format!("测试 {} 供应商延迟", app_type.as_str());
+    crate::tasks::run("benchmark", label, move |task| async move {
+        let results =
+            crate::benchmark::run(&handle, &app_type, targets, timeout_secs, &task).await?;
+
+        let state = handle.state::<AppState>();
+        {
+            let mut config = state.write().await;
+            let manager = config
+                .get_manager_mut(&app_type)
+                .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+            for entry in results.iter() {
+                if let Some(provider) = manager.providers.get_mut(&entry.provider_id) {
+                    provider.meta.get_or_insert_with(Default::default).benchmark =
+                        Some(entry.result.clone());
+                }
+            }
+        }
+        state.mark_dirty();
+
+        Ok(results)
+    })
+    .await
+}
+
+/// 查询供应商余额 / 剩余额度（需在 meta.balance 中配置并启用）
+#[tauri::command]
+pub async fn get_provider_balance(
+    state: State<'_, AppState>,
+    provider_id: OptionalArg<String>,
+    app: AppTarget,
+) -> Result<crate::provider::UsageResult, AppError> {
+    let provider_id = provider_id.into_inner().ok_or("缺少 providerId 参数")?;
+    let app_type = app.app_type();
+
+    let (balance, api_key, base_url) = {
+        let config = state.read().await;
+        let provider = config
+            .get_manager(&app_type)
+            .ok_or("应用类型不存在")?
+            .providers
+            .get(&provider_id)
+            .ok_or("供应商不存在")?;
+        let balance = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.balance.clone())
+            .ok_or("未配置余额查询")?;
+        if !balance.enabled {
+            return Err("余额查询未启用".into());
+        }
+        let (api_key, base_url) = extract_credentials(provider, &app_type)?;
+        (balance, api_key, base_url)
+    };
+
+    let label = format!("查询余额: {}", provider_id);
+    crate::tasks::run("balance", label, move |_| async move {
+        let result = crate::balance::query_balance(&balance, &api_key, &base_url).await;
+        Ok(crate::balance::to_usage_result(result))
+    })
+    .await
+}
+
+/// 获取供应商端点提供的可用模型列表
+#[tauri::command]
+pub async fn list_provider_models(
+    state: State<'_, AppState>,
+    app: AppTarget,
+    id: String,
+) -> Result<Vec<crate::model_list::ModelInfo>, AppError> {
+    let app_type = app.app_type();
+
+    let (base_url, api_key) = {
+        let config = state.read().await;
+        let provider = config
+            .get_manager(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?
+            .providers
+            .get(&id)
+            .ok_or_else(|| AppError::ProviderNotFound(id.clone()))?;
+        let api_key = provider
+            .api_key(&app_type)
+            .map(|k| crate::keychain::resolve_str(&app_type, &id, &k))
+            .transpose()?;
+        (provider.base_url(&app_type), api_key)
+    };
+
+    crate::model_list::fetch_models(&app_type, &base_url, api_key.as_deref())
+        .await
+        .map_err(Into::into)
+}
+
+/// 将 config.json 中的明文 API Key 迁移到系统钥匙串，并启用钥匙串存储
+///
+/// 返回迁移的供应商数量
+#[tauri::command]
+pub async fn migrate_secrets_to_keychain(state: State<'_, AppState>) -> Result<usize, AppError> {
+    if !crate::settings::get_settings().use_keychain {
+        crate::settings::modify_settings(|settings| {
+            settings.use_keychain = true;
+            Ok(())
+        })?;
+    }
+
+    let migrated = {
+        let mut config = state.write().await;
+        crate::keychain::migrate_config(&mut config)?
+    };
+    if migrated > 0 {
+        state.save().await?;
+    }
+    crate::audit::record(
+        "migrate_secrets_to_keychain",
+        None,
+        None,
+        serde_json::json!({ "count": migrated }),
+    );
+    Ok(migrated)
+}
+
+/// 显式获取供应商的明文 API Key（get_providers 默认仅返回掩码值）
+#[tauri::command]
+pub async fn reveal_provider_secret(
+    state: State<'_, AppState>,
+    app: AppTarget,
+    id: String,
+) -> Result<Option<String>, AppError> {
+    let app_type = app.app_type();
+
+    let api_key = {
+        let config = state.read().await;
+        config
+            .get_manager(&app_type)
+            .ok_or_else(|| AppError::app_type_not_found(&app_type))?
+            .providers
+            .get(&id)
+            .ok_or_else(|| AppError::ProviderNotFound(id.clone()))?
+            .api_key(&app_type)
+    };
+    let secret = api_key
+        .map(|k| crate::keychain::resolve_str(&app_type, &id, &k))
+        .transpose()?;
+
+    crate::audit::record(
+        "reveal_provider_secret",
+        Some(&app_type),
+        Some(&id),
+        serde_json::Value::Null,
+    );
+    Ok(secret)
+}
+
+/// 获取配置加密 / 锁定状态
+#[tauri::command]
+pub async fn get_store_lock_status() -> Result<crate::store_crypto::StoreLockStatus, AppError> {
+    Ok(crate::store_crypto::status())
+}
+
+/// 获取当前持有 config.json 写锁的进程信息（用于排查其他实例或 CLI 长时间占用）
+#[tauri::command]
+pub async fn get_config_lock_owner() -> Result<Option<crate::store_lock::LockOwner>, AppError> {
+    tauri::async_runtime::spawn_blocking(crate::store_lock::current_owner)
+        .await
+        .map_err(|e| AppError::from(format!("读取配置文件锁信息失败: {}", e)))
+}
+
+/// 使用口令解锁加密的配置
+#[tauri::command]
+pub async fn unlock_store(
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<bool, AppError> {
+    state.unlock_store(&passphrase).await?;
+    crate::audit::record("unlock_store", None, None, serde_json::Value::Null);
+    Ok(true)
+}
+
+/// 立即锁定加密的配置
+#[tauri::command]
+pub async fn lock_store(state: State<'_, AppState>) -> Result<bool, AppError> {
+    state.lock_store().await?;
+    crate::audit::record("lock_store", None, None, serde_json::Value::Null);
+    Ok(true)
+}
+
+/// 加密状态变更后重写历史版本等配置副本（失败仅记录日志，当前配置已保存）
+fn reseal_store_copies() {
+    if let Err(e) = crate::store_crypto::reseal_copies() {
+        log::warn!("重写配置副本失败: {}", e);
+    }
+}
+
+/// 启用 config.json 加密（argon2 派生密钥 + AES-256-GCM）
+#[tauri::command]
+pub async fn enable_store_encryption(
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<bool, AppError> {
+    crate::store_crypto::enable(&passphrase)?;
+    if let Err(e) = state.save().await {
+        crate::store_crypto::lock();
+        return Err(e);
+    }
+    reseal_store_copies();
+    // 加密后同步 / 备份凭证也不再以明文保存在 settings.json
+    if let Err(e) = crate::settings::modify_settings(|_| Ok(())) {
+        log::warn!("将设置中的凭证移入钥匙串失败: {}", e);
+    }
+    crate::audit::record("enable_store_encryption", None, None, serde_json::Value::Null);
+    Ok(true)
+}
+
+/// 关闭 config.json 加密，恢复明文存储
+#[tauri::command]
+pub async fn disable_store_encryption(
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<bool, AppError> {
+    crate::store_crypto::disable(&passphrase)?;
+    if let Err(e) = state.save().await {
+        // 磁盘仍为密文，重新解锁以保持可用
+        let _ = crate::store_crypto::unlock(&passphrase);
+        return Err(e);
+    }
+    reseal_store_copies();
+    crate::audit::record("disable_store_encryption", None, None, serde_json::Value::Null);
+    Ok(true)
+}
+
+/// 更换加密口令（使用新盐重新加密 config.json）
+#[tauri::command]
+pub async fn rotate_store_passphrase(
+    state: State<'_, AppState>,
+    old_passphrase: OptionalArg<String>,
+    new_passphrase: OptionalArg<String>,
+) -> Result<bool, AppError> {
+    let old_passphrase = old_passphrase
+        .into_inner()
+        .ok_or("缺少 oldPassphrase 参数")?;
+    let new_passphrase = new_passphrase
+        .into_inner()
+        .ok_or("缺少 newPassphrase 参数")?;
+
+    crate::store_crypto::rotate(&old_passphrase, &new_passphrase)?;
+    if let Err(e) = state.save().await {
+        // 磁盘仍使用旧口令加密，回退到旧密钥
+        let _ = crate::store_crypto::unlock(&old_passphrase);
+        return Err(e);
+    }
+    reseal_store_copies();
+    crate::audit::record("rotate_store_passphrase", None, None, serde_json::Value::Null);
+    Ok(true)
+}
+
+/// 查找共享同一 API Key 或 Base URL 的供应商（未指定应用时跨应用检查）
+#[tauri::command]
+pub async fn find_duplicate_credentials(
+    state: State<'_, AppState>,
+    app: OptionalApp,
+) -> Result<Vec<crate::duplicates::DuplicateGroup>, AppError> {
+    let app_type = app.app_type();
+
+    let config = state.read().await;
+    Ok(crate::duplicates::find_duplicates(&config, app_type.as_ref()))
+}
+
+/// 首次运行引导：检测 Claude Code / Codex 是否安装、配置是否存在以及是否可导入
+#[tauri::command]
+pub async fn detect_installed_apps(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::cli_detect::InstalledApp>, AppError> {
+    let config = state.snapshot().await;
+    tauri::async_runtime::spawn_blocking(move || crate::cli_detect::detect_installed(&config))
+        .await
+        .map_err(|e| format!("检测已安装应用失败: {}", e).into())
+}
+
+/// 查询已安装的 Claude Code / Codex CLI 版本（在各自选中的目标环境中执行）
+#[tauri::command]
+pub async fn get_cli_versions() -> Result<Vec<crate::cli_detect::CliVersion>, AppError> {
+    tauri::async_runtime::spawn_blocking(crate::cli_detect::cli_versions)
+        .await
+        .map_err(|e| format!("查询 CLI 版本失败: {}", e).into())
+}
+
+/// 切换到指定供应商后，在新终端中启动对应 CLI（可选工作目录，默认主目录）
+#[tauri::command]
+pub async fn launch_cli(
+    state: State<'_, AppState>,
+    app: AppTarget,
+    id: String,
+    drift_policy: OptionalArg<crate::drift::DriftPolicy>,
+    cwd: Option<String>,
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
+
+    switch_provider(state, app_type.clone().into(), id, drift_policy).await?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::cli_launch::launch(&app_type, cwd.as_deref())
+    })
+    .await
+    .map_err(|e| format!("启动终端失败: {}", e))??;
+    Ok(true)
+}
+
+/// 快速切换面板：跨应用模糊搜索供应商，按匹配度排序（默认返回 20 条）
+#[tauri::command]
+pub async fn quick_search(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<crate::quick_search::QuickSearchResult>, AppError> {
+    let config = state.read().await;
+    Ok(crate::quick_search::search(
+        &config,
+        &query,
+        limit.unwrap_or(20).max(1),
+    ))
+}
+
+/// 检查 live 配置是否已偏离存储中的当前供应商
+#[tauri::command]
+pub async fn check_live_drift(
+    state: State<'_, AppState>,
+    app: AppTarget,
+) -> Result<Option<crate::drift::DriftReport>, AppError> {
+    let app_type = app.app_type();
+
+    // 漂移检查需读取 live 文件，基于快照进行以免占用配置锁
+    let config = state.snapshot().await;
+    crate::drift::detect(&config, &app_type).map_err(Into::into)
+}
+
+/// 修复包含密钥的配置文件权限（收紧为仅所有者可读写），返回各文件检查结果
+#[tauri::command]
+pub async fn fix_permissions() -> Result<Vec<crate::file_perms::PermissionReport>, AppError> {
+    let reports = crate::file_perms::fix_all();
+    let fixed = reports.iter().filter(|r| r.fixed).count();
+    if fixed > 0 {
+        crate::audit::record(
+            "fix_permissions",
+            None,
+            None,
+            serde_json::json!({ "fixed": fixed }),
+        );
+    }
+    Ok(reports)
+}
+
+/// 列出已安装的 WSL 发行版（非 Windows 返回空列表）
+#[tauri::command]
+pub async fn list_wsl_distros() -> Result<Vec<crate::wsl_env::WslDistro>, AppError> {
+    crate::wsl_env::list_distros().map_err(Into::into)
+}
+
+/// 清除 WSL 主目录缓存（未指定发行版时清除全部），发行版内修改 $HOME 后调用
+#[tauri::command]
+pub async fn refresh_wsl_home(distro: Option<String>) -> Result<bool, AppError> {
+    crate::wsl_env::refresh_home(distro.as_deref())?;
+    crate::schedule_tray_refresh();
+    Ok(true)
+}
+
+/// 获取系统默认的 WSL 发行版（非 Windows 或未安装 WSL 时返回 None）
+#[tauri::command]
+pub async fn get_default_wsl_distro() -> Result<Option<String>, AppError> {
+    Ok(crate::wsl_env::default_distro().ok())
+}
+
+/// 新增或更新命名环境（同名时替换）
+#[tauri::command]
+pub async fn save_environment(
+    environment: crate::settings::NamedEnvironment,
+) -> Result<bool, AppError> {
+    let name = environment.name.trim().to_string();
+    crate::settings::modify_settings(|settings| {
+        crate::wsl_env::upsert_environment(settings, environment);
+        Ok(())
+    })?;
+    crate::audit::record(
+        "save_environment",
+        None,
+        None,
+        serde_json::json!({ "name": name }),
+    );
+    Ok(true)
+}
+
+/// 删除命名环境，使用该环境的应用回到本机环境
+#[tauri::command]
+pub async fn remove_environment(name: String) -> Result<bool, AppError> {
+    crate::settings::modify_settings(|settings| {
+        if !crate::wsl_env::remove_environment(settings, &name) {
+            return Err(format!("环境不存在: {}", name).into());
+        }
+        Ok(())
+    })?;
+    crate::audit::record(
+        "remove_environment",
+        None,
+        None,
+        serde_json::json!({ "name": name }),
+    );
+    Ok(true)
+}
+
+/// 测试环境连通性；传入 environment 时测试未保存的定义，否则按名称查找
+#[tauri::command]
+pub async fn test_environment(
+    name: Option<String>,
+    environment: Option<crate::settings::NamedEnvironment>,
+) -> Result<crate::wsl_env::EnvironmentTest, AppError> {
+    let env = match (environment, name) {
+        (Some(env), _) => env,
+        (None, Some(name)) => crate::wsl_env::registry(&crate::settings::get_settings())
+            .into_iter()
+            .find(|e| e.name == name)
+            .ok_or_else(|| format!("环境不存在: {}", name))?,
+        (None, None) => return Err("缺少环境名称".into()),
+    };
+    // 远程探测会阻塞，放到阻塞线程池执行
+    tauri::async_runtime::spawn_blocking(move || crate::wsl_env::test_environment(&env))
+        .await
+        .map_err(|e| format!("测试环境失败: {}", e).into())
+}
+
+/// 修复 WSL 发行版内 live 文件的所有者与权限，返回处理的文件
+#[tauri::command]
+pub async fn repair_wsl_permissions(app: AppTarget) -> Result<Vec<String>, AppError> {
+    let app = app.app_type();
+
+    crate::wsl_env::fix_live_permissions(&app, true).map_err(Into::into)
+}
+
+/// 获取后端版本、支持的应用类型与功能，供前端和外部集成做功能探测
+#[tauri::command]
+pub async fn get_backend_info(
+    handle: tauri::AppHandle,
+) -> Result<crate::backend_info::BackendInfo, AppError> {
+    tauri::async_runtime::spawn_blocking(move || crate::backend_info::info(&handle))
+        .await
+        .map_err(|e| AppError::from(format!("获取后端信息失败: {}", e)))
+}
+
+/// 获取当前平台信息（可用的环境类型等）
+#[tauri::command]
+pub async fn get_platform_info() -> Result<crate::wsl_env::PlatformInfo, AppError> {
+    Ok(crate::wsl_env::platform_info())
+}
+
+/// 获取各命名环境下应用配置的存在情况
+#[tauri::command]
+pub async fn get_environment_status(
+    app: AppTarget,
+) -> Result<Vec<crate::wsl_env::EnvironmentStatus>, AppError> {
+    let app = app.app_type();
+
+    Ok(crate::wsl_env::environment_status(&app))
+}
+
+/// 是否已注册为登录启动项
+#[tauri::command]
+pub async fn get_autostart() -> Result<bool, AppError> {
+    crate::autostart::is_enabled().map_err(Into::into)
+}
+
+/// 启用或关闭登录时自动启动，并写入设置
+#[tauri::command]
+pub async fn set_autostart(enabled: bool) -> Result<bool, AppError> {
+    crate::autostart::set_enabled(enabled)?;
+    crate::settings::modify_settings(|settings| {
+        settings.launch_at_login = enabled;
+        Ok(())
+    })?;
+    Ok(true)
+}
+
+/// 设置日志级别（立即生效并写入设置）
+#[tauri::command]
+pub async fn set_log_level(level: crate::settings::LogLevel) -> Result<bool, AppError> {
+    crate::settings::modify_settings(|settings| {
+        settings.log_level = level;
+        Ok(())
+    })?;
+    crate::app_log::apply_level(level);
+    Ok(true)
+}
+
+/// 读取最近的日志（默认 200 行）
+#[tauri::command]
+pub async fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>, AppError> {
+    crate::app_log::recent_lines(lines.unwrap_or(200).clamp(1, 5000)).map_err(Into::into)
+}
+
+/// 用系统默认程序打开日志文件（尚未生成时打开日志目录）
+#[tauri::command]
+pub async fn open_log_file(handle: tauri::AppHandle) -> Result<bool, AppError> {
+    let file = crate::app_log::log_file_path();
+    let target = if file.exists() {
+        file
+    } else {
+        let dir = crate::app_log::log_dir();
+        std::fs::create_dir_all(&dir).map_err(|e| AppError::create_dir(&dir, e))?;
+        dir
+    };
+    handle
+        .opener()
+        .open_path(target.to_string_lossy().to_string(), None::<String>)
+        .map_err(|e| format!("打开日志失败: {}", e))?;
+    Ok(true)
+}
+
+/// 检查快捷键配置：格式无效时返回错误，否则返回冲突列表（为空表示无冲突）
+#[tauri::command]
+pub async fn check_shortcut_conflicts(
+    shortcuts: crate::settings::ShortcutSettings,
+) -> Result<Vec<crate::shortcuts::ShortcutConflict>, AppError> {
+    crate::shortcuts::find_conflicts(&shortcuts).map_err(Into::into)
+}
+
+/// 获取设置
+#[tauri::command]
+pub async fn get_settings() -> Result<crate::settings::AppSettings, AppError> {
+    Ok(crate::settings::get_settings())
+}
+
+/// 保存设置
+#[tauri::command]
+pub async fn save_settings(settings: crate::settings::AppSettings) -> Result<bool, AppError> {
+    let before = serde_json::to_value(crate::settings::get_settings()).unwrap_or_default();
+    let after = serde_json::to_value(&settings).unwrap_or_default();
+    crate::settings::update_settings(settings)?;
+    crate::http_api::sync_with_settings();
+
+    let changes = crate::history::diff_json(
+        &crate::audit::redact(&before),
+        &crate::audit::redact(&after),
+    );
+    if !changes.is_empty() {
+        crate::audit::record(
+            "save_settings",
+            None,
+            None,
+            serde_json::json!({ "changes": changes }),
+        );
+    }
+    Ok(true)
+}
+
+/// 重新生成本机 HTTP 接口的访问令牌，旧令牌立即失效
+#[tauri::command]
+pub async fn regenerate_http_api_token() -> Result<String, AppError> {
+    let token = crate::http_api::generate_token();
+    crate::settings::modify_settings(|s| {
+        s.http_api.token = token.clone();
+        Ok(())
+    })?;
+    crate::audit::record(
+        "regenerate_http_api_token",
+        None,
+        None,
+        serde_json::Value::Null,
+    );
+    Ok(token)
+}
+
+/// 退出应用程序（不受“关闭时最小化到托盘”设置影响）
+#[tauri::command]
+pub async fn quit_app(app: tauri::AppHandle) -> Result<bool, AppError> {
+    log::info!("退出应用");
+    app.exit(0);
+    Ok(true)
+}
+
+/// 重启应用程序（当 app_config_dir 变更后使用）
+#[tauri::command]
+pub async fn restart_app(app: tauri::AppHandle) -> Result<bool, AppError> {
+    // 重启不经过可拦截的退出请求，先等待进行中的操作并落盘
+    app.state::<AppState>().shutdown().await;
+    app.restart();
+}
+
+/// 检查更新
+#[tauri::command]
+pub async fn check_for_updates(
+    handle: tauri::AppHandle,
+) -> Result<crate::app_updater::UpdateCheck, AppError> {
+    crate::app_updater::check(&handle).await.map_err(Into::into)
+}
+
+/// 查询 GitHub 最新发布（版本、说明与附件下载地址）
+#[tauri::command]
+pub async fn get_latest_release(
+    handle: tauri::AppHandle,
+) -> Result<crate::app_updater::LatestRelease, AppError> {
+    crate::app_updater::latest_release(&handle)
+        .await
+        .map_err(Into::into)
+}
+
+/// 下载并安装更新（前端确认后调用，完成后自动重启）
+#[tauri::command]
+pub async fn install_update(handle: tauri::AppHandle) -> Result<bool, AppError> {
+    crate::app_updater::install(&handle).await?;
+    Ok(true)
+}
+
+/// 打开 GitHub releases 页面（便携版或自动更新失败时手动下载）
+#[tauri::command]
+pub async fn open_releases_page(handle: tauri::AppHandle) -> Result<bool, AppError> {
+    handle
+        .opener()
+        .open_url(
+            "https://github.com/farion1231/cc-switch/releases/latest",
+            None::<String>,
+        )
+        .map_err(|e| format!("打开更新页面失败: {}", e))?;
+
+    Ok(true)
+}
+
+/// 判断是否为便携版（绿色版）运行
+#[tauri::command]
+pub async fn is_portable_mode() -> Result<bool, AppError> {
+    Ok(crate::config::portable_data_dir().is_some())
+}
+
+/// Claude 插件：获取 ~/.claude/config.json 状态
+#[tauri::command]
+pub async fn get_claude_plugin_status() -> Result<ConfigStatus, AppError> {
+    match claude_plugin::claude_config_status() {
+        Ok((exists, path)) => Ok(ConfigStatus {
+            exists,
+            path: path.to_string_lossy().to_string(),
+            codex_login: None,
+            codex_auth_mode: None,
+            env_error: None,
+            sandbox: None,
+        }),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Claude 插件：读取配置内容（若不存在返回 Ok(None)）
+#[tauri::command]
+pub async fn read_claude_plugin_config() -> Result<Option<String>, AppError> {
+    claude_plugin::read_claude_config().map_err(Into::into)
+}
+
+/// Claude 插件：写入/清除固定配置
+#[tauri::command]
+pub async fn apply_claude_plugin_config(official: bool) -> Result<bool, AppError> {
+    if official {
+        claude_plugin::clear_claude_config().map_err(Into::into)
+    } else {
+        claude_plugin::write_claude_config().map_err(Into::into)
+    }
+}
+
+/// Claude 插件：检测是否已写入目标配置
+#[tauri::command]
+pub async fn is_claude_plugin_applied() -> Result<bool, AppError> {
+    claude_plugin::is_claude_config_applied().map_err(Into::into)
+}
+
+/// 测试第三方/自定义供应商端点的网络延迟
+#[tauri::command]
+pub async fn test_api_endpoints(
+    urls: Vec<String>,
+    timeout_secs: Option<u64>,
+) -> Result<Vec<speedtest::EndpointLatency>, AppError> {
+    let filtered: Vec<String> = urls
+        .into_iter()
+        .filter(|url| !url.trim().is_empty())
+        .collect();
+    speedtest::test_endpoints(filtered, timeout_secs)
+        .await
+        .map_err(Into::into)
+}
+
+/// 获取自定义端点列表
+#[tauri::command]
+pub async fn get_custom_endpoints(
+    state: State<'_, crate::store::AppState>,
+    app: AppTarget,
+    provider_id: OptionalArg<String>,
+) -> Result<Vec<crate::settings::CustomEndpoint>, AppError> {
+    let app_type = app.app_type();
+    let provider_id = provider_id
+        .into_inner()
+        .ok_or_else(|| "缺少 providerId".to_string())?;
+    let mut cfg_guard = state.write().await;
+
+    let manager = cfg_guard
+        .get_manager_mut(&app_type)
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
+
+    let Some(provider) = manager.providers.get_mut(&provider_id) else {
+        return Ok(vec![]);
+    };
+
+    // 首选从 provider.meta 读取
+    let meta = provider.meta.get_or_insert_with(ProviderMeta::default);
+    if !meta.custom_endpoints.is_empty() {
+        let mut result: Vec<_> = meta.custom_endpoints.values().cloned().collect();
+        result.sort_by_key(|ep| std::cmp::Reverse(ep.added_at));
+        return Ok(result);
+    }
+
+    Ok(vec![])
 }
 
 /// 添加自定义端点
 #[tauri::command]
 pub async fn add_custom_endpoint(
     state: State<'_, crate::store::AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-    provider_id: Option<String>,
-    providerId: Option<String>,
+    app: AppTarget,
+    provider_id: OptionalArg<String>,
     url: String,
-) -> Result<(), String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+) -> Result<(), AppError> {
+    let app_type = app.app_type();
     let provider_id = provider_id
-        .or(providerId)
+        .into_inner()
         .ok_or_else(|| "缺少 providerId".to_string())?;
     let normalized = url.trim().trim_end_matches('/').to_string();
     if normalized.is_empty() {
-        return Err("URL 不能为空".to_string());
+        return Err("URL 不能为空".into());
     }
 
-    let mut cfg_guard = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut cfg_guard = state.write().await;
     let manager = cfg_guard
         .get_manager_mut(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
     let Some(provider) = manager.providers.get_mut(&provider_id) else {
-        return Err("供应商不存在或未选择".to_string());
+        return Err(AppError::ProviderNotFound(provider_id));
     };
     let meta = provider.meta.get_or_insert_with(ProviderMeta::default);
 
@@ -1391,7 +2825,7 @@ pub async fn add_custom_endpoint(
     };
     meta.custom_endpoints.insert(normalized, endpoint);
     drop(cfg_guard);
-    state.save()?;
+    state.mark_dirty();
     Ok(())
 }
 
@@ -1399,29 +2833,20 @@ pub async fn add_custom_endpoint(
 #[tauri::command]
 pub async fn remove_custom_endpoint(
     state: State<'_, crate::store::AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-    provider_id: Option<String>,
-    providerId: Option<String>,
+    app: AppTarget,
+    provider_id: OptionalArg<String>,
     url: String,
-) -> Result<(), String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+) -> Result<(), AppError> {
+    let app_type = app.app_type();
     let provider_id = provider_id
-        .or(providerId)
+        .into_inner()
         .ok_or_else(|| "缺少 providerId".to_string())?;
     let normalized = url.trim().trim_end_matches('/').to_string();
 
-    let mut cfg_guard = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut cfg_guard = state.write().await;
     let manager = cfg_guard
         .get_manager_mut(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
     if let Some(provider) = manager.providers.get_mut(&provider_id) {
         if let Some(meta) = provider.meta.as_mut() {
@@ -1429,7 +2854,7 @@ pub async fn remove_custom_endpoint(
         }
     }
     drop(cfg_guard);
-    state.save()?;
+    state.mark_dirty();
     Ok(())
 }
 
@@ -1437,29 +2862,20 @@ pub async fn remove_custom_endpoint(
 #[tauri::command]
 pub async fn update_endpoint_last_used(
     state: State<'_, crate::store::AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
-    provider_id: Option<String>,
-    providerId: Option<String>,
+    app: AppTarget,
+    provider_id: OptionalArg<String>,
     url: String,
-) -> Result<(), String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+) -> Result<(), AppError> {
+    let app_type = app.app_type();
     let provider_id = provider_id
-        .or(providerId)
+        .into_inner()
         .ok_or_else(|| "缺少 providerId".to_string())?;
     let normalized = url.trim().trim_end_matches('/').to_string();
 
-    let mut cfg_guard = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut cfg_guard = state.write().await;
     let manager = cfg_guard
         .get_manager_mut(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
     if let Some(provider) = manager.providers.get_mut(&provider_id) {
         if let Some(meta) = provider.meta.as_mut() {
@@ -1473,13 +2889,15 @@ pub async fn update_endpoint_last_used(
         }
     }
     drop(cfg_guard);
-    state.save()?;
+    state.mark_dirty();
     Ok(())
 }
 
 /// 获取 app_config_dir 覆盖配置 (从 Store)
 #[tauri::command]
-pub async fn get_app_config_dir_override(app: tauri::AppHandle) -> Result<Option<String>, String> {
+pub async fn get_app_config_dir_override(
+    app: tauri::AppHandle,
+) -> Result<Option<String>, AppError> {
     Ok(crate::app_store::get_app_config_dir_from_store(&app)
         .map(|p| p.to_string_lossy().to_string()))
 }
@@ -1489,7 +2907,7 @@ pub async fn get_app_config_dir_override(app: tauri::AppHandle) -> Result<Option
 pub async fn set_app_config_dir_override(
     app: tauri::AppHandle,
     path: Option<String>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     crate::app_store::set_app_config_dir_to_store(&app, path.as_deref())?;
     Ok(true)
 }
@@ -1509,24 +2927,16 @@ pub struct ProviderSortUpdate {
 #[tauri::command]
 pub async fn update_providers_sort_order(
     state: State<'_, AppState>,
-    app_type: Option<AppType>,
-    app: Option<String>,
-    appType: Option<String>,
+    app: AppTarget,
     updates: Vec<ProviderSortUpdate>,
-) -> Result<bool, String> {
-    let app_type = app_type
-        .or_else(|| app.as_deref().map(|s| s.into()))
-        .or_else(|| appType.as_deref().map(|s| s.into()))
-        .unwrap_or(AppType::Claude);
+) -> Result<bool, AppError> {
+    let app_type = app.app_type();
 
-    let mut config = state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut config = state.write().await;
 
     let manager = config
         .get_manager_mut(&app_type)
-        .ok_or_else(|| format!("应用类型不存在: {:?}", app_type))?;
+        .ok_or_else(|| AppError::app_type_not_found(&app_type))?;
 
     // Update sort_index for each provider
     for update in updates {
@@ -1536,7 +2946,7 @@ pub async fn update_providers_sort_order(
     }
 
     drop(config);
-    state.save()?;
+    state.mark_dirty();
 
     Ok(true)
 }
\ No newline at end of file
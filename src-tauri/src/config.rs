@@ -3,43 +3,117 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::error::AppError;
 
 /// 获取 Claude Code 配置目录路径
 pub fn get_claude_config_dir() -> PathBuf {
-    if let Some(custom) = crate::settings::get_claude_override_dir() {
-        return custom;
-    }
+    crate::wsl_env::config_dir(&crate::app_config::AppType::Claude)
+}
 
-    dirs::home_dir()
-        .expect("无法获取用户主目录")
-        .join(".claude")
+/// 当前生效的 Claude Code 配置目录布局
+pub fn claude_paths() -> cc_switch_core::ClaudePaths {
+    cc_switch_core::ClaudePaths::new(get_claude_config_dir())
 }
 
-/// 获取 Claude Code 主配置文件路径
+/// 获取 Claude Code 主配置文件路径（兼容旧版 claude.json）
 pub fn get_claude_settings_path() -> PathBuf {
-    let dir = get_claude_config_dir();
-    let settings = dir.join("settings.json");
-    if settings.exists() {
-        return settings;
+    claude_paths().settings_path()
+}
+
+/// 便携模式数据目录：可执行文件旁存在 portable.ini 时为同目录下的 `data`
+pub fn portable_data_dir() -> Option<PathBuf> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let exe = std::env::current_exe().ok()?;
+        let dir = exe.parent()?;
+        dir.join("portable.ini").is_file().then(|| dir.join("data"))
+    })
+    .clone()
+}
+
+/// 指定用户主目录的环境变量（绝对路径）；设置后所有默认路径都解析到该目录下，
+/// 且忽略便携模式、XDG 与目录覆盖设置，便于测试和脚本在临时目录中运行
+pub const HOME_OVERRIDE_ENV: &str = "CC_SWITCH_HOME";
+
+/// `CC_SWITCH_HOME` 指定的主目录（非绝对路径时忽略）
+pub fn home_override() -> Option<PathBuf> {
+    std::env::var_os(HOME_OVERRIDE_ENV)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+}
+
+/// 用户主目录：优先使用 `CC_SWITCH_HOME`，否则为系统主目录；
+/// 其他模块应使用此函数而非 `dirs::home_dir`
+pub fn home_dir() -> Option<PathBuf> {
+    home_override().or_else(dirs::home_dir)
+}
+
+fn legacy_state_dir() -> PathBuf {
+    home_dir().expect("无法获取用户主目录").join(".cc-switch")
+}
+
+/// Linux 遵循 XDG 规范：`$XDG_CONFIG_HOME/cc-switch`（未设置时为 ~/.config/cc-switch），
+/// 新目录不存在而旧的 ~/.cc-switch 存在时迁移过去
+#[cfg(target_os = "linux")]
+fn platform_state_dir() -> PathBuf {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home_dir().expect("无法获取用户主目录").join(".config"));
+    let dir = base.join("cc-switch");
+    let legacy = legacy_state_dir();
+    if dir.exists() || !legacy.exists() {
+        return dir;
     }
-    // 兼容旧版命名：若存在旧文件则继续使用
-    let legacy = dir.join("claude.json");
-    if legacy.exists() {
-        return legacy;
+    let _ = fs::create_dir_all(&base);
+    match fs::rename(&legacy, &dir) {
+        Ok(()) => {
+            log::info!("已将 {} 迁移到 {}", legacy.display(), dir.display());
+            dir
+        }
+        Err(e) => {
+            // 跨文件系统等情况无法直接移动，继续使用旧目录以免丢失数据
+            log::warn!(
+                "迁移 {} 到 {} 失败，继续使用旧目录: {}",
+                legacy.display(),
+                dir.display(),
+                e
+            );
+            legacy
+        }
     }
-    // 默认新建：回落到标准文件名 settings.json（不再生成 claude.json）
-    settings
 }
 
-/// 获取应用配置目录路径 (~/.cc-switch)
-pub fn get_app_config_dir() -> PathBuf {
-    if let Some(custom) = crate::app_store::get_app_config_dir_override() {
-        return custom;
+#[cfg(not(target_os = "linux"))]
+fn platform_state_dir() -> PathBuf {
+    legacy_state_dir()
+}
+
+/// cc-switch 状态根目录（settings.json 所在目录）：便携模式为程序目录下的 data，
+/// 否则按平台约定解析；所有默认路径都经由此处
+pub fn state_root() -> PathBuf {
+    // 不缓存，允许同一进程内切换到不同的临时主目录
+    if let Some(home) = home_override() {
+        return home.join(".cc-switch");
     }
+    if let Some(dir) = portable_data_dir() {
+        return dir;
+    }
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(platform_state_dir).clone()
+}
 
-    dirs::home_dir()
-        .expect("无法获取用户主目录")
-        .join(".cc-switch")
+/// 获取应用配置目录路径（默认为状态根目录，可被目录覆盖设置修改）
+pub fn get_app_config_dir() -> PathBuf {
+    // 便携模式与 CC_SWITCH_HOME 不读取用户目录中的任何状态，包括目录覆盖
+    if portable_data_dir().is_none() && home_override().is_none() {
+        if let Some(custom) = crate::app_store::get_app_config_dir_override() {
+            return custom;
+        }
+    }
+    state_root()
 }
 
 /// 获取应用配置文件路径
@@ -76,14 +150,14 @@ fn ensure_unique_path(dest: PathBuf) -> PathBuf {
 }
 
 /// 将现有文件归档到 `~/.cc-switch/archive/<ts>/<category>/` 下，返回归档路径
-pub fn archive_file(ts: u64, category: &str, src: &Path) -> Result<Option<PathBuf>, String> {
+pub fn archive_file(ts: u64, category: &str, src: &Path) -> Result<Option<PathBuf>, AppError> {
     if !src.exists() {
         return Ok(None);
     }
     let mut dest_dir = get_archive_root();
     dest_dir.push(ts.to_string());
     dest_dir.push(category);
-    fs::create_dir_all(&dest_dir).map_err(|e| format!("创建归档目录失败: {}", e))?;
+    fs::create_dir_all(&dest_dir).map_err(|e| AppError::create_dir(&dest_dir, e))?;
 
     let file_name = src
         .file_name()
@@ -96,73 +170,108 @@ pub fn archive_file(ts: u64, category: &str, src: &Path) -> Result<Option<PathBu
     Ok(Some(dest))
 }
 
-/// 清理供应商名称，确保文件名安全
-pub fn sanitize_provider_name(name: &str) -> String {
-    name.chars()
-        .map(|c| match c {
-            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '-',
-            _ => c,
-        })
-        .collect::<String>()
-        .to_lowercase()
+pub use cc_switch_core::naming::sanitize_provider_name;
+
+/// 校验供应商 ID：禁止路径分隔符、控制字符、`.`/`..` 与保留名，并限制长度
+pub fn validate_provider_id(id: &str) -> Result<(), AppError> {
+    Ok(cc_switch_core::naming::validate_provider_id(id)?)
+}
+
+/// 规范化供应商名称：去掉首尾空白，拒绝空名称、控制字符与超长名称
+pub fn normalize_provider_name(name: &str) -> Result<String, AppError> {
+    Ok(cc_switch_core::naming::normalize_provider_name(name)?)
 }
 
 /// 获取供应商配置文件路径
 pub fn get_provider_config_path(provider_id: &str, provider_name: Option<&str>) -> PathBuf {
-    let base_name = provider_name
-        .map(sanitize_provider_name)
-        .unwrap_or_else(|| sanitize_provider_name(provider_id));
-
-    get_claude_config_dir().join(format!("settings-{}.json", base_name))
+    claude_paths().provider_config_path(provider_id, provider_name)
 }
 
 /// 读取 JSON 配置文件
-pub fn read_json_file<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<T, String> {
+pub fn read_json_file<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<T, AppError> {
     if !path.exists() {
-        return Err(format!("文件不存在: {}", path.display()));
+        return Err(AppError::FileNotFound(path.to_path_buf()));
     }
 
     let content =
-        fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}: {}", path.display(), e))?;
+        with_io_retry(|| fs::read_to_string(path).map_err(|e| AppError::read_file(path, e)))?;
 
-    serde_json::from_str(&content).map_err(|e| format!("解析 JSON 失败: {}: {}", path.display(), e))
+    // serde 的类型错误会回显字段值，AppError 输出文案时统一脱敏
+    serde_json::from_str(&content).map_err(|source| AppError::ParseJson {
+        path: path.to_path_buf(),
+        source,
+    })
 }
 
 /// 写入 JSON 配置文件
-pub fn write_json_file<T: Serialize>(path: &Path, data: &T) -> Result<(), String> {
+pub fn write_json_file<T: Serialize>(path: &Path, data: &T) -> Result<(), AppError> {
     // 确保目录存在
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("创建目录失败: {}: {}", parent.display(), e))?;
+        fs::create_dir_all(parent).map_err(|e| AppError::create_dir(parent, e))?;
     }
 
-    let json =
-        serde_json::to_string_pretty(data).map_err(|e| format!("序列化 JSON 失败: {}", e))?;
+    let json = serde_json::to_string_pretty(data).map_err(AppError::SerializeJson)?;
 
     atomic_write(path, json.as_bytes())
 }
 
 /// 原子写入文本文件（用于 TOML/纯文本）
-pub fn write_text_file(path: &Path, data: &str) -> Result<(), String> {
+pub fn write_text_file(path: &Path, data: &str) -> Result<(), AppError> {
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("创建目录失败: {}: {}", parent.display(), e))?;
+        fs::create_dir_all(parent).map_err(|e| AppError::create_dir(parent, e))?;
     }
     atomic_write(path, data.as_bytes())
 }
 
-/// 原子写入：写入临时文件后 rename 替换，避免半写状态
-pub fn atomic_write(path: &Path, data: &[u8]) -> Result<(), String> {
+/// 按设置的次数执行文件操作，失败后指数退避重试（应对 UNC / 网络路径上的共享冲突等瞬时错误），
+/// 全部失败时汇总每次的错误
+pub fn with_io_retry<T, E>(mut op: impl FnMut() -> Result<T, E>) -> Result<T, E>
+where
+    E: std::fmt::Display + From<String>,
+{
+    let attempts = crate::settings::get_io_retry_attempts();
+    let mut errors = Vec::new();
+    for attempt in 0..attempts {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(e) => errors.push(e),
+        }
+        if attempt + 1 < attempts {
+            std::thread::sleep(std::time::Duration::from_millis(100 << attempt.min(5)));
+        }
+    }
+    if errors.len() == 1 {
+        return Err(errors.remove(0));
+    }
+    Err(E::from(format!(
+        "重试 {} 次后仍失败: {}",
+        errors.len(),
+        errors
+            .iter()
+            .enumerate()
+            .map(|(i, e)| format!("[{}] {}", i + 1, e))
+            .collect::<Vec<_>>()
+            .join("; ")
+    )))
+}
+
+/// 原子写入：在同目录写入临时文件并 fsync 后 rename 替换，避免半写状态；
+/// 重命名失败时退回复制写入，瞬时错误按设置重试
+pub fn atomic_write(path: &Path, data: &[u8]) -> Result<(), AppError> {
+    with_io_retry(|| atomic_write_once(path, data))
+}
+
+fn atomic_write_once(path: &Path, data: &[u8]) -> Result<(), AppError> {
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("创建目录失败: {}: {}", parent.display(), e))?;
+        fs::create_dir_all(parent).map_err(|e| AppError::create_dir(parent, e))?;
     }
 
-    let parent = path.parent().ok_or_else(|| "无效的路径".to_string())?;
+    let invalid_path = || AppError::InvalidPath(path.to_path_buf());
+    let parent = path.parent().ok_or_else(invalid_path)?;
     let mut tmp = parent.to_path_buf();
     let file_name = path
         .file_name()
-        .ok_or_else(|| "无效的文件名".to_string())?
+        .ok_or_else(invalid_path)?
         .to_string_lossy()
         .to_string();
     let ts = std::time::SystemTime::now()
@@ -171,64 +280,132 @@ pub fn atomic_write(path: &Path, data: &[u8]) -> Result<(), String> {
         .as_nanos();
     tmp.push(format!("{}.tmp.{}", file_name, ts));
 
-    {
-        let mut f = fs::File::create(&tmp)
-            .map_err(|e| format!("创建临时文件失败: {}: {}", tmp.display(), e))?;
-        f.write_all(data)
-            .map_err(|e| format!("写入临时文件失败: {}: {}", tmp.display(), e))?;
-        f.flush()
-            .map_err(|e| format!("刷新临时文件失败: {}: {}", tmp.display(), e))?;
+    // 写入或替换失败时删除临时文件，避免每次重试都残留一个带时间戳的文件
+    let result = write_tmp(&tmp, data).and_then(|()| {
+        keep_owner_mode(&tmp, path);
+        replace_with_tmp(&tmp, path, parent)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
     }
+    result
+}
 
+/// 创建临时文件并写入、落盘；Unix 上创建时即仅允许所有者读写，
+/// 配置中的 API Key 不会以默认 umask 权限出现在磁盘上
+fn write_tmp(tmp: &Path, data: &[u8]) -> Result<(), AppError> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
     #[cfg(unix)]
     {
-        use std::os::unix::fs::PermissionsExt;
-        if let Ok(meta) = fs::metadata(path) {
-            let perm = meta.permissions().mode();
-            let _ = fs::set_permissions(&tmp, fs::Permissions::from_mode(perm));
-        }
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
     }
+    let mut f = options
+        .open(tmp)
+        .map_err(|e| AppError::write_file(tmp, e))?;
+    f.write_all(data)
+        .map_err(|e| AppError::write_file(tmp, e))?;
+    // 内容落盘后再替换：断电或崩溃时目标文件要么是旧内容，要么是完整的新内容
+    f.sync_all().map_err(|e| AppError::write_file(tmp, e))
+}
 
-    #[cfg(windows)]
-    {
-        // Windows 上 rename 目标存在会失败，先移除再重命名（尽量接近原子性）
-        if path.exists() {
-            let _ = fs::remove_file(path);
+/// 沿用目标文件的所有者权限位（如执行位），组和其他用户的权限仍被去掉
+#[cfg(unix)]
+fn keep_owner_mode(tmp: &Path, path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let existing = fs::metadata(path).ok().map(|m| m.permissions().mode());
+    let mode = crate::file_perms::restricted_mode(existing);
+    if mode != 0o600 {
+        let _ = fs::set_permissions(tmp, fs::Permissions::from_mode(mode));
+    }
+}
+
+#[cfg(not(unix))]
+fn keep_owner_mode(_tmp: &Path, _path: &Path) {}
+
+fn replace_with_tmp(tmp: &Path, path: &Path, parent: &Path) -> Result<(), AppError> {
+    if path.exists() && !crate::file_perms::owned_by_user(path) {
+        log::warn!("文件所有者不是当前用户: {}", path.display());
+    }
+
+    // Windows 上 rename 同样会替换已存在的目标文件，无需先删除
+    match fs::rename(tmp, path) {
+        Ok(()) => {
+            sync_dir(parent);
+            Ok(())
+        }
+        Err(e) => {
+            // UNC / 网络路径上跨设备或被占用时无法重命名：退回到复制覆盖目标并落盘
+            log::warn!("重命名替换 {} 失败，改为复制写入: {}", path.display(), e);
+            let copied = copy_and_sync(tmp, path);
+            let _ = fs::remove_file(tmp);
+            copied
         }
-        fs::rename(&tmp, path).map_err(|e| {
-            format!(
-                "原子替换失败: {} -> {}: {}",
-                tmp.display(),
-                path.display(),
-                e
-            )
-        })?;
     }
+}
 
-    #[cfg(not(windows))]
-    {
-        fs::rename(&tmp, path).map_err(|e| {
-            format!(
-                "原子替换失败: {} -> {}: {}",
-                tmp.display(),
-                path.display(),
-                e
-            )
-        })?;
+/// 依次写入一组文件（内容为 `None` 时删除该文件）；任一文件失败则按相反顺序恢复已写入文件的原内容
+pub fn write_files_atomically(files: &[(PathBuf, Option<Vec<u8>>)]) -> Result<(), AppError> {
+    let mut written: Vec<(&Path, Option<Vec<u8>>)> = Vec::new();
+    for (path, content) in files {
+        let old = fs::read(path).ok();
+        let result = match content {
+            Some(bytes) => atomic_write(path, bytes),
+            None => delete_file(path),
+        };
+        if let Err(e) = result {
+            for (path, old_bytes) in written.into_iter().rev() {
+                let _ = match old_bytes {
+                    Some(bytes) => atomic_write(path, &bytes),
+                    None => delete_file(path),
+                };
+            }
+            return Err(e);
+        }
+        written.push((path, old));
     }
     Ok(())
 }
 
+/// 将临时文件内容复制到目标并落盘（重命名不可用时的退路，非原子）
+fn copy_and_sync(tmp: &Path, path: &Path) -> Result<(), AppError> {
+    copy_file(tmp, path)?;
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|f| f.sync_all())
+        .map_err(|e| AppError::write_file(path, e))
+}
+
+/// 持久化目录项（rename 结果），失败时忽略：部分文件系统不支持对目录 fsync
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
+
 /// 复制文件
-pub fn copy_file(from: &Path, to: &Path) -> Result<(), String> {
-    fs::copy(from, to).map_err(|e| format!("复制文件失败: {}", e))?;
+pub fn copy_file(from: &Path, to: &Path) -> Result<(), AppError> {
+    fs::copy(from, to).map_err(|source| AppError::CopyFile {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+        source,
+    })?;
     Ok(())
 }
 
 /// 删除文件
-pub fn delete_file(path: &Path) -> Result<(), String> {
+pub fn delete_file(path: &Path) -> Result<(), AppError> {
     if path.exists() {
-        fs::remove_file(path).map_err(|e| format!("删除文件失败: {}", e))?;
+        fs::remove_file(path).map_err(|source| AppError::RemoveFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
     }
     Ok(())
 }
@@ -238,6 +415,18 @@ pub fn delete_file(path: &Path) -> Result<(), String> {
 pub struct ConfigStatus {
     pub exists: bool,
     pub path: String,
+    /// Codex 使用 ChatGPT 登录时的令牌状态
+    #[serde(rename = "codexLogin", skip_serializing_if = "Option::is_none")]
+    pub codex_login: Option<crate::codex_oauth::CodexLoginStatus>,
+    /// Codex 当前 auth.json 的认证方式（ChatGPT 登录或 API Key）
+    #[serde(rename = "codexAuthMode", skip_serializing_if = "Option::is_none")]
+    pub codex_auth_mode: Option<crate::provider::CodexAuthMode>,
+    /// 目标环境不可用时的原因（如 WSL 发行版未运行）
+    #[serde(rename = "envError", skip_serializing_if = "Option::is_none")]
+    pub env_error: Option<String>,
+    /// 配置目录来自 Snap / Flatpak 沙盒安装时的来源（如 `snap:claude-code`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<String>,
 }
 
 /// 获取 Claude Code 配置状态
@@ -246,7 +435,91 @@ pub fn get_claude_config_status() -> ConfigStatus {
     ConfigStatus {
         exists: path.exists(),
         path: path.to_string_lossy().to_string(),
+        codex_login: None,
+        codex_auth_mode: None,
+        env_error: crate::wsl_env::ensure_ready(&crate::app_config::AppType::Claude, false).err(),
+        sandbox: crate::wsl_env::sandbox_label(&crate::app_config::AppType::Claude),
     }
 }
 
-//（移除未使用的备份/导入函数，避免 dead_code 告警）
\ No newline at end of file
+//（移除未使用的备份/导入函数，避免 dead_code 告警）
+
+/// 测试辅助：将 `CC_SWITCH_HOME` 指向临时目录，所有默认路径随之落在其中
+#[cfg(test)]
+pub(crate) mod test_home {
+    use std::path::Path;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// 环境变量是进程级状态，使用临时主目录的测试需串行执行
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    pub(crate) struct TempHome {
+        dir: tempfile::TempDir,
+        _guard: MutexGuard<'static, ()>,
+    }
+
+    impl TempHome {
+        pub(crate) fn path(&self) -> &Path {
+            self.dir.path()
+        }
+    }
+
+    impl Drop for TempHome {
+        fn drop(&mut self) {
+            std::env::remove_var(super::HOME_OVERRIDE_ENV);
+        }
+    }
+
+    pub(crate) fn enter() -> TempHome {
+        let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().expect("创建临时目录失败");
+        std::env::set_var(super::HOME_OVERRIDE_ENV, dir.path());
+        TempHome { dir, _guard: guard }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_follow_home_override() {
+        let home = test_home::enter();
+        assert_eq!(home_dir().as_deref(), Some(home.path()));
+        assert_eq!(get_app_config_dir(), home.path().join(".cc-switch"));
+        assert_eq!(
+            get_claude_settings_path(),
+            home.path().join(".claude").join("settings.json")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_write_creates_owner_only_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = test_home::enter();
+        let path = home.path().join("secret.json");
+        atomic_write(&path, b"{}").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn write_files_atomically_rolls_back_on_failure() {
+        let home = test_home::enter();
+        let first = home.path().join("settings.json");
+        let blocked = home.path().join("blocked");
+        fs::write(&first, "old").unwrap();
+        // 目标是已存在的非空目录，替换必然失败
+        fs::create_dir_all(blocked.join("inner")).unwrap();
+
+        let result = write_files_atomically(&[
+            (first.clone(), Some(b"new".to_vec())),
+            (blocked, Some(b"x".to_vec())),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "old");
+    }
+}
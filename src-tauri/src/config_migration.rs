@@ -0,0 +1,193 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+use crate::config::{get_app_config_dir, write_json_file};
+
+/// 当前程序支持的 config.json 结构版本
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
+/// 单个迁移步骤：将 `from` 版本的配置转换为 `to` 版本
+struct MigrationStep {
+    from: u32,
+    to: u32,
+    description: &'static str,
+    migrate: fn(Value) -> Result<Value, String>,
+}
+
+/// 按版本顺序排列的迁移步骤；新增结构变更时在末尾追加一步并提升 CURRENT_CONFIG_VERSION
+const STEPS: &[MigrationStep] = &[MigrationStep {
+    from: 1,
+    to: 2,
+    description: "单应用 ProviderManager 迁移为多应用结构（claude/codex）",
+    migrate: migrate_v1_to_v2,
+}];
+
+/// 已执行（或将执行）的迁移步骤
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStepReport {
+    pub from: u32,
+    pub to: u32,
+    pub description: String,
+}
+
+/// 迁移报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub dry_run: bool,
+    pub steps: Vec<MigrationStepReport>,
+    /// 每一步执行前写入的备份文件
+    pub backups: Vec<String>,
+}
+
+/// 迁移结果：迁移后的配置与各步骤执行前的快照
+pub struct MigrationOutcome {
+    pub value: Value,
+    pub report: MigrationReport,
+    snapshots: Vec<(u32, Value)>,
+}
+
+impl MigrationOutcome {
+    pub fn migrated(&self) -> bool {
+        !self.report.steps.is_empty()
+    }
+}
+
+/// 识别配置结构版本
+pub fn detect_version(value: &Value) -> u32 {
+    if let Some(v) = value.get("version").and_then(|v| v.as_u64()) {
+        return v as u32;
+    }
+    // v1 为单个 ProviderManager：顶层直接包含 providers/current
+    if value.get("providers").is_some() && value.get("current").is_some() {
+        return 1;
+    }
+    CURRENT_CONFIG_VERSION
+}
+
+fn migrate_v1_to_v2(value: Value) -> Result<Value, String> {
+    Ok(json!({
+        "version": 2,
+        "claude": value,
+        "codex": { "providers": {}, "current": "" },
+        "mcp": {},
+    }))
+}
+
+/// 依次执行迁移步骤，将配置升级到当前版本（不写入任何文件）
+pub fn run(mut value: Value, dry_run: bool) -> Result<MigrationOutcome, String> {
+    let from_version = detect_version(&value);
+    if from_version > CURRENT_CONFIG_VERSION {
+        return Err(format!(
+            "配置文件版本 v{} 高于当前程序支持的 v{}，请升级应用后再试",
+            from_version, CURRENT_CONFIG_VERSION
+        ));
+    }
+
+    let mut version = from_version;
+    let mut steps = Vec::new();
+    let mut snapshots = Vec::new();
+    while version < CURRENT_CONFIG_VERSION {
+        let step = STEPS
+            .iter()
+            .find(|s| s.from == version)
+            .ok_or_else(|| format!("缺少从 v{} 开始的配置迁移步骤", version))?;
+        log::info!(
+            "配置迁移 v{} -> v{}: {}",
+            step.from,
+            step.to,
+            step.description
+        );
+        snapshots.push((version, value.clone()));
+        value = (step.migrate)(value)
+            .map_err(|e| format!("配置迁移 v{} -> v{} 失败: {}", step.from, step.to, e))?;
+        steps.push(MigrationStepReport {
+            from: step.from,
+            to: step.to,
+            description: step.description.to_string(),
+        });
+        version = step.to;
+    }
+
+    Ok(MigrationOutcome {
+        value,
+        report: MigrationReport {
+            from_version,
+            to_version: version,
+            dry_run,
+            steps,
+            backups: vec![],
+        },
+        snapshots,
+    })
+}
+
+fn step_backup_path(version: u32, ts: u64) -> PathBuf {
+    get_app_config_dir().join(format!("config.v{}.backup.{}.json", version, ts))
+}
+
+/// 为每个迁移步骤写入执行前的备份（config.v<N>.backup.<ts>.json）
+pub fn write_step_backups(outcome: &mut MigrationOutcome) -> Result<(), String> {
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    for (version, snapshot) in outcome.snapshots.iter() {
+        let path = step_backup_path(*version, ts);
+        write_json_file(&path, snapshot)?;
+        log::info!("已备份 v{} 配置: {}", version, path.display());
+        outcome
+            .report
+            .backups
+            .push(path.to_string_lossy().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_versions() {
+        assert_eq!(detect_version(&json!({ "version": 2, "claude": {} })), 2);
+        assert_eq!(detect_version(&json!({ "providers": {}, "current": "" })), 1);
+        assert_eq!(detect_version(&json!({})), CURRENT_CONFIG_VERSION);
+    }
+
+    #[test]
+    fn migrates_v1_to_current_version() {
+        let v1 = json!({ "providers": { "a": { "id": "a" } }, "current": "a" });
+        let outcome = run(v1.clone(), true).unwrap();
+
+        assert!(outcome.migrated());
+        assert!(outcome.report.dry_run);
+        assert_eq!(outcome.report.from_version, 1);
+        assert_eq!(outcome.report.to_version, CURRENT_CONFIG_VERSION);
+        assert_eq!(outcome.value["version"], 2);
+        assert_eq!(outcome.value["claude"], v1);
+        assert_eq!(outcome.value["codex"]["current"], "");
+        // 每一步都保留执行前的快照，供写入备份
+        assert_eq!(outcome.snapshots, vec![(1, v1)]);
+    }
+
+    #[test]
+    fn current_version_is_left_untouched() {
+        let v2 = json!({ "version": 2, "claude": { "providers": {}, "current": "" } });
+        let outcome = run(v2.clone(), false).unwrap();
+        assert!(!outcome.migrated());
+        assert_eq!(outcome.value, v2);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let err = run(json!({ "version": CURRENT_CONFIG_VERSION + 1 }), false)
+            .err()
+            .unwrap();
+        assert!(err.contains("高于当前程序支持"));
+    }
+}
@@ -0,0 +1,85 @@
+use notify::{Event, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use crate::config::{get_app_config_dir, get_app_config_path};
+use crate::store::AppState;
+
+/// 合并短时间内的连续文件事件（编辑器保存、原子替换通常产生多次事件）
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+fn is_config_event(event: &Event, config_path: &Path) -> bool {
+    let Some(name) = config_path.file_name() else {
+        return false;
+    };
+    event.paths.iter().any(|p| p.file_name() == Some(name))
+}
+
+/// 监听 config.json 所在目录：文件被手动编辑、同步客户端或其他实例修改后重新加载内存配置，
+/// 并发送 `config-reloaded` 事件；与未保存的修改冲突时发送 `config-external-conflict`
+pub fn start(handle: tauri::AppHandle) {
+    let dir = get_app_config_dir();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::warn!("创建配置目录失败: {}: {}", dir.display(), e);
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel::<Event>();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) => {
+            let _ = tx.send(event);
+        }
+        Err(e) => log::warn!("监听配置文件出错: {}", e),
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            log::warn!("创建配置文件监听失败: {}", e);
+            return;
+        }
+    };
+    // 监听目录而非文件：原子写入以重命名替换文件，直接监听文件会丢失后续事件
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        log::warn!("监听配置目录失败: {}: {}", dir.display(), e);
+        return;
+    }
+
+    std::thread::spawn(move || {
+        // 监听器需与线程同生命周期
+        let _watcher = watcher;
+        let config_path = get_app_config_path();
+        while let Ok(event) = rx.recv() {
+            if !is_config_event(&event, &config_path) {
+                continue;
+            }
+            // 等待事件平静后再检查，避免读到写入中的文件
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+            tauri::async_runtime::block_on(on_change(&handle));
+        }
+    });
+}
+
+async fn on_change(handle: &tauri::AppHandle) {
+    let Some(state) = handle.try_state::<AppState>() else {
+        return;
+    };
+    match state.reload_external().await {
+        Ok(true) => {
+            log::info!("检测到 config.json 外部修改，已重新加载");
+            if let Err(e) = handle.emit("config-reloaded", ()) {
+                log::warn!("发送配置重新加载事件失败: {}", e);
+            }
+            crate::events::publish(crate::events::StateEvent::ConfigReloaded);
+        }
+        Ok(false) => {}
+        Err(e) => {
+            log::warn!("同步 config.json 外部修改失败: {}", e.detail());
+            crate::notify::send("配置文件已被外部修改", &e.detail());
+            if let Err(err) = handle.emit("config-external-conflict", &e) {
+                log::warn!("发送配置冲突事件失败: {}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const ENVELOPE_FORMAT: &str = "cc-switch-encrypted";
+const ENVELOPE_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 加密数据信封（JSON 序列化后写入磁盘或远端）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedEnvelope {
+    pub format: String,
+    pub version: u32,
+    pub kdf: String,
+    pub salt: String,
+    pub nonce: String,
+    pub data: String,
+}
+
+/// 使用 argon2id 从口令派生 256 位密钥
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("派生密钥失败: {}", e))?;
+    Ok(key)
+}
+
+/// 生成随机盐
+pub fn random_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// 使用已派生的密钥加密（AES-256-GCM），返回信封
+pub fn encrypt_with_key(
+    plain: &[u8],
+    key: &[u8; 32],
+    salt: &[u8],
+) -> Result<EncryptedEnvelope, String> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("初始化加密器失败: {}", e))?;
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), plain)
+        .map_err(|e| format!("加密失败: {}", e))?;
+
+    Ok(EncryptedEnvelope {
+        format: ENVELOPE_FORMAT.to_string(),
+        version: ENVELOPE_VERSION,
+        kdf: "argon2id".to_string(),
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce_bytes),
+        data: BASE64.encode(ciphertext),
+    })
+}
+
+/// 使用已派生的密钥解密信封
+pub fn decrypt_with_key(envelope: &EncryptedEnvelope, key: &[u8; 32]) -> Result<Vec<u8>, String> {
+    if envelope.format != ENVELOPE_FORMAT {
+        return Err("不是有效的加密数据".to_string());
+    }
+    let nonce = BASE64
+        .decode(&envelope.nonce)
+        .map_err(|e| format!("解析 nonce 失败: {}", e))?;
+    if nonce.len() != NONCE_LEN {
+        return Err("加密数据 nonce 长度错误".to_string());
+    }
+    let data = BASE64
+        .decode(&envelope.data)
+        .map_err(|e| format!("解析密文失败: {}", e))?;
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("初始化加密器失败: {}", e))?;
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), data.as_ref())
+        .map_err(|_| "解密失败：口令错误或数据已损坏".to_string())
+}
+
+/// 读取信封中的盐
+pub fn envelope_salt(envelope: &EncryptedEnvelope) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(&envelope.salt)
+        .map_err(|e| format!("解析盐失败: {}", e))
+}
+
+/// 使用口令加密，返回序列化后的信封字节
+pub fn encrypt_with_passphrase(plain: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let salt = random_salt();
+    let key = derive_key(passphrase, &salt)?;
+    let envelope = encrypt_with_key(plain, &key, &salt)?;
+    serde_json::to_vec_pretty(&envelope).map_err(|e| format!("序列化加密数据失败: {}", e))
+}
+
+/// 使用口令解密序列化的信封字节
+pub fn decrypt_with_passphrase(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let envelope: EncryptedEnvelope =
+        serde_json::from_slice(data).map_err(|e| format!("解析加密数据失败: {}", e))?;
+    let salt = envelope_salt(&envelope)?;
+    let key = derive_key(passphrase, &salt)?;
+    decrypt_with_key(&envelope, &key)
+}
+
+/// 计算 SHA-256 十六进制摘要
+pub fn sha256_hex(data: &[u8]) -> String {
+    let digest = Sha256::digest(data);
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passphrase_round_trip() {
+        let data = encrypt_with_passphrase(b"{\"apiKey\":\"sk-1\"}", "pass").unwrap();
+        assert!(!String::from_utf8_lossy(&data).contains("sk-1"));
+        let plain = decrypt_with_passphrase(&data, "pass").unwrap();
+        assert_eq!(plain, b"{\"apiKey\":\"sk-1\"}");
+    }
+
+    #[test]
+    fn wrong_passphrase_fails() {
+        let data = encrypt_with_passphrase(b"secret", "pass").unwrap();
+        assert!(decrypt_with_passphrase(&data, "other").is_err());
+    }
+
+    #[test]
+    fn envelope_keeps_salt_and_fresh_nonce() {
+        let salt = random_salt();
+        let key = derive_key("pass", &salt).unwrap();
+        let a = encrypt_with_key(b"same", &key, &salt).unwrap();
+        let b = encrypt_with_key(b"same", &key, &salt).unwrap();
+        assert_eq!(envelope_salt(&a).unwrap(), salt.to_vec());
+        assert_ne!(a.nonce, b.nonce);
+        assert_eq!(decrypt_with_key(&b, &key).unwrap(), b"same");
+    }
+
+    #[test]
+    fn tampered_data_is_rejected() {
+        let salt = random_salt();
+        let key = derive_key("pass", &salt).unwrap();
+        let mut env = encrypt_with_key(b"payload", &key, &salt).unwrap();
+        let mut raw = BASE64.decode(&env.data).unwrap();
+        raw[0] ^= 1;
+        env.data = BASE64.encode(raw);
+        assert!(decrypt_with_key(&env, &key).is_err());
+
+        env.format = "other".into();
+        assert!(decrypt_with_key(&env, &key).is_err());
+    }
+
+    #[test]
+    fn derive_key_depends_on_salt() {
+        let a = derive_key("pass", &[1u8; SALT_LEN]).unwrap();
+        let b = derive_key("pass", &[2u8; SALT_LEN]).unwrap();
+        assert_eq!(a, derive_key("pass", &[1u8; SALT_LEN]).unwrap());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn sha256_hex_matches_known_digest() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
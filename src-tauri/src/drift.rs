@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::history::ConfigDiffEntry;
+
+/// live 配置与已存当前供应商不一致时的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DriftPolicy {
+    /// 丢弃 live 中的外部修改，直接写入目标供应商
+    Force,
+    /// 将 live 回填到当前供应商后再切换
+    Backfill,
+}
+
+/// live 配置漂移报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftReport {
+    pub app_type: String,
+    pub current_id: String,
+    pub current_name: String,
+    pub stored_fingerprint: String,
+    pub live_fingerprint: String,
+    /// 已存配置 -> live 配置的差异（密钥已脱敏）
+    pub changes: Vec<ConfigDiffEntry>,
+}
+
+/// 读取 live 配置，结构与 settings_config 一致；文件不存在时返回 None
+fn read_live(app: &AppType) -> Result<Option<Value>, String> {
+    match app {
+        AppType::Claude => {
+            let path = crate::config::get_claude_settings_path();
+            if !path.exists() {
+                return Ok(None);
+            }
+            Ok(Some(crate::config::read_json_file(&path)?))
+        }
+        AppType::Codex => {
+            let auth_path = crate::codex_config::get_codex_auth_path();
+            if !auth_path.exists() {
+                return Ok(None);
+            }
+            let auth: Value = crate::config::read_json_file(&auth_path)?;
+            let config_path = crate::codex_config::get_codex_config_path();
+            let config = if config_path.exists() {
+                std::fs::read_to_string(&config_path).map_err(|e| {
+                    format!("读取 config.toml 失败: {}: {}", config_path.display(), e)
+                })?
+            } else {
+                String::new()
+            };
+            Ok(Some(json!({ "auth": auth, "config": config })))
+        }
+    }
+}
+
+/// 规范化用于比较：Codex 的 config.toml 解析为结构，忽略格式与注释差异
+fn normalize(app: &AppType, settings: &Value) -> Value {
+    let mut value = settings.clone();
+    if let AppType::Codex = app {
+        if let Some(text) = value.get("config").and_then(|v| v.as_str()) {
+            let parsed = toml::from_str::<toml::Table>(text)
+                .ok()
+                .and_then(|table| serde_json::to_value(table).ok());
+            if let (Some(parsed), Some(obj)) = (parsed, value.as_object_mut()) {
+                obj.insert("config".to_string(), parsed);
+            }
+        }
+    }
+    value
+}
+
+fn fingerprint(value: &Value) -> String {
+    crate::crypto::sha256_hex(value.to_string().as_bytes())
+}
+
+/// 比较 live 配置与已存当前供应商的配置；一致、无当前供应商或 live 不存在时返回 None
+pub fn detect(config: &MultiAppConfig, app: &AppType) -> Result<Option<DriftReport>, String> {
+    let Some(manager) = config.get_manager(app) else {
+        return Ok(None);
+    };
+    let Some(current) = manager.providers.get(&manager.current) else {
+        return Ok(None);
+    };
+    let Some(live) = read_live(app)? else {
+        return Ok(None);
+    };
+
+    let stored = crate::live_merge::with_model(
+        app,
+        current.model.as_deref(),
+        crate::keychain::resolve(app, &current.id, &current.settings_config)?,
+    );
+    let stored = match app {
+        AppType::Codex => {
+            let stored = crate::codex_config::compose_settings(config, current, stored)?;
+            crate::live_merge::with_codex_user_sections(stored, &live)
+        }
+        AppType::Claude => stored,
+    };
+    let stored = normalize(app, &crate::live_merge::comparable(app, &stored));
+    let live = normalize(app, &crate::live_merge::comparable(app, &live));
+    let stored_fingerprint = fingerprint(&stored);
+    let live_fingerprint = fingerprint(&live);
+    if stored_fingerprint == live_fingerprint {
+        return Ok(None);
+    }
+
+    Ok(Some(DriftReport {
+        app_type: app.as_str().to_string(),
+        current_id: current.id.clone(),
+        current_name: current.name.clone(),
+        stored_fingerprint,
+        live_fingerprint,
+        changes: crate::history::diff_json(
+            &crate::audit::redact(&stored),
+            &crate::audit::redact(&live),
+        ),
+    }))
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::provider::Provider;
+
+/// 重复类型
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateKind {
+    ApiKey,
+    BaseUrl,
+}
+
+/// 重复组中的供应商
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateMember {
+    pub app_type: String,
+    pub id: String,
+    pub name: String,
+}
+
+/// 共享同一 API Key 或 Base URL 的供应商组
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    /// API Key 为摘要前缀（不返回明文），Base URL 为规范化后的地址
+    pub fingerprint: String,
+    pub providers: Vec<DuplicateMember>,
+}
+
+/// 供应商显式配置的 Base URL（不含官方默认地址）
+fn explicit_base_url(app: &AppType, provider: &Provider) -> Option<String> {
+    let url = match app {
+        AppType::Claude => provider
+            .settings_config
+            .get("env")
+            .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        AppType::Codex => provider
+            .settings_config
+            .get("config")
+            .and_then(|v| v.as_str())
+            .and_then(crate::codex_config::extract_base_url),
+    }?;
+    let url = url.trim().trim_end_matches('/').to_lowercase();
+    (!url.is_empty()).then_some(url)
+}
+
+/// 扫描配置中 API Key / Base URL 相同的供应商；`app` 为空时跨应用检查
+pub fn find_duplicates(config: &MultiAppConfig, app: Option<&AppType>) -> Vec<DuplicateGroup> {
+    let apps = match app {
+        Some(AppType::Claude) => vec![AppType::Claude],
+        Some(AppType::Codex) => vec![AppType::Codex],
+        None => vec![AppType::Claude, AppType::Codex],
+    };
+
+    let mut groups: BTreeMap<(DuplicateKind, String), Vec<DuplicateMember>> = BTreeMap::new();
+    for app in apps.iter() {
+        let Some(manager) = config.get_manager(app) else {
+            continue;
+        };
+        for provider in manager.providers.values() {
+            let member = DuplicateMember {
+                app_type: app.as_str().to_string(),
+                id: provider.id.clone(),
+                name: provider.name.clone(),
+            };
+            // 钥匙串引用各不相同，需解析后再比较；读取失败的条目跳过
+            let key = provider
+                .api_key(app)
+                .and_then(|k| crate::keychain::resolve_str(app, &provider.id, &k).ok());
+            if let Some(key) = key {
+                let digest = crate::crypto::sha256_hex(key.as_bytes())[..12].to_string();
+                groups
+                    .entry((DuplicateKind::ApiKey, digest))
+                    .or_default()
+                    .push(member.clone());
+            }
+            if let Some(url) = explicit_base_url(app, provider) {
+                groups
+                    .entry((DuplicateKind::BaseUrl, url))
+                    .or_default()
+                    .push(member);
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|((kind, fingerprint), mut providers)| {
+            providers.sort_by(|a, b| (&a.app_type, &a.name).cmp(&(&b.app_type, &b.name)));
+            DuplicateGroup {
+                kind,
+                fingerprint,
+                providers,
+            }
+        })
+        .collect()
+}
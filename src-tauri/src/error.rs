@@ -0,0 +1,275 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Map, Value};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::app_config::AppType;
+
+/// 命令错误：序列化为 `{ code, message, params }`，前端按 code 本地化，message 为中文兜底文案。
+/// 底层 io / serde 错误作为 source 保留，文案依次包含操作、文件与底层错误
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// 应用类型不存在
+    #[error("应用类型不存在: {0}")]
+    AppTypeNotFound(String),
+    /// 供应商不存在
+    #[error("供应商不存在: {0}")]
+    ProviderNotFound(String),
+    /// 已存在相同 ID 的供应商
+    #[error("已存在相同 ID 的供应商: {0}")]
+    ProviderExists(String),
+    /// 不能删除当前正在使用的供应商
+    #[error("不能删除当前正在使用的供应商")]
+    DeleteCurrentProvider,
+    /// 供应商 ID 不合法
+    #[error("{reason}")]
+    InvalidProviderId { id: String, reason: String },
+    /// 供应商名称不合法
+    #[error("{reason}")]
+    InvalidProviderName { reason: String },
+    /// 无效的路径
+    #[error("无效的路径: {}", .0.display())]
+    InvalidPath(PathBuf),
+    /// 文件不存在
+    #[error("文件不存在: {}", .0.display())]
+    FileNotFound(PathBuf),
+    /// 读取文件失败
+    #[error("读取文件失败: {}: {source}", .path.display())]
+    ReadFile { path: PathBuf, source: io::Error },
+    /// 写入文件失败
+    #[error("写入文件失败: {}: {source}", .path.display())]
+    WriteFile { path: PathBuf, source: io::Error },
+    /// 删除文件失败
+    #[error("删除文件失败: {}: {source}", .path.display())]
+    RemoveFile { path: PathBuf, source: io::Error },
+    /// 复制文件失败
+    #[error("复制文件失败: {} -> {}: {source}", .from.display(), .to.display())]
+    CopyFile {
+        from: PathBuf,
+        to: PathBuf,
+        source: io::Error,
+    },
+    /// 创建目录失败
+    #[error("创建目录失败: {}: {source}", .path.display())]
+    CreateDir { path: PathBuf, source: io::Error },
+    /// 解析 JSON 失败
+    #[error("解析 JSON 失败: {}: {source}", .path.display())]
+    ParseJson {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// 序列化 JSON 失败
+    #[error("序列化 JSON 失败: {0}")]
+    SerializeJson(#[source] serde_json::Error),
+    /// config.toml 语法错误（toml 的错误文本会回显出错行原文，因此只保留行列号与描述）
+    #[error("config.toml 语法错误{}: {reason}", toml_location(.line, .col))]
+    TomlSyntax {
+        line: Option<usize>,
+        col: Option<usize>,
+        reason: String,
+    },
+    /// config.toml 检查未通过
+    #[error("config.toml 检查未通过: {}", .0.join("; "))]
+    InvalidCodexConfig(Vec<String>),
+    /// live 配置被外部修改，与当前供应商的存储不一致；需以 force 或 backfill 重新切换
+    #[error(
+        "live 配置与当前供应商「{}」的存储不一致（{} 处差异），请通过 check_live_drift 查看差异，并以 force 或 backfill 重新切换",
+        .report.current_name,
+        .report.changes.len()
+    )]
+    LiveDrift {
+        report: Box<crate::drift::DriftReport>,
+    },
+    /// 附加了操作上下文的错误
+    #[error("{context}: {source}")]
+    Context {
+        context: String,
+        source: Box<AppError>,
+    },
+    /// 未分类的错误
+    #[error("{0}")]
+    Message(String),
+}
+
+fn toml_location(line: &Option<usize>, col: &Option<usize>) -> String {
+    match (line, col) {
+        (Some(line), Some(col)) => format!("（第 {} 行，第 {} 列）", line, col),
+        _ => String::new(),
+    }
+}
+
+impl AppError {
+    pub fn app_type_not_found(app: &AppType) -> Self {
+        Self::AppTypeNotFound(app.as_str().to_string())
+    }
+
+    pub fn read_file(path: &Path, source: io::Error) -> Self {
+        Self::ReadFile {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn write_file(path: &Path, source: io::Error) -> Self {
+        Self::WriteFile {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn create_dir(path: &Path, source: io::Error) -> Self {
+        Self::CreateDir {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// 稳定的错误码（snake_case），供前端与脚本匹配；附加上下文不改变错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::AppTypeNotFound(_) => "app_type_not_found",
+            Self::ProviderNotFound(_) => "provider_not_found",
+            Self::ProviderExists(_) => "provider_exists",
+            Self::DeleteCurrentProvider => "delete_current_provider",
+            Self::InvalidProviderId { .. } => "invalid_provider_id",
+            Self::InvalidProviderName { .. } => "invalid_provider_name",
+            Self::InvalidPath(_) => "invalid_path",
+            Self::FileNotFound(_) => "file_not_found",
+            Self::ReadFile { .. } => "read_file_failed",
+            Self::WriteFile { .. } => "write_file_failed",
+            Self::RemoveFile { .. } => "remove_file_failed",
+            Self::CopyFile { .. } => "copy_file_failed",
+            Self::CreateDir { .. } => "create_dir_failed",
+            Self::ParseJson { .. } => "parse_json_failed",
+            Self::SerializeJson(_) => "serialize_json_failed",
+            Self::TomlSyntax { .. } => "toml_syntax_error",
+            Self::InvalidCodexConfig(_) => "invalid_codex_config",
+            Self::LiveDrift { .. } => "live_drift",
+            Self::Context { source, .. } => source.code(),
+            Self::Message(_) => "internal",
+        }
+    }
+
+    /// 本地化文案中的插值参数
+    pub fn params(&self) -> Map<String, Value> {
+        let value = match self {
+            Self::AppTypeNotFound(app) => json!({ "app": app }),
+            Self::ProviderNotFound(id) | Self::ProviderExists(id) => json!({ "id": id }),
+            Self::InvalidProviderId { id, reason } => json!({ "id": id, "reason": reason }),
+            Self::InvalidProviderName { reason } => json!({ "reason": reason }),
+            Self::InvalidPath(path) | Self::FileNotFound(path) => {
+                json!({ "path": path.display().to_string() })
+            }
+            Self::ReadFile { path, source }
+            | Self::WriteFile { path, source }
+            | Self::RemoveFile { path, source }
+            | Self::CreateDir { path, source } => {
+                json!({ "path": path.display().to_string(), "reason": source.to_string() })
+            }
+            Self::CopyFile { from, to, source } => json!({
+                "from": from.display().to_string(),
+                "to": to.display().to_string(),
+                "reason": source.to_string(),
+            }),
+            Self::ParseJson { path, source } => {
+                json!({ "path": path.display().to_string(), "reason": source.to_string() })
+            }
+            Self::SerializeJson(source) => json!({ "reason": source.to_string() }),
+            Self::TomlSyntax { line, col, reason } => {
+                json!({ "line": line, "col": col, "reason": reason })
+            }
+            Self::InvalidCodexConfig(problems) => json!({ "problems": problems }),
+            Self::LiveDrift { report } => json!({
+                "name": report.current_name,
+                "count": report.changes.len(),
+                "report": report,
+            }),
+            Self::Context { context, source } => {
+                let mut params = source.params();
+                params.insert("context".to_string(), json!(context));
+                return params;
+            }
+            Self::DeleteCurrentProvider | Self::Message(_) => json!({}),
+        };
+        match value {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        }
+    }
+
+    /// 脱敏后的完整文案（serde 错误会回显字段值），用于返回前端与写日志
+    pub fn detail(&self) -> String {
+        crate::redact::redact_text(&self.to_string())
+    }
+}
+
+/// 为错误附加操作上下文（例如"写入设置失败"），原错误保留为 source
+pub trait ResultExt<T> {
+    fn context(self, context: impl Into<String>) -> Result<T, AppError>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, AppError> {
+        self.map_err(|e| AppError::Context {
+            context: context.into(),
+            source: Box::new(e.into()),
+        })
+    }
+}
+
+impl From<cc_switch_core::CoreError> for AppError {
+    fn from(e: cc_switch_core::CoreError) -> Self {
+        use cc_switch_core::CoreError;
+        match e {
+            CoreError::InvalidProviderId { id, reason } => Self::InvalidProviderId { id, reason },
+            CoreError::InvalidProviderName { reason } => Self::InvalidProviderName { reason },
+            CoreError::TomlSyntax { line, col, reason } => Self::TomlSyntax {
+                line,
+                col,
+                reason: crate::redact::redact_text(&reason),
+            },
+            CoreError::InvalidCodexConfig(problems) => Self::InvalidCodexConfig(problems),
+            CoreError::TomlSerialize(reason) => {
+                Self::Message(format!("序列化 config.toml 失败: {}", reason))
+            }
+        }
+    }
+}
+
+impl From<String> for AppError {
+    fn from(e: String) -> Self {
+        Self::Message(e)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(e: &str) -> Self {
+        Self::Message(e.to_string())
+    }
+}
+
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.detail()
+    }
+}
+
+/// 返回前端前对文案与参数中的密钥做掩码
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let params: Map<String, Value> = self
+            .params()
+            .into_iter()
+            .map(|(k, v)| match v {
+                Value::String(s) => (k, Value::String(crate::redact::redact_text(&s))),
+                other => (k, other),
+            })
+            .collect();
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.detail())?;
+        state.serialize_field("params", &params)?;
+        state.end()
+    }
+}
@@ -0,0 +1,153 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use tauri::Emitter;
+use tokio::sync::broadcast;
+
+use crate::app_config::AppType;
+
+/// 统一状态事件的通道名（前端通过 listen 订阅）
+pub const STATE_CHANNEL: &str = "state-changed";
+
+/// 事件结构版本：字段含义或事件类型发生不兼容变化时递增，新增事件类型与可选字段不递增
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// 进程内订阅者的缓冲条数（落后过多的订阅者会丢失较早的事件）
+const SUBSCRIBER_BUFFER: usize = 64;
+
+/// 状态变化事件，按 `type` 区分（驼峰命名）
+///
+/// 事件只描述“发生了什么”，订阅方需要完整数据时应重新查询；不包含任何密钥
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum StateEvent {
+    /// 新增供应商（含导入）
+    ProviderAdded {
+        app_type: String,
+        provider_id: String,
+    },
+    /// 供应商配置或元数据被修改
+    ProviderUpdated {
+        app_type: String,
+        provider_id: String,
+    },
+    /// 供应商被删除（移入回收站）
+    ProviderDeleted {
+        app_type: String,
+        provider_id: String,
+    },
+    /// 从回收站恢复供应商
+    ProviderRestored {
+        app_type: String,
+        provider_id: String,
+    },
+    /// 当前供应商变化（切换、撤销、恢复中断的切换）
+    ProviderSwitched {
+        app_type: String,
+        provider_id: String,
+        previous_id: String,
+    },
+    /// 设置已保存
+    SettingsChanged,
+    /// config.json 被外部修改并已重新加载
+    ConfigReloaded,
+    /// 同步或备份状态（target 为 webdav / s3；status 为 started / succeeded / conflict / failed 等）
+    SyncStatus {
+        target: String,
+        status: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// 后台任务状态与进度
+    TaskUpdated { task: crate::tasks::TaskInfo },
+}
+
+impl StateEvent {
+    pub fn provider_added(app: &AppType, id: &str) -> Self {
+        Self::ProviderAdded {
+            app_type: app.as_str().to_string(),
+            provider_id: id.to_string(),
+        }
+    }
+
+    pub fn provider_updated(app: &AppType, id: &str) -> Self {
+        Self::ProviderUpdated {
+            app_type: app.as_str().to_string(),
+            provider_id: id.to_string(),
+        }
+    }
+
+    pub fn provider_deleted(app: &AppType, id: &str) -> Self {
+        Self::ProviderDeleted {
+            app_type: app.as_str().to_string(),
+            provider_id: id.to_string(),
+        }
+    }
+
+    pub fn provider_restored(app: &AppType, id: &str) -> Self {
+        Self::ProviderRestored {
+            app_type: app.as_str().to_string(),
+            provider_id: id.to_string(),
+        }
+    }
+
+    pub fn provider_switched(app: &AppType, id: &str, previous: &str) -> Self {
+        Self::ProviderSwitched {
+            app_type: app.as_str().to_string(),
+            provider_id: id.to_string(),
+            previous_id: previous.to_string(),
+        }
+    }
+
+    pub fn sync_status(target: &str, status: &str, error: Option<String>) -> Self {
+        Self::SyncStatus {
+            target: target.to_string(),
+            status: status.to_string(),
+            error,
+        }
+    }
+}
+
+/// 事件信封：版本号、单调递增序号与时间戳
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChange {
+    pub version: u32,
+    /// 进程内递增序号，订阅方可据此发现丢失的事件
+    pub seq: u64,
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub event: StateEvent,
+}
+
+fn sender() -> &'static broadcast::Sender<StateChange> {
+    static SENDER: OnceLock<broadcast::Sender<StateChange>> = OnceLock::new();
+    SENDER.get_or_init(|| broadcast::channel(SUBSCRIBER_BUFFER).0)
+}
+
+/// 进程内订阅状态事件（托盘、HTTP 接口等）
+pub fn subscribe() -> broadcast::Receiver<StateChange> {
+    sender().subscribe()
+}
+
+/// 发布状态事件：推送到前端通道并分发给进程内订阅者
+pub fn publish(event: StateEvent) {
+    static SEQ: AtomicU64 = AtomicU64::new(1);
+    let change = StateChange {
+        version: EVENT_SCHEMA_VERSION,
+        seq: SEQ.fetch_add(1, Ordering::SeqCst),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        event,
+    };
+    if let Some(handle) = crate::app_store::get_app_handle() {
+        if let Err(e) = handle.emit(STATE_CHANNEL, &change) {
+            log::warn!("发送状态事件失败: {}", e);
+        }
+    }
+    // 没有订阅者时发送失败属正常情况
+    let _ = sender().send(change);
+}
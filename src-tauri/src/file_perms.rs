@@ -0,0 +1,107 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// 单个文件的权限检查 / 修复结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionReport {
+    pub path: String,
+    /// 修复前的权限位（八进制，如 "644"；非 Unix 平台为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode_before: Option<String>,
+    pub fixed: bool,
+    /// 文件所有者是否为当前用户（以主目录所有者为准）
+    pub owned_by_user: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 包含 API Key 的配置文件
+pub fn sensitive_files() -> Vec<PathBuf> {
+    let app_dir = crate::config::get_app_config_dir();
+    vec![
+        crate::config::get_claude_settings_path(),
+        crate::codex_config::get_codex_auth_path(),
+        crate::codex_config::get_codex_config_path(),
+        crate::config::get_app_config_path(),
+        app_dir.join("config.json.bak"),
+        crate::settings::get_settings_path(),
+    ]
+}
+
+/// 写入时使用的权限：去掉组和其他用户的所有权限，新文件为 0600
+#[cfg(unix)]
+pub fn restricted_mode(existing: Option<u32>) -> u32 {
+    existing.map(|mode| mode & 0o700).unwrap_or(0o600) | 0o600
+}
+
+/// 文件所有者是否与主目录所有者一致
+#[cfg(unix)]
+pub fn owned_by_user(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let Some(home_uid) = crate::config::home_dir()
+        .and_then(|h| std::fs::metadata(h).ok())
+        .map(|m| m.uid())
+    else {
+        return true;
+    };
+    std::fs::metadata(path)
+        .map(|m| m.uid() == home_uid)
+        .unwrap_or(true)
+}
+
+#[cfg(not(unix))]
+pub fn owned_by_user(_path: &Path) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn fix_one(path: &Path) -> PermissionReport {
+    use std::os::unix::fs::PermissionsExt;
+    let mut report = PermissionReport {
+        path: path.to_string_lossy().to_string(),
+        mode_before: None,
+        fixed: false,
+        owned_by_user: owned_by_user(path),
+        error: None,
+    };
+    let mode = match std::fs::metadata(path) {
+        Ok(meta) => meta.permissions().mode() & 0o777,
+        Err(e) => {
+            report.error = Some(format!("读取文件信息失败: {}", e));
+            return report;
+        }
+    };
+    report.mode_before = Some(format!("{:o}", mode));
+    if mode & 0o077 != 0 {
+        match std::fs::set_permissions(
+            path,
+            std::fs::Permissions::from_mode(restricted_mode(Some(mode))),
+        ) {
+            Ok(()) => report.fixed = true,
+            Err(e) => report.error = Some(format!("修改权限失败: {}", e)),
+        }
+    }
+    report
+}
+
+#[cfg(not(unix))]
+fn fix_one(path: &Path) -> PermissionReport {
+    // Windows 下文件默认仅对当前用户及管理员可见，不做 ACL 调整
+    PermissionReport {
+        path: path.to_string_lossy().to_string(),
+        mode_before: None,
+        fixed: false,
+        owned_by_user: owned_by_user(path),
+        error: None,
+    }
+}
+
+/// 修复已存在的敏感配置文件中权限过宽的问题
+pub fn fix_all() -> Vec<PermissionReport> {
+    sensitive_files()
+        .into_iter()
+        .filter(|p| p.exists())
+        .map(|p| fix_one(&p))
+        .collect()
+}
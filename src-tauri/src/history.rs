@@ -0,0 +1,268 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::get_app_config_dir;
+
+const HISTORY_PREFIX: &str = "config.";
+const HISTORY_SUFFIX: &str = ".json";
+
+/// 历史版本信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigVersion {
+    pub id: String,
+    pub created_at: i64,
+    pub size: u64,
+}
+
+/// 差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// 单条差异（path 为以 `.` 连接的字段路径，数组下标以 `[i]` 表示）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiffEntry {
+    pub path: String,
+    pub kind: DiffKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+/// 历史版本目录 ~/.cc-switch/history
+pub fn get_history_dir() -> PathBuf {
+    get_app_config_dir().join("history")
+}
+
+pub(crate) fn version_path(id: &str) -> PathBuf {
+    get_history_dir().join(format!("{}{}{}", HISTORY_PREFIX, id, HISTORY_SUFFIX))
+}
+
+fn version_id_from_path(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let id = name
+        .strip_prefix(HISTORY_PREFIX)?
+        .strip_suffix(HISTORY_SUFFIX)?;
+    Some(id.to_string())
+}
+
+/// 列出历史版本（按时间倒序）
+pub fn list_versions() -> Result<Vec<ConfigVersion>, String> {
+    let dir = get_history_dir();
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let entries =
+        fs::read_dir(&dir).map_err(|e| format!("读取历史目录失败: {}: {}", dir.display(), e))?;
+    let mut versions: Vec<ConfigVersion> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let id = version_id_from_path(&path)?;
+            let meta = entry.metadata().ok()?;
+            let created_at = meta
+                .modified()
+                .ok()
+                .map(|t| chrono::DateTime::<Utc>::from(t).timestamp_millis())
+                .unwrap_or(0);
+            Some(ConfigVersion {
+                id,
+                created_at,
+                size: meta.len(),
+            })
+        })
+        .collect();
+    // ID 由时间戳构成，字典序即时间顺序
+    versions.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(versions)
+}
+
+/// 记录一次 config.json 版本（与最近版本内容相同则跳过），并按设置裁剪历史
+pub fn record_version(content: &[u8]) -> Result<Option<String>, String> {
+    let versions = list_versions()?;
+    if let Some(latest) = versions.first() {
+        if let Ok(prev) = fs::read(version_path(&latest.id)) {
+            if prev == content {
+                return Ok(None);
+            }
+        }
+    }
+
+    let dir = get_history_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("创建历史目录失败: {}: {}", dir.display(), e))?;
+
+    let base_id = Utc::now().format("%Y%m%d_%H%M%S_%3f").to_string();
+    let mut id = base_id.clone();
+    let mut suffix = 2;
+    while version_path(&id).exists() {
+        id = format!("{}-{}", base_id, suffix);
+        suffix += 1;
+    }
+    crate::config::atomic_write(&version_path(&id), content)?;
+
+    let max_versions = crate::settings::get_settings().history_max_versions;
+    if max_versions > 0 {
+        // versions 不含本次新增，因此保留 max_versions - 1 份旧版本
+        for old in versions.iter().skip(max_versions.saturating_sub(1)) {
+            if let Err(e) = fs::remove_file(version_path(&old.id)) {
+                log::warn!("删除旧历史版本失败: {}: {}", old.id, e);
+            }
+        }
+    }
+
+    Ok(Some(id))
+}
+
+/// 读取配置文件（已加密时解密）
+fn read_config_value(path: &Path) -> Result<Value, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}: {}", path.display(), e))?;
+    let content = crate::store_crypto::decode_content(&content)?;
+    serde_json::from_str(&content).map_err(|e| format!("解析 JSON 失败: {}", e))
+}
+
+/// 读取指定版本；`current` 表示当前 config.json
+fn read_version(id: &str) -> Result<Value, String> {
+    if id == "current" {
+        return read_config_value(&crate::config::get_app_config_path());
+    }
+    if id.trim().is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("无效的历史版本 ID: {}", id));
+    }
+    let path = version_path(id);
+    if !path.exists() {
+        return Err(format!("历史版本不存在: {}", id));
+    }
+    read_config_value(&path)
+}
+
+fn join_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+fn diff_values(path: &str, before: &Value, after: &Value, out: &mut Vec<ConfigDiffEntry>) {
+    match (before, after) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = join_path(path, key);
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_values(&child, x, y, out),
+                    (Some(x), None) => out.push(ConfigDiffEntry {
+                        path: child,
+                        kind: DiffKind::Removed,
+                        before: Some(x.clone()),
+                        after: None,
+                    }),
+                    (None, Some(y)) => out.push(ConfigDiffEntry {
+                        path: child,
+                        kind: DiffKind::Added,
+                        before: None,
+                        after: Some(y.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child = format!("{}[{}]", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(x), Some(y)) => diff_values(&child, x, y, out),
+                    (Some(x), None) => out.push(ConfigDiffEntry {
+                        path: child,
+                        kind: DiffKind::Removed,
+                        before: Some(x.clone()),
+                        after: None,
+                    }),
+                    (None, Some(y)) => out.push(ConfigDiffEntry {
+                        path: child,
+                        kind: DiffKind::Added,
+                        before: None,
+                        after: Some(y.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ => {
+            if before != after {
+                out.push(ConfigDiffEntry {
+                    path: path.to_string(),
+                    kind: DiffKind::Changed,
+                    before: Some(before.clone()),
+                    after: Some(after.clone()),
+                });
+            }
+        }
+    }
+}
+
+/// 计算两个 JSON 值之间的结构化差异
+pub fn diff_json(before: &Value, after: &Value) -> Vec<ConfigDiffEntry> {
+    let mut out = Vec::new();
+    diff_values("", before, after, &mut out);
+    out
+}
+
+/// 比较两个历史版本（`a` 为旧版本，`b` 为新版本）；密钥字段先脱敏，
+/// 差异中只体现是否变化，明文需经 `reveal_provider_secret` 单独获取
+pub fn diff_versions(a: &str, b: &str) -> Result<Vec<ConfigDiffEntry>, String> {
+    let before = read_version(a)?;
+    let after = read_version(b)?;
+    Ok(diff_redacted(&before, &after))
+}
+
+fn diff_redacted(before: &Value, after: &Value) -> Vec<ConfigDiffEntry> {
+    diff_json(&crate::audit::redact(before), &crate::audit::redact(after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_json_reports_paths() {
+        let before = json!({ "a": { "b": 1, "gone": true }, "list": [1] });
+        let after = json!({ "a": { "b": 2, "new": "x" }, "list": [1, 2] });
+        let diff = diff_json(&before, &after);
+        let paths: Vec<(&str, DiffKind)> = diff.iter().map(|d| (d.path.as_str(), d.kind)).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("a.b", DiffKind::Changed),
+                ("a.gone", DiffKind::Removed),
+                ("a.new", DiffKind::Added),
+                ("list[1]", DiffKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn version_diff_never_contains_plaintext_secrets() {
+        let before = json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-old-secret-value" } });
+        let after = json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-new-secret-value" } });
+        let diff = diff_redacted(&before, &after);
+        assert_eq!(diff.len(), 1);
+        let text = serde_json::to_string(&diff).unwrap();
+        assert!(!text.contains("secret-value"));
+        assert!(text.contains("<redacted:"));
+    }
+}
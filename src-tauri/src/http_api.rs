@@ -0,0 +1,515 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::{Mutex, OnceLock};
+use tauri::Manager;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::AbortHandle;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::settings::HttpApiSettings;
+use crate::store::AppState;
+
+/// 请求头与请求体的大小上限
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// 正在运行的服务：(端口, 监听任务)
+fn server() -> &'static Mutex<Option<(u16, AbortHandle)>> {
+    static SERVER: OnceLock<Mutex<Option<(u16, AbortHandle)>>> = OnceLock::new();
+    SERVER.get_or_init(|| Mutex::new(None))
+}
+
+/// 校验 HTTP 接口设置
+pub fn validate(settings: &HttpApiSettings) -> Result<(), String> {
+    if settings.enabled && settings.port < 1024 {
+        return Err(format!(
+            "HTTP 接口端口需在 1024-65535 之间: {}",
+            settings.port
+        ));
+    }
+    Ok(())
+}
+
+/// 生成新的访问令牌
+pub fn generate_token() -> String {
+    let mut bytes = crate::crypto::random_salt().to_vec();
+    bytes.extend_from_slice(&crate::crypto::random_salt());
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 按设置启动、重启或停止 HTTP 接口；启用时令牌为空则先生成
+pub fn sync_with_settings() {
+    let mut settings = crate::settings::get_settings().http_api;
+    let Ok(mut running) = server().lock() else {
+        return;
+    };
+    if !settings.enabled {
+        if let Some((port, abort)) = running.take() {
+            abort.abort();
+            log::info!("已停止 HTTP 接口（端口 {}）", port);
+        }
+        return;
+    }
+    if settings.token.trim().is_empty() {
+        let token = generate_token();
+        match crate::settings::modify_settings(|s| {
+            s.http_api.token = token.clone();
+            Ok(())
+        }) {
+            Ok(()) => settings.token = token,
+            Err(e) => {
+                log::warn!("生成 HTTP 接口令牌失败: {}", e.detail());
+                return;
+            }
+        }
+    }
+    if matches!(running.as_ref(), Some((port, _)) if *port == settings.port) {
+        return;
+    }
+    if let Some((_, abort)) = running.take() {
+        abort.abort();
+    }
+    let port = settings.port;
+    let task = tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("HTTP 接口监听 127.0.0.1:{} 失败: {}", port, e);
+                return;
+            }
+        };
+        log::info!("HTTP 接口已启动: http://127.0.0.1:{}", port);
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve(stream, port));
+                }
+                Err(e) => log::warn!("HTTP 接口接受连接失败: {}", e),
+            }
+        }
+    });
+    *running = Some((port, task.inner().abort_handle()));
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, (u16, String)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEADER_BYTES {
+            return Err((431, "请求头过大".to_string()));
+        }
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| (400, format!("读取请求失败: {}", e)))?;
+        if n == 0 {
+            return Err((400, "请求不完整".to_string()));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+    let query = tauri::Url::parse(&format!("http://localhost/?{}", query))
+        .map(|url| url.query_pairs().into_owned().collect())
+        .unwrap_or_default();
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Err((413, "请求体过大".to_string()));
+    }
+    let mut body = buf[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| (400, format!("读取请求失败: {}", e)))?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+        body,
+    })
+}
+
+/// 常量时间比较，避免通过响应时间猜测令牌
+fn token_matches(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len()
+        && expected
+            .bytes()
+            .zip(actual.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// 仅接受指向本机的 Host，防止 DNS 重绑定
+fn host_allowed(request: &Request, port: u16) -> bool {
+    match request.header("host") {
+        Some(host) => [format!("127.0.0.1:{}", port), format!("localhost:{}", port)]
+            .iter()
+            .any(|allowed| host.eq_ignore_ascii_case(allowed)),
+        None => false,
+    }
+}
+
+/// 校验 Host 与令牌：Host 不指向本机返回 403，令牌为空或不匹配返回 401
+fn authorize(request: &Request, port: u16, token: &str) -> Result<(), (u16, String)> {
+    if !host_allowed(request, port) {
+        return Err((403, "不允许的 Host".to_string()));
+    }
+    let bearer = request
+        .header("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if token.is_empty() || !token_matches(token, bearer.trim()) {
+        return Err((401, "令牌无效".to_string()));
+    }
+    Ok(())
+}
+
+async fn serve(mut stream: TcpStream, port: u16) {
+    let (status, body) = match read_request(&mut stream).await {
+        Ok(request) => {
+            let token = crate::settings::get_settings().http_api.token;
+            let result = match authorize(&request, port, &token) {
+                Ok(()) => route(&request).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(value) => (200, value),
+                Err((status, e)) => (status, json!({ "error": e })),
+            }
+        }
+        Err((status, e)) => (status, json!({ "error": e })),
+    };
+    let body = body.to_string();
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        log::debug!("HTTP 接口写入响应失败: {}", e);
+    }
+    let _ = stream.shutdown().await;
+}
+
+#[derive(Deserialize)]
+struct SwitchBody {
+    app: Option<String>,
+    id: String,
+}
+
+/// 解析应用类型参数：仅接受 claude / codex，其他取值返回 400
+fn parse_app(value: &str) -> Result<AppType, (u16, String)> {
+    serde_json::from_value(Value::String(value.to_string()))
+        .map_err(|_| (400, format!("不支持的应用类型: {}", value)))
+}
+
+/// 查询参数 `app`，未提供时为 Claude
+fn app_param(request: &Request) -> Result<AppType, (u16, String)> {
+    request
+        .query("app")
+        .map(parse_app)
+        .unwrap_or(Ok(AppType::Claude))
+}
+
+enum Endpoint {
+    Providers,
+    Current,
+    Switch,
+    Status,
+}
+
+/// 按方法与路径匹配接口：路径存在但方法不符返回 405，未知路径返回 404
+fn endpoint(request: &Request) -> Result<Endpoint, (u16, String)> {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/v1/providers") => Ok(Endpoint::Providers),
+        ("GET", "/v1/current") => Ok(Endpoint::Current),
+        ("POST", "/v1/switch") => Ok(Endpoint::Switch),
+        ("GET", "/v1/status") => Ok(Endpoint::Status),
+        (_, "/v1/providers" | "/v1/current" | "/v1/status" | "/v1/switch") => {
+            Err((405, "不支持的请求方法".to_string()))
+        }
+        _ => Err((404, "未知接口".to_string())),
+    }
+}
+
+fn app_error(e: AppError) -> (u16, String) {
+    let status = match e {
+        AppError::ProviderNotFound(_) => 404,
+        _ => 500,
+    };
+    (status, e.detail())
+}
+
+async fn route(request: &Request) -> Result<Value, (u16, String)> {
+    let endpoint = endpoint(request)?;
+    let handle = crate::app_store::get_app_handle().ok_or((500, "应用尚未初始化".to_string()))?;
+    let state = handle
+        .try_state::<AppState>()
+        .ok_or((500, "应用尚未初始化".to_string()))?;
+    let to_json = |v: serde_json::Result<Value>| v.map_err(|e| (500, e.to_string()));
+
+    match endpoint {
+        Endpoint::Providers => {
+            let query = crate::provider_query::ProviderQuery {
+                app: request.query("app").map(parse_app).transpose()?,
+                ..Default::default()
+            };
+            let config = state.read().await;
+            to_json(serde_json::to_value(crate::provider_query::query(
+                &config, &query,
+            )))
+        }
+        Endpoint::Current => {
+            let app = app_param(request)?;
+            let config = state.read().await;
+            let manager = config
+                .get_manager(&app)
+                .ok_or_else(|| app_error(AppError::app_type_not_found(&app)))?;
+            let name = manager
+                .providers
+                .get(&manager.current)
+                .map(|p| p.name.clone());
+            Ok(json!({ "app": app.as_str(), "id": manager.current, "name": name }))
+        }
+        Endpoint::Switch => {
+            let body: SwitchBody = serde_json::from_slice(&request.body)
+                .map_err(|e| (400, format!("请求体无效: {}", e)))?;
+            let app = match body.app.as_deref() {
+                Some(app) => parse_app(app)?,
+                None => app_param(request)?,
+            };
+            crate::switch_provider_internal(&handle, app.clone(), body.id.clone())
+                .await
+                .map_err(|e| (500, e))?;
+            Ok(json!({ "app": app.as_str(), "id": body.id }))
+        }
+        Endpoint::Status => {
+            let status = crate::app_status::collect(state.snapshot().await)
+                .await
+                .map_err(app_error)?;
+            to_json(serde_json::to_value(status))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, headers: &[(&str, &str)]) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: Vec::new(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: Vec::new(),
+        }
+    }
+
+    /// 通过本机回环连接发送原始请求，返回服务端解析结果
+    fn parse_raw(raw: Vec<u8>) -> Result<Request, (u16, String)> {
+        tauri::async_runtime::block_on(async move {
+            let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let client = tauri::async_runtime::spawn(async move {
+                let mut stream = TcpStream::connect(addr).await.unwrap();
+                // 服务端可能在读完前拒绝请求并关闭连接，写入失败不影响断言
+                let _ = stream.write_all(&raw).await;
+                let _ = stream.shutdown().await;
+            });
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let result = read_request(&mut stream).await;
+            drop(stream);
+            let _ = client.await;
+            result
+        })
+    }
+
+    #[test]
+    fn token_matches_requires_exact_token() {
+        assert!(token_matches("abc123", "abc123"));
+        assert!(!token_matches("abc123", "abc124"));
+        assert!(!token_matches("abc123", "abc12"));
+        assert!(!token_matches("abc123", ""));
+    }
+
+    #[test]
+    fn host_allowed_only_for_loopback_on_port() {
+        for host in ["127.0.0.1:8080", "localhost:8080", "LOCALHOST:8080"] {
+            assert!(host_allowed(&request("GET", "/", &[("Host", host)]), 8080));
+        }
+        for host in ["127.0.0.1:9090", "evil.example:8080", "localhost"] {
+            assert!(!host_allowed(&request("GET", "/", &[("Host", host)]), 8080));
+        }
+        assert!(!host_allowed(&request("GET", "/", &[]), 8080));
+    }
+
+    #[test]
+    fn authorize_rejects_bad_host_and_token() {
+        let ok = request(
+            "GET",
+            "/v1/status",
+            &[("Host", "127.0.0.1:8080"), ("Authorization", "Bearer tok")],
+        );
+        assert!(authorize(&ok, 8080, "tok").is_ok());
+
+        let bad_host = request(
+            "GET",
+            "/v1/status",
+            &[("Host", "evil.example"), ("Authorization", "Bearer tok")],
+        );
+        assert_eq!(authorize(&bad_host, 8080, "tok").unwrap_err().0, 403);
+
+        let bad_token = request(
+            "GET",
+            "/v1/status",
+            &[("Host", "127.0.0.1:8080"), ("Authorization", "Bearer nope")],
+        );
+        assert_eq!(authorize(&bad_token, 8080, "tok").unwrap_err().0, 401);
+        let no_token = request("GET", "/v1/status", &[("Host", "127.0.0.1:8080")]);
+        assert_eq!(authorize(&no_token, 8080, "tok").unwrap_err().0, 401);
+        // 未配置令牌时一律拒绝
+        assert_eq!(authorize(&no_token, 8080, "").unwrap_err().0, 401);
+    }
+
+    #[test]
+    fn endpoint_returns_405_for_wrong_method_and_404_for_unknown_path() {
+        assert!(endpoint(&request("GET", "/v1/current", &[])).is_ok());
+        assert!(endpoint(&request("POST", "/v1/switch", &[])).is_ok());
+        assert_eq!(
+            endpoint(&request("GET", "/v1/switch", &[]))
+                .err()
+                .unwrap()
+                .0,
+            405
+        );
+        assert_eq!(
+            endpoint(&request("DELETE", "/v1/providers", &[]))
+                .err()
+                .unwrap()
+                .0,
+            405
+        );
+        assert_eq!(
+            endpoint(&request("GET", "/v2/providers", &[]))
+                .err()
+                .unwrap()
+                .0,
+            404
+        );
+    }
+
+    #[test]
+    fn unknown_app_is_rejected_with_400() {
+        let mut req = request("GET", "/v1/current", &[]);
+        assert!(matches!(app_param(&req), Ok(AppType::Claude)));
+        req.query = vec![("app".to_string(), "codex".to_string())];
+        assert!(matches!(app_param(&req), Ok(AppType::Codex)));
+        req.query = vec![("app".to_string(), "gemini".to_string())];
+        assert_eq!(app_param(&req).err().unwrap().0, 400);
+    }
+
+    #[test]
+    fn read_request_parses_head_query_and_body() {
+        let raw = b"POST /v1/switch?app=codex HTTP/1.1\r\nHost: 127.0.0.1:8080\r\nContent-Length: 10\r\n\r\n{\"id\":\"a\"}"
+            .to_vec();
+        let req = parse_raw(raw).unwrap();
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/v1/switch");
+        assert_eq!(req.query("app"), Some("codex"));
+        assert_eq!(req.header("host"), Some("127.0.0.1:8080"));
+        assert_eq!(req.body, br#"{"id":"a"}"#.to_vec());
+    }
+
+    #[test]
+    fn read_request_rejects_missing_host() {
+        let req = parse_raw(b"GET /v1/status HTTP/1.1\r\n\r\n".to_vec()).unwrap();
+        assert!(!host_allowed(&req, 8080));
+        assert_eq!(authorize(&req, 8080, "tok").unwrap_err().0, 403);
+    }
+
+    #[test]
+    fn read_request_rejects_oversized_header() {
+        let mut raw = b"GET /v1/status HTTP/1.1\r\nX-Pad: ".to_vec();
+        raw.resize(raw.len() + MAX_HEADER_BYTES + 1, b'a');
+        assert_eq!(parse_raw(raw).err().unwrap().0, 431);
+    }
+
+    #[test]
+    fn read_request_rejects_oversized_body() {
+        let raw = format!(
+            "POST /v1/switch HTTP/1.1\r\nHost: 127.0.0.1:8080\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_BYTES + 1
+        )
+        .into_bytes();
+        assert_eq!(parse_raw(raw).err().unwrap().0, 413);
+    }
+}
@@ -0,0 +1,29 @@
+use reqwest::ClientBuilder;
+
+use crate::settings::{ProxyMode, ProxySettings};
+
+/// 按设置中的代理配置创建 HTTP 客户端构建器，供所有后端网络请求共用
+pub fn builder() -> Result<ClientBuilder, String> {
+    apply_proxy(
+        reqwest::Client::builder(),
+        &crate::settings::get_settings().proxy,
+    )
+}
+
+fn apply_proxy(builder: ClientBuilder, proxy: &ProxySettings) -> Result<ClientBuilder, String> {
+    match proxy.mode {
+        // reqwest 默认读取 HTTP_PROXY / HTTPS_PROXY / ALL_PROXY / NO_PROXY
+        ProxyMode::System => Ok(builder),
+        ProxyMode::None => Ok(builder.no_proxy()),
+        ProxyMode::Custom => {
+            let url = proxy.url.trim();
+            if url.is_empty() {
+                return Err("已选择自定义代理，但未填写代理地址".to_string());
+            }
+            let proxy = reqwest::Proxy::all(url).map_err(|e| {
+                crate::redact::redact_text(&format!("代理地址无效: {}: {}", url, e))
+            })?;
+            Ok(builder.proxy(proxy))
+        }
+    }
+}
@@ -1,11 +1,17 @@
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
 // 默认仅保留最近 10 份备份，避免目录无限膨胀
 const MAX_BACKUPS: usize = 10;
 
+// 状态归档格式版本（manifest.json 中的 formatVersion）
+const ARCHIVE_FORMAT: &str = "cc-switch-state";
+const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
 /// 创建配置文件备份
 pub fn create_backup(config_path: &PathBuf) -> Result<String, String> {
     if !config_path.exists() {
@@ -81,8 +87,12 @@ fn cleanup_old_backups(backup_dir: &PathBuf, retain: usize) -> Result<(), String
 
 /// 导出配置文件
 #[tauri::command]
-pub async fn export_config_to_file(file_path: String) -> Result<Value, String> {
-    // 读取当前配置文件
+pub async fn export_config_to_file(
+    file_path: String,
+    state: tauri::State<'_, crate::store::AppState>,
+) -> Result<Value, String> {
+    // 先写入尚未落盘的修改，再读取当前配置文件
+    state.flush().await?;
     let config_path = crate::config::get_app_config_path();
     let config_content = fs::read_to_string(&config_path)
         .map_err(|e| format!("Failed to read configuration: {}", e))?;
@@ -103,9 +113,12 @@ pub async fn import_config_from_file(
     file_path: String,
     state: tauri::State<'_, crate::store::AppState>,
 ) -> Result<Value, String> {
+    let _operation = state.begin_operation()?;
     // 读取导入的文件
     let import_content =
         fs::read_to_string(&file_path).map_err(|e| format!("Failed to read import file: {}", e))?;
+    // Encrypted exports can be imported while the store is unlocked with the same passphrase
+    let import_content = crate::store_crypto::decode_content(&import_content)?;
 
     // 验证并解析为配置对象
     let new_config: crate::app_config::MultiAppConfig = serde_json::from_str(&import_content)
@@ -116,18 +129,26 @@ pub async fn import_config_from_file(
     let backup_id = create_backup(&config_path)?;
 
     // 写入新配置到磁盘
-    fs::write(&config_path, &import_content)
-        .map_err(|e| format!("Failed to write configuration: {}", e))?;
+    let stored = crate::store_crypto::encode_content(import_content.as_bytes())?;
+    {
+        let _file_lock = crate::store_lock::acquire("导入配置")?;
+        crate::config::atomic_write(&config_path, &stored)
+            .map_err(|e| format!("Failed to write configuration: {}", e))?;
+    }
 
     // 更新内存中的状态
     {
-        let mut config_state = state
-            .config
-            .lock()
-            .map_err(|e| format!("Failed to lock config: {}", e))?;
+        let mut config_state = state.write().await;
         *config_state = new_config;
     }
 
+    crate::audit::record(
+        "import_config",
+        None,
+        None,
+        json!({ "file": file_path, "backupId": backup_id }),
+    );
+
     Ok(json!({
         "success": true,
         "message": "Configuration imported successfully",
@@ -135,6 +156,296 @@ pub async fn import_config_from_file(
     }))
 }
 
+/// 状态归档清单
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveManifest {
+    format: String,
+    format_version: u32,
+    app_version: String,
+    created_at: i64,
+    config_version: u32,
+    files: Vec<String>,
+}
+
+/// 递归收集目录下的文件（返回相对 base 的 `/` 分隔路径）
+fn collect_files(base: &Path, dir: &Path, out: &mut Vec<(String, PathBuf)>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(base, &path, out)?;
+        } else if let Ok(rel) = path.strip_prefix(base) {
+            let rel = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            out.push((rel, path));
+        }
+    }
+    Ok(())
+}
+
+/// 导出完整应用状态（config.json、settings.json、backups）为 zip 归档
+#[tauri::command]
+pub async fn export_state_archive(
+    file_path: String,
+    state: tauri::State<'_, crate::store::AppState>,
+) -> Result<Value, String> {
+    use zip::write::SimpleFileOptions;
+
+    // 先落盘，确保归档内容与内存一致
+    state.flush().await?;
+
+    let config_version = state.read().await.version;
+
+    let mut entries: Vec<(String, PathBuf)> = Vec::new();
+    let config_path = crate::config::get_app_config_path();
+    if config_path.exists() {
+        entries.push(("config.json".to_string(), config_path));
+    }
+    let settings_path = crate::settings::get_settings_path();
+    if settings_path.exists() {
+        entries.push(("settings.json".to_string(), settings_path));
+    }
+    let backups_root = crate::backup::get_backups_root();
+    if backups_root.exists() {
+        let mut backup_files = Vec::new();
+        collect_files(&backups_root, &backups_root, &mut backup_files)?;
+        for (rel, path) in backup_files {
+            entries.push((format!("backups/{}", rel), path));
+        }
+    }
+
+    let manifest = ArchiveManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        format_version: ARCHIVE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now().timestamp_millis(),
+        config_version,
+        files: entries.iter().map(|(name, _)| name.clone()).collect(),
+    };
+
+    let file =
+        fs::File::create(&file_path).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.start_file("manifest.json", options)
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    zip.write_all(&manifest_json)
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+
+    for (name, path) in entries.iter() {
+        let bytes =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        zip.write_all(&bytes)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+    }
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize archive: {}", e))?;
+
+    Ok(json!({
+        "success": true,
+        "message": "State archive exported successfully",
+        "filePath": file_path,
+        "fileCount": entries.len()
+    }))
+}
+
+fn read_archive_entry<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read archive entry {}: {}", name, e)),
+    };
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)
+        .map_err(|e| format!("Failed to read archive entry {}: {}", name, e))?;
+    Ok(Some(buf))
+}
+
+/// 从 zip 归档导入完整应用状态（导入前自动备份当前配置，旧版配置自动迁移）
+#[tauri::command]
+pub async fn import_state_archive(
+    file_path: String,
+    state: tauri::State<'_, crate::store::AppState>,
+) -> Result<Value, String> {
+    let file = fs::File::open(&file_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Invalid state archive: {}", e))?;
+
+    let manifest_bytes = read_archive_entry(&mut archive, "manifest.json")?
+        .ok_or("Invalid state archive: missing manifest.json")?;
+    let manifest: ArchiveManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| format!("Invalid archive manifest: {}", e))?;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err("Invalid state archive: unknown format".to_string());
+    }
+    if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(format!(
+            "Archive format version {} is newer than supported ({})",
+            manifest.format_version, ARCHIVE_FORMAT_VERSION
+        ));
+    }
+
+    // 解析并迁移配置（在写入任何文件之前完成校验）
+    let new_config = match read_archive_entry(&mut archive, "config.json")? {
+        Some(bytes) => {
+            let content = String::from_utf8(bytes)
+                .map_err(|e| format!("Invalid configuration in archive: {}", e))?;
+            let (config, migrated) =
+                crate::app_config::MultiAppConfig::parse_with_migration(&content)?;
+            if migrated {
+                log::info!("归档中的配置来自旧版本，已自动迁移");
+            }
+            Some(config)
+        }
+        None => None,
+    };
+    let new_settings = match read_archive_entry(&mut archive, "settings.json")? {
+        Some(bytes) => Some(
+            serde_json::from_slice::<crate::settings::AppSettings>(&bytes)
+                .map_err(|e| format!("Invalid settings in archive: {}", e))?,
+        ),
+        None => None,
+    };
+
+    // 备份当前配置
+    let config_path = crate::config::get_app_config_path();
+    let backup_id = create_backup(&config_path)?;
+
+    // 还原备份目录（不覆盖已存在的文件）
+    let backups_root = crate::backup::get_backups_root();
+    let mut restored_backups = 0usize;
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(rel) = entry.enclosed_name() else {
+            continue;
+        };
+        let Ok(rel) = rel.strip_prefix("backups") else {
+            continue;
+        };
+        let dest = backups_root.join(rel);
+        if dest.exists() {
+            continue;
+        }
+        let mut buf = Vec::new();
+        entry
+            .read_to_end(&mut buf)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        crate::config::atomic_write(&dest, &buf)?;
+        restored_backups += 1;
+    }
+
+    if let Some(settings) = new_settings {
+        crate::settings::update_settings(settings)?;
+    }
+    if let Some(config) = new_config {
+        state.replace_config(config).await?;
+    }
+
+    crate::audit::record(
+        "import_state_archive",
+        None,
+        None,
+        json!({
+            "file": file_path,
+            "backupId": backup_id,
+            "archiveVersion": manifest.app_version,
+        }),
+    );
+
+    Ok(json!({
+        "success": true,
+        "message": "State archive imported successfully",
+        "backupId": backup_id,
+        "archiveVersion": manifest.app_version,
+        "restoredBackups": restored_backups
+    }))
+}
+
+/// 恢复出厂设置：先将 ~/.cc-switch 整体归档为带时间戳的 zip（存放于 resets 目录），
+/// 再将供应商与设置重置为默认值
+#[tauri::command]
+pub async fn reset_app_data(
+    state: tauri::State<'_, crate::store::AppState>,
+) -> Result<Value, String> {
+    use zip::write::SimpleFileOptions;
+
+    let app_dir = crate::config::get_app_config_dir();
+    let resets_dir = app_dir.join("resets");
+
+    let mut entries: Vec<(String, PathBuf)> = Vec::new();
+    if app_dir.exists() {
+        collect_files(&app_dir, &app_dir, &mut entries)?;
+    }
+    // 跳过历史重置归档与锁文件
+    entries.retain(|(rel, _)| !rel.starts_with("resets/") && !rel.ends_with(".lock"));
+    // settings.json 固定位于状态根目录，可能不在自定义的配置目录内
+    let settings_path = crate::settings::get_settings_path();
+    if settings_path.exists() && !settings_path.starts_with(&app_dir) {
+        entries.push(("settings.json".to_string(), settings_path));
+    }
+
+    let archive_path = resets_dir.join(format!(
+        "cc-switch-reset-{}.zip",
+        Utc::now().format("%Y%m%d_%H%M%S")
+    ));
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, path) in entries.iter() {
+        let bytes =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        zip.write_all(&bytes)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+    }
+    let bytes = zip
+        .finish()
+        .map_err(|e| format!("Failed to finalize archive: {}", e))?
+        .into_inner();
+    // 归档中包含 API Key，使用原子写入并收紧权限
+    crate::config::atomic_write(&archive_path, &bytes)?;
+    log::info!("已归档当前数据: {}", archive_path.display());
+
+    // 归档成功后才重置
+    crate::settings::update_settings(crate::settings::AppSettings::default())?;
+    state
+        .replace_config(crate::app_config::MultiAppConfig::default())
+        .await?;
+
+    let archive = archive_path.to_string_lossy().to_string();
+    crate::audit::record(
+        "reset_app_data",
+        None,
+        None,
+        json!({ "archive": archive, "fileCount": entries.len() }),
+    );
+
+    Ok(json!({
+        "success": true,
+        "message": "Application data has been reset",
+        "archivePath": archive,
+        "fileCount": entries.len()
+    }))
+}
+
 /// 保存文件对话框
 #[tauri::command]
 pub async fn save_file_dialog<R: tauri::Runtime>(
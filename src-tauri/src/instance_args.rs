@@ -0,0 +1,141 @@
+use tauri::{Emitter, Manager};
+
+use crate::app_config::AppType;
+use crate::store::AppState;
+
+/// 解析 `claude:work` 形式的切换目标
+fn parse_target(raw: &str) -> Option<(AppType, String)> {
+    let (app, provider) = raw.split_once(':')?;
+    let app = match app.trim().to_lowercase().as_str() {
+        "claude" => AppType::Claude,
+        "codex" => AppType::Codex,
+        _ => return None,
+    };
+    let provider = provider.trim();
+    (!provider.is_empty()).then(|| (app, provider.to_string()))
+}
+
+/// 按 ID 或名称（忽略大小写）查找供应商
+async fn resolve_provider(app: &tauri::AppHandle, app_type: &AppType, key: &str) -> Option<String> {
+    let state = app.try_state::<AppState>()?;
+    let config = state.read().await;
+    let manager = config.get_manager(app_type)?;
+    if manager.providers.contains_key(key) {
+        return Some(key.to_string());
+    }
+    manager
+        .providers
+        .values()
+        .find(|p| p.name.eq_ignore_ascii_case(key))
+        .map(|p| p.id.clone())
+}
+
+fn switch_to(app: &tauri::AppHandle, app_type: AppType, key: &str) {
+    let app = app.clone();
+    let key = key.to_string();
+    tauri::async_runtime::spawn(async move {
+        let Some(provider_id) = resolve_provider(&app, &app_type, &key).await else {
+            let message = format!("{} 中不存在供应商: {}", app_type.as_str(), key);
+            log::warn!("{}", message);
+            crate::notify::send("供应商切换失败", &message);
+            return;
+        };
+        if let Err(e) = crate::switch_provider_internal(&app, app_type, provider_id).await {
+            log::error!("命令行切换供应商失败: {}", e);
+            crate::notify::send("供应商切换失败", &e);
+        }
+    });
+}
+
+/// 链接可能来自任意网页或程序，切换前弹窗请用户确认
+fn confirm_switch(app: &tauri::AppHandle, app_type: AppType, key: String) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+
+    crate::show_main_window(app);
+    let handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "外部链接请求将 {} 切换到供应商「{}」，是否继续？",
+            app_type.as_str(),
+            key
+        ))
+        .title("确认切换供应商")
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "切换".to_string(),
+            "取消".to_string(),
+        ))
+        .show(move |confirmed| {
+            if confirmed {
+                switch_to(&handle, app_type, &key);
+            } else {
+                log::info!("用户取消了链接发起的供应商切换: {}", key);
+            }
+        });
+}
+
+/// 处理 `ccswitch://` 链接：`switch?app=claude&provider=work` 经用户确认后切换供应商，
+/// `provider?data=...` 为分享链接，交给前端确认导入
+pub fn handle_deep_link(app: &tauri::AppHandle, link: &str) {
+    let Ok(url) = tauri::Url::parse(link) else {
+        log::warn!("无法解析链接: {}", link);
+        return;
+    };
+    match url.host_str() {
+        Some("switch") => {
+            let query = |name: &str| {
+                url.query_pairs()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.to_string())
+            };
+            let target = query("app")
+                .zip(query("provider"))
+                .and_then(|(app, provider)| parse_target(&format!("{}:{}", app, provider)));
+            match target {
+                Some((app_type, provider)) => confirm_switch(app, app_type, provider),
+                None => log::warn!("切换链接缺少 app 或 provider 参数: {}", link),
+            }
+        }
+        Some("provider") => {
+            crate::show_main_window(app);
+            // 仅预览名称与应用；导入需用户在界面确认
+            match crate::share_link::decode(link) {
+                Ok(share) => {
+                    let received = crate::share_link::ReceivedShare {
+                        payload: link.to_string(),
+                        app: share.app,
+                        name: share.name,
+                        url: share.url,
+                    };
+                    if let Err(e) = app.emit("share-link-received", &received) {
+                        log::error!("发射分享链接事件失败: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("分享链接无效: {}", e),
+            }
+        }
+        _ => log::warn!("未知的链接: {}", link),
+    }
+}
+
+/// 处理启动参数（首次启动或由单实例插件转发自第二个实例）；
+/// 有可执行的参数时返回 true，否则调用方应显示主窗口
+pub fn handle(app: &tauri::AppHandle, args: &[String]) -> bool {
+    if crate::shortcuts::handle_args(app, args) {
+        return true;
+    }
+    let mut handled = false;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--switch" {
+            match iter.next().and_then(|raw| parse_target(raw)) {
+                Some((app_type, provider)) => switch_to(app, app_type, &provider),
+                None => log::warn!("--switch 参数格式应为 <claude|codex>:<供应商>"),
+            }
+            handled = true;
+        } else if arg.starts_with("ccswitch://") {
+            handle_deep_link(app, arg);
+            handled = true;
+        }
+    }
+    handled
+}
@@ -0,0 +1,60 @@
+use serde::Serialize;
+
+use crate::app_config::AppType;
+use crate::provider::Provider;
+
+/// 密钥格式检查结果（仅提示，不阻止保存）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyFormatWarning {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub messages: Vec<String>,
+}
+
+/// 按供应商分类期望的密钥前缀：官方 Claude 为 `sk-ant-`，官方 OpenAI 为 `sk-`
+fn expected_prefix(app: &AppType, category: Option<&str>) -> Option<&'static str> {
+    match (app, category) {
+        (AppType::Claude, Some("official")) => Some("sk-ant-"),
+        (AppType::Codex, Some("official")) => Some("sk-"),
+        _ => None,
+    }
+}
+
+/// 检查密钥中常见的粘贴错误，返回提示信息（无问题时为空）
+pub fn check(app: &AppType, provider: &Provider) -> Vec<String> {
+    let Some(key) = provider.api_key(app) else {
+        return Vec::new();
+    };
+    // 钥匙串引用无法检查明文格式
+    if key.is_empty() || crate::keychain::is_ref(&key) {
+        return Vec::new();
+    }
+
+    let mut messages = Vec::new();
+    if key.trim() != key {
+        messages.push("API Key 首尾包含空白字符".to_string());
+    }
+    let trimmed = key.trim();
+    if trimmed
+        .get(..7)
+        .is_some_and(|p| p.eq_ignore_ascii_case("bearer "))
+    {
+        messages.push("API Key 不应包含 \"Bearer \" 前缀".to_string());
+    } else if trimmed.chars().any(char::is_whitespace) {
+        messages.push("API Key 中间包含空白或换行，可能粘贴了多余内容".to_string());
+    }
+    if trimmed.starts_with(['"', '\'']) || trimmed.ends_with(['"', '\'']) {
+        messages.push("API Key 包含引号".to_string());
+    }
+    if !trimmed.is_ascii() {
+        messages.push("API Key 包含非 ASCII 字符（如全角符号）".to_string());
+    }
+    if let Some(prefix) = expected_prefix(app, provider.category.as_deref()) {
+        if !trimmed.starts_with(prefix) {
+            messages.push(format!("官方供应商的 API Key 通常以 \"{}\" 开头", prefix));
+        }
+    }
+    messages
+}
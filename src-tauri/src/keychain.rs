@@ -0,0 +1,294 @@
+use serde_json::Value;
+use std::sync::OnceLock;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::settings::AppSettings;
+
+/// 系统钥匙串中的服务名
+const KEYRING_SERVICE: &str = "cc-switch";
+/// config.json 中钥匙串引用的前缀
+const REF_PREFIX: &str = "keychain:";
+
+/// 各应用 settings_config 中需要存入钥匙串的字段（对象路径）
+pub(crate) fn secret_fields(app: &AppType) -> &'static [(&'static str, &'static str)] {
+    match app {
+        AppType::Claude => &[
+            ("env", "ANTHROPIC_AUTH_TOKEN"),
+            ("env", "ANTHROPIC_API_KEY"),
+        ],
+        AppType::Codex => &[("auth", "OPENAI_API_KEY")],
+    }
+}
+
+fn entry(account: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, account).map_err(|e| format!("访问系统钥匙串失败: {}", e))
+}
+
+fn account_for(app: &AppType, provider_id: &str, field: &str) -> String {
+    format!("{}/{}/{}", app.as_str(), provider_id, field)
+}
+
+/// 系统钥匙串是否可用（如 Linux 未运行 Secret Service 时不可用）；结果在进程内缓存
+pub fn available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        // 读取一个不存在的条目：能区分“无此条目”与“钥匙串不可访问”
+        match entry("probe").map(|e| e.get_password()) {
+            Ok(Ok(_)) | Ok(Err(keyring::Error::NoEntry)) => true,
+            Ok(Err(e)) => {
+                log::info!("系统钥匙串不可用: {}", e);
+                false
+            }
+            Err(e) => {
+                log::info!("{}", e);
+                false
+            }
+        }
+    })
+}
+
+/// 是否为钥匙串引用
+pub fn is_ref(value: &str) -> bool {
+    value.starts_with(REF_PREFIX)
+}
+
+/// 供应商某个密钥字段唯一允许的钥匙串引用
+fn own_ref(app: &AppType, provider_id: &str, field: &str) -> String {
+    format!("{}{}", REF_PREFIX, account_for(app, provider_id, field))
+}
+
+/// 在任意位置查找钥匙串引用
+fn contains_ref(value: &Value) -> bool {
+    match value {
+        Value::String(s) => is_ref(s),
+        Value::Object(map) => map.values().any(contains_ref),
+        Value::Array(items) => items.iter().any(contains_ref),
+        _ => false,
+    }
+}
+
+/// 校验 settings 中的钥匙串引用：只允许出现在本供应商的密钥字段上，且指向该字段自身的条目。
+/// 客户端提交或导入的配置可能携带指向其它供应商的引用，借此把别人的密钥发往自己的端点。
+pub fn validate_refs(app: &AppType, provider_id: &str, settings: &Value) -> Result<(), String> {
+    let mut rest = settings.clone();
+    for (section, field) in secret_fields(app) {
+        let Some(slot) = rest.get_mut(*section).and_then(|s| s.get_mut(*field)) else {
+            continue;
+        };
+        match slot.as_str() {
+            Some(value) if is_ref(value) => {
+                if value != own_ref(app, provider_id, field) {
+                    return Err(format!(
+                        "{} 引用了其它供应商的钥匙串条目，请重新填写密钥",
+                        field
+                    ));
+                }
+                *slot = Value::Null;
+            }
+            _ => {}
+        }
+    }
+    if contains_ref(&rest) {
+        return Err("配置中包含不允许的钥匙串引用".to_string());
+    }
+    Ok(())
+}
+
+/// 将明文密钥写入钥匙串，返回替换为引用后的 settings（未启用钥匙串时原样返回）
+pub fn protect(app: &AppType, provider_id: &str, mut settings: Value) -> Result<Value, String> {
+    validate_refs(app, provider_id, &settings)?;
+    if !crate::settings::get_settings().use_keychain {
+        return Ok(settings);
+    }
+    for (section, field) in secret_fields(app) {
+        let Some(slot) = settings.get_mut(*section).and_then(|s| s.get_mut(*field)) else {
+            continue;
+        };
+        let Some(secret) = slot.as_str() else {
+            continue;
+        };
+        if secret.is_empty() || is_ref(secret) {
+            continue;
+        }
+        let account = account_for(app, provider_id, field);
+        entry(&account)?
+            .set_password(secret)
+            .map_err(|e| format!("写入系统钥匙串失败: {}", e))?;
+        *slot = Value::String(format!("{}{}", REF_PREFIX, account));
+    }
+    Ok(settings)
+}
+
+fn read_secret(reference: &str) -> Result<String, String> {
+    let account = reference.strip_prefix(REF_PREFIX).unwrap_or(reference);
+    entry(account)?
+        .get_password()
+        .map_err(|e| format!("从系统钥匙串读取密钥失败（{}）: {}", account, e))
+}
+
+/// 解析供应商的单个密钥值：仅读取属于该供应商的钥匙串引用，其余值原样返回
+pub fn resolve_str(app: &AppType, provider_id: &str, value: &str) -> Result<String, String> {
+    if !is_ref(value) {
+        return Ok(value.to_string());
+    }
+    let owned = secret_fields(app)
+        .iter()
+        .any(|(_, field)| value == own_ref(app, provider_id, field));
+    if !owned {
+        return Err("密钥引用了其它供应商的钥匙串条目".to_string());
+    }
+    read_secret(value)
+}
+
+/// 将供应商密钥字段中的钥匙串引用解析为明文（用于写入 live 配置或发起请求）
+pub fn resolve(app: &AppType, provider_id: &str, settings: &Value) -> Result<Value, String> {
+    validate_refs(app, provider_id, settings)?;
+    let mut resolved = settings.clone();
+    for (section, field) in secret_fields(app) {
+        let Some(slot) = resolved.get_mut(*section).and_then(|s| s.get_mut(*field)) else {
+            continue;
+        };
+        if let Some(reference) = slot.as_str().filter(|v| is_ref(v)) {
+            *slot = Value::String(read_secret(reference)?);
+        }
+    }
+    Ok(resolved)
+}
+
+/// settings.json 中存入钥匙串的凭证：(钥匙串账户, 字段)
+fn settings_secrets(settings: &mut AppSettings) -> [(&'static str, &mut String); 4] {
+    [
+        (
+            "settings/webdavSync/password",
+            &mut settings.webdav_sync.password,
+        ),
+        (
+            "settings/webdavSync/passphrase",
+            &mut settings.webdav_sync.passphrase,
+        ),
+        (
+            "settings/s3Backup/secretAccessKey",
+            &mut settings.s3_backup.secret_access_key,
+        ),
+        (
+            "settings/s3Backup/passphrase",
+            &mut settings.s3_backup.passphrase,
+        ),
+    ]
+}
+
+/// 将设置中的同步 / 备份凭证写入钥匙串并替换为引用
+///
+/// 启用钥匙串或 config.json 加密时生效，否则仅校验引用
+pub fn protect_settings(settings: &mut AppSettings) -> Result<(), String> {
+    let enabled = settings.use_keychain || crate::store_crypto::status().encrypted;
+    for (account, slot) in settings_secrets(settings) {
+        if is_ref(slot) {
+            if slot.as_str() != format!("{}{}", REF_PREFIX, account) {
+                return Err("设置中包含不允许的钥匙串引用".to_string());
+            }
+            continue;
+        }
+        if !enabled || slot.is_empty() {
+            continue;
+        }
+        entry(account)?
+            .set_password(slot)
+            .map_err(|e| format!("写入系统钥匙串失败: {}", e))?;
+        *slot = format!("{}{}", REF_PREFIX, account);
+    }
+    Ok(())
+}
+
+/// 返回凭证已解析为明文的设置（用于发起同步 / 备份请求）
+pub fn resolve_settings(mut settings: AppSettings) -> Result<AppSettings, String> {
+    for (account, slot) in settings_secrets(&mut settings) {
+        if is_ref(slot) {
+            if slot.as_str() != format!("{}{}", REF_PREFIX, account) {
+                return Err("设置中包含不允许的钥匙串引用".to_string());
+            }
+            *slot = read_secret(slot)?;
+        }
+    }
+    Ok(settings)
+}
+
+/// 删除供应商在钥匙串中的条目（永久删除供应商时调用）
+pub fn forget(app: &AppType, provider_id: &str) {
+    for (_, field) in secret_fields(app) {
+        let account = account_for(app, provider_id, field);
+        if let Ok(e) = entry(&account) {
+            match e.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => {}
+                Err(err) => log::warn!("删除钥匙串条目失败: {}: {}", account, err),
+            }
+        }
+    }
+}
+
+/// 将配置中所有明文密钥迁移到钥匙串，返回迁移的供应商数量
+pub fn migrate_config(config: &mut MultiAppConfig) -> Result<usize, String> {
+    let mut migrated = 0;
+    for app in [AppType::Claude, AppType::Codex] {
+        let Some(manager) = config.get_manager_mut(&app) else {
+            continue;
+        };
+        for provider in manager.providers.values_mut() {
+            let protected = protect(&app, &provider.id, provider.settings_config.clone())?;
+            if protected != provider.settings_config {
+                provider.settings_config = protected;
+                migrated += 1;
+            }
+        }
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn accepts_own_refs_and_plaintext() {
+        let settings = json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": "keychain:claude/p1/ANTHROPIC_AUTH_TOKEN",
+                "ANTHROPIC_BASE_URL": "https://api.example.com"
+            }
+        });
+        assert!(validate_refs(&AppType::Claude, "p1", &settings).is_ok());
+        let plain = json!({ "auth": { "OPENAI_API_KEY": "sk-plain" }, "config": "" });
+        assert!(validate_refs(&AppType::Codex, "p1", &plain).is_ok());
+    }
+
+    #[test]
+    fn rejects_ref_to_other_provider() {
+        let settings = json!({
+            "env": { "ANTHROPIC_AUTH_TOKEN": "keychain:claude/victim/ANTHROPIC_AUTH_TOKEN" }
+        });
+        assert!(validate_refs(&AppType::Claude, "attacker", &settings).is_err());
+        assert!(resolve(&AppType::Claude, "attacker", &settings).is_err());
+    }
+
+    #[test]
+    fn rejects_ref_on_other_field_or_path() {
+        let swapped = json!({
+            "env": { "ANTHROPIC_API_KEY": "keychain:claude/p1/ANTHROPIC_AUTH_TOKEN" }
+        });
+        assert!(validate_refs(&AppType::Claude, "p1", &swapped).is_err());
+        let elsewhere = json!({
+            "env": { "ANTHROPIC_CUSTOM_HEADERS": "keychain:claude/p1/ANTHROPIC_AUTH_TOKEN" }
+        });
+        assert!(validate_refs(&AppType::Claude, "p1", &elsewhere).is_err());
+    }
+
+    #[test]
+    fn resolve_str_only_reads_own_entries() {
+        assert_eq!(
+            resolve_str(&AppType::Codex, "p1", "sk-plain").unwrap(),
+            "sk-plain"
+        );
+        assert!(resolve_str(&AppType::Codex, "p1", "keychain:codex/p2/OPENAI_API_KEY").is_err());
+    }
+}
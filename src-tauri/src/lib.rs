@@ -1,38 +1,91 @@
 mod app_config;
+mod app_log;
+mod app_status;
 mod app_store;
+mod app_updater;
+mod audit;
+mod autostart;
+mod backend_info;
+mod backup;
+mod balance;
+mod benchmark;
 mod claude_mcp;
 mod claude_plugin;
+mod cli_detect;
+mod cli_launch;
 mod codex_config;
+mod codex_oauth;
+mod codex_profiles;
+mod command_args;
 mod commands;
 mod config;
+mod config_migration;
+mod config_watch;
+mod crypto;
+mod drift;
+mod duplicates;
+mod error;
+mod events;
+mod file_perms;
+mod history;
+mod http_api;
+mod http_client;
 mod import_export;
+mod instance_args;
+mod key_format;
+mod keychain;
+mod live_merge;
+mod live_write;
 mod mcp;
+mod mcp_server;
 mod migration;
+mod model_list;
+mod notify;
+mod paste_import;
 mod provider;
+mod provider_query;
+mod quick_search;
+mod redact;
+mod remote_env;
+mod rpc;
+mod s3_backup;
+mod secret_mask;
 mod settings;
+mod settings_schema;
+mod share_link;
+mod shared_sync;
+mod shortcuts;
+mod snapshot;
 mod speedtest;
-mod usage_script;
 mod store;
+mod store_crypto;
+mod store_health;
+mod store_lock;
+mod switch_journal;
+mod tasks;
+mod tool_import;
+mod tray_badge;
+mod tray_links;
+mod usage_script;
+mod webdav_sync;
+mod wsl_env;
 
 use store::AppState;
 use tauri::{
-    menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem, SubmenuBuilder},
     tray::{TrayIconBuilder, TrayIconEvent},
 };
 #[cfg(target_os = "macos")]
-use tauri::{ActivationPolicy, RunEvent};
-use tauri::{Emitter, Manager};
+use tauri::ActivationPolicy;
+use tauri::{Emitter, Manager, RunEvent};
+#[cfg(desktop)]
+use tauri_plugin_deep_link::DeepLinkExt;
 
 /// 创建动态托盘菜单
 fn create_tray_menu(
     app: &tauri::AppHandle,
-    app_state: &AppState,
+    config: &app_config::MultiAppConfig,
 ) -> Result<Menu<tauri::Wry>, String> {
-    let config = app_state
-        .config
-        .lock()
-        .map_err(|e| format!("获取锁失败: {}", e))?;
-
     let mut menu_builder = MenuBuilder::new(app);
 
     // 顶部：打开主界面
@@ -153,6 +206,54 @@ fn create_tray_menu(
         }
     }
 
+    // 自定义托盘项（设置中配置的链接与命令）
+    let tray_links = crate::settings::get_settings().tray_links;
+    if !tray_links.is_empty() {
+        menu_builder = menu_builder.separator();
+        for (index, link) in tray_links.iter().enumerate() {
+            let item = MenuItem::with_id(
+                app,
+                format!("link_{}", index),
+                link.label.trim(),
+                true,
+                None::<&str>,
+            )
+            .map_err(|e| format!("创建菜单项失败: {}", e))?;
+            menu_builder = menu_builder.item(&item);
+        }
+    }
+
+    // 最近使用：跨应用列出最近切换过的供应商，便于一键切回
+    let mut recent_items = Vec::new();
+    for (app_type, label) in [
+        (crate::app_config::AppType::Claude, "Claude"),
+        (crate::app_config::AppType::Codex, "Codex"),
+    ] {
+        if let Some(manager) = config.get_manager(&app_type) {
+            for provider in manager.recent_providers() {
+                let item = MenuItem::with_id(
+                    app,
+                    format!("recent_{}_{}", app_type.as_str(), provider.id),
+                    format!("{} · {}", label, provider.name),
+                    true,
+                    None::<&str>,
+                )
+                .map_err(|e| format!("创建菜单项失败: {}", e))?;
+                recent_items.push(item);
+            }
+        }
+    }
+    if !recent_items.is_empty() {
+        let mut submenu_builder = SubmenuBuilder::new(app, "最近使用");
+        for item in recent_items.iter() {
+            submenu_builder = submenu_builder.item(item);
+        }
+        let recent_submenu = submenu_builder
+            .build()
+            .map_err(|e| format!("创建最近使用菜单失败: {}", e))?;
+        menu_builder = menu_builder.separator().item(&recent_submenu);
+    }
+
     // 分隔符和退出菜单
     let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)
         .map_err(|e| format!("创建退出菜单失败: {}", e))?;
@@ -181,30 +282,64 @@ fn apply_tray_policy(app: &tauri::AppHandle, dock_visible: bool) {
     }
 }
 
+/// 显示并聚焦主窗口
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        #[cfg(target_os = "windows")]
+        {
+            let _ = window.set_skip_taskbar(false);
+        }
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        #[cfg(target_os = "macos")]
+        {
+            apply_tray_policy(app, true);
+        }
+    }
+}
+
+/// 隐藏主窗口到托盘
+fn hide_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+        #[cfg(target_os = "windows")]
+        {
+            let _ = window.set_skip_taskbar(true);
+        }
+        #[cfg(target_os = "macos")]
+        {
+            apply_tray_policy(app, false);
+        }
+    }
+}
+
 /// 处理托盘菜单事件
 fn handle_tray_menu_event(app: &tauri::AppHandle, event_id: &str) {
     log::info!("处理托盘菜单事件: {}", event_id);
 
     match event_id {
-        "show_main" => {
-            if let Some(window) = app.get_webview_window("main") {
-                #[cfg(target_os = "windows")]
-                {
-                    let _ = window.set_skip_taskbar(false);
-                }
-                let _ = window.unminimize();
-                let _ = window.show();
-                let _ = window.set_focus();
-                #[cfg(target_os = "macos")]
-                {
-                    apply_tray_policy(app, true);
-                }
-            }
-        }
+        "show_main" => show_main_window(app),
         "quit" => {
             log::info!("退出应用");
             app.exit(0);
         }
+        id if id.starts_with("link_") => {
+            let link = id
+                .strip_prefix("link_")
+                .and_then(|n| n.parse::<usize>().ok())
+                .and_then(|n| crate::settings::get_settings().tray_links.get(n).cloned());
+            if let Some(link) = link {
+                if let Err(e) = tray_links::open(app, &link) {
+                    log::error!("{}", e);
+                    notify::send("自定义托盘项执行失败", &e);
+                }
+            }
+        }
+        // 最近使用菜单项：recent_<app>_<id> 复用对应应用的切换逻辑
+        id if id.starts_with("recent_") => {
+            handle_tray_menu_event(app, id.strip_prefix("recent_").unwrap());
+        }
         id if id.starts_with("claude_") => {
             let provider_id = id.strip_prefix("claude_").unwrap();
             log::info!("切换到Claude供应商: {}", provider_id);
@@ -221,6 +356,7 @@ fn handle_tray_menu_event(app: &tauri::AppHandle, event_id: &str) {
                 .await
                 {
                     log::error!("切换Claude供应商失败: {}", e);
+                    notify::send("Claude 供应商切换失败", &e);
                 }
             });
         }
@@ -240,6 +376,7 @@ fn handle_tray_menu_event(app: &tauri::AppHandle, event_id: &str) {
                 .await
                 {
                     log::error!("切换Codex供应商失败: {}", e);
+                    notify::send("Codex 供应商切换失败", &e);
                 }
             });
         }
@@ -252,7 +389,7 @@ fn handle_tray_menu_event(app: &tauri::AppHandle, event_id: &str) {
 //
 
 /// 内部切换供应商函数
-async fn switch_provider_internal(
+pub(crate) async fn switch_provider_internal(
     app: &tauri::AppHandle,
     app_type: crate::app_config::AppType,
     provider_id: String,
@@ -264,21 +401,27 @@ async fn switch_provider_internal(
 
         crate::commands::switch_provider(
             app_state.clone(),
-            Some(app_type),
-            None,
-            None,
+            app_type.clone().into(),
             provider_id,
+            // 托盘切换无法交互确认，沿用回填行为
+            Some(crate::drift::DriftPolicy::Backfill).into(),
         )
         .await?;
 
-        // 切换成功后重新创建托盘菜单
-        if let Ok(new_menu) = create_tray_menu(app, app_state.inner()) {
-            if let Some(tray) = app.tray_by_id("main") {
-                if let Err(e) = tray.set_menu(Some(new_menu)) {
-                    log::error!("更新托盘菜单失败: {}", e);
-                }
-            }
-        }
+        let provider_name = app_state
+            .read()
+            .await
+            .get_manager(&app_type)
+            .and_then(|m| m.providers.get(&provider_id_clone))
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| provider_id_clone.clone());
+        let app_label = match app_type {
+            crate::app_config::AppType::Claude => "Claude",
+            crate::app_config::AppType::Codex => "Codex",
+        };
+        notify::send("供应商已切换", &format!("{}: {}", app_label, provider_name));
+
+        // 托盘菜单由配置保存事件自动重建（见 schedule_tray_refresh）
 
         // 发射事件到前端，通知供应商已切换
         let event_data = serde_json::json!({
@@ -292,33 +435,76 @@ async fn switch_provider_internal(
     Ok(())
 }
 
+/// 重建托盘菜单并刷新图标徽标（基于配置快照，构建菜单时不占用配置锁）
+async fn rebuild_tray(app: &tauri::AppHandle, app_state: &AppState) -> Result<bool, String> {
+    let config = app_state.snapshot().await;
+    tray_badge::refresh(app, &config);
+    let new_menu = create_tray_menu(app, &config)?;
+    match app.tray_by_id("main") {
+        Some(tray) => {
+            tray.set_menu(Some(new_menu))
+                .map_err(|e| format!("更新托盘菜单失败: {}", e))?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 配置变更（供应商增删改、切换、解锁等）后在后台重建托盘菜单
+pub(crate) fn schedule_tray_refresh() {
+    let Some(handle) = crate::app_store::get_app_handle() else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = handle.try_state::<AppState>() else {
+            return;
+        };
+        if let Err(e) = rebuild_tray(&handle, state.inner()).await {
+            log::error!("{}", e);
+        }
+    });
+}
+
+/// 订阅状态事件：设置变更（徽标、自定义项等）后重建托盘
+fn watch_settings_for_tray() {
+    let mut rx = events::subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(change) => {
+                    if matches!(change.event, events::StateEvent::SettingsChanged) {
+                        schedule_tray_refresh();
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                    schedule_tray_refresh();
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
 /// 更新托盘菜单的Tauri命令
 #[tauri::command]
 async fn update_tray_menu(
     app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<bool, String> {
-    if let Ok(new_menu) = create_tray_menu(&app, state.inner()) {
-        if let Some(tray) = app.tray_by_id("main") {
-            tray.set_menu(Some(new_menu))
-                .map_err(|e| format!("更新托盘菜单失败: {}", e))?;
-            return Ok(true);
-        }
-    }
-    Ok(false)
+    rebuild_tray(&app, state.inner()).await
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let mut builder = tauri::Builder::default();
 
+    // JSON-RPC / MCP 模式与图形界面实例互不转发，可同时运行（config.json 由文件锁保护）
     #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-    {
-        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.unminimize();
-                let _ = window.show();
-                let _ = window.set_focus();
+    if !rpc::requested() {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            // 第二个实例的参数（--switch、--shortcut、ccswitch:// 链接）转发给已运行实例执行
+            if !instance_args::handle(app, &args) {
+                show_main_window(app);
             }
         }));
     }
@@ -327,19 +513,15 @@ pub fn run() {
         // 拦截窗口关闭：根据设置决定是否最小化到托盘
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // 仅主窗口受该设置控制，其它窗口正常关闭
+                if window.label() != "main" {
+                    return;
+                }
                 let settings = crate::settings::get_settings();
 
                 if settings.minimize_to_tray_on_close {
                     api.prevent_close();
-                    let _ = window.hide();
-                    #[cfg(target_os = "windows")]
-                    {
-                        let _ = window.set_skip_taskbar(true);
-                    }
-                    #[cfg(target_os = "macos")]
-                    {
-                        apply_tray_policy(window.app_handle(), false);
-                    }
+                    hide_main_window(window.app_handle());
                 } else {
                     window.app_handle().exit(0);
                 }
@@ -349,6 +531,8 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
             // 设置全局 AppHandle 以供 Store 使用
             app_store::set_app_handle(app.handle().clone());
@@ -393,26 +577,21 @@ pub fn run() {
                 }
             }
 
-            // 初始化日志
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
-            }
+            // 初始化日志（文件输出并按设置的级别过滤）
+            app.handle().plugin(app_log::plugin())?;
+            app_log::apply_level(crate::settings::get_settings().log_level);
 
             // 初始化应用状态（仅创建一次，并在本函数末尾注入 manage）
             let app_state = AppState::new();
 
             // 迁移旧的 app_config_dir 配置到 Store
-            if let Err(e) = app_store::migrate_app_config_dir_from_settings(&app.handle()) {
+            if let Err(e) = app_store::migrate_app_config_dir_from_settings(app.handle()) {
                 log::warn!("迁移 app_config_dir 失败: {}", e);
             }
 
             // 首次启动迁移：扫描副本文件，合并到 config.json，并归档副本；旧 config.json 先归档
             {
-                let mut config_guard = app_state.config.lock().unwrap();
+                let mut config_guard = app_state.blocking_write();
                 let migrated = migration::migrate_copies_into_config(&mut config_guard)?;
                 if migrated {
                     log::info!("已将副本文件导入到 config.json，并完成归档");
@@ -422,11 +601,25 @@ pub fn run() {
                 config_guard.ensure_app(&app_config::AppType::Codex);
             }
 
-            // 保存配置
-            let _ = app_state.save();
+            // 上次切换若在写入 live 后中断，提醒用户回滚或完成
+            switch_journal::check_on_startup(&app_state.blocking_read());
+
+            // 保存配置（加载失败时内存中是默认配置，不能覆盖磁盘上的原文件）
+            if !store_health::load_failed() {
+                let _ = tauri::async_runtime::block_on(app_state.save());
+            }
+
+            // JSON-RPC / MCP 模式：不创建托盘与后台任务，仅通过 stdin/stdout 提供服务
+            if let Some(mode) = rpc::mode() {
+                app.manage(app_state);
+                // GUI 或其他实例修改 config.json 后同步内存配置，避免基于旧配置处理请求或覆盖其改动
+                config_watch::start(app.handle().clone());
+                rpc::start(app.handle().clone(), mode);
+                return Ok(());
+            }
 
             // 创建动态托盘菜单
-            let menu = create_tray_menu(app.handle(), &app_state)?;
+            let menu = create_tray_menu(app.handle(), &app_state.blocking_read())?;
 
             // 构建托盘
             let mut tray_builder = TrayIconBuilder::with_id("main")
@@ -445,20 +638,62 @@ pub fn run() {
             tray_builder = tray_builder.icon(app.default_window_icon().unwrap().clone());
 
             let _tray = tray_builder.build(app)?;
+            tray_badge::refresh(app.handle(), &app_state.blocking_read());
             // 将同一个实例注入到全局状态，避免重复创建导致的不一致
             app.manage(app_state);
+
+            // 首次启动时同样执行命令行参数
+            let args: Vec<String> = std::env::args().collect();
+            instance_args::handle(app.handle(), &args);
+
+            // ccswitch:// 链接：Windows / Linux 经启动参数（或单实例转发）传入，macOS 通过系统事件送达
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                log::warn!("注册 ccswitch:// 协议失败: {}", e);
+            }
+            #[cfg(target_os = "macos")]
+            {
+                let handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    for url in event.urls() {
+                        instance_args::handle_deep_link(&handle, url.as_str());
+                    }
+                });
+            }
+
+            // 加密配置空闲自动锁定
+            store_crypto::start_idle_watch(app.handle().clone());
+
+            // 监听 config.json 的外部修改
+            config_watch::start(app.handle().clone());
+
+            // 设置变更后重建托盘
+            watch_settings_for_tray();
+
+            // 按设置同步登录启动项
+            autostart::sync_with_settings();
+
+            // 按设置启动本机 HTTP 接口
+            http_api::sync_with_settings();
+
+            // 后台定期检查更新
+            #[cfg(desktop)]
+            app_updater::start_periodic_check(app.handle().clone());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_providers,
+            commands::query_providers,
             commands::get_current_provider,
             commands::add_provider,
             commands::update_provider,
+            commands::set_provider_model,
             commands::delete_provider,
             commands::switch_provider,
             commands::import_default_config,
             commands::get_claude_config_status,
             commands::get_config_status,
+            commands::get_all_status,
             commands::get_claude_code_config_path,
             commands::get_config_dir,
             commands::open_config_folder,
@@ -467,10 +702,79 @@ pub fn run() {
             commands::get_app_config_path,
             commands::open_app_config_folder,
             commands::read_live_provider_settings,
+            commands::list_backups,
+            commands::restore_backup,
+            commands::sync_now,
+            commands::s3_backup_now,
+            commands::list_tasks,
+            commands::cancel_task,
+            commands::list_s3_backups,
+            commands::restore_s3_backup,
+            commands::list_config_versions,
+            commands::diff_config_versions,
+            commands::undo_switch,
+            commands::get_interrupted_switches,
+            commands::recover_switch,
+            commands::get_shared_conflict,
+            commands::resolve_shared_conflict,
+            commands::preview_config_migration,
+            commands::get_store_health,
+            commands::get_settings_health,
+            commands::set_log_level,
+            commands::get_recent_logs,
+            commands::open_log_file,
+            commands::get_audit_log,
+            commands::list_trash,
+            commands::restore_provider,
+            commands::purge_trash,
+            commands::get_codex_fragments,
+            commands::save_codex_fragment,
+            commands::delete_codex_fragment,
+            commands::import_from_tool,
+            commands::import_from_clipboard_text,
+            commands::encode_provider_share,
+            commands::import_provider_share,
+            commands::benchmark_providers,
+            commands::get_provider_balance,
+            commands::list_provider_models,
+            commands::migrate_secrets_to_keychain,
+            commands::reveal_provider_secret,
+            commands::get_store_lock_status,
+            commands::get_config_lock_owner,
+            commands::unlock_store,
+            commands::lock_store,
+            commands::enable_store_encryption,
+            commands::disable_store_encryption,
+            commands::rotate_store_passphrase,
+            commands::find_duplicate_credentials,
+            commands::quick_search,
+            commands::detect_installed_apps,
+            commands::get_cli_versions,
+            commands::launch_cli,
+            commands::check_shortcut_conflicts,
+            commands::check_live_drift,
+            commands::fix_permissions,
+            commands::get_environment_status,
+            commands::get_platform_info,
+            commands::get_backend_info,
+            commands::get_default_wsl_distro,
+            commands::list_wsl_distros,
+            commands::refresh_wsl_home,
+            commands::save_environment,
+            commands::remove_environment,
+            commands::test_environment,
+            commands::repair_wsl_permissions,
+            commands::get_autostart,
+            commands::set_autostart,
             commands::get_settings,
             commands::save_settings,
+            commands::regenerate_http_api_token,
             commands::restart_app,
+            commands::quit_app,
             commands::check_for_updates,
+            commands::get_latest_release,
+            commands::install_update,
+            commands::open_releases_page,
             commands::is_portable_mode,
             commands::get_claude_plugin_status,
             commands::read_claude_plugin_config,
@@ -507,16 +811,50 @@ pub fn run() {
             // theirs: config import/export and dialogs
             import_export::export_config_to_file,
             import_export::import_config_from_file,
+            import_export::export_state_archive,
+            import_export::import_state_archive,
+            import_export::reset_app_data,
             import_export::save_file_dialog,
             import_export::open_file_dialog,
             update_tray_menu,
         ]);
 
+    let mut context = tauri::generate_context!();
+    if rpc::requested() {
+        for window in context.config_mut().app.windows.iter_mut() {
+            window.visible = false;
+        }
+    }
+
     let app = builder
-        .build(tauri::generate_context!())
+        .build(context)
         .expect("error while running tauri application");
 
     app.run(|app_handle, event| {
+        // 退出请求：先等待进行中的切换等操作完成并写入未落盘的修改，再真正退出
+        if let RunEvent::ExitRequested { code, api, .. } = &event {
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                if !state.is_shut_down() {
+                    api.prevent_exit();
+                    let handle = app_handle.clone();
+                    let code = code.unwrap_or(0);
+                    tauri::async_runtime::spawn(async move {
+                        handle.state::<AppState>().shutdown().await;
+                        handle.exit(code);
+                    });
+                }
+            }
+        }
+
+        // 兜底：未经过退出请求的路径（例如重启）在此写入尚未落盘的配置修改
+        if let RunEvent::Exit = event {
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                if let Err(e) = tauri::async_runtime::block_on(state.flush()) {
+                    log::error!("退出前保存配置失败: {}", e.detail());
+                }
+            }
+        }
+
         #[cfg(target_os = "macos")]
         // macOS 在 Dock 图标被点击并重新激活应用时会触发 Reopen 事件，这里手动恢复主窗口
         if let RunEvent::Reopen { .. } = event {
@@ -531,10 +869,5 @@ pub fn run() {
                 apply_tray_policy(app_handle, true);
             }
         }
-
-        #[cfg(not(target_os = "macos"))]
-        {
-            let _ = (app_handle, event);
-        }
     });
-}
\ No newline at end of file
+}
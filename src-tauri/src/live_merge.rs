@@ -0,0 +1,112 @@
+use cc_switch_core::merge::merge_claude;
+use serde_json::{Map, Value};
+
+use crate::app_config::AppType;
+use crate::app_config::MultiAppConfig;
+use crate::provider::Provider;
+use crate::settings::LiveWriteMode;
+
+pub use cc_switch_core::live::{strip_model, with_model};
+
+fn claude_mode() -> LiveWriteMode {
+    crate::settings::get_settings().claude_live_mode
+}
+
+fn codex_mode() -> LiveWriteMode {
+    crate::settings::get_settings().codex_live_mode
+}
+
+fn mode_of(app: &AppType) -> LiveWriteMode {
+    match app {
+        AppType::Claude => claude_mode(),
+        AppType::Codex => codex_mode(),
+    }
+}
+
+/// 以 `base` 为底替换 Codex 配置中与供应商相关的部分（见 [`cc_switch_core::merge::merge_codex_config`]）
+pub fn merge_codex_config(base: &str, source: &str) -> Result<String, String> {
+    cc_switch_core::merge::merge_codex_config(base, source)
+        .map_err(|e| crate::error::AppError::from(e).into())
+}
+
+/// 替换模式：以供应商配置为准，保留 live 中用户维护的表（mcp_servers、tui 等）
+fn replace_codex_config(provider_config: &str) -> String {
+    let live = match crate::codex_config::read_codex_config_text() {
+        Ok(text) => text,
+        Err(e) => {
+            log::warn!("读取 live config.toml 失败，按供应商配置整体写入: {}", e);
+            return provider_config.to_string();
+        }
+    };
+    crate::codex_config::preserve_user_sections(&live, provider_config).unwrap_or_else(|e| {
+        log::warn!("保留用户配置段失败，按供应商配置整体写入: {}", e);
+        provider_config.to_string()
+    })
+}
+
+/// 替换模式下写入时会保留 live 中用户维护的表；漂移比较前对已存配置做同样处理
+pub fn with_codex_user_sections(mut stored: Value, live: &Value) -> Value {
+    if codex_mode() != LiveWriteMode::Replace {
+        return stored;
+    }
+    let live_config = live.get("config").and_then(|v| v.as_str()).unwrap_or("");
+    let stored_config = stored.get("config").and_then(|v| v.as_str()).unwrap_or("");
+    if let Ok(text) = crate::codex_config::preserve_user_sections(live_config, stored_config) {
+        if let Some(obj) = stored.as_object_mut() {
+            obj.insert("config".to_string(), Value::String(text));
+        }
+    }
+    stored
+}
+
+/// 计算写入 Codex live 的 config.toml：合并模式下保留现有文件中的非供应商段落，
+/// profile 模式下写入全部供应商的 profile 并切换到目标供应商
+pub fn codex_live_config(
+    app_config: &MultiAppConfig,
+    provider: &Provider,
+    provider_config: Option<&str>,
+) -> Result<Option<String>, String> {
+    // 先合并供应商引用的配置片段
+    let fragments = crate::codex_config::fragment_texts(app_config, &provider.config_fragments)?;
+    let composed = crate::codex_config::compose_config(&fragments, provider_config.unwrap_or(""))?;
+    match codex_mode() {
+        LiveWriteMode::Replace => return Ok(Some(replace_codex_config(&composed))),
+        LiveWriteMode::Profiles => {
+            return crate::codex_profiles::live_config(app_config, &provider.id, &composed)
+                .map(Some)
+        }
+        LiveWriteMode::Merge => {}
+    }
+    let path = crate::codex_config::get_codex_config_path();
+    let live = if path.exists() {
+        std::fs::read_to_string(&path)
+            .map_err(|e| format!("读取 config.toml 失败: {}: {}", path.display(), e))?
+    } else {
+        String::new()
+    };
+    merge_codex_config(&live, &composed).map(Some)
+}
+
+/// 计算写入 Claude live 的内容：合并模式下保留现有 settings.json 中的非供应商字段
+pub fn claude_live_content(provider_settings: &Value) -> Result<Value, String> {
+    if claude_mode() == LiveWriteMode::Replace {
+        return Ok(provider_settings.clone());
+    }
+    let path = crate::config::get_claude_settings_path();
+    let live = if path.exists() {
+        crate::config::read_json_file::<Value>(&path)?
+    } else {
+        Value::Object(Map::new())
+    };
+    Ok(merge_claude(&live, provider_settings))
+}
+
+/// 计算回填到供应商的配置（按当前写入方式，见 [`cc_switch_core::live::backfill`]）
+pub fn backfill(app: &AppType, stored: &Value, live: Value) -> Value {
+    cc_switch_core::live::backfill(app, mode_of(app), stored, live)
+}
+
+/// 漂移检测时参与比较的部分（按当前写入方式，见 [`cc_switch_core::live::comparable`]）
+pub fn comparable(app: &AppType, settings: &Value) -> Value {
+    cc_switch_core::live::comparable(app, mode_of(app), settings)
+}
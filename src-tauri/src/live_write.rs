@@ -0,0 +1,129 @@
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// 将供应商配置写入 live 文件：先拉取远程环境、备份现有 live，再以明文密钥（含供应商模型）写入，
+/// 最后推送到远程环境并同步镜像环境
+pub fn write_provider_live(
+    config: &MultiAppConfig,
+    app: &AppType,
+    provider: &Provider,
+) -> Result<(), AppError> {
+    crate::remote_env::pull_live(app)?;
+    if let Err(e) = crate::backup::backup_live_configs(app) {
+        log::warn!("备份 live 配置失败: {}", e);
+    }
+    // live 文件需要明文密钥：解析钥匙串引用
+    let live_settings = crate::live_merge::with_model(
+        app,
+        provider.model.as_deref(),
+        crate::keychain::resolve(app, &provider.id, &provider.settings_config)?,
+    );
+    match app {
+        AppType::Claude => {
+            let settings_path = crate::config::get_claude_settings_path();
+            crate::config::write_json_file(
+                &settings_path,
+                &crate::live_merge::claude_live_content(&live_settings)?,
+            )?;
+        }
+        AppType::Codex => {
+            let auth = live_settings
+                .get("auth")
+                .ok_or_else(|| "目标供应商缺少 auth 配置".to_string())?;
+            let cfg_text = crate::live_merge::codex_live_config(
+                config,
+                provider,
+                live_settings.get("config").and_then(|v| v.as_str()),
+            )?;
+            crate::codex_config::write_codex_live_atomic(auth, cfg_text.as_deref())?;
+        }
+    }
+    crate::remote_env::push_live(app)?;
+    if let Err(e) = crate::wsl_env::mirror_live(app) {
+        log::warn!("同步镜像环境失败: {}", e);
+    }
+    Ok(())
+}
+
+/// 整体替换 config.json（如同步拉取远端）后，将各应用的当前供应商重新写入 live，
+/// 使 live 与存储保持一致；单个应用失败不影响其余应用，错误汇总返回。
+/// 每个应用持有其 live 写入锁，并在锁内读取最新配置，避免与进行中的切换交错
+pub async fn apply_current_providers(state: &AppState) -> Result<(), AppError> {
+    let mut errors = Vec::new();
+    for app in [AppType::Claude, AppType::Codex] {
+        let _switch = state.lock_switch(&app).await;
+        let config = state.snapshot().await;
+        let Some(provider) = config
+            .get_manager(&app)
+            .and_then(|m| m.providers.get(&m.current))
+        else {
+            continue;
+        };
+        if let Err(e) = write_provider_live(&config, &app, provider) {
+            errors.push(format!("{}: {}", app.as_str(), e));
+        }
+        if let AppType::Codex = app {
+            if let Err(e) = crate::mcp::sync_enabled_to_codex(&config) {
+                errors.push(format!("{}: {}", app.as_str(), e));
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Message(format!(
+            "写入 live 配置失败: {}",
+            errors.join("; ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_home;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn switch_writes_claude_live_settings() {
+        let home = test_home::enter();
+        std::fs::create_dir_all(home.path().join(".claude")).unwrap();
+        let provider = Provider::with_id(
+            "work".into(),
+            "Work".into(),
+            json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-work" } }),
+            None,
+        );
+
+        write_provider_live(&MultiAppConfig::default(), &AppType::Claude, &provider).unwrap();
+
+        let live: Value =
+            crate::config::read_json_file(&crate::config::get_claude_settings_path()).unwrap();
+        assert_eq!(live["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-work");
+    }
+
+    #[test]
+    fn switch_writes_codex_auth_and_config() {
+        let home = test_home::enter();
+        std::fs::create_dir_all(home.path().join(".codex")).unwrap();
+        let provider = Provider::with_id(
+            "relay".into(),
+            "Relay".into(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-relay" },
+                "config": "model = \"gpt-5\"\n",
+            }),
+            None,
+        );
+
+        write_provider_live(&MultiAppConfig::default(), &AppType::Codex, &provider).unwrap();
+
+        let auth: Value =
+            crate::config::read_json_file(&crate::codex_config::get_codex_auth_path()).unwrap();
+        assert_eq!(auth["OPENAI_API_KEY"], "sk-relay");
+        let config = std::fs::read_to_string(crate::codex_config::get_codex_config_path()).unwrap();
+        assert!(config.contains("model = \"gpt-5\""));
+    }
+}
@@ -274,8 +274,8 @@ pub fn delete_in_config_for(
     Ok(existed)
 }
 
-/// 设置启用状态并同步到 ~/.claude.json
-pub fn set_enabled_and_sync_for(
+/// 设置 MCP 服务器启用状态（仅修改内存配置，同步 live 见 `sync_enabled_for`）
+pub fn set_enabled_for(
     config: &mut MultiAppConfig,
     app: &AppType,
     id: &str,
@@ -297,19 +297,15 @@ pub fn set_enabled_and_sync_for(
         // 若不存在则直接返回 false
         return Ok(false);
     }
+    Ok(true)
+}
 
-    // 同步启用项
+/// 将指定应用的启用项投影到其 live 配置（Claude: ~/.claude.json，Codex: ~/.codex/config.toml）
+pub fn sync_enabled_for(config: &MultiAppConfig, app: &AppType) -> Result<(), String> {
     match app {
-        AppType::Claude => {
-            // 将启用项投影到 ~/.claude.json
-            sync_enabled_to_claude(config)?;
-        }
-        AppType::Codex => {
-            // 将启用项投影到 ~/.codex/config.toml
-            sync_enabled_to_codex(config)?;
-        }
+        AppType::Claude => sync_enabled_to_claude(config),
+        AppType::Codex => sync_enabled_to_codex(config),
     }
-    Ok(true)
 }
 
 /// 将 config.json 中 enabled==true 的项投影写入 ~/.claude.json
@@ -318,14 +314,12 @@ pub fn sync_enabled_to_claude(config: &MultiAppConfig) -> Result<(), String> {
     crate::claude_mcp::set_mcp_servers_map(&enabled)
 }
 
-/// 从 ~/.claude.json 导入 mcpServers 到 config.json（设为 enabled=true）。
+/// 将 ~/.claude.json 的内容（`text`）中的 mcpServers 导入 config.json（设为 enabled=true）。
 /// 已存在的项仅强制 enabled=true，不覆盖其他字段。
-pub fn import_from_claude(config: &mut MultiAppConfig) -> Result<usize, String> {
-    let text_opt = crate::claude_mcp::read_mcp_json()?;
-    let Some(text) = text_opt else { return Ok(0) };
+pub fn import_from_claude(config: &mut MultiAppConfig, text: &str) -> Result<usize, String> {
     let mut changed = normalize_servers_for(config, &AppType::Claude);
     let v: Value =
-        serde_json::from_str(&text).map_err(|e| format!("解析 ~/.claude.json 失败: {}", e))?;
+        serde_json::from_str(text).map_err(|e| format!("解析 ~/.claude.json 失败: {}", e))?;
     let Some(map) = v.get("mcpServers").and_then(|x| x.as_object()) else {
         return Ok(changed);
     };
@@ -391,18 +385,17 @@ pub fn import_from_claude(config: &mut MultiAppConfig) -> Result<usize, String>
     Ok(changed)
 }
 
-/// 从 ~/.codex/config.toml 导入 MCP 到 config.json（Codex 作用域），并将导入项设为 enabled=true。
+/// 将 ~/.codex/config.toml 的内容（`text`）中的 MCP 导入 config.json（Codex 作用域），并将导入项设为 enabled=true。
 /// 支持两种 schema：[mcp.servers.<id>] 与 [mcp_servers.<id>]。
 /// 已存在的项仅强制 enabled=true，不覆盖其他字段。
-pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, String> {
-    let text = crate::codex_config::read_and_validate_codex_config_text()?;
+pub fn import_from_codex(config: &mut MultiAppConfig, text: &str) -> Result<usize, String> {
     if text.trim().is_empty() {
         return Ok(0);
     }
     let mut changed_total = normalize_servers_for(config, &AppType::Codex);
 
     let root: toml::Table =
-        toml::from_str(&text).map_err(|e| format!("解析 ~/.codex/config.toml 失败: {}", e))?;
+        toml::from_str(text).map_err(|e| format!("解析 ~/.codex/config.toml 失败: {}", e))?;
 
     // helper：处理一组 servers 表
     let mut import_servers_tbl = |servers_tbl: &toml::value::Table| {
@@ -579,6 +572,7 @@ pub fn sync_enabled_to_codex(config: &MultiAppConfig) -> Result<(), String> {
         toml::from_str::<TomlTable>(&base_text)
             .map_err(|e| format!("解析 config.toml 失败: {}", e))?
     };
+    let original = root.clone();
 
     // 3) 写入 servers 表（支持 mcp.servers 与 mcp_servers；优先沿用已有风格，默认 mcp_servers）
     let prefer_mcp_servers = root.get("mcp_servers").is_some() || root.get("mcp").is_none();
@@ -722,6 +716,11 @@ pub fn sync_enabled_to_codex(config: &MultiAppConfig) -> Result<(), String> {
         }
     }
 
+    // MCP 无变化时不重写文件，保留用户的注释与格式
+    if root == original {
+        return Ok(());
+    }
+
     // 4) 序列化并写回 config.toml（仅改 TOML，不触碰 auth.json）
     let new_text = toml::to_string(&TomlValue::Table(root))
         .map_err(|e| format!("序列化 config.toml 失败: {}", e))?;
@@ -0,0 +1,215 @@
+use serde_json::{json, Map, Value};
+use tauri::Manager;
+
+use crate::app_config::AppType;
+use crate::command_args::app_type_from_args;
+use crate::provider_query::{ProviderQuery, ProviderSummary};
+use crate::rpc::{param, required, RpcError, METHOD_NOT_FOUND};
+use crate::store::AppState;
+
+/// 客户端请求的版本不受支持（或未提供）时使用的 MCP 版本
+const DEFAULT_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// 支持的 MCP 协议版本
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] =
+    &[DEFAULT_PROTOCOL_VERSION, "2025-03-26", "2024-11-05"];
+
+/// 协商协议版本：客户端请求的版本受支持时沿用，否则返回默认版本由客户端决定是否断开
+fn negotiate_protocol_version(requested: Option<&str>) -> &'static str {
+    requested
+        .and_then(|v| SUPPORTED_PROTOCOL_VERSIONS.iter().find(|s| **s == v))
+        .copied()
+        .unwrap_or(DEFAULT_PROTOCOL_VERSION)
+}
+
+/// 对外提供的工具定义
+fn tools() -> Value {
+    let app_schema = json!({
+        "type": "string",
+        "enum": ["claude", "codex"],
+        "description": "目标应用，默认 claude"
+    });
+    json!([
+        {
+            "name": "list_providers",
+            "description": "列出供应商（不含密钥），标记当前供应商",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "app": app_schema,
+                    "keyword": { "type": "string", "description": "按名称或请求地址过滤" }
+                }
+            }
+        },
+        {
+            "name": "get_status",
+            "description": "获取各应用的当前供应商、配置状态与 live 配置漂移情况",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "switch_provider",
+            "description": "切换到指定供应商（按 ID 或名称）",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "app": app_schema,
+                    "provider": { "type": "string", "description": "供应商 ID 或名称" }
+                },
+                "required": ["provider"]
+            }
+        },
+        {
+            "name": "rotate_provider",
+            "description": "按列表顺序切换到下一个供应商，例如当前供应商触发限流时",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "app": app_schema }
+            }
+        }
+    ])
+}
+
+fn app_of(args: &Map<String, Value>) -> Result<AppType, String> {
+    Ok(app_type_from_args(args)?.unwrap_or(AppType::Claude))
+}
+
+async fn providers(state: &AppState, app: &AppType) -> Vec<ProviderSummary> {
+    let config = state.read().await;
+    crate::provider_query::query(
+        &config,
+        &ProviderQuery {
+            app: Some(app.clone()),
+            ..Default::default()
+        },
+    )
+}
+
+/// 切换并返回切换后的供应商摘要
+async fn switch_to(
+    handle: &tauri::AppHandle,
+    app: AppType,
+    provider: &ProviderSummary,
+) -> Result<Value, String> {
+    crate::switch_provider_internal(handle, app.clone(), provider.id.clone()).await?;
+    Ok(json!({ "app": app.as_str(), "id": provider.id, "name": provider.name }))
+}
+
+async fn call_tool(
+    handle: &tauri::AppHandle,
+    name: &str,
+    args: &Map<String, Value>,
+) -> Result<Value, String> {
+    let state = handle.state::<AppState>();
+    match name {
+        "list_providers" => {
+            let config = state.read().await;
+            let query = ProviderQuery {
+                app: app_type_from_args(args)?,
+                keyword: args
+                    .get("keyword")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                ..Default::default()
+            };
+            serde_json::to_value(crate::provider_query::query(&config, &query))
+                .map_err(|e| e.to_string())
+        }
+        "get_status" => {
+            let status = crate::app_status::collect(state.snapshot().await)
+                .await
+                .map_err(|e| e.detail())?;
+            serde_json::to_value(status).map_err(|e| e.to_string())
+        }
+        "switch_provider" => {
+            let app = app_of(args)?;
+            let key = args
+                .get("provider")
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .ok_or("缺少参数 provider")?;
+            let list = providers(&state, &app).await;
+            let target = list
+                .iter()
+                .find(|p| p.id == key)
+                .or_else(|| list.iter().find(|p| p.name.eq_ignore_ascii_case(key)))
+                .ok_or_else(|| format!("{} 中不存在供应商: {}", app.as_str(), key))?;
+            switch_to(handle, app, target).await
+        }
+        "rotate_provider" => {
+            let app = app_of(args)?;
+            let list = providers(&state, &app).await;
+            if list.len() < 2 {
+                return Err(format!("{} 没有可轮换的其他供应商", app.as_str()));
+            }
+            let next = match list.iter().position(|p| p.is_current) {
+                Some(i) => &list[(i + 1) % list.len()],
+                None => &list[0],
+            };
+            switch_to(handle, app, next).await
+        }
+        _ => Err(format!("未知工具: {}", name)),
+    }
+}
+
+/// 处理 MCP 请求（initialize、tools/list、tools/call 等）
+pub async fn dispatch(
+    handle: &tauri::AppHandle,
+    method: &str,
+    params: &Map<String, Value>,
+) -> Result<Value, RpcError> {
+    match method {
+        "initialize" => {
+            let requested = param::<String>(params, &["protocolVersion"])?;
+            let version = negotiate_protocol_version(requested.as_deref());
+            Ok(json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "cc-switch",
+                    "version": handle.package_info().version.to_string()
+                }
+            }))
+        }
+        "ping" | "notifications/initialized" | "notifications/cancelled" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => {
+            let name: String = required(params, &["name"])?;
+            let args: Map<String, Value> = param(params, &["arguments"])?.unwrap_or_default();
+            // 工具执行失败以 isError 结果返回，供模型读取原因
+            Ok(match call_tool(handle, &name, &args).await {
+                Ok(value) => json!({
+                    "content": [{
+                        "type": "text",
+                        "text": serde_json::to_string_pretty(&value).unwrap_or_default()
+                    }],
+                    "structuredContent": { "result": value },
+                    "isError": false
+                }),
+                Err(e) => json!({
+                    "content": [{ "type": "text", "text": e }],
+                    "isError": true
+                }),
+            })
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("未知方法: {}", method),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_only_supported_protocol_versions() {
+        assert_eq!(negotiate_protocol_version(Some("2024-11-05")), "2024-11-05");
+        assert_eq!(
+            negotiate_protocol_version(Some("2099-01-01")),
+            DEFAULT_PROTOCOL_VERSION
+        );
+        assert_eq!(negotiate_protocol_version(None), DEFAULT_PROTOCOL_VERSION);
+    }
+}
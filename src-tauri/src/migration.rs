@@ -22,7 +22,7 @@ fn sanitized_id(base: &str) -> String {
     crate::config::sanitize_provider_name(base)
 }
 
-fn next_unique_id(existing: &HashSet<String>, base: &str) -> String {
+pub(crate) fn next_unique_id(existing: &HashSet<String>, base: &str) -> String {
     let base = sanitized_id(base);
     if !existing.contains(&base) {
         return base;
@@ -36,7 +36,7 @@ fn next_unique_id(existing: &HashSet<String>, base: &str) -> String {
     format!("{}-dup", base)
 }
 
-fn extract_claude_api_key(value: &Value) -> Option<String> {
+pub(crate) fn extract_claude_api_key(value: &Value) -> Option<String> {
     value
         .get("env")
         .and_then(|env| env.get("ANTHROPIC_AUTH_TOKEN"))
@@ -44,7 +44,7 @@ fn extract_claude_api_key(value: &Value) -> Option<String> {
         .map(|s| s.to_string())
 }
 
-fn extract_codex_api_key(value: &Value) -> Option<String> {
+pub(crate) fn extract_codex_api_key(value: &Value) -> Option<String> {
     value
         .get("auth")
         .and_then(|auth| {
@@ -415,7 +415,7 @@ pub fn dedupe_config(config: &mut MultiAppConfig) -> usize {
             }
         }
         for id in remove.iter() {
-            mgr.providers.remove(id);
+            mgr.providers.shift_remove(id);
         }
         remove.len()
     }
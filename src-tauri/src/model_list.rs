@@ -0,0 +1,122 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::app_config::AppType;
+
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+/// Anthropic /v1/models 分页上限，避免异常响应导致死循环
+const MAX_PAGES: usize = 10;
+
+/// 供应商可用模型
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owned_by: Option<String>,
+}
+
+fn parse_models(body: &Value) -> Vec<ModelInfo> {
+    body.get("data")
+        .or_else(|| body.get("models"))
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let id = item
+                        .get("id")
+                        .or_else(|| item.get("name"))
+                        .and_then(|v| v.as_str())?
+                        .to_string();
+                    Some(ModelInfo {
+                        id,
+                        display_name: item
+                            .get("display_name")
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string()),
+                        owned_by: item
+                            .get("owned_by")
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string()),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 模型列表接口地址：Claude 使用站点根 + /v1/models，Codex 使用 base_url + /models
+fn models_url(app: &AppType, base_url: &str) -> String {
+    let base = base_url.trim().trim_end_matches('/');
+    match app {
+        AppType::Claude => {
+            let root = base.strip_suffix("/v1").unwrap_or(base);
+            format!("{}/v1/models", root)
+        }
+        AppType::Codex => format!("{}/models", base),
+    }
+}
+
+/// 请求供应商的模型列表（兼容 OpenAI 与 Anthropic 格式，自动翻页）
+pub async fn fetch_models(
+    app: &AppType,
+    base_url: &str,
+    api_key: Option<&str>,
+) -> Result<Vec<ModelInfo>, String> {
+    let client = crate::http_client::builder()?
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let url = models_url(app, base_url);
+
+    let mut models = Vec::new();
+    let mut after_id: Option<String> = None;
+    for _ in 0..MAX_PAGES {
+        let mut req = client.get(&url);
+        if let Some(after) = &after_id {
+            req = req.query(&[("after_id", after.as_str())]);
+        }
+        if let Some(key) = api_key {
+            req = req.bearer_auth(key);
+            if matches!(app, AppType::Claude) {
+                req = req
+                    .header("x-api-key", key)
+                    .header("anthropic-version", "2023-06-01");
+            }
+        }
+
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| format!("请求模型列表失败: {}", e))?;
+        let status = resp.status();
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| format!("读取模型列表响应失败: {}", e))?;
+        if !status.is_success() {
+            let snippet: String = text.chars().take(200).collect();
+            return Err(format!("模型列表接口返回 {}: {}", status.as_u16(), snippet));
+        }
+        let body: Value =
+            serde_json::from_str(&text).map_err(|e| format!("解析模型列表失败: {}", e))?;
+        models.extend(parse_models(&body));
+
+        let has_more = body.get("has_more").and_then(|v| v.as_bool()) == Some(true);
+        after_id = body
+            .get("last_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        if !has_more || after_id.is_none() {
+            break;
+        }
+    }
+
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models.dedup_by(|a, b| a.id == b.id);
+    Ok(models)
+}
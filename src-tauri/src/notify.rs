@@ -0,0 +1,20 @@
+use tauri_plugin_notification::NotificationExt;
+
+/// 发送系统通知（设置中关闭或应用尚未初始化时忽略）
+pub fn send(title: &str, body: &str) {
+    if !crate::settings::get_settings().notifications_enabled {
+        return;
+    }
+    let Some(handle) = crate::app_store::get_app_handle() else {
+        return;
+    };
+    if let Err(e) = handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+    {
+        log::warn!("发送系统通知失败: {}", e);
+    }
+}
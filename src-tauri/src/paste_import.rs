@@ -0,0 +1,266 @@
+use serde_json::{json, Map, Value};
+
+use crate::app_config::AppType;
+use crate::tool_import::ImportedEntry;
+
+/// 从 start 开始跳过空白与注释，返回下一个有效字符
+fn next_significant(chars: &[char], mut i: usize) -> Option<char> {
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            c => return Some(c),
+        }
+    }
+    None
+}
+
+/// 去除 JSON 片段中的 `//`、`/* */` 注释与尾随逗号
+///
+/// 注释替换为空格并保留换行，使解析错误的行列号仍对应原文
+pub fn sanitize_json(text: &str) -> String {
+    let chars: Vec<char> = text.trim_start_matches('\u{feff}').chars().collect();
+    let mut out = String::with_capacity(chars.len());
+    let mut i = 0;
+    let mut in_string = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            out.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                out.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+                i += 1;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    out.push(' ');
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                out.push_str("  ");
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    out.push(if chars[i] == '\n' { '\n' } else { ' ' });
+                    i += 1;
+                }
+                if i < chars.len() {
+                    out.push_str("  ");
+                    i += 2;
+                }
+            }
+            ',' => {
+                // 尾随逗号：其后（跳过空白与注释）紧跟 } 或 ]
+                if matches!(next_significant(&chars, i + 1), Some('}') | Some(']')) {
+                    out.push(' ');
+                } else {
+                    out.push(c);
+                }
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// 解析 `export KEY=VALUE`、`set KEY=VALUE`、`$env:KEY="VALUE"` 形式的环境变量片段
+fn parse_env_lines(text: &str) -> Option<Map<String, Value>> {
+    let mut env = Map::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let rest = line
+            .strip_prefix("export ")
+            .or_else(|| line.strip_prefix("set "))
+            .or_else(|| line.strip_prefix("$env:"))
+            .unwrap_or(line);
+        let (key, value) = rest.split_once('=')?;
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        env.insert(key.to_string(), json!(value));
+    }
+    if env.is_empty() {
+        None
+    } else {
+        Some(env)
+    }
+}
+
+fn host_name(url: &str) -> Option<String> {
+    let rest = url.split("://").nth(1).unwrap_or(url);
+    let host = rest.split(['/', ':']).next()?.trim();
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_string())
+    }
+}
+
+/// 由 env 构造供应商配置（ANTHROPIC_* 归为 Claude，OPENAI_* 归为 Codex）
+fn entry_from_env(env: Map<String, Value>, name: Option<&str>) -> Result<ImportedEntry, String> {
+    let get = |k: &str| env.get(k).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    if env.keys().any(|k| k.starts_with("ANTHROPIC_")) {
+        let name = name
+            .map(|s| s.to_string())
+            .or_else(|| get("ANTHROPIC_BASE_URL").and_then(|u| host_name(&u)))
+            .unwrap_or_else(|| "Imported".to_string());
+        return Ok(ImportedEntry {
+            app_type: AppType::Claude,
+            name,
+            settings_config: json!({ "env": env }),
+            website_url: None,
+        });
+    }
+
+    if let Some(key) = get("OPENAI_API_KEY") {
+        let base_url = get("OPENAI_BASE_URL");
+        let name = name
+            .map(|s| s.to_string())
+            .or_else(|| base_url.as_deref().and_then(host_name))
+            .unwrap_or_else(|| "Imported".to_string());
+        let config = base_url
+            .map(|u| crate::codex_config::generate_third_party_config(&name, &u, "gpt-5-codex"))
+            .unwrap_or_default();
+        return Ok(ImportedEntry {
+            app_type: AppType::Codex,
+            name,
+            settings_config: json!({ "auth": { "OPENAI_API_KEY": key }, "config": config }),
+            website_url: None,
+        });
+    }
+
+    Err("未找到 ANTHROPIC_* 或 OPENAI_API_KEY 环境变量".to_string())
+}
+
+fn entry_from_json(value: Value, name: Option<&str>) -> Result<ImportedEntry, String> {
+    let obj = value
+        .as_object()
+        .ok_or_else(|| "JSON 片段必须是对象".to_string())?;
+
+    // Claude settings.json：{ "env": { ... } }
+    if let Some(env) = obj.get("env") {
+        let env = env
+            .as_object()
+            .ok_or_else(|| "env 字段必须是对象".to_string())?;
+        let mut entry = entry_from_env(env.clone(), name)?;
+        if matches!(entry.app_type, AppType::Claude) {
+            entry.settings_config = value.clone();
+        }
+        return Ok(entry);
+    }
+
+    // Codex：{ "auth": {...}, "config": "..." }
+    if obj.contains_key("auth") || obj.contains_key("config") {
+        let name = name.map(|s| s.to_string()).unwrap_or_else(|| {
+            obj.get("config")
+                .and_then(|c| c.as_str())
+                .and_then(crate::codex_config::extract_base_url)
+                .and_then(|u| host_name(&u))
+                .unwrap_or_else(|| "Imported".to_string())
+        });
+        let mut settings = value.clone();
+        if settings.get("auth").is_none() {
+            settings["auth"] = json!({});
+        }
+        return Ok(ImportedEntry {
+            app_type: AppType::Codex,
+            name,
+            settings_config: settings,
+            website_url: None,
+        });
+    }
+
+    // 仅有环境变量键值对：{ "ANTHROPIC_BASE_URL": ..., ... }
+    if obj
+        .keys()
+        .any(|k| k.starts_with("ANTHROPIC_") || k == "OPENAI_API_KEY")
+    {
+        return entry_from_env(obj.clone(), name);
+    }
+
+    if crate::tool_import::detect_format(&value).is_some() {
+        return Err("该内容是供应商列表，请使用从其他工具导入".to_string());
+    }
+    Err("无法识别的 JSON 片段：需要包含 env、auth/config 或 ANTHROPIC_* 字段".to_string())
+}
+
+fn entry_from_toml(text: &str, name: Option<&str>) -> Result<ImportedEntry, String> {
+    let table =
+        toml::from_str::<toml::Table>(text).map_err(|e| format!("TOML 片段格式错误: {}", e))?;
+    if !table.contains_key("model_provider") && !table.contains_key("model_providers") {
+        return Err("TOML 片段缺少 model_provider / model_providers 配置".to_string());
+    }
+    let name = name
+        .map(|s| s.to_string())
+        .or_else(|| crate::codex_config::extract_base_url(text).and_then(|u| host_name(&u)))
+        .unwrap_or_else(|| "Imported".to_string());
+    Ok(ImportedEntry {
+        app_type: AppType::Codex,
+        name,
+        settings_config: json!({ "auth": {}, "config": text.trim() }),
+        website_url: None,
+    })
+}
+
+/// 识别并解析粘贴的配置片段（JSON / TOML / 环境变量）
+pub fn parse_snippet(text: &str, name: Option<&str>) -> Result<ImportedEntry, String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err("粘贴的内容为空".to_string());
+    }
+
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        let sanitized = sanitize_json(trimmed);
+        let value: Value = serde_json::from_str(&sanitized).map_err(|e| {
+            format!(
+                "JSON 格式错误（第 {} 行，第 {} 列）: {}",
+                e.line(),
+                e.column(),
+                e
+            )
+        })?;
+        return entry_from_json(value, name);
+    }
+
+    if let Some(env) = parse_env_lines(trimmed) {
+        return entry_from_env(env, name);
+    }
+
+    entry_from_toml(trimmed, name)
+}
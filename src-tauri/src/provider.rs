@@ -1,7 +1,10 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+use crate::app_config::AppType;
+
 // SSOT 模式：不再写供应商副本文件
 
 /// 供应商结构体
@@ -25,9 +28,95 @@ pub struct Provider {
     /// 供应商元数据（不写入 live 配置，仅存于 ~/.cc-switch/config.json）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub meta: Option<ProviderMeta>,
+    /// Codex 的认证方式；为空时按 auth.json 内容推断
+    #[serde(rename = "authMode", skip_serializing_if = "Option::is_none")]
+    pub auth_mode: Option<CodexAuthMode>,
+    /// Codex 引用的配置片段 ID（按顺序合并，供应商自身的 config 最后合并）
+    #[serde(
+        rename = "configFragments",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub config_fragments: Vec<String>,
+    /// 切换时写入的模型：Claude 为 env.ANTHROPIC_MODEL，Codex 为 config.toml 的 model；为空时不改动
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Codex 的认证方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CodexAuthMode {
+    /// API Key（auth.json 中的 OPENAI_API_KEY）
+    ApiKey,
+    /// ChatGPT 登录（auth.json 中的 tokens）
+    Chatgpt,
+}
+
+impl CodexAuthMode {
+    /// 根据 auth.json 内容推断认证方式：含 tokens 为 ChatGPT 登录，否则有 OPENAI_API_KEY 为 API Key
+    pub fn detect(auth: &Value) -> Option<Self> {
+        if auth.get("tokens").is_some_and(|t| t.is_object()) {
+            return Some(Self::Chatgpt);
+        }
+        auth.get("OPENAI_API_KEY")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .map(|_| Self::ApiKey)
+    }
 }
 
 impl Provider {
+    /// 供应商请求的基础地址（未配置时使用官方地址）
+    pub fn base_url(&self, app: &AppType) -> String {
+        let url = match app {
+            AppType::Claude => self
+                .settings_config
+                .get("env")
+                .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "https://api.anthropic.com".to_string()),
+            AppType::Codex => self
+                .settings_config
+                .get("config")
+                .and_then(|v| v.as_str())
+                .and_then(crate::codex_config::extract_base_url)
+                .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+        };
+        url.trim_end_matches('/').to_string()
+    }
+
+    /// 供应商的 API Key（若已配置）
+    pub fn api_key(&self, app: &AppType) -> Option<String> {
+        let key = match app {
+            AppType::Claude => self
+                .settings_config
+                .get("env")
+                .and_then(|env| {
+                    env.get("ANTHROPIC_AUTH_TOKEN")
+                        .or_else(|| env.get("ANTHROPIC_API_KEY"))
+                })
+                .and_then(|v| v.as_str()),
+            AppType::Codex => self
+                .settings_config
+                .get("auth")
+                .and_then(|auth| auth.get("OPENAI_API_KEY"))
+                .and_then(|v| v.as_str()),
+        };
+        key.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+    }
+
+    /// Codex 供应商的认证方式：优先使用显式设置，否则按 auth 推断
+    pub fn codex_auth_mode(&self) -> Option<CodexAuthMode> {
+        self.auth_mode.or_else(|| {
+            self.settings_config
+                .get("auth")
+                .and_then(CodexAuthMode::detect)
+        })
+    }
+
     /// 从现有ID创建供应商
     pub fn with_id(
         id: String,
@@ -44,6 +133,9 @@ impl Provider {
             created_at: None,
             sort_index: None,
             meta: None,
+            auth_mode: None,
+            config_fragments: Vec::new(),
+            model: None,
         }
     }
 }
@@ -51,8 +143,12 @@ impl Provider {
 /// 供应商管理器
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProviderManager {
-    pub providers: HashMap<String, Provider>,
+    /// 按添加顺序保存，config.json、界面与托盘中的顺序保持稳定
+    pub providers: IndexMap<String, Provider>,
     pub current: String,
+    /// 最近切换到的供应商 ID（最新在前）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent: Vec<String>,
 }
 
 /// 用量查询脚本配置
@@ -108,11 +204,52 @@ pub struct ProviderMeta {
     /// 用量查询脚本配置
     #[serde(skip_serializing_if = "Option::is_none")]
     pub usage_script: Option<UsageScript>,
+    /// 余额查询配置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<crate::balance::BalanceConfig>,
+    /// 最近一次延迟测试结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<BenchmarkResult>,
+}
+
+/// 供应商延迟测试结果（缓存于 config.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    pub url: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 测试时间（毫秒时间戳）
+    pub tested_at: i64,
 }
 
+/// 每个应用保留的最近供应商数量
+const RECENT_LIMIT: usize = 5;
+
 impl ProviderManager {
     /// 获取所有供应商
-    pub fn get_all_providers(&self) -> &HashMap<String, Provider> {
+    pub fn get_all_providers(&self) -> &IndexMap<String, Provider> {
         &self.providers
     }
+
+    /// 记录一次切换，将目标供应商移到最近列表首位
+    pub fn record_recent(&mut self, id: &str) {
+        self.recent.retain(|r| r != id);
+        self.recent.insert(0, id.to_string());
+        self.recent.truncate(RECENT_LIMIT);
+    }
+
+    /// 最近使用且仍存在的供应商（不含当前供应商）
+    pub fn recent_providers(&self) -> Vec<&Provider> {
+        self.recent
+            .iter()
+            .filter(|id| **id != self.current)
+            .filter_map(|id| self.providers.get(id))
+            .collect()
+    }
 }
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::provider::{CodexAuthMode, Provider, ProviderManager};
+
+/// 列表排序方式
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderSort {
+    /// 与界面、托盘一致：sortIndex → createdAt → 名称
+    #[default]
+    Manual,
+    /// 名称（不区分大小写）
+    Name,
+    /// 当前供应商在前，其次按最近切换顺序，其余保持手动顺序
+    LastUsed,
+}
+
+/// 供应商列表查询条件；均为可选，缺省返回全部应用的全部供应商
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderQuery {
+    /// 仅返回该应用的供应商
+    pub app: Option<AppType>,
+    /// 按分类（标签）过滤
+    pub category: Option<String>,
+    /// true 仅返回当前供应商，false 排除当前供应商
+    pub current: Option<bool>,
+    /// 名称或请求地址包含的关键词（不区分大小写）
+    pub keyword: Option<String>,
+    #[serde(default)]
+    pub sort: ProviderSort,
+    /// 最多返回的条数
+    pub limit: Option<usize>,
+}
+
+/// 供应商摘要：不含 settingsConfig，仅标记是否配置了密钥
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSummary {
+    pub app_type: String,
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website_url: Option<String>,
+    pub base_url: String,
+    pub has_api_key: bool,
+    /// Codex 供应商的认证方式（显式设置或按 auth 推断）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_mode: Option<CodexAuthMode>,
+    pub is_current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    /// 在最近切换列表中的位置（0 为最近一次），未出现时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent_rank: Option<usize>,
+}
+
+impl ProviderSummary {
+    fn new(app: &AppType, manager: &ProviderManager, provider: &Provider) -> Self {
+        Self {
+            app_type: app.as_str().to_string(),
+            id: provider.id.clone(),
+            name: provider.name.clone(),
+            category: provider.category.clone(),
+            website_url: provider.website_url.clone(),
+            base_url: provider.base_url(app),
+            has_api_key: provider.api_key(app).is_some(),
+            auth_mode: match app {
+                AppType::Codex => provider.codex_auth_mode(),
+                AppType::Claude => None,
+            },
+            is_current: manager.current == provider.id,
+            sort_index: provider.sort_index,
+            created_at: provider.created_at,
+            recent_rank: manager.recent.iter().position(|id| *id == provider.id),
+        }
+    }
+
+    fn matches(&self, query: &ProviderQuery, keyword: Option<&str>) -> bool {
+        if let Some(category) = query.category.as_deref() {
+            if self.category.as_deref() != Some(category) {
+                return false;
+            }
+        }
+        if let Some(current) = query.current {
+            if self.is_current != current {
+                return false;
+            }
+        }
+        match keyword {
+            Some(keyword) => {
+                self.name.to_lowercase().contains(keyword)
+                    || self.base_url.to_lowercase().contains(keyword)
+            }
+            None => true,
+        }
+    }
+}
+
+/// 手动顺序：sortIndex → createdAt → 名称（与界面、托盘保持一致）
+fn manual_order(a: &ProviderSummary, b: &ProviderSummary) -> Ordering {
+    match (a.sort_index, b.sort_index) {
+        (Some(x), Some(y)) => return x.cmp(&y),
+        (Some(_), None) => return Ordering::Less,
+        (None, Some(_)) => return Ordering::Greater,
+        _ => {}
+    }
+    match (a.created_at, b.created_at) {
+        (Some(x), Some(y)) => return x.cmp(&y),
+        (Some(_), None) => return Ordering::Greater,
+        (None, Some(_)) => return Ordering::Less,
+        _ => {}
+    }
+    a.name.cmp(&b.name)
+}
+
+/// 按条件筛选并排序供应商摘要
+pub fn query(config: &MultiAppConfig, query: &ProviderQuery) -> Vec<ProviderSummary> {
+    let apps = match &query.app {
+        Some(app) => vec![app.clone()],
+        None => vec![AppType::Claude, AppType::Codex],
+    };
+    let keyword = query
+        .keyword
+        .as_deref()
+        .map(|k| k.trim().to_lowercase())
+        .filter(|k| !k.is_empty());
+
+    let mut results = Vec::new();
+    for app in apps {
+        let Some(manager) = config.get_manager(&app) else {
+            continue;
+        };
+        let mut summaries: Vec<ProviderSummary> = manager
+            .providers
+            .values()
+            .map(|p| ProviderSummary::new(&app, manager, p))
+            .filter(|s| s.matches(query, keyword.as_deref()))
+            .collect();
+        summaries.sort_by(manual_order);
+        results.extend(summaries);
+    }
+
+    match query.sort {
+        ProviderSort::Manual => {}
+        ProviderSort::Name => {
+            results.sort_by_key(|s| s.name.to_lowercase());
+        }
+        ProviderSort::LastUsed => {
+            results.sort_by_key(|s| (!s.is_current, s.recent_rank.unwrap_or(usize::MAX)));
+        }
+    }
+    if let Some(limit) = query.limit {
+        results.truncate(limit);
+    }
+    results
+}
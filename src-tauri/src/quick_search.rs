@@ -0,0 +1,118 @@
+use serde::Serialize;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::provider::Provider;
+
+/// 快速切换面板中的一条搜索结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickSearchResult {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub base_url: String,
+    pub is_current: bool,
+    pub score: u32,
+}
+
+/// 单个字段的模糊匹配得分：完全匹配 > 前缀 > 子串（越靠前越高）> 子序列（连续字符加分）
+fn field_score(query: &str, text: &str) -> Option<u32> {
+    let text = text.to_lowercase();
+    if text == query {
+        return Some(100);
+    }
+    if text.starts_with(query) {
+        return Some(80);
+    }
+    if let Some(pos) = text.find(query) {
+        return Some(60u32.saturating_sub(pos.min(30) as u32));
+    }
+
+    let mut chars = text.chars();
+    let mut score = 10u32;
+    let mut streak = 0u32;
+    for q in query.chars() {
+        let mut skipped = false;
+        loop {
+            match chars.next() {
+                Some(c) if c == q => break,
+                Some(_) => skipped = true,
+                None => return None,
+            }
+        }
+        streak = if skipped { 0 } else { streak + 1 };
+        score += 1 + streak;
+    }
+    Some(score.min(40))
+}
+
+/// 供应商在各字段上的最高得分（按字段权重折算）
+fn provider_score(token: &str, app: &AppType, provider: &Provider, base_url: &str) -> Option<u32> {
+    let host = base_url
+        .split("://")
+        .nth(1)
+        .unwrap_or(base_url)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let fields: [(Option<&str>, u32); 4] = [
+        (Some(provider.name.as_str()), 10),
+        (provider.category.as_deref(), 7),
+        (Some(host), 6),
+        (Some(app.as_str()), 5),
+    ];
+    fields
+        .iter()
+        .filter_map(|(text, weight)| {
+            text.and_then(|t| field_score(token, t))
+                .map(|s| s * weight / 10)
+        })
+        .max()
+}
+
+/// 跨应用模糊搜索供应商（名称、分类、地址、应用类型）；多个关键词需全部命中
+pub fn search(config: &MultiAppConfig, query: &str, limit: usize) -> Vec<QuickSearchResult> {
+    let tokens: Vec<String> = query.split_whitespace().map(|t| t.to_lowercase()).collect();
+
+    let mut results = Vec::new();
+    for app in [AppType::Claude, AppType::Codex] {
+        let Some(manager) = config.get_manager(&app) else {
+            continue;
+        };
+        for provider in manager.providers.values() {
+            let base_url = provider.base_url(&app);
+            let is_current = manager.current == provider.id;
+            let mut score = 0;
+            let matched = tokens.iter().all(|token| {
+                provider_score(token, &app, provider, &base_url)
+                    .map(|s| score += s)
+                    .is_some()
+            });
+            if !matched {
+                continue;
+            }
+            results.push(QuickSearchResult {
+                app_type: app.as_str().to_string(),
+                provider_id: provider.id.clone(),
+                provider_name: provider.name.clone(),
+                category: provider.category.clone(),
+                base_url,
+                is_current,
+                score,
+            });
+        }
+    }
+
+    // 得分相同时当前供应商靠后（快速切换通常是切到其它供应商），再按名称排序
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.is_current.cmp(&b.is_current))
+            .then_with(|| a.provider_name.cmp(&b.provider_name))
+            .then_with(|| a.app_type.cmp(&b.app_type))
+    });
+    results.truncate(limit);
+    results
+}
@@ -0,0 +1,53 @@
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+use crate::secret_mask::mask;
+
+struct Patterns {
+    /// JSON / TOML / env / URL 参数形式的键值对
+    pair: Regex,
+    /// Authorization: Bearer <token>
+    bearer: Regex,
+    /// 常见的 sk- 前缀密钥
+    sk_token: Regex,
+    /// URL 中的 user:password@
+    userinfo: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        pair: Regex::new(r#"([A-Za-z_][A-Za-z0-9_\-]*)("?\s*[:=]\s*"?)([^"'\s,}&]+)"#).unwrap(),
+        bearer: Regex::new(r"(?i)\b(bearer\s+)([A-Za-z0-9._~+/=\-]+)").unwrap(),
+        sk_token: Regex::new(r"\bsk-[A-Za-z0-9_\-]{8,}").unwrap(),
+        userinfo: Regex::new(r"(://[^/\s:@]+:)([^@\s/]+)@").unwrap(),
+    })
+}
+
+/// 排除数值（如 max_tokens）、认证方案名与钥匙串引用等非密钥值
+fn is_secret_value(value: &str) -> bool {
+    !value.chars().all(|c| c.is_ascii_digit())
+        && !value.eq_ignore_ascii_case("bearer")
+        && !value.eq_ignore_ascii_case("basic")
+        && !value.contains("****")
+        && !crate::keychain::is_ref(value)
+}
+
+/// 对任意文本中的密钥做掩码（用于日志与返回给前端的错误信息）
+pub fn redact_text(text: &str) -> String {
+    let p = patterns();
+    let text = p.bearer.replace_all(text, |caps: &Captures| {
+        format!("{}{}", &caps[1], mask(&caps[2]))
+    });
+    let text = p.pair.replace_all(&text, |caps: &Captures| {
+        if crate::audit::is_secret_key(&caps[1]) && is_secret_value(&caps[3]) {
+            format!("{}{}{}", &caps[1], &caps[2], mask(&caps[3]))
+        } else {
+            caps[0].to_string()
+        }
+    });
+    let text = p
+        .sk_token
+        .replace_all(&text, |caps: &Captures| mask(&caps[0]));
+    p.userinfo.replace_all(&text, "${1}****@").into_owned()
+}
@@ -0,0 +1,296 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::app_config::AppType;
+use crate::settings::{NamedEnvironment, SshTarget, TargetEnv, WslWriteMode};
+
+/// 远程文件不存在时 shell 命令的退出码
+const MISSING_EXIT_CODE: i32 = 44;
+
+/// 是否通过远程 shell（ssh 或 wsl.exe exec）同步 live 文件，而非直接读写文件系统
+pub fn uses_mirror(env: &NamedEnvironment) -> bool {
+    match env.target_env {
+        TargetEnv::Ssh => true,
+        TargetEnv::Wsl => env.wsl_write_mode == WslWriteMode::Exec,
+        TargetEnv::Native => false,
+    }
+}
+
+/// 远程环境在本地的镜像主目录（~/.cc-switch/remote/<环境名>），live 读写均在镜像中进行，
+/// 再通过远程 shell 与环境内的主目录同步
+pub fn mirror_home(env: &NamedEnvironment) -> PathBuf {
+    crate::config::get_app_config_dir()
+        .join("remote")
+        .join(crate::config::sanitize_provider_name(&env.name))
+}
+
+fn target(env: &NamedEnvironment) -> Result<&SshTarget, String> {
+    env.ssh
+        .as_ref()
+        .filter(|t| !t.host.trim().is_empty())
+        .ok_or_else(|| format!("SSH 环境 {} 未配置主机", env.name))
+}
+
+fn expand_home(raw: &str) -> PathBuf {
+    match (raw.strip_prefix("~/"), crate::config::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(raw),
+    }
+}
+
+fn ssh_command(target: &SshTarget, remote_cmd: &str) -> Command {
+    let mut cmd = Command::new("ssh");
+    // 非交互：禁止密码提示，避免后台进程挂起
+    cmd.args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10"]);
+    if let Some(port) = target.port {
+        cmd.arg("-p").arg(port.to_string());
+    }
+    if let Some(key) = target.key_path.as_deref().filter(|k| !k.trim().is_empty()) {
+        cmd.arg("-i").arg(expand_home(key.trim()));
+    }
+    // 以 `--` 结束选项解析，避免以 `-` 开头的主机或用户名被当作 ssh 选项
+    cmd.arg("--");
+    let host = target.host.trim();
+    match target
+        .user
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+    {
+        Some(user) => cmd.arg(format!("{}@{}", user, host)),
+        None => cmd.arg(host),
+    };
+    cmd.arg(remote_cmd);
+    cmd
+}
+
+fn wsl_command(env: &NamedEnvironment, remote_cmd: &str) -> Result<Command, String> {
+    let distro = crate::wsl_env::resolve_distro(env)?;
+    let mut cmd = Command::new("wsl.exe");
+    cmd.args(["-d", distro.as_str(), "-e", "sh", "-c", remote_cmd]);
+    Ok(cmd)
+}
+
+/// 在环境内执行 shell 命令：SSH 环境经 ssh，WSL 环境经 wsl.exe
+fn shell_command(env: &NamedEnvironment, remote_cmd: &str) -> Result<Command, String> {
+    #[allow(unused_mut)]
+    let mut cmd = match env.target_env {
+        TargetEnv::Ssh => ssh_command(target(env)?, remote_cmd),
+        TargetEnv::Wsl => wsl_command(env, remote_cmd)?,
+        TargetEnv::Native => return Err("本机环境无需远程执行".to_string()),
+    };
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x0800_0000);
+    }
+    Ok(cmd)
+}
+
+/// 远程 shell 中引用 `$HOME` 下的相对路径
+fn remote_path(rel: &str) -> String {
+    format!("\"$HOME\"/'{}'", rel.replace('\'', "'\\''"))
+}
+
+fn stderr_text(output: &std::process::Output) -> String {
+    crate::redact::redact_text(String::from_utf8_lossy(&output.stderr).trim())
+}
+
+/// 应用 live 文件相对主目录的路径（使用 `/` 分隔）
+fn live_files(app: &AppType, home: &Path) -> Result<Vec<String>, String> {
+    let files = match app {
+        AppType::Claude => vec![crate::config::get_claude_settings_path()],
+        AppType::Codex => vec![
+            crate::codex_config::get_codex_auth_path(),
+            crate::codex_config::get_codex_config_path(),
+        ],
+    };
+    files
+        .iter()
+        .map(|path| {
+            path.strip_prefix(home)
+                .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+                .map_err(|_| format!("远程环境不支持自定义配置目录: {}", path.display()))
+        })
+        .collect()
+}
+
+fn pull_file(env: &NamedEnvironment, rel: &str, local: &Path) -> Result<(), String> {
+    let remote = remote_path(rel);
+    let output = shell_command(
+        env,
+        &format!(
+            "if [ -f {0} ]; then cat {0}; else exit {1}; fi",
+            remote, MISSING_EXIT_CODE
+        ),
+    )?
+    .output()
+    .map_err(|e| format!("启动远程命令失败: {}", e))?;
+    match output.status.code() {
+        Some(0) => crate::config::atomic_write(local, &output.stdout).map_err(Into::into),
+        Some(MISSING_EXIT_CODE) => crate::config::delete_file(local).map_err(Into::into),
+        _ => Err(format!(
+            "读取 {} 中的文件 {} 失败: {}",
+            env.name,
+            rel,
+            stderr_text(&output)
+        )),
+    }
+}
+
+/// 写入临时文件后在环境内 mv 替换，避免半写状态
+pub fn push_file(env: &NamedEnvironment, rel: &str, data: &[u8]) -> Result<(), String> {
+    let remote = remote_path(rel);
+    let tmp = remote_path(&format!("{}.cc-switch.tmp", rel));
+    let dir = rel.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(".");
+    let mut child = shell_command(
+        env,
+        &format!(
+            "mkdir -p {dir} && umask 077 && cat > {tmp} && mv {tmp} {remote}",
+            dir = remote_path(dir),
+            tmp = tmp,
+            remote = remote
+        ),
+    )?
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|e| format!("启动远程命令失败: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(data)
+            .map_err(|e| format!("发送文件 {} 失败: {}", rel, e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("等待远程命令结束失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "写入 {} 中的文件 {} 失败: {}",
+            env.name,
+            rel,
+            stderr_text(&output)
+        ));
+    }
+    Ok(())
+}
+
+/// 仅保留环境主目录下 `rel` 中按名称倒序排列的前 `keep` 个条目（用于按时间命名的备份目录）
+pub fn prune_dir(env: &NamedEnvironment, rel: &str, keep: usize) -> Result<(), String> {
+    let output = run_shell(
+        env,
+        &format!(
+            "cd {dir} 2>/dev/null || exit 0; ls -1 | sort -r | tail -n +{skip} | while IFS= read -r d; do rm -rf -- \"$d\"; done",
+            dir = remote_path(rel),
+            skip = keep + 1
+        ),
+    )?;
+    if !output.status.success() {
+        return Err(format!(
+            "清理 {} 中的 {} 失败: {}",
+            env.name,
+            rel,
+            stderr_text(&output)
+        ));
+    }
+    Ok(())
+}
+
+/// 当前应用选中的、需经远程 shell 同步的环境
+fn active_remote(app: &AppType) -> Option<NamedEnvironment> {
+    let env = crate::wsl_env::active_environment(&crate::settings::get_settings(), app);
+    uses_mirror(&env).then_some(env)
+}
+
+/// 写入 live 前的准备：确认 WSL 发行版在运行，并将环境内的 live 文件拉取到本地镜像
+/// （选中环境直接读写文件系统时仅做运行检查）
+pub fn pull_live(app: &AppType) -> Result<(), String> {
+    crate::wsl_env::ensure_ready(app, true)?;
+    let Some(env) = active_remote(app) else {
+        return Ok(());
+    };
+    let home = mirror_home(&env);
+    for rel in live_files(app, &home)? {
+        pull_file(&env, &rel, &home.join(&rel))?;
+    }
+    Ok(())
+}
+
+/// 写入 live 后的收尾：将本地镜像推送到环境内；经 UNC 写入的 WSL 环境则修正文件权限
+pub fn push_live(app: &AppType) -> Result<(), String> {
+    let Some(env) = active_remote(app) else {
+        // 权限修正失败不影响切换结果
+        if let Err(e) = crate::wsl_env::fix_live_permissions(app, false) {
+            log::warn!("{}", e);
+        }
+        return Ok(());
+    };
+    let home = mirror_home(&env);
+    for rel in live_files(app, &home)? {
+        let local = home.join(&rel);
+        if !local.exists() {
+            continue;
+        }
+        let data = std::fs::read(&local)
+            .map_err(|e| format!("读取文件失败: {}: {}", local.display(), e))?;
+        push_file(&env, &rel, &data)?;
+    }
+    Ok(())
+}
+
+/// 在环境内执行 shell 命令并返回输出（本机环境不适用）
+pub fn run_shell(env: &NamedEnvironment, remote_cmd: &str) -> Result<std::process::Output, String> {
+    shell_command(env, remote_cmd)?
+        .output()
+        .map_err(|e| format!("启动远程命令失败: {}", e))
+}
+
+/// 环境内应用的主 live 配置文件是否存在
+pub fn live_exists(env: &NamedEnvironment, app: &AppType) -> Result<bool, String> {
+    let home = mirror_home(env);
+    match live_files(app, &home)?.first() {
+        Some(rel) => remote_exists(env, rel),
+        None => Ok(false),
+    }
+}
+
+/// 环境主目录下的相对路径是否存在
+pub fn remote_exists(env: &NamedEnvironment, rel: &str) -> Result<bool, String> {
+    let output = shell_command(
+        env,
+        &format!("[ -e {} ] || exit {}", remote_path(rel), MISSING_EXIT_CODE),
+    )?
+    .output()
+    .map_err(|e| format!("启动远程命令失败: {}", e))?;
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(MISSING_EXIT_CODE) => Ok(false),
+        _ => Err(format!("连接 {} 失败: {}", env.name, stderr_text(&output))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssh_destination_follows_option_terminator() {
+        let target = SshTarget {
+            host: "-oProxyCommand=evil".to_string(),
+            user: Some("dev".to_string()),
+            port: Some(2222),
+            key_path: None,
+        };
+        let cmd = ssh_command(&target, "true");
+        let args: Vec<String> = cmd
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+        let sep = args.iter().position(|a| a == "--").unwrap();
+        assert_eq!(args[sep + 1], "dev@-oProxyCommand=evil");
+        assert_eq!(args[sep + 2], "true");
+        assert!(args[..sep].iter().all(|a| !a.contains("ProxyCommand")));
+    }
+}
@@ -0,0 +1,252 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, Write};
+use std::sync::OnceLock;
+use tauri::Manager;
+
+use crate::app_config::AppType;
+use crate::command_args::{app_type_from_args, AppTarget};
+use crate::commands;
+use crate::error::AppError;
+use crate::store::AppState;
+
+/// 启用 JSON-RPC 模式的命令行参数
+pub const RPC_FLAG: &str = "--rpc";
+/// 启用 MCP 服务模式的命令行参数
+pub const MCP_FLAG: &str = "--mcp";
+
+// JSON-RPC 2.0 标准错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// 命令执行失败，data 为与前端一致的 `{ code, message, params }`
+const COMMAND_ERROR: i64 = -32000;
+
+/// 可调用的方法，与同名 Tauri 命令使用相同的实现
+const METHODS: &[&str] = &[
+    "rpc.methods",
+    "get_backend_info",
+    "get_all_status",
+    "get_providers",
+    "query_providers",
+    "get_current_provider",
+    "switch_provider",
+    "undo_switch",
+    "check_live_drift",
+    "get_interrupted_switches",
+    "list_tasks",
+    "cancel_task",
+];
+
+/// stdio 服务模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StdioMode {
+    /// `--rpc`：以命令名为方法的 JSON-RPC
+    Rpc,
+    /// `--mcp`：MCP 服务（工具调用）
+    Mcp,
+}
+
+/// 启动参数指定的 stdio 服务模式
+pub fn mode() -> Option<StdioMode> {
+    static MODE: OnceLock<Option<StdioMode>> = OnceLock::new();
+    *MODE.get_or_init(|| {
+        std::env::args().find_map(|a| match a.as_str() {
+            RPC_FLAG => Some(StdioMode::Rpc),
+            MCP_FLAG => Some(StdioMode::Mcp),
+            _ => None,
+        })
+    })
+}
+
+/// 是否以 `--rpc` / `--mcp` 启动：不显示窗口与托盘，仅通过 stdin/stdout 提供服务
+pub fn requested() -> bool {
+    mode().is_some()
+}
+
+pub(crate) struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    pub(crate) fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub(crate) fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+impl From<AppError> for RpcError {
+    fn from(e: AppError) -> Self {
+        Self {
+            code: COMMAND_ERROR,
+            message: e.detail(),
+            data: serde_json::to_value(&e).ok(),
+        }
+    }
+}
+
+/// 读取参数，依次尝试给出的键名（snake_case 与 camelCase）
+pub(crate) fn param<T: DeserializeOwned>(
+    params: &Map<String, Value>,
+    keys: &[&str],
+) -> Result<Option<T>, RpcError> {
+    for key in keys {
+        match params.get(*key) {
+            None | Some(Value::Null) => continue,
+            Some(value) => {
+                return serde_json::from_value(value.clone())
+                    .map(Some)
+                    .map_err(|e| RpcError::invalid_params(format!("参数 {} 无效: {}", key, e)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+pub(crate) fn required<T: DeserializeOwned>(
+    params: &Map<String, Value>,
+    keys: &[&str],
+) -> Result<T, RpcError> {
+    param(params, keys)?.ok_or_else(|| RpcError::invalid_params(format!("缺少参数 {}", keys[0])))
+}
+
+fn app_target(params: &Map<String, Value>) -> Result<AppTarget, RpcError> {
+    let app = app_type_from_args(params).map_err(RpcError::invalid_params)?;
+    Ok(app.unwrap_or(AppType::Claude).into())
+}
+
+fn to_value<T: Serialize>(result: Result<T, AppError>) -> Result<Value, RpcError> {
+    let value = result?;
+    serde_json::to_value(value)
+        .map_err(|e| RpcError::new(COMMAND_ERROR, format!("序列化结果失败: {}", e)))
+}
+
+async fn dispatch(
+    handle: &tauri::AppHandle,
+    method: &str,
+    params: &Map<String, Value>,
+) -> Result<Value, RpcError> {
+    let state = handle.state::<AppState>();
+    match method {
+        "rpc.methods" => Ok(json!(METHODS)),
+        "get_backend_info" => to_value(commands::get_backend_info(handle.clone()).await),
+        "get_all_status" => to_value(commands::get_all_status(state).await),
+        "get_providers" => to_value(commands::get_providers(state, app_target(params)?).await),
+        "query_providers" => {
+            let query = param(params, &["query"])?;
+            to_value(commands::query_providers(state, query).await)
+        }
+        "get_current_provider" => {
+            to_value(commands::get_current_provider(state, app_target(params)?).await)
+        }
+        "switch_provider" => {
+            let app = app_target(params)?;
+            let id = required(params, &["id"])?;
+            let drift_policy = param(params, &["drift_policy", "driftPolicy"])?;
+            to_value(commands::switch_provider(state, app, id, drift_policy.into()).await)
+        }
+        "undo_switch" => to_value(commands::undo_switch(state, app_target(params)?).await),
+        "check_live_drift" => {
+            to_value(commands::check_live_drift(state, app_target(params)?).await)
+        }
+        "get_interrupted_switches" => to_value(commands::get_interrupted_switches(state).await),
+        "list_tasks" => to_value(commands::list_tasks().await),
+        "cancel_task" => to_value(commands::cancel_task(required(params, &["id"])?).await),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("未知方法: {}", method),
+        )),
+    }
+}
+
+/// 处理一行请求；通知（无 id）不返回响应
+async fn handle_line(handle: &tauri::AppHandle, mode: StdioMode, line: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, format!("解析请求失败: {}", e)),
+            ))
+        }
+    };
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(|m| m.as_str()) else {
+        return Some(error_response(
+            id.unwrap_or(Value::Null),
+            RpcError::new(INVALID_REQUEST, "缺少 method"),
+        ));
+    };
+    let params = match request.get("params") {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(map)) => map.clone(),
+        Some(_) => {
+            return Some(error_response(
+                id.unwrap_or(Value::Null),
+                RpcError::invalid_params("params 必须为对象"),
+            ))
+        }
+    };
+
+    let result = match mode {
+        StdioMode::Rpc => dispatch(handle, method, &params).await,
+        StdioMode::Mcp => crate::mcp_server::dispatch(handle, method, &params).await,
+    };
+    let id = id?;
+    Some(match result {
+        Ok(value) => json!({ "jsonrpc": "2.0", "id": id, "result": value }),
+        Err(e) => error_response(id, e),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    let mut body = json!({ "code": error.code, "message": error.message });
+    if let Some(data) = error.data {
+        body["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": body })
+}
+
+/// 在后台线程中逐行读取 stdin 的请求并顺序执行；stdin 关闭后退出应用
+pub fn start(handle: tauri::AppHandle, mode: StdioMode) {
+    std::thread::spawn(move || {
+        log::info!("stdio 服务已启动: {:?}", mode);
+        let stdin = std::io::stdin();
+        for line in stdin.lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    log::error!("读取 JSON-RPC 请求失败: {}", e);
+                    break;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some(response) = tauri::async_runtime::block_on(handle_line(&handle, mode, &line))
+            else {
+                continue;
+            };
+            let mut stdout = std::io::stdout().lock();
+            if writeln!(stdout, "{}", response)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+        log::info!("stdio 输入已关闭，正在退出");
+        handle.exit(0);
+    });
+}
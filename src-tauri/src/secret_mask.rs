@@ -0,0 +1,70 @@
+use serde_json::Value;
+
+use crate::app_config::AppType;
+use crate::keychain::{is_ref, secret_fields};
+
+/// 掩码占位符
+const MASK: &str = "****";
+
+/// 对单个密钥做掩码：保留常见前缀（如 `sk-`）与末 4 位，例如 `sk-****abcd`
+pub fn mask(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return MASK.to_string();
+    }
+    let prefix: String = match secret.find('-') {
+        Some(idx) if idx > 0 && idx <= 4 => secret[..=idx].to_string(),
+        _ => String::new(),
+    };
+    let suffix: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}{}{}", prefix, MASK, suffix)
+}
+
+/// 拒绝写入仍为掩码的密钥（例如复制供应商时未显示原密钥）
+pub fn ensure_unmasked(app: &AppType, settings: &Value) -> Result<(), String> {
+    for (section, field) in secret_fields(app) {
+        let masked = settings
+            .get(*section)
+            .and_then(|s| s.get(*field))
+            .and_then(|v| v.as_str())
+            .map(|s| s.contains(MASK))
+            .unwrap_or(false);
+        if masked {
+            return Err(format!("{} 为掩码值，请重新填写密钥", field));
+        }
+    }
+    Ok(())
+}
+
+/// 返回掩码后的 settings（钥匙串引用本身不含密钥，原样保留）
+pub fn mask_settings(app: &AppType, settings: &Value) -> Value {
+    let mut masked = settings.clone();
+    for (section, field) in secret_fields(app) {
+        if let Some(slot) = masked.get_mut(*section).and_then(|s| s.get_mut(*field)) {
+            if let Some(secret) = slot.as_str() {
+                if !secret.is_empty() && !is_ref(secret) {
+                    *slot = Value::String(mask(secret));
+                }
+            }
+        }
+    }
+    masked
+}
+
+/// 前端回传的掩码值与已存密钥的掩码一致时，还原为已存值
+pub fn unmask_settings(app: &AppType, incoming: &mut Value, stored: &Value) {
+    for (section, field) in secret_fields(app) {
+        let Some(stored_secret) = stored
+            .get(*section)
+            .and_then(|s| s.get(*field))
+            .and_then(|v| v.as_str())
+        else {
+            continue;
+        };
+        if let Some(slot) = incoming.get_mut(*section).and_then(|s| s.get_mut(*field)) {
+            if slot.as_str() == Some(mask(stored_secret).as_str()) {
+                *slot = Value::String(stored_secret.to_string());
+            }
+        }
+    }
+}
@@ -2,7 +2,12 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::{OnceLock, RwLock};
+use std::sync::{Mutex, OnceLock, RwLock};
+use tauri::Emitter;
+
+use crate::error::{AppError, ResultExt};
+
+pub use cc_switch_core::LiveWriteMode;
 
 /// 自定义端点配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,14 +19,301 @@ pub struct CustomEndpoint {
     pub last_used: Option<i64>,
 }
 
+/// 同步冲突处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncConflictStrategy {
+    /// 以最后修改的一方为准
+    #[default]
+    LastWriterWins,
+    /// 交由用户选择保留哪一方
+    Prompt,
+}
+
+/// 应用（Claude Code / Codex）运行所在的环境
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TargetEnv {
+    /// 与 cc-switch 相同的系统环境
+    #[default]
+    Native,
+    /// Windows 下的 WSL 发行版
+    Wsl,
+    /// 通过 SSH 访问的远程主机
+    Ssh,
+}
+
+/// WSL 环境 live 文件的写入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum WslWriteMode {
+    /// 通过 `\\wsl.localhost\<发行版>` UNC 路径直接读写
+    #[default]
+    Unc,
+    /// 通过 `wsl.exe -d <发行版> sh -c` 在发行版内读写（发行版未运行时更稳定）
+    Exec,
+}
+
+/// SSH 远程环境的连接参数（使用系统 ssh 客户端，需已配置免密登录）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SshTarget {
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// 私钥路径，为空时使用 ssh 默认密钥与 ~/.ssh/config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+}
+
+/// 命名的目标环境（如 Windows、Ubuntu-22.04、Debian）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedEnvironment {
+    pub name: String,
+    #[serde(default)]
+    pub target_env: TargetEnv,
+    /// WSL 发行版名称（如 Ubuntu），仅 `wsl` 环境使用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wsl_distro: Option<String>,
+    /// WSL 环境的写入方式
+    #[serde(default)]
+    pub wsl_write_mode: WslWriteMode,
+    /// WSL 发行版未运行时，写入前自动启动
+    #[serde(default)]
+    pub wsl_auto_start: bool,
+    /// 经 UNC 写入后在发行版内修正 live 文件的所有者与权限（chown + chmod 600）
+    #[serde(default = "default_wsl_fix_permissions")]
+    pub wsl_fix_permissions: bool,
+    /// SSH 连接参数，仅 `ssh` 环境使用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<SshTarget>,
+}
+
+fn default_wsl_fix_permissions() -> bool {
+    true
+}
+
+/// 日志级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+/// 固定供应商快捷键（按列表顺序对应“切换到固定供应商 N”）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedShortcut {
+    pub app_type: crate::app_config::AppType,
+    pub provider_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accelerator: Option<String>,
+}
+
+/// 自定义托盘菜单项：打开网址（如中转站的账单页）或执行命令，二选一
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayLink {
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// 全局快捷键配置（如 `CmdOrCtrl+Shift+S`，为空表示未绑定）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutSettings {
+    /// 显示 / 隐藏主窗口
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toggle_window: Option<String>,
+    /// 打开快速切换面板
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quick_switcher: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<PinnedShortcut>,
+}
+
+/// 网络代理模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ProxyMode {
+    /// 使用系统代理（HTTP_PROXY / HTTPS_PROXY / ALL_PROXY 等环境变量）
+    #[default]
+    System,
+    /// 直连，不使用任何代理
+    None,
+    /// 使用下方指定的代理地址
+    Custom,
+}
+
+/// 后端网络请求（测速、余额与用量查询、同步等）的代理配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub mode: ProxyMode,
+    /// 代理地址，支持 http / https，例如 http://127.0.0.1:7890
+    #[serde(default)]
+    pub url: String,
+}
+
+/// 本机 HTTP 接口（仅监听 127.0.0.1，供 Raycast / Alfred / AutoHotkey 等集成调用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_http_api_port")]
+    pub port: u16,
+    /// 访问令牌，请求需携带 `Authorization: Bearer <token>`；启用时为空则自动生成
+    #[serde(default)]
+    pub token: String,
+}
+
+fn default_http_api_port() -> u16 {
+    17925
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_http_api_port(),
+            token: String::new(),
+        }
+    }
+}
+
+/// WebDAV 同步配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebDavSyncSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// WebDAV 根地址，例如 https://cloud.example.com/remote.php/dav/files/<user>
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// 客户端加密口令（远端仅保存密文）
+    #[serde(default)]
+    pub passphrase: String,
+    /// 远端文件路径（相对 url）
+    #[serde(default = "default_webdav_remote_path")]
+    pub remote_path: String,
+    /// 配置变更后自动同步
+    #[serde(default)]
+    pub auto_sync: bool,
+    #[serde(default)]
+    pub conflict_strategy: SyncConflictStrategy,
+}
+
+fn default_webdav_remote_path() -> String {
+    "cc-switch/config.json.enc".to_string()
+}
+
+impl Default for WebDavSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            passphrase: String::new(),
+            remote_path: default_webdav_remote_path(),
+            auto_sync: false,
+            conflict_strategy: SyncConflictStrategy::default(),
+        }
+    }
+}
+
+/// S3 兼容对象存储备份配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3BackupSettings {
+    /// 服务地址，例如 https://s3.amazonaws.com 或 https://<account>.r2.cloudflarestorage.com
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    #[serde(default)]
+    pub bucket: String,
+    #[serde(default)]
+    pub access_key_id: String,
+    #[serde(default)]
+    pub secret_access_key: String,
+    /// 对象键前缀
+    #[serde(default = "default_s3_prefix")]
+    pub prefix: String,
+    /// 客户端加密口令（桶内仅保存密文）
+    #[serde(default)]
+    pub passphrase: String,
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_s3_prefix() -> String {
+    "cc-switch".to_string()
+}
+
+impl Default for S3BackupSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            region: default_s3_region(),
+            bucket: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            prefix: default_s3_prefix(),
+            passphrase: String::new(),
+        }
+    }
+}
+
 /// 应用设置结构，允许覆盖默认配置目录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSettings {
-    #[serde(default = "default_show_in_tray")]
+    #[serde(default = "default_show_in_tray", alias = "showInDock")]
     pub show_in_tray: bool,
-    #[serde(default = "default_minimize_to_tray_on_close")]
+    /// 关闭主窗口时隐藏到托盘而非退出（兼容旧版 snake_case 字段名）
+    #[serde(
+        default = "default_minimize_to_tray_on_close",
+        alias = "minimize_to_tray_on_close"
+    )]
     pub minimize_to_tray_on_close: bool,
+    /// 托盘图标叠加当前供应商颜色徽标（官方为绿色）
+    #[serde(default = "default_show_tray_badge")]
+    pub show_tray_badge: bool,
+    /// macOS 菜单栏图标旁显示当前供应商名称
+    #[serde(default = "default_show_tray_title")]
+    pub show_tray_title: bool,
+    /// 切换结果、写入失败、备份完成等事件发送系统通知
+    #[serde(default = "default_notifications_enabled")]
+    pub notifications_enabled: bool,
+    /// 后台定期检查新版本
+    #[serde(default = "default_auto_check_updates")]
+    pub auto_check_updates: bool,
+    /// 后台检查更新的间隔（小时）
+    #[serde(default = "default_update_check_interval_hours")]
+    pub update_check_interval_hours: u64,
+    /// 登录系统时自动启动
+    #[serde(default)]
+    pub launch_at_login: bool,
     /// 是否启用 Claude 插件联动
     #[serde(default)]
     pub enable_claude_plugin_integration: bool,
@@ -37,27 +329,165 @@ pub struct AppSettings {
     /// Codex 自定义端点列表
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub custom_endpoints_codex: HashMap<String, CustomEndpoint>,
+    /// live 配置备份最多保留份数（0 表示不限制）
+    #[serde(default = "default_backup_max_count")]
+    pub backup_max_count: usize,
+    /// live 配置备份最长保留天数（0 表示不限制）
+    #[serde(default = "default_backup_max_age_days")]
+    pub backup_max_age_days: u64,
+    /// config.json 历史版本最多保留份数（0 表示不限制）
+    #[serde(default = "default_history_max_versions")]
+    pub history_max_versions: usize,
+    /// config.json 位于网盘同步目录时记录写入者信息（外部修改的合并始终启用）
+    #[serde(default)]
+    pub shared_folder_sync: bool,
+    /// 回收站中的供应商保留天数（0 表示不自动清理）
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u64,
+    /// 将供应商 API Key 存入系统钥匙串，config.json 仅保存引用
+    #[serde(default)]
+    pub use_keychain: bool,
+    /// 加密配置空闲自动锁定时间（分钟，0 表示不自动锁定）
+    #[serde(default = "default_store_auto_lock_minutes")]
+    pub store_auto_lock_minutes: u64,
+    /// live 配置读写失败时的尝试次数（含首次，用于 UNC / 网络路径上的瞬时错误）
+    #[serde(default = "default_io_retry_attempts")]
+    pub io_retry_attempts: u32,
+    /// 日志级别（写入 ~/.cc-switch/logs）
+    #[serde(default)]
+    pub log_level: LogLevel,
+    /// 全局快捷键
+    #[serde(default)]
+    pub shortcuts: ShortcutSettings,
+    /// 托盘菜单中的自定义链接与命令
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tray_links: Vec<TrayLink>,
+    /// 网络代理配置
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    /// Claude settings.json 的写入方式
+    #[serde(default)]
+    pub claude_live_mode: LiveWriteMode,
+    /// Codex config.toml 的写入方式
+    #[serde(default)]
+    pub codex_live_mode: LiveWriteMode,
+    /// 用户定义的目标环境（内置的本机环境 `native` 无需定义）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub environments: Vec<NamedEnvironment>,
+    /// Claude Code 使用的环境名称（为空表示本机）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_environment: Option<String>,
+    /// Codex 使用的环境名称（为空表示本机）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex_environment: Option<String>,
+    /// 切换 Claude 供应商时同步写入的镜像环境（如同时写入 Windows 与 WSL）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_mirror_environment: Option<String>,
+    /// 切换 Codex 供应商时同步写入的镜像环境
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex_mirror_environment: Option<String>,
+    /// WebDAV 同步配置
+    #[serde(default)]
+    pub webdav_sync: WebDavSyncSettings,
+    /// S3 兼容对象存储备份配置
+    #[serde(default)]
+    pub s3_backup: S3BackupSettings,
+    /// 本机 HTTP 接口
+    #[serde(default)]
+    pub http_api: HttpApiSettings,
 }
 
 fn default_show_in_tray() -> bool {
     true
 }
 
+fn default_show_tray_badge() -> bool {
+    true
+}
+
+fn default_show_tray_title() -> bool {
+    true
+}
+
+fn default_notifications_enabled() -> bool {
+    true
+}
+
+fn default_auto_check_updates() -> bool {
+    true
+}
+
+fn default_update_check_interval_hours() -> u64 {
+    24
+}
+
 fn default_minimize_to_tray_on_close() -> bool {
     true
 }
 
+fn default_backup_max_count() -> usize {
+    20
+}
+
+fn default_backup_max_age_days() -> u64 {
+    30
+}
+
+fn default_history_max_versions() -> usize {
+    50
+}
+
+fn default_trash_retention_days() -> u64 {
+    30
+}
+
+fn default_store_auto_lock_minutes() -> u64 {
+    15
+}
+
+fn default_io_retry_attempts() -> u32 {
+    3
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
             show_in_tray: true,
             minimize_to_tray_on_close: true,
+            show_tray_badge: default_show_tray_badge(),
+            show_tray_title: default_show_tray_title(),
+            auto_check_updates: default_auto_check_updates(),
+            update_check_interval_hours: default_update_check_interval_hours(),
+            launch_at_login: false,
+            notifications_enabled: default_notifications_enabled(),
             enable_claude_plugin_integration: false,
             claude_config_dir: None,
             codex_config_dir: None,
             language: None,
             custom_endpoints_claude: HashMap::new(),
             custom_endpoints_codex: HashMap::new(),
+            backup_max_count: default_backup_max_count(),
+            backup_max_age_days: default_backup_max_age_days(),
+            history_max_versions: default_history_max_versions(),
+            shared_folder_sync: false,
+            trash_retention_days: default_trash_retention_days(),
+            use_keychain: false,
+            store_auto_lock_minutes: default_store_auto_lock_minutes(),
+            io_retry_attempts: default_io_retry_attempts(),
+            log_level: LogLevel::default(),
+            shortcuts: ShortcutSettings::default(),
+            tray_links: Vec::new(),
+            proxy: ProxySettings::default(),
+            claude_live_mode: LiveWriteMode::default(),
+            codex_live_mode: LiveWriteMode::default(),
+            environments: Vec::new(),
+            claude_environment: None,
+            codex_environment: None,
+            claude_mirror_environment: None,
+            codex_mirror_environment: None,
+            webdav_sync: WebDavSyncSettings::default(),
+            s3_backup: S3BackupSettings::default(),
+            http_api: HttpApiSettings::default(),
         }
     }
 }
@@ -66,10 +496,7 @@ impl AppSettings {
     fn settings_path() -> PathBuf {
         // settings.json 必须使用固定路径，不能被 app_config_dir 覆盖
         // 否则会造成循环依赖：读取 settings 需要知道路径，但路径在 settings 中
-        dirs::home_dir()
-            .expect("无法获取用户主目录")
-            .join(".cc-switch")
-            .join("settings.json")
+        crate::config::state_root().join("settings.json")
     }
 
     fn normalize_paths(&mut self) {
@@ -95,44 +522,193 @@ impl AppSettings {
             .map(|s| s.to_string());
     }
 
+    /// 目录覆盖必须为绝对路径（允许 `~` 开头），避免随工作目录变化
+    fn validate_paths(&self) -> Result<(), String> {
+        for (label, dir) in [
+            ("Claude", &self.claude_config_dir),
+            ("Codex", &self.codex_config_dir),
+        ] {
+            if let Some(dir) = dir {
+                if !resolve_override_path(dir).is_absolute() {
+                    return Err(format!("{} 配置目录必须为绝对路径: {}", label, dir));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_live_modes(&self) -> Result<(), String> {
+        if self.claude_live_mode == LiveWriteMode::Profiles {
+            return Err("Claude 不支持 profile 写入方式".to_string());
+        }
+        Ok(())
+    }
+
+    fn backup_path() -> PathBuf {
+        let mut name = Self::settings_path().into_os_string();
+        name.push(".bak");
+        PathBuf::from(name)
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let mut settings =
+            serde_json::from_str::<AppSettings>(content).map_err(|e| e.to_string())?;
+        settings.normalize_paths();
+        Ok(settings)
+    }
+
     pub fn load() -> Self {
         let path = Self::settings_path();
-        if let Ok(content) = fs::read_to_string(&path) {
-            match serde_json::from_str::<AppSettings>(&content) {
-                Ok(mut settings) => {
-                    settings.normalize_paths();
-                    settings
-                }
-                Err(err) => {
-                    log::warn!(
-                        "解析设置文件失败，将使用默认设置。路径: {}, 错误: {}",
-                        path.display(),
-                        err
-                    );
-                    Self::default()
-                }
+        let mut health = SettingsHealth::new(SettingsLoadStatus::Loaded);
+        let settings = match fs::read_to_string(&path) {
+            Ok(content) => match Self::parse(&content) {
+                Ok(settings) => settings,
+                Err(err) => Self::recover(&path, &err, &mut health),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                // 读取失败（如权限问题）时不隔离、不覆盖原文件
+                log::warn!(
+                    "读取设置文件失败，将使用默认设置: {}: {}",
+                    path.display(),
+                    e
+                );
+                health.status = SettingsLoadStatus::Reset;
+                health.error = Some(e.to_string());
+                Self::default()
             }
-        } else {
-            Self::default()
+        };
+        if let Ok(mut guard) = settings_health_store().write() {
+            *guard = health;
         }
+        settings
     }
 
-    pub fn save(&self) -> Result<(), String> {
+    /// settings.json 解析失败：隔离损坏文件，优先从 settings.json.bak 恢复，否则使用默认设置
+    fn recover(path: &std::path::Path, error: &str, health: &mut SettingsHealth) -> Self {
+        log::warn!("解析设置文件失败: {}: {}", path.display(), error);
+        health.error = Some(error.to_string());
+
+        let mut quarantined = path.as_os_str().to_owned();
+        quarantined.push(format!(
+            ".corrupt.{}",
+            chrono::Utc::now().format("%Y%m%d_%H%M%S")
+        ));
+        match fs::rename(path, &quarantined) {
+            Ok(()) => {
+                health.quarantined_path =
+                    Some(PathBuf::from(quarantined).to_string_lossy().to_string())
+            }
+            Err(e) => log::warn!("隔离损坏的设置文件失败: {}", e),
+        }
+
+        let backup = Self::backup_path();
+        let restored = fs::read_to_string(&backup)
+            .map_err(|e| e.to_string())
+            .and_then(|content| Self::parse(&content));
+        let settings = match restored {
+            Ok(settings) => {
+                log::info!("已从备份恢复设置: {}", backup.display());
+                health.status = SettingsLoadStatus::Restored;
+                health.restored_from = Some(backup.to_string_lossy().to_string());
+                settings
+            }
+            Err(e) => {
+                log::warn!("设置备份不可用，使用默认设置: {}", e);
+                health.status = SettingsLoadStatus::Reset;
+                Self::default()
+            }
+        };
+        // 此时设置存储尚在初始化，不能调用 save()（原子写入会读取设置中的重试次数）
+        if health.status == SettingsLoadStatus::Restored {
+            if let Err(e) = fs::copy(&backup, path) {
+                log::warn!("写回恢复后的设置失败: {}", e);
+            }
+        }
+        settings
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
         let mut normalized = self.clone();
         normalized.normalize_paths();
         let path = Self::settings_path();
 
         if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|e| format!("创建设置目录失败: {}", e))?;
+            fs::create_dir_all(parent).map_err(|e| AppError::create_dir(parent, e))?;
         }
 
-        let json = serde_json::to_string_pretty(&normalized)
-            .map_err(|e| format!("序列化设置失败: {}", e))?;
-        fs::write(&path, json).map_err(|e| format!("写入设置失败: {}", e))?;
+        // 覆盖前将当前可解析的设置保存为 .bak，供损坏时恢复
+        if let Ok(current) = fs::read_to_string(&path) {
+            if Self::parse(&current).is_ok() {
+                if let Err(e) =
+                    crate::config::atomic_write(&Self::backup_path(), current.as_bytes())
+                {
+                    log::warn!("备份设置文件失败: {}", e);
+                }
+            }
+        }
+
+        let json = serde_json::to_string_pretty(&normalized).map_err(AppError::SerializeJson)?;
+        // 设置中包含 WebDAV / S3 凭据，使用原子写入并收紧权限
+        crate::config::atomic_write(&path, json.as_bytes()).context("写入设置失败")?;
         Ok(())
     }
 }
 
+/// settings.json 启动加载结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SettingsLoadStatus {
+    /// 正常加载（或首次运行使用默认设置）
+    Loaded,
+    /// 检测到损坏并已从 settings.json.bak 恢复
+    Restored,
+    /// 无法读取或已损坏且无可用备份，已重置为默认设置
+    Reset,
+}
+
+/// settings.json 加载/恢复报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsHealth {
+    pub status: SettingsLoadStatus,
+    pub settings_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<String>,
+    /// 损坏文件被隔离后的路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quarantined_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SettingsHealth {
+    fn new(status: SettingsLoadStatus) -> Self {
+        Self {
+            status,
+            settings_path: AppSettings::settings_path().to_string_lossy().to_string(),
+            restored_from: None,
+            quarantined_path: None,
+            error: None,
+        }
+    }
+}
+
+fn settings_health_store() -> &'static RwLock<SettingsHealth> {
+    static HEALTH: OnceLock<RwLock<SettingsHealth>> = OnceLock::new();
+    HEALTH.get_or_init(|| RwLock::new(SettingsHealth::new(SettingsLoadStatus::Loaded)))
+}
+
+/// 获取 settings.json 的加载/恢复报告
+pub fn get_settings_health() -> SettingsHealth {
+    // 确保设置已加载
+    let _ = settings_store();
+    settings_health_store()
+        .read()
+        .map(|g| g.clone())
+        .unwrap_or_else(|_| SettingsHealth::new(SettingsLoadStatus::Loaded))
+}
+
 fn settings_store() -> &'static RwLock<AppSettings> {
     static STORE: OnceLock<RwLock<AppSettings>> = OnceLock::new();
     STORE.get_or_init(|| RwLock::new(AppSettings::load()))
@@ -140,32 +716,80 @@ fn settings_store() -> &'static RwLock<AppSettings> {
 
 fn resolve_override_path(raw: &str) -> PathBuf {
     if raw == "~" {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::config::home_dir() {
             return home;
         }
     } else if let Some(stripped) = raw.strip_prefix("~/") {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::config::home_dir() {
             return home.join(stripped);
         }
     } else if let Some(stripped) = raw.strip_prefix("~\\") {
-        if let Some(home) = dirs::home_dir() {
-            return home.join(stripped);
+        // 反斜杠仅在 Windows 下是路径分隔符
+        if cfg!(windows) {
+            if let Some(home) = crate::config::home_dir() {
+                return home.join(stripped);
+            }
         }
     }
 
     PathBuf::from(raw)
 }
 
+/// settings.json 的固定路径
+pub fn get_settings_path() -> PathBuf {
+    AppSettings::settings_path()
+}
+
+/// 内存中的设置副本；settings.json 仅在启动时读取，之后只在更新时写入
 pub fn get_settings() -> AppSettings {
     settings_store().read().expect("读取设置锁失败").clone()
 }
 
-pub fn update_settings(mut new_settings: AppSettings) -> Result<(), String> {
+/// 串行化设置更新：读取-修改-保存需作为整体，避免并发命令互相覆盖
+fn update_lock() -> &'static Mutex<()> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| Mutex::new(()))
+}
+
+/// 以新设置整体替换；校验并落盘后更新内存，并发送 `settings-changed` 事件
+pub fn update_settings(new_settings: AppSettings) -> Result<(), AppError> {
+    let _updating = update_lock().lock().expect("获取设置更新锁失败");
+    apply_settings(new_settings)
+}
+
+/// 在当前设置上修改并保存；闭包返回错误时不做任何写入
+pub fn modify_settings<T>(
+    f: impl FnOnce(&mut AppSettings) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let _updating = update_lock().lock().expect("获取设置更新锁失败");
+    let mut settings = get_settings();
+    let result = f(&mut settings)?;
+    apply_settings(settings)?;
+    Ok(result)
+}
+
+fn apply_settings(mut new_settings: AppSettings) -> Result<(), AppError> {
     new_settings.normalize_paths();
+    new_settings.validate_paths()?;
+    new_settings.validate_live_modes()?;
+    crate::wsl_env::validate(&new_settings)?;
+    crate::shortcuts::validate(&new_settings.shortcuts)?;
+    crate::tray_links::validate(&new_settings.tray_links)?;
+    crate::http_api::validate(&new_settings.http_api)?;
+    // 启用钥匙串时同步 / 备份凭证只在 settings.json 中保存引用
+    crate::keychain::protect_settings(&mut new_settings)?;
     new_settings.save()?;
 
     let mut guard = settings_store().write().expect("写入设置锁失败");
-    *guard = new_settings;
+    *guard = new_settings.clone();
+    drop(guard);
+
+    if let Some(handle) = crate::app_store::get_app_handle() {
+        if let Err(e) = handle.emit("settings-changed", &new_settings) {
+            log::warn!("发送设置变更事件失败: {}", e);
+        }
+    }
+    crate::events::publish(crate::events::StateEvent::SettingsChanged);
     Ok(())
 }
 
@@ -177,6 +801,15 @@ pub fn get_claude_override_dir() -> Option<PathBuf> {
         .map(|p| resolve_override_path(p))
 }
 
+/// 文件读写的尝试次数（至少 1 次）
+pub fn get_io_retry_attempts() -> u32 {
+    settings_store()
+        .read()
+        .map(|s| s.io_retry_attempts)
+        .unwrap_or_else(|_| default_io_retry_attempts())
+        .max(1)
+}
+
 pub fn get_codex_override_dir() -> Option<PathBuf> {
     let settings = settings_store().read().ok()?;
     settings
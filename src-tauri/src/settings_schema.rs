@@ -0,0 +1,149 @@
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// Claude Code settings.json 的 JSON Schema（随应用发布）
+const CLAUDE_SETTINGS_SCHEMA: &str = include_str!("../schemas/claude-settings.schema.json");
+
+/// 单条校验错误
+#[derive(Debug, Clone)]
+pub struct SchemaError {
+    /// 字段路径，例如 `env.ANTHROPIC_BASE_URL`（根为 `$`）
+    pub path: String,
+    pub message: String,
+}
+
+fn claude_schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        serde_json::from_str(CLAUDE_SETTINGS_SCHEMA).expect("内置 Claude settings schema 无效")
+    })
+}
+
+fn join_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(value: &Value, expected: &str) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+/// 按 JSON Schema 子集（type/enum/properties/additionalProperties/required/items/minimum）校验
+fn validate_node(schema: &Value, value: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+    let at = |p: &str| {
+        if p.is_empty() {
+            "$".to_string()
+        } else {
+            p.to_string()
+        }
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|t| matches_type(value, t)) {
+            errors.push(SchemaError {
+                path: at(path),
+                message: format!("应为 {}，实际为 {}", types.join(" 或 "), type_name(value)),
+            });
+            return;
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            let allowed: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+            errors.push(SchemaError {
+                path: at(path),
+                message: format!("取值必须是 {} 之一", allowed.join(", ")),
+            });
+        }
+    }
+
+    if let (Some(min), Some(n)) = (
+        schema.get("minimum").and_then(|m| m.as_f64()),
+        value.as_f64(),
+    ) {
+        if n < min {
+            errors.push(SchemaError {
+                path: at(path),
+                message: format!("不能小于 {}", min),
+            });
+        }
+    }
+
+    if let Value::Object(map) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                if !map.contains_key(key) {
+                    errors.push(SchemaError {
+                        path: join_path(path, key),
+                        message: "缺少必填字段".to_string(),
+                    });
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        for (key, child) in map {
+            let child_path = join_path(path, key);
+            match properties.and_then(|p| p.get(key)) {
+                Some(child_schema) => validate_node(child_schema, child, &child_path, errors),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => errors.push(SchemaError {
+                        path: child_path,
+                        message: "不支持的字段".to_string(),
+                    }),
+                    Some(extra @ Value::Object(_)) => {
+                        validate_node(extra, child, &child_path, errors)
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (idx, item) in items.iter().enumerate() {
+            validate_node(item_schema, item, &format!("{}[{}]", at(path), idx), errors);
+        }
+    }
+}
+
+/// 校验 Claude settings.json 内容，返回全部错误
+pub fn validate_claude_settings(value: &Value) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    validate_node(claude_schema(), value, "", &mut errors);
+    errors
+}
+
+/// 校验 Claude settings.json 内容，失败时返回带字段路径的错误描述
+pub fn ensure_valid_claude_settings(value: &Value) -> Result<(), String> {
+    let errors = validate_claude_settings(value);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let details: Vec<String> = errors
+        .iter()
+        .map(|e| format!("{}: {}", e.path, e.message))
+        .collect();
+    Err(format!("Claude 配置校验失败: {}", details.join("; ")))
+}
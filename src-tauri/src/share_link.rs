@@ -0,0 +1,140 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{Read, Write};
+
+use crate::app_config::AppType;
+use crate::provider::Provider;
+
+const SHARE_VERSION: u32 = 1;
+const SHARE_URL_PREFIX: &str = "ccswitch://provider?data=";
+/// 解码后 JSON 的大小上限，防止恶意载荷解压膨胀
+const MAX_DECODED_BYTES: u64 = 256 * 1024;
+
+/// 分享载荷（序列化为 JSON 后 deflate 压缩并 base64url 编码）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharePayload {
+    pub v: u32,
+    pub app: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub settings: Value,
+}
+
+/// 通过 ccswitch://provider 链接收到的分享，交由前端确认后以 `payload` 调用 import_provider_share
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedShare {
+    pub payload: String,
+    pub app: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// 分享链接
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLink {
+    /// 可直接点击/复制的链接
+    pub url: String,
+    /// 适合放入二维码的紧凑载荷（base64url）
+    pub payload: String,
+    /// 是否包含密钥
+    pub include_key: bool,
+}
+
+/// 将敏感字段的字符串值清空（保留字段本身，便于接收方填写）
+fn strip_secrets(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| {
+                    let v = match v {
+                        Value::String(_) if crate::audit::is_secret_key(k) => {
+                            Value::String(String::new())
+                        }
+                        _ => strip_secrets(v),
+                    };
+                    (k.clone(), v)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(strip_secrets).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// 编码供应商为分享链接；include_key 为 false 时移除密钥
+pub fn encode(app: &AppType, provider: &Provider, include_key: bool) -> Result<ShareLink, String> {
+    let settings = if include_key {
+        provider.settings_config.clone()
+    } else {
+        strip_secrets(&provider.settings_config)
+    };
+    let payload = SharePayload {
+        v: SHARE_VERSION,
+        app: app.as_str().to_string(),
+        name: provider.name.clone(),
+        url: provider.website_url.clone(),
+        settings,
+    };
+    let json = serde_json::to_vec(&payload).map_err(|e| format!("序列化分享数据失败: {}", e))?;
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(&json)
+        .map_err(|e| format!("压缩分享数据失败: {}", e))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| format!("压缩分享数据失败: {}", e))?;
+
+    let encoded = BASE64_URL.encode(compressed);
+    Ok(ShareLink {
+        url: format!("{}{}", SHARE_URL_PREFIX, encoded),
+        payload: encoded,
+        include_key,
+    })
+}
+
+/// 解码分享链接或载荷
+pub fn decode(input: &str) -> Result<SharePayload, String> {
+    let input = input.trim();
+    let encoded = input
+        .strip_prefix(SHARE_URL_PREFIX)
+        .or_else(|| input.split("data=").nth(1))
+        .unwrap_or(input)
+        .split('&')
+        .next()
+        .unwrap_or_default();
+    let compressed = BASE64_URL
+        .decode(encoded.trim_end_matches('='))
+        .map_err(|_| "分享链接格式错误".to_string())?;
+
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_DECODED_BYTES + 1)
+        .read_to_end(&mut json)
+        .map_err(|_| "分享链接数据已损坏".to_string())?;
+    if json.len() as u64 > MAX_DECODED_BYTES {
+        return Err("分享数据过大".to_string());
+    }
+
+    let payload: SharePayload =
+        serde_json::from_slice(&json).map_err(|e| format!("解析分享数据失败: {}", e))?;
+    if payload.v > SHARE_VERSION {
+        return Err(format!(
+            "分享链接版本 v{} 高于当前支持的 v{}，请升级应用",
+            payload.v, SHARE_VERSION
+        ));
+    }
+    if payload.app != "claude" && payload.app != "codex" {
+        return Err(format!("不支持的应用类型: {}", payload.app));
+    }
+    Ok(payload)
+}
@@ -0,0 +1,365 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::app_config::MultiAppConfig;
+use crate::config::{get_app_config_path, read_json_file, write_json_file};
+use crate::webdav_sync::ConflictChoice;
+
+/// 上次由本实例读取/写入的 config.json 状态（写入围栏的依据）
+struct FenceState {
+    modified: Option<SystemTime>,
+    hash: String,
+    base: Value,
+}
+
+/// 写入者信息（config.json 旁的 config.json.writer 文件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriterInfo {
+    pub instance_id: String,
+    pub written_at: i64,
+    pub hash: String,
+}
+
+/// 未解决的共享目录冲突
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedConflict {
+    /// 冲突的条目路径，例如 `claude.providers.<id>`
+    pub paths: Vec<String>,
+    /// 另一端的写入者（若可识别）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_writer: Option<WriterInfo>,
+}
+
+fn fence() -> &'static Mutex<Option<FenceState>> {
+    static FENCE: OnceLock<Mutex<Option<FenceState>>> = OnceLock::new();
+    FENCE.get_or_init(|| Mutex::new(None))
+}
+
+fn pending() -> &'static Mutex<Option<SharedConflict>> {
+    static PENDING: OnceLock<Mutex<Option<SharedConflict>>> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(None))
+}
+
+fn writer_path() -> PathBuf {
+    let mut path = get_app_config_path().into_os_string();
+    path.push(".writer");
+    PathBuf::from(path)
+}
+
+fn enabled() -> bool {
+    crate::settings::get_settings().shared_folder_sync
+}
+
+fn file_modified() -> Option<SystemTime> {
+    fs::metadata(get_app_config_path())
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+/// 记录当前磁盘上的 config.json 作为后续写入的基线（加载后与写入后调用）
+pub fn remember_disk() {
+    let path = get_app_config_path();
+    let Ok(bytes) = fs::read(&path) else {
+        return;
+    };
+    let base = crate::store_crypto::decode_content(&String::from_utf8_lossy(&bytes))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or(Value::Null);
+    if let Ok(mut guard) = fence().lock() {
+        *guard = Some(FenceState {
+            modified: file_modified(),
+            hash: crate::crypto::sha256_hex(&bytes),
+            base,
+        });
+    }
+}
+
+/// 写入完成后更新基线与写入者信息
+pub fn after_write() {
+    remember_disk();
+    if !enabled() {
+        return;
+    }
+    let hash = fence()
+        .lock()
+        .ok()
+        .and_then(|g| g.as_ref().map(|s| s.hash.clone()))
+        .unwrap_or_default();
+    let info = WriterInfo {
+        instance_id: crate::app_store::get_instance_id(),
+        written_at: Utc::now().timestamp_millis(),
+        hash,
+    };
+    if let Err(e) = write_json_file(&writer_path(), &info) {
+        log::warn!("写入 config.json 写入者信息失败: {}", e);
+    }
+}
+
+/// config.json 是否在本实例上次读取/写入之后被外部修改（手动编辑、同步客户端或其他实例）
+pub fn disk_changed() -> bool {
+    let path = get_app_config_path();
+    let Ok(guard) = fence().lock() else {
+        return false;
+    };
+    let Some(state) = guard.as_ref() else {
+        return path.exists();
+    };
+    if state.modified.is_some() && state.modified == file_modified() {
+        return false;
+    }
+    fs::read(&path)
+        .map(|bytes| crate::crypto::sha256_hex(&bytes) != state.hash)
+        .unwrap_or(false)
+}
+
+/// 获取未解决的共享目录冲突
+pub fn pending_conflict() -> Option<SharedConflict> {
+    pending().lock().ok().and_then(|g| g.clone())
+}
+
+/// 写入前检查 config.json 是否已被外部修改（共享目录中的其他设备、手动编辑等）
+///
+/// - 未修改：返回 Ok(None)，直接写入
+/// - 已修改且可自动合并（双方改动了不同的供应商）：返回合并后的配置
+/// - 同一条目双方均有改动：`keep` 为 None 时记录冲突并返回错误，否则按选择保留一方
+pub fn reconcile(
+    local: &MultiAppConfig,
+    keep: Option<ConflictChoice>,
+) -> Result<Option<MultiAppConfig>, String> {
+    let path = get_app_config_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let base = {
+        let guard = fence().lock().map_err(|e| format!("获取锁失败: {}", e))?;
+        let Some(state) = guard.as_ref() else {
+            return Ok(None);
+        };
+        // mtime 未变则视为未被外部修改，避免每次都计算摘要
+        if state.modified.is_some() && state.modified == file_modified() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+        if crate::crypto::sha256_hex(&bytes) == state.hash {
+            return Ok(None);
+        }
+        state.base.clone()
+    };
+
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let (theirs, _) = MultiAppConfig::parse_with_migration(&content)?;
+    let theirs = serde_json::to_value(&theirs).map_err(|e| format!("序列化配置失败: {}", e))?;
+    let ours = serde_json::to_value(local).map_err(|e| format!("序列化配置失败: {}", e))?;
+
+    let mut conflicts = Vec::new();
+    let merged = merge3(
+        &mut Vec::new(),
+        Some(&base),
+        Some(&ours),
+        Some(&theirs),
+        keep,
+        &mut conflicts,
+    )
+    .unwrap_or(Value::Null);
+
+    if !conflicts.is_empty() && keep.is_none() {
+        let remote_writer = read_json_file::<WriterInfo>(&writer_path()).ok();
+        let conflict = SharedConflict {
+            paths: conflicts,
+            remote_writer,
+        };
+        let msg = format!(
+            "config.json 已被外部修改，以下条目存在冲突: {}",
+            conflict.paths.join(", ")
+        );
+        if let Ok(mut guard) = pending().lock() {
+            *guard = Some(conflict);
+        }
+        return Err(msg);
+    }
+
+    if let Ok(mut guard) = pending().lock() {
+        *guard = None;
+    }
+    let merged: MultiAppConfig =
+        serde_json::from_value(merged).map_err(|e| format!("解析合并后的配置失败: {}", e))?;
+    log::info!("已合并 config.json 的外部修改");
+    Ok(Some(merged))
+}
+
+/// 是否继续按键逐项合并（供应商、MCP 服务器为最小合并单元）
+fn is_container(path: &[String]) -> bool {
+    match path.len() {
+        0 | 1 => true,
+        2 => path[1] == "providers" || path[0] == "mcp",
+        3 => path[0] == "mcp" && path[2] == "servers",
+        _ => false,
+    }
+}
+
+/// 三方合并；返回 None 表示该键应被删除
+fn merge3(
+    path: &mut Vec<String>,
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    keep: Option<ConflictChoice>,
+    conflicts: &mut Vec<String>,
+) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+
+    if let (Some(Value::Object(o)), Some(Value::Object(t))) = (ours, theirs) {
+        if is_container(path) {
+            let b = base.and_then(|v| v.as_object());
+            let mut keys: Vec<&String> = o.keys().chain(t.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            let mut out = Map::new();
+            for key in keys {
+                path.push(key.clone());
+                let merged = merge3(
+                    path,
+                    b.and_then(|m| m.get(key)),
+                    o.get(key),
+                    t.get(key),
+                    keep,
+                    conflicts,
+                );
+                path.pop();
+                if let Some(v) = merged {
+                    out.insert(key.clone(), v);
+                }
+            }
+            return Some(Value::Object(out));
+        }
+    }
+
+    // 当前供应商指针双方都改动时，以本机的选择为准
+    if path.len() == 2 && path[1] == "current" {
+        return ours.cloned();
+    }
+
+    conflicts.push(path.join("."));
+    match keep {
+        Some(ConflictChoice::Remote) => theirs.cloned(),
+        _ => ours.cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn merge(
+        base: &Value,
+        ours: &Value,
+        theirs: &Value,
+        keep: Option<ConflictChoice>,
+    ) -> (Option<Value>, Vec<String>) {
+        let mut conflicts = Vec::new();
+        let merged = merge3(
+            &mut Vec::new(),
+            Some(base),
+            Some(ours),
+            Some(theirs),
+            keep,
+            &mut conflicts,
+        );
+        (merged, conflicts)
+    }
+
+    #[test]
+    fn takes_the_side_that_changed() {
+        let base = json!({ "claude": { "providers": { "a": { "name": "A" } }, "current": "a" } });
+        let ours = base.clone();
+        let mut theirs = base.clone();
+        theirs["claude"]["providers"]["a"]["name"] = json!("A2");
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs, None);
+        assert_eq!(merged, Some(theirs.clone()));
+        assert!(conflicts.is_empty());
+
+        let (merged, conflicts) = merge(&base, &theirs, &ours, None);
+        assert_eq!(merged, Some(theirs));
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn merges_edits_to_different_providers() {
+        let base = json!({ "claude": { "providers": { "a": { "v": 1 }, "b": { "v": 1 } } } });
+        let mut ours = base.clone();
+        ours["claude"]["providers"]["a"]["v"] = json!(2);
+        let mut theirs = base.clone();
+        theirs["claude"]["providers"]["b"]["v"] = json!(3);
+        theirs["claude"]["providers"]["c"] = json!({ "v": 1 });
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs, None);
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            merged,
+            Some(json!({ "claude": { "providers": {
+                "a": { "v": 2 },
+                "b": { "v": 3 },
+                "c": { "v": 1 },
+            } } }))
+        );
+    }
+
+    #[test]
+    fn deletion_on_one_side_is_kept() {
+        let base = json!({ "claude": { "providers": { "a": { "v": 1 }, "b": { "v": 1 } } } });
+        let mut theirs = base.clone();
+        theirs["claude"]["providers"]
+            .as_object_mut()
+            .unwrap()
+            .remove("b");
+
+        let (merged, conflicts) = merge(&base, &base, &theirs, None);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, Some(theirs));
+    }
+
+    #[test]
+    fn same_provider_edited_on_both_sides_conflicts() {
+        let base = json!({ "claude": { "providers": { "a": { "v": 1 } } } });
+        let mut ours = base.clone();
+        ours["claude"]["providers"]["a"]["v"] = json!(2);
+        let mut theirs = base.clone();
+        theirs["claude"]["providers"]["a"]["v"] = json!(3);
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs, None);
+        assert_eq!(conflicts, vec!["claude.providers.a".to_string()]);
+        assert_eq!(merged, Some(ours.clone()));
+
+        let (merged, _) = merge(&base, &ours, &theirs, Some(ConflictChoice::Remote));
+        assert_eq!(merged, Some(theirs));
+    }
+
+    #[test]
+    fn current_pointer_prefers_local_choice() {
+        let base = json!({ "claude": { "providers": {}, "current": "a" } });
+        let mut ours = base.clone();
+        ours["claude"]["current"] = json!("b");
+        let mut theirs = base.clone();
+        theirs["claude"]["current"] = json!("c");
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs, Some(ConflictChoice::Remote));
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, Some(ours));
+    }
+}
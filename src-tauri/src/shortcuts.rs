@@ -0,0 +1,207 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{Emitter, Manager};
+
+use crate::settings::ShortcutSettings;
+
+/// 快捷键可触发的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    ToggleWindow,
+    QuickSwitcher,
+    /// 切换到第 N 个固定供应商（从 1 开始）
+    SwitchPinned(usize),
+}
+
+impl ShortcutAction {
+    /// 动作标识，同时用于命令行参数 `--shortcut <动作>`
+    pub fn id(&self) -> String {
+        match self {
+            Self::ToggleWindow => "toggle-window".to_string(),
+            Self::QuickSwitcher => "quick-switcher".to_string(),
+            Self::SwitchPinned(n) => format!("pinned-{}", n),
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim() {
+            "toggle-window" => Some(Self::ToggleWindow),
+            "quick-switcher" => Some(Self::QuickSwitcher),
+            other => other
+                .strip_prefix("pinned-")
+                .and_then(|n| n.parse().ok())
+                .filter(|n| *n > 0)
+                .map(Self::SwitchPinned),
+        }
+    }
+}
+
+/// 多个动作绑定了同一组合键
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutConflict {
+    pub accelerator: String,
+    pub actions: Vec<String>,
+}
+
+const MODIFIERS: [(&str, &str); 9] = [
+    ("cmdorctrl", "CmdOrCtrl"),
+    ("commandorcontrol", "CmdOrCtrl"),
+    ("ctrl", "Ctrl"),
+    ("control", "Ctrl"),
+    ("alt", "Alt"),
+    ("option", "Alt"),
+    ("shift", "Shift"),
+    ("super", "Super"),
+    ("cmd", "Super"),
+];
+
+/// 规范化组合键写法（修饰键排序、大小写统一），便于比较冲突
+pub fn normalize_accelerator(raw: &str) -> Result<String, String> {
+    let parts: Vec<&str> = raw.split('+').map(str::trim).collect();
+    let (key, modifiers) = parts
+        .split_last()
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("快捷键格式无效: {}", raw))?;
+
+    let mut names = Vec::new();
+    for m in modifiers {
+        let name = MODIFIERS
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(m))
+            .map(|(_, name)| *name)
+            .ok_or_else(|| format!("快捷键修饰键无效: {}", m))?;
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        return Err(format!("全局快捷键至少需要一个修饰键: {}", raw));
+    }
+    let order = ["CmdOrCtrl", "Ctrl", "Alt", "Shift", "Super"];
+    names.sort_by_key(|n| order.iter().position(|o| o == n));
+
+    let key = if key.chars().count() == 1 {
+        key.to_uppercase()
+    } else {
+        let mut chars = key.chars();
+        chars
+            .next()
+            .map(|c| {
+                c.to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    Ok(format!("{}+{}", names.join("+"), key))
+}
+
+fn bindings(shortcuts: &ShortcutSettings) -> Vec<(ShortcutAction, &str)> {
+    let mut list = Vec::new();
+    if let Some(acc) = shortcuts.toggle_window.as_deref() {
+        list.push((ShortcutAction::ToggleWindow, acc));
+    }
+    if let Some(acc) = shortcuts.quick_switcher.as_deref() {
+        list.push((ShortcutAction::QuickSwitcher, acc));
+    }
+    for (i, pinned) in shortcuts.pinned.iter().enumerate() {
+        if let Some(acc) = pinned.accelerator.as_deref() {
+            list.push((ShortcutAction::SwitchPinned(i + 1), acc));
+        }
+    }
+    list.retain(|(_, acc)| !acc.trim().is_empty());
+    list
+}
+
+/// 检查快捷键格式并找出冲突的绑定
+pub fn find_conflicts(shortcuts: &ShortcutSettings) -> Result<Vec<ShortcutConflict>, String> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (action, acc) in bindings(shortcuts) {
+        groups
+            .entry(normalize_accelerator(acc)?)
+            .or_default()
+            .push(action.id());
+    }
+    Ok(groups
+        .into_iter()
+        .filter(|(_, actions)| actions.len() > 1)
+        .map(|(accelerator, actions)| ShortcutConflict {
+            accelerator,
+            actions,
+        })
+        .collect())
+}
+
+/// 保存设置前校验：格式无效或存在冲突时拒绝
+pub fn validate(shortcuts: &ShortcutSettings) -> Result<(), String> {
+    let conflicts = find_conflicts(shortcuts)?;
+    if let Some(c) = conflicts.first() {
+        return Err(format!(
+            "快捷键 {} 同时绑定了多个动作: {}",
+            c.accelerator,
+            c.actions.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// 执行快捷键动作
+pub fn run(app: &tauri::AppHandle, action: ShortcutAction) {
+    match action {
+        ShortcutAction::ToggleWindow => {
+            let visible = app
+                .get_webview_window("main")
+                .and_then(|w| w.is_visible().ok())
+                .unwrap_or(false);
+            if visible {
+                crate::hide_main_window(app);
+            } else {
+                crate::show_main_window(app);
+            }
+        }
+        ShortcutAction::QuickSwitcher => {
+            crate::show_main_window(app);
+            if let Err(e) = app.emit("open-quick-switcher", ()) {
+                log::error!("发射快速切换事件失败: {}", e);
+            }
+        }
+        ShortcutAction::SwitchPinned(n) => {
+            let Some(pinned) = crate::settings::get_settings()
+                .shortcuts
+                .pinned
+                .get(n - 1)
+                .cloned()
+            else {
+                log::warn!("未配置第 {} 个固定供应商", n);
+                return;
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) =
+                    crate::switch_provider_internal(&app, pinned.app_type, pinned.provider_id).await
+                {
+                    log::error!("切换固定供应商失败: {}", e);
+                    crate::notify::send("供应商切换失败", &e);
+                }
+            });
+        }
+    }
+}
+
+/// 处理命令行中的 `--shortcut <动作>`（由已运行实例接收），
+/// 便于在系统或第三方工具中把全局热键绑定到该命令
+pub fn handle_args(app: &tauri::AppHandle, args: &[String]) -> bool {
+    let Some(raw) = args
+        .iter()
+        .position(|a| a == "--shortcut")
+        .and_then(|i| args.get(i + 1))
+    else {
+        return false;
+    };
+    match ShortcutAction::parse(raw) {
+        Some(action) => run(app, action),
+        None => log::warn!("未知的快捷键动作: {}", raw),
+    }
+    true
+}
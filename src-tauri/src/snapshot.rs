@@ -0,0 +1,144 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::app_config::AppType;
+use crate::config::{get_app_config_dir, read_json_file, write_json_file};
+
+/// 每个应用最多保留的切换快照数
+const MAX_SNAPSHOTS_PER_APP: usize = 10;
+
+/// 快照中的单个 live 文件（content 为 None 表示切换前文件不存在）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFile {
+    pub name: String,
+    pub content: Option<String>,
+}
+
+/// 切换前快照：live 文件内容 + 当前供应商指针
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchSnapshot {
+    pub id: String,
+    pub app_type: String,
+    pub created_at: i64,
+    /// 切换前的当前供应商 ID
+    pub previous_current: String,
+    /// 切换的目标供应商 ID
+    pub target_id: String,
+    pub files: Vec<SnapshotFile>,
+}
+
+fn app_snapshot_dir(app: &AppType) -> PathBuf {
+    get_app_config_dir().join("snapshots").join(app.as_str())
+}
+
+/// 列出快照文件路径（按时间倒序）
+fn snapshot_paths(app: &AppType) -> Vec<PathBuf> {
+    let dir = app_snapshot_dir(app);
+    let mut paths: Vec<PathBuf> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+            .collect(),
+        Err(_) => return vec![],
+    };
+    // 文件名由时间戳构成，字典序即时间顺序
+    paths.sort();
+    paths.reverse();
+    paths
+}
+
+/// 切换前记录快照，并仅保留最近 10 份
+pub fn take_snapshot(
+    app: &AppType,
+    previous_current: &str,
+    target_id: &str,
+) -> Result<String, String> {
+    let mut files = Vec::new();
+    for path in crate::backup::live_files(app) {
+        let name = path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".into());
+        let content = if path.exists() {
+            Some(
+                fs::read_to_string(&path)
+                    .map_err(|e| format!("读取 live 配置失败: {}: {}", path.display(), e))?,
+            )
+        } else {
+            None
+        };
+        files.push(SnapshotFile { name, content });
+    }
+
+    let now = Utc::now();
+    let dir = app_snapshot_dir(app);
+    let base_id = now.format("%Y%m%d_%H%M%S_%3f").to_string();
+    let mut id = base_id.clone();
+    let mut suffix = 2;
+    while dir.join(format!("{}.json", id)).exists() {
+        id = format!("{}-{}", base_id, suffix);
+        suffix += 1;
+    }
+
+    let snapshot = SwitchSnapshot {
+        id: id.clone(),
+        app_type: app.as_str().to_string(),
+        created_at: now.timestamp_millis(),
+        previous_current: previous_current.to_string(),
+        target_id: target_id.to_string(),
+        files,
+    };
+    write_json_file(&dir.join(format!("{}.json", id)), &snapshot)?;
+
+    for old in snapshot_paths(app).into_iter().skip(MAX_SNAPSHOTS_PER_APP) {
+        if let Err(e) = fs::remove_file(&old) {
+            log::warn!("删除旧快照失败: {}: {}", old.display(), e);
+        }
+    }
+
+    Ok(id)
+}
+
+/// 获取最近一次快照
+pub fn latest_snapshot(app: &AppType) -> Option<SwitchSnapshot> {
+    snapshot_paths(app)
+        .into_iter()
+        .find_map(|p| read_json_file::<SwitchSnapshot>(&p).ok())
+}
+
+/// 按 ID 读取快照
+pub fn find_snapshot(app: &AppType, id: &str) -> Option<SwitchSnapshot> {
+    if id.contains(['/', '\\']) || id.contains("..") {
+        return None;
+    }
+    read_json_file::<SwitchSnapshot>(&app_snapshot_dir(app).join(format!("{}.json", id))).ok()
+}
+
+/// 将快照中的 live 文件写回；任一文件写入失败则回滚已写入的文件
+pub fn restore_live_files(app: &AppType, snapshot: &SwitchSnapshot) -> Result<(), String> {
+    let files: Vec<(PathBuf, Option<Vec<u8>>)> = snapshot
+        .files
+        .iter()
+        .map(|file| {
+            (
+                crate::backup::live_target_for(app, &file.name),
+                file.content.as_ref().map(|c| c.as_bytes().to_vec()),
+            )
+        })
+        .collect();
+    crate::config::write_files_atomically(&files).map_err(Into::into)
+}
+
+/// 删除指定快照（撤销成功后调用）
+pub fn remove_snapshot(app: &AppType, id: &str) -> Result<(), String> {
+    let path = app_snapshot_dir(app).join(format!("{}.json", id));
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("删除快照失败: {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
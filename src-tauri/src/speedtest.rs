@@ -15,8 +15,8 @@ pub struct EndpointLatency {
     pub error: Option<String>,
 }
 
-fn build_client(timeout_secs: u64) -> Result<Client, String> {
-    Client::builder()
+pub(crate) fn build_client(timeout_secs: u64) -> Result<Client, String> {
+    crate::http_client::builder()?
         .timeout(Duration::from_secs(timeout_secs))
         .redirect(reqwest::redirect::Policy::limited(5))
         .user_agent("cc-switch-speedtest/1.0")
@@ -24,7 +24,7 @@ fn build_client(timeout_secs: u64) -> Result<Client, String> {
         .map_err(|e| format!("创建 HTTP 客户端失败: {e}"))
 }
 
-fn sanitize_timeout(timeout_secs: Option<u64>) -> u64 {
+pub(crate) fn sanitize_timeout(timeout_secs: Option<u64>) -> u64 {
     let secs = timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
     secs.clamp(MIN_TIMEOUT_SECS, MAX_TIMEOUT_SECS)
 }
@@ -42,65 +42,68 @@ pub async fn test_endpoints(
 
     let tasks = urls.into_iter().map(|raw_url| {
         let client = client.clone();
-        async move {
-            let trimmed = raw_url.trim().to_string();
-            if trimmed.is_empty() {
-                return EndpointLatency {
-                    url: raw_url,
-                    latency: None,
-                    status: None,
-                    error: Some("URL 不能为空".to_string()),
-                };
-            }
+        async move { probe_endpoint(&client, raw_url).await }
+    });
 
-            let parsed_url = match Url::parse(&trimmed) {
-                Ok(url) => url,
-                Err(err) => {
-                    return EndpointLatency {
-                        url: trimmed,
-                        latency: None,
-                        status: None,
-                        error: Some(format!("URL 无效: {err}")),
-                    };
-                }
-            };
+    let results = join_all(tasks).await;
+    Ok(results)
+}
 
-            // 先进行一次“热身”请求，忽略其结果，仅用于复用连接/绕过首包惩罚
-            let _ = client.get(parsed_url.clone()).send().await;
+/// 测试单个端点的延迟（先热身一次，再计时第二次请求）
+pub(crate) async fn probe_endpoint(client: &Client, raw_url: String) -> EndpointLatency {
+    let trimmed = raw_url.trim().to_string();
+    if trimmed.is_empty() {
+        return EndpointLatency {
+            url: raw_url,
+            latency: None,
+            status: None,
+            error: Some("URL 不能为空".to_string()),
+        };
+    }
 
-            // 第二次请求开始计时，并将其作为结果返回
-            let start = Instant::now();
-            match client.get(parsed_url).send().await {
-                Ok(resp) => {
-                    let latency = start.elapsed().as_millis();
-                    EndpointLatency {
-                        url: trimmed,
-                        latency: Some(latency),
-                        status: Some(resp.status().as_u16()),
-                        error: None,
-                    }
-                }
-                Err(err) => {
-                    let status = err.status().map(|s| s.as_u16());
-                    let error_message = if err.is_timeout() {
-                        "请求超时".to_string()
-                    } else if err.is_connect() {
-                        "连接失败".to_string()
-                    } else {
-                        err.to_string()
-                    };
+    let parsed_url = match Url::parse(&trimmed) {
+        Ok(url) => url,
+        Err(err) => {
+            return EndpointLatency {
+                url: trimmed,
+                latency: None,
+                status: None,
+                error: Some(format!("URL 无效: {err}")),
+            };
+        }
+    };
 
-                    EndpointLatency {
-                        url: trimmed,
-                        latency: None,
-                        status,
-                        error: Some(error_message),
-                    }
-                }
+    // 先进行一次“热身”请求，忽略其结果，仅用于复用连接/绕过首包惩罚
+    let _ = client.get(parsed_url.clone()).send().await;
+
+    // 第二次请求开始计时，并将其作为结果返回
+    let start = Instant::now();
+    match client.get(parsed_url).send().await {
+        Ok(resp) => {
+            let latency = start.elapsed().as_millis();
+            EndpointLatency {
+                url: trimmed,
+                latency: Some(latency),
+                status: Some(resp.status().as_u16()),
+                error: None,
             }
         }
-    });
+        Err(err) => {
+            let status = err.status().map(|s| s.as_u16());
+            let error_message = if err.is_timeout() {
+                "请求超时".to_string()
+            } else if err.is_connect() {
+                "连接失败".to_string()
+            } else {
+                err.to_string()
+            };
 
-    let results = join_all(tasks).await;
-    Ok(results)
+            EndpointLatency {
+                url: trimmed,
+                latency: None,
+                status,
+                error: Some(error_message),
+            }
+        }
+    }
 }
@@ -1,31 +1,404 @@
-use crate::app_config::MultiAppConfig;
-use std::sync::Mutex;
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::error::{AppError, ResultExt};
+use crate::webdav_sync::ConflictChoice;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::sync::{Mutex, MutexGuard, Notify, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// 标记修改后延迟落盘的时间；期间的连续修改合并为一次写入
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+/// 退出时等待进行中操作（切换、恢复等）完成的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// 全局应用状态
+///
+/// 配置放在异步读写锁中；持锁期间只做内存操作，读写文件、访问网络须先取快照或释放锁，
+/// 避免慢速 I/O（例如 WSL 的 UNC 路径）阻塞其他命令
 pub struct AppState {
-    pub config: Mutex<MultiAppConfig>,
+    config: RwLock<MultiAppConfig>,
+    /// 每次获取写锁递增，用于判断保存期间内存配置是否被改动
+    revision: AtomicU64,
+    /// 串行化保存流程（合并检查与写入需作为整体）
+    save_lock: Mutex<()>,
+    /// 按应用串行化写 live 的流程（切换、恢复备份、撤销等），避免并发切换交错写入
+    claude_switch_lock: Mutex<()>,
+    codex_switch_lock: Mutex<()>,
+    /// 内存配置有尚未落盘的修改
+    dirty: AtomicBool,
+    /// 每次标记修改递增；延迟任务到期时仅最新一次负责落盘
+    dirty_ticket: AtomicU64,
+    /// 进行中的写 live / 恢复类操作数量，退出前等待其归零
+    in_flight: AtomicUsize,
+    in_flight_idle: Notify,
+    /// 已开始退出流程，不再接受新的操作
+    shutting_down: AtomicBool,
+    /// 退出前的等待与落盘已完成
+    shutdown_done: AtomicBool,
+}
+
+/// 进行中操作的登记，drop 时注销
+pub struct OperationGuard<'a> {
+    state: &'a AppState,
+}
+
+impl Drop for OperationGuard<'_> {
+    fn drop(&mut self) {
+        if self.state.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.state.in_flight_idle.notify_waiters();
+        }
+    }
 }
 
 impl AppState {
     /// 创建新的应用状态
     pub fn new() -> Self {
-        let config = MultiAppConfig::load().unwrap_or_else(|e| {
-            log::warn!("加载配置失败: {}, 使用默认配置", e);
+        let mut config = MultiAppConfig::load().unwrap_or_else(|e| {
+            if crate::store_crypto::is_locked() {
+                log::info!("配置已加密，等待解锁");
+            } else {
+                log::warn!("加载配置失败: {}, 使用默认配置", e);
+                crate::store_health::mark_load_failed(&e);
+            }
             MultiAppConfig::default()
         });
+        crate::shared_sync::remember_disk();
+
+        // 启动时清理回收站中超期的供应商
+        let retention_days = crate::settings::get_settings().trash_retention_days;
+        let purged = config.purge_expired_trash(retention_days);
+        if purged > 0 {
+            log::info!("已清理回收站中 {} 个超期供应商", purged);
+            if let Err(e) = config.save() {
+                log::warn!("保存配置失败: {}", e);
+            }
+            crate::shared_sync::remember_disk();
+        }
 
         Self {
-            config: Mutex::new(config),
+            config: RwLock::new(config),
+            revision: AtomicU64::new(0),
+            save_lock: Mutex::new(()),
+            claude_switch_lock: Mutex::new(()),
+            codex_switch_lock: Mutex::new(()),
+            dirty: AtomicBool::new(false),
+            dirty_ticket: AtomicU64::new(0),
+            in_flight: AtomicUsize::new(0),
+            in_flight_idle: Notify::new(),
+            shutting_down: AtomicBool::new(false),
+            shutdown_done: AtomicBool::new(false),
+        }
+    }
+
+    /// 获取配置读锁
+    pub async fn read(&self) -> RwLockReadGuard<'_, MultiAppConfig> {
+        self.config.read().await
+    }
+
+    /// 获取配置写锁
+    pub async fn write(&self) -> RwLockWriteGuard<'_, MultiAppConfig> {
+        let guard = self.config.write().await;
+        self.revision.fetch_add(1, Ordering::SeqCst);
+        guard
+    }
+
+    /// 同步上下文（托盘菜单、启动流程）中获取读锁；不可在异步任务中调用
+    pub fn blocking_read(&self) -> RwLockReadGuard<'_, MultiAppConfig> {
+        self.config.blocking_read()
+    }
+
+    /// 同步上下文中获取写锁；不可在异步任务中调用
+    pub fn blocking_write(&self) -> RwLockWriteGuard<'_, MultiAppConfig> {
+        let guard = self.config.blocking_write();
+        self.revision.fetch_add(1, Ordering::SeqCst);
+        guard
+    }
+
+    /// 当前配置的副本，供需要在锁外做 I/O 的流程使用
+    pub async fn snapshot(&self) -> MultiAppConfig {
+        self.config.read().await.clone()
+    }
+
+    /// 立即保存配置到文件（切换供应商、导入恢复等需确保落盘的场景）
+    pub async fn save(&self) -> Result<(), AppError> {
+        crate::store_crypto::touch();
+        self.save_resolving(None).await
+    }
+
+    /// 标记配置已修改并在短暂延迟后于后台落盘，连续的界面编辑只写入一次；
+    /// 后台保存失败时发送 `config-save-failed` 事件并保留修改标记
+    pub fn mark_dirty(&self) {
+        crate::store_crypto::touch();
+        if crate::store_health::load_failed() {
+            log::warn!("config.json 加载失败，修改仅保留在内存中，不会写入磁盘");
+            return;
+        }
+        self.dirty.store(true, Ordering::SeqCst);
+        let ticket = self.dirty_ticket.fetch_add(1, Ordering::SeqCst) + 1;
+        crate::schedule_tray_refresh();
+
+        let Some(handle) = crate::app_store::get_app_handle() else {
+            return;
+        };
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(SAVE_DEBOUNCE).await;
+            let Some(state) = handle.try_state::<AppState>() else {
+                return;
+            };
+            // 期间又有新的修改：交给最新的延迟任务处理
+            if state.dirty_ticket.load(Ordering::SeqCst) != ticket {
+                return;
+            }
+            if let Err(e) = state.flush().await {
+                log::error!("后台保存配置失败: {}", e.detail());
+                crate::notify::send("保存配置失败", &e.detail());
+                if let Err(err) = handle.emit("config-save-failed", &e) {
+                    log::warn!("发送保存失败事件失败: {}", err);
+                }
+            }
+        });
+    }
+
+    /// 若有尚未落盘的修改则立即保存（退出前、切换供应商前调用）
+    pub async fn flush(&self) -> Result<(), AppError> {
+        if !self.dirty.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        self.save_resolving(None).await
+    }
+
+    /// 获取应用的 live 写入锁：从落盘、写 live 到保存配置需整体持有
+    pub async fn lock_switch(&self, app: &AppType) -> MutexGuard<'_, ()> {
+        match app {
+            AppType::Claude => self.claude_switch_lock.lock().await,
+            AppType::Codex => self.codex_switch_lock.lock().await,
+        }
+    }
+
+    /// 登记一个需在退出前完成的操作（切换供应商、恢复备份等）；退出流程开始后拒绝新操作
+    pub fn begin_operation(&self) -> Result<OperationGuard<'_>, AppError> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = OperationGuard { state: self };
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err("应用正在退出，操作已取消".into());
         }
+        Ok(guard)
     }
 
-    /// 保存配置到文件
-    pub fn save(&self) -> Result<(), String> {
-        let config = self
-            .config
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+    /// 退出前调用：等待进行中的操作完成（最多 10 秒），再写入尚未落盘的修改
+    pub async fn shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let idle = async {
+            loop {
+                // 先注册等待再检查计数，避免错过最后一次通知
+                let notified = self.in_flight_idle.notified();
+                if self.in_flight.load(Ordering::SeqCst) == 0 {
+                    break;
+                }
+                notified.await;
+            }
+        };
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, idle).await.is_err() {
+            log::warn!(
+                "等待进行中的操作超时（{} 个未完成），继续退出",
+                self.in_flight.load(Ordering::SeqCst)
+            );
+        }
+        if let Err(e) = self.flush().await {
+            log::error!("退出前保存配置失败: {}", e.detail());
+        }
+        self.shutdown_done.store(true, Ordering::SeqCst);
+    }
+
+    /// 退出前的等待与落盘是否已完成
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown_done.load(Ordering::SeqCst)
+    }
+
+    /// 使用口令解锁加密配置，并重新加载到内存
+    pub async fn unlock_store(&self, passphrase: &str) -> Result<(), AppError> {
+        let passphrase = passphrase.to_string();
+        let loaded = tauri::async_runtime::spawn_blocking(move || -> Result<_, AppError> {
+            crate::store_crypto::unlock(&passphrase)?;
+            let loaded = MultiAppConfig::load().context("加载解锁后的配置失败")?;
+            crate::shared_sync::remember_disk();
+            Ok(loaded)
+        })
+        .await
+        .map_err(|e| format!("解锁配置任务失败: {}", e))??;
+        {
+            let mut config = self.write().await;
+            *config = loaded;
+            config.ensure_app(&crate::app_config::AppType::Claude);
+            config.ensure_app(&crate::app_config::AppType::Codex);
+        }
+        crate::schedule_tray_refresh();
+        Ok(())
+    }
+
+    /// 锁定加密配置：丢弃内存中的密钥与供应商数据
+    pub async fn lock_store(&self) -> Result<(), AppError> {
+        if !crate::store_crypto::status().encrypted {
+            return Err("配置未启用加密".into());
+        }
+        // 锁定会丢弃内存配置，先写入尚未落盘的修改
+        self.flush().await?;
+        let mut config = self.write().await;
+        crate::store_crypto::lock();
+        *config = MultiAppConfig::default();
+        drop(config);
+        crate::schedule_tray_refresh();
+        Ok(())
+    }
+
+    /// 保存配置；共享目录中的 config.json 被其他设备修改时先合并，
+    /// 同一条目冲突时按 `keep` 选择保留的一方（None 则报错并等待用户选择）
+    ///
+    /// 仅在读锁内复制配置，落盘在阻塞线程池中进行，不占用配置锁
+    pub async fn save_resolving(&self, keep: Option<ConflictChoice>) -> Result<(), AppError> {
+        // 加载失败时内存中只有默认配置，写入会覆盖用户原有的 config.json 与 .bak
+        if crate::store_health::load_failed() {
+            return Err(
+                "config.json 加载失败，为避免覆盖原文件已停止保存，请处理后重启应用".into(),
+            );
+        }
+        let _saving = self.save_lock.lock().await;
+        // 先清除修改标记：保存期间的新修改会重新标记，不会被遗漏
+        self.dirty.store(false, Ordering::SeqCst);
+        let (snapshot, revision) = {
+            let config = self.config.read().await;
+            (config.clone(), self.revision.load(Ordering::SeqCst))
+        };
+
+        let merged = tauri::async_runtime::spawn_blocking(move || -> Result<_, AppError> {
+            // 跨进程写锁：合并检查与写入需作为整体，避免与其他实例交错保存
+            let _file_lock =
+                crate::store_lock::acquire("保存配置").context("获取配置文件写锁失败")?;
+            let merged = crate::shared_sync::reconcile(&snapshot, keep)?;
+            merged
+                .as_ref()
+                .unwrap_or(&snapshot)
+                .save()
+                .context("保存配置失败")?;
+            crate::shared_sync::after_write();
+            Ok(merged)
+        })
+        .await
+        .map_err(|e| AppError::from(format!("保存配置任务失败: {}", e)))
+        .and_then(|r| r)
+        .inspect_err(|_| self.dirty.store(true, Ordering::SeqCst))?;
+
+        // 合并结果仅在保存期间内存未被改动时回写；否则留给下一次保存重新合并
+        if let Some(merged) = merged {
+            let mut config = self.config.write().await;
+            if self.revision.load(Ordering::SeqCst) == revision {
+                *config = merged;
+            }
+        }
+
+        crate::webdav_sync::schedule_auto_sync();
+        crate::schedule_tray_refresh();
+        Ok(())
+    }
+
+    /// config.json 被外部修改后同步到内存；返回内存配置是否发生变化
+    ///
+    /// 无未保存修改时直接重新加载磁盘内容；有未保存修改时与磁盘内容三方合并，
+    /// 同一条目冲突则返回错误（冲突记录在 shared_sync 中，等待用户选择）
+    pub async fn reload_external(&self) -> Result<bool, AppError> {
+        // 与保存互斥：本实例写入与更新基线完成前不做判断，避免把自己的写入当作外部修改
+        let _saving = self.save_lock.lock().await;
+        if crate::store_crypto::is_locked() {
+            return Ok(false);
+        }
+        let changed = tauri::async_runtime::spawn_blocking(crate::shared_sync::disk_changed)
+            .await
+            .map_err(|e| format!("检查配置文件失败: {}", e))?;
+        if !changed {
+            return Ok(false);
+        }
+
+        if !self.dirty.load(Ordering::SeqCst) {
+            let loaded = tauri::async_runtime::spawn_blocking(|| -> Result<_, AppError> {
+                let path = crate::config::get_app_config_path();
+                let content =
+                    std::fs::read_to_string(&path).map_err(|e| AppError::read_file(&path, e))?;
+                // 编辑到一半的无效内容不会覆盖内存，也不触发损坏恢复
+                let (config, _) = MultiAppConfig::parse_with_migration(&content)?;
+                crate::shared_sync::remember_disk();
+                Ok(config)
+            })
+            .await
+            .map_err(|e| format!("重新加载配置失败: {}", e))??;
+            let mut config = self.write().await;
+            *config = loaded;
+            config.ensure_app(&crate::app_config::AppType::Claude);
+            config.ensure_app(&crate::app_config::AppType::Codex);
+        } else {
+            let (snapshot, revision) = {
+                let config = self.config.read().await;
+                (config.clone(), self.revision.load(Ordering::SeqCst))
+            };
+            let merged = tauri::async_runtime::spawn_blocking(move || {
+                crate::shared_sync::reconcile(&snapshot, None)
+            })
+            .await
+            .map_err(|e| format!("合并外部修改失败: {}", e))??;
+            let Some(merged) = merged else {
+                return Ok(false);
+            };
+            {
+                let mut config = self.config.write().await;
+                // 合并期间内存又被修改：留给下一次保存重新合并
+                if self.revision.load(Ordering::SeqCst) != revision {
+                    return Ok(false);
+                }
+                *config = merged;
+            }
+            // 合并结果仍需落盘（dirty 保持），基线更新为当前磁盘内容
+            crate::shared_sync::remember_disk();
+        }
+        crate::schedule_tray_refresh();
+        Ok(true)
+    }
+
+    /// 整体替换内存配置并落盘（用于同步/恢复）
+    ///
+    /// 用户主动导入或恢复即明确要覆盖磁盘内容，因此解除加载失败时的写入保护
+    pub async fn replace_config(&self, mut new_config: MultiAppConfig) -> Result<(), AppError> {
+        if crate::store_health::load_failed() {
+            crate::store_health::mark_healthy();
+        }
+        new_config.ensure_app(&crate::app_config::AppType::Claude);
+        new_config.ensure_app(&crate::app_config::AppType::Codex);
+        *self.write().await = new_config;
+        self.save().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_home;
+
+    #[test]
+    fn newer_config_is_never_overwritten() {
+        let _home = test_home::enter();
+        let path = crate::config::get_app_config_path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let original = br#"{ "version": 3, "claude": { "providers": {}, "current": "" } }"#;
+        std::fs::write(&path, original).unwrap();
+
+        let state = AppState::new();
+        assert!(crate::store_health::load_failed());
+        state.mark_dirty();
+        assert!(tauri::async_runtime::block_on(state.save()).is_err());
+        assert!(tauri::async_runtime::block_on(state.flush()).is_ok());
 
-        config.save()
+        assert_eq!(std::fs::read(&path).unwrap(), original);
+        assert!(!crate::config::get_app_config_dir()
+            .join("config.json.bak")
+            .exists());
+        crate::store_health::mark_healthy();
     }
 }
@@ -0,0 +1,288 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+use crate::config::{get_app_config_dir, get_app_config_path};
+use crate::crypto::{
+    decrypt_with_key, derive_key, encrypt_with_key, envelope_salt, random_salt, EncryptedEnvelope,
+};
+use crate::store::AppState;
+
+/// 自动锁定检查间隔
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 已解锁的密钥（仅驻留内存）
+struct UnlockedKey {
+    key: [u8; 32],
+    salt: Vec<u8>,
+}
+
+struct CryptoState {
+    unlocked: Option<UnlockedKey>,
+    /// 启用 / 关闭加密或更换口令前的密钥，用于解密仍由旧密钥加密的历史副本
+    previous: Option<UnlockedKey>,
+    last_activity: Instant,
+}
+
+fn state() -> &'static Mutex<CryptoState> {
+    static STATE: OnceLock<Mutex<CryptoState>> = OnceLock::new();
+    STATE.get_or_init(|| {
+        Mutex::new(CryptoState {
+            unlocked: None,
+            previous: None,
+            last_activity: Instant::now(),
+        })
+    })
+}
+
+/// 配置存储的加密状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreLockStatus {
+    /// config.json 是否以加密形式保存
+    pub encrypted: bool,
+    /// 是否处于锁定状态（已加密且未解锁）
+    pub locked: bool,
+    /// 空闲自动锁定时间（分钟，0 表示不自动锁定）
+    pub auto_lock_minutes: u64,
+}
+
+fn parse_envelope(content: &str) -> Option<EncryptedEnvelope> {
+    serde_json::from_str::<EncryptedEnvelope>(content)
+        .ok()
+        .filter(|env| env.format == "cc-switch-encrypted")
+}
+
+/// 磁盘上的 config.json 是否为加密信封
+pub fn disk_encrypted() -> bool {
+    std::fs::read_to_string(get_app_config_path())
+        .map(|content| parse_envelope(&content).is_some())
+        .unwrap_or(false)
+}
+
+fn is_unlocked() -> bool {
+    state()
+        .lock()
+        .map(|guard| guard.unlocked.is_some())
+        .unwrap_or(false)
+}
+
+/// 是否处于锁定状态
+pub fn is_locked() -> bool {
+    !is_unlocked() && disk_encrypted()
+}
+
+pub fn status() -> StoreLockStatus {
+    let unlocked = is_unlocked();
+    let encrypted = unlocked || disk_encrypted();
+    StoreLockStatus {
+        encrypted,
+        locked: encrypted && !unlocked,
+        auto_lock_minutes: crate::settings::get_settings().store_auto_lock_minutes,
+    }
+}
+
+/// 记录一次访问，用于空闲自动锁定计时
+pub fn touch() {
+    if let Ok(mut guard) = state().lock() {
+        guard.last_activity = Instant::now();
+    }
+}
+
+/// 解密配置文本；非加密内容原样返回
+pub fn decode_content(content: &str) -> Result<String, String> {
+    let Some(envelope) = parse_envelope(content) else {
+        return Ok(content.to_string());
+    };
+    let guard = state().lock().map_err(|e| format!("获取锁失败: {}", e))?;
+    if guard.unlocked.is_none() && guard.previous.is_none() {
+        return Err("配置已加密且处于锁定状态，请先解锁".to_string());
+    }
+    let salt = envelope_salt(&envelope)?;
+    let key = [guard.unlocked.as_ref(), guard.previous.as_ref()]
+        .into_iter()
+        .flatten()
+        .find(|k| k.salt == salt)
+        .ok_or_else(|| "该配置使用其他口令加密，无法解密".to_string())?;
+    let plain = decrypt_with_key(&envelope, &key.key)?;
+    String::from_utf8(plain).map_err(|e| format!("解密后的配置不是有效文本: {}", e))
+}
+
+/// 按当前加密状态编码待写入的配置内容
+///
+/// 已解锁时加密；磁盘已加密但处于锁定状态时拒绝写入，避免以明文覆盖
+pub fn encode_content(plain: &[u8]) -> Result<Vec<u8>, String> {
+    let guard = state().lock().map_err(|e| format!("获取锁失败: {}", e))?;
+    match guard.unlocked.as_ref() {
+        Some(unlocked) => {
+            let envelope = encrypt_with_key(plain, &unlocked.key, &unlocked.salt)?;
+            serde_json::to_vec_pretty(&envelope).map_err(|e| format!("序列化加密数据失败: {}", e))
+        }
+        None if disk_encrypted() => Err("配置已加密且处于锁定状态，无法保存".to_string()),
+        None => Ok(plain.to_vec()),
+    }
+}
+
+/// 使用口令解锁磁盘上的加密配置
+pub fn unlock(passphrase: &str) -> Result<(), String> {
+    let content = std::fs::read_to_string(get_app_config_path())
+        .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let envelope = parse_envelope(&content).ok_or_else(|| "配置未加密".to_string())?;
+    let salt = envelope_salt(&envelope)?;
+    let key = derive_key(passphrase, &salt)?;
+    decrypt_with_key(&envelope, &key)?;
+
+    let mut guard = state().lock().map_err(|e| format!("获取锁失败: {}", e))?;
+    guard.unlocked = Some(UnlockedKey { key, salt });
+    guard.previous = None;
+    guard.last_activity = Instant::now();
+    Ok(())
+}
+
+/// 丢弃内存中的密钥
+pub fn lock() {
+    if let Ok(mut guard) = state().lock() {
+        guard.unlocked = None;
+        guard.previous = None;
+    }
+}
+
+fn verify_passphrase(passphrase: &str) -> Result<(), String> {
+    let guard = state().lock().map_err(|e| format!("获取锁失败: {}", e))?;
+    let unlocked = guard
+        .unlocked
+        .as_ref()
+        .ok_or_else(|| "配置未加密或处于锁定状态".to_string())?;
+    if derive_key(passphrase, &unlocked.salt)? != unlocked.key {
+        return Err("口令错误".to_string());
+    }
+    Ok(())
+}
+
+/// 替换当前密钥（None 表示关闭加密），旧密钥保留到 `reseal_copies` 完成
+fn replace_key(new_key: Option<UnlockedKey>) -> Result<(), String> {
+    let mut guard = state().lock().map_err(|e| format!("获取锁失败: {}", e))?;
+    let old = std::mem::replace(&mut guard.unlocked, new_key);
+    if old.is_some() {
+        guard.previous = old;
+    }
+    guard.last_activity = Instant::now();
+    Ok(())
+}
+
+fn set_new_key(passphrase: &str) -> Result<(), String> {
+    let salt = random_salt();
+    let key = derive_key(passphrase, &salt)?;
+    replace_key(Some(UnlockedKey {
+        key,
+        salt: salt.to_vec(),
+    }))
+}
+
+/// 启用加密（调用方随后保存配置即以密文写入）
+pub fn enable(passphrase: &str) -> Result<(), String> {
+    if passphrase.is_empty() {
+        return Err("口令不能为空".to_string());
+    }
+    if is_unlocked() || disk_encrypted() {
+        return Err("配置已启用加密".to_string());
+    }
+    set_new_key(passphrase)
+}
+
+/// 关闭加密（调用方随后保存配置即以明文写入）
+pub fn disable(passphrase: &str) -> Result<(), String> {
+    verify_passphrase(passphrase)?;
+    replace_key(None)
+}
+
+/// 更换口令（调用方随后保存配置即以新密钥写入）
+pub fn rotate(old_passphrase: &str, new_passphrase: &str) -> Result<(), String> {
+    if new_passphrase.is_empty() {
+        return Err("新口令不能为空".to_string());
+    }
+    verify_passphrase(old_passphrase)?;
+    set_new_key(new_passphrase)
+}
+
+/// 按当前加密状态重写一份副本；无法解密（其他口令）的密文保持不变
+fn reseal_file(path: &Path) -> Result<(), String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("读取文件失败: {}: {}", path.display(), e))?;
+    let plain = match decode_content(&content) {
+        Ok(plain) => plain,
+        Err(e) => {
+            log::warn!("跳过无法解密的配置副本: {}: {}", path.display(), e);
+            return Ok(());
+        }
+    };
+    let bytes = encode_content(plain.as_bytes())?;
+    if bytes != content.as_bytes() {
+        crate::config::atomic_write(path, &bytes).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 启用 / 关闭加密或更换口令并保存配置后调用：
+/// 用当前密钥重写历史版本与 config.json.bak，并清除保存了 live 明文的切换快照，
+/// 完成后丢弃旧密钥
+pub fn reseal_copies() -> Result<(), String> {
+    let mut paths = vec![get_app_config_dir().join("config.json.bak")];
+    if let Ok(versions) = crate::history::list_versions() {
+        paths.extend(versions.iter().map(|v| crate::history::version_path(&v.id)));
+    }
+    let mut first_err = None;
+    for path in paths.iter().filter(|p| p.exists()) {
+        if let Err(e) = reseal_file(path) {
+            first_err.get_or_insert(e);
+        }
+    }
+
+    let snapshots = get_app_config_dir().join("snapshots");
+    if snapshots.exists() {
+        if let Err(e) = fs::remove_dir_all(&snapshots) {
+            log::warn!("清除切换快照失败: {}: {}", snapshots.display(), e);
+        }
+    }
+
+    if let Ok(mut guard) = state().lock() {
+        guard.previous = None;
+    }
+    first_err.map_or(Ok(()), Err)
+}
+
+/// 后台检查空闲时间，超时后锁定配置并清空内存中的供应商数据
+pub fn start_idle_watch(handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            let minutes = crate::settings::get_settings().store_auto_lock_minutes;
+            if minutes == 0 {
+                continue;
+            }
+            let expired = state()
+                .lock()
+                .map(|guard| {
+                    guard.unlocked.is_some()
+                        && guard.last_activity.elapsed() >= Duration::from_secs(minutes * 60)
+                })
+                .unwrap_or(false);
+            if !expired {
+                continue;
+            }
+            if let Some(app_state) = handle.try_state::<AppState>() {
+                if let Err(e) = app_state.lock_store().await {
+                    log::warn!("自动锁定配置失败: {}", e);
+                    continue;
+                }
+            }
+            log::info!("空闲超过 {} 分钟，已自动锁定配置", minutes);
+            if let Err(e) = handle.emit("store-locked", ()) {
+                log::warn!("发送锁定事件失败: {}", e);
+            }
+        }
+    });
+}
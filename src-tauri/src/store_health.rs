@@ -0,0 +1,177 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use crate::app_config::MultiAppConfig;
+use crate::config::get_app_config_dir;
+
+/// config.json 健康状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StoreStatus {
+    /// 正常加载
+    Healthy,
+    /// 检测到损坏并已从备份恢复
+    Recovered,
+    /// 检测到损坏且无可用备份，已使用空配置
+    Reset,
+    /// 加载失败（非损坏原因，例如版本过高），当前使用内存中的默认配置
+    LoadFailed,
+}
+
+/// 启动时的加载/恢复报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreHealth {
+    pub status: StoreStatus,
+    pub config_path: String,
+    pub checked_at: i64,
+    /// 恢复所用的备份文件
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovered_from: Option<String>,
+    /// 损坏文件被隔离后的路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quarantined_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl StoreHealth {
+    fn new(status: StoreStatus) -> Self {
+        Self {
+            status,
+            config_path: crate::config::get_app_config_path()
+                .to_string_lossy()
+                .to_string(),
+            checked_at: Utc::now().timestamp_millis(),
+            recovered_from: None,
+            quarantined_path: None,
+            error: None,
+        }
+    }
+}
+
+fn health_store() -> &'static RwLock<StoreHealth> {
+    static HEALTH: OnceLock<RwLock<StoreHealth>> = OnceLock::new();
+    HEALTH.get_or_init(|| RwLock::new(StoreHealth::new(StoreStatus::Healthy)))
+}
+
+fn record(health: StoreHealth) {
+    if let Ok(mut guard) = health_store().write() {
+        *guard = health;
+    }
+}
+
+/// 获取最近一次加载的健康报告
+pub fn get_health() -> StoreHealth {
+    health_store()
+        .read()
+        .map(|g| g.clone())
+        .unwrap_or_else(|_| StoreHealth::new(StoreStatus::Healthy))
+}
+
+/// 记录正常加载
+pub fn mark_healthy() {
+    record(StoreHealth::new(StoreStatus::Healthy));
+}
+
+/// 本次启动是否未能加载 config.json（此时内存中是默认配置，不得写回磁盘）
+pub fn load_failed() -> bool {
+    health_store()
+        .read()
+        .map(|g| g.status == StoreStatus::LoadFailed)
+        .unwrap_or(false)
+}
+
+/// 记录加载失败（非损坏）
+pub fn mark_load_failed(error: &str) {
+    let mut health = StoreHealth::new(StoreStatus::LoadFailed);
+    health.error = Some(error.to_string());
+    record(health);
+    crate::notify::send("配置加载失败", error);
+}
+
+/// 恢复候选：config.json.bak → 最新历史版本 → 最新导入前备份
+fn recovery_candidates() -> Vec<PathBuf> {
+    let dir = get_app_config_dir();
+    let mut candidates = vec![dir.join("config.json.bak")];
+
+    if let Ok(versions) = crate::history::list_versions() {
+        candidates.extend(versions.iter().map(|v| crate::history::version_path(&v.id)));
+    }
+
+    if let Ok(entries) = fs::read_dir(crate::backup::get_backups_root()) {
+        let mut backups: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_file()
+                    && p.file_name()
+                        .and_then(|s| s.to_str())
+                        .map(|s| s.starts_with("backup_") && s.ends_with(".json"))
+                        .unwrap_or(false)
+            })
+            .collect();
+        backups.sort();
+        backups.reverse();
+        candidates.extend(backups);
+    }
+
+    candidates.into_iter().filter(|p| p.exists()).collect()
+}
+
+/// 将损坏的 config.json 重命名为 config.json.corrupt.<ts> 隔离保存
+fn quarantine(config_path: &Path) -> Result<PathBuf, String> {
+    let ts = Utc::now().format("%Y%m%d_%H%M%S");
+    let mut name = config_path.as_os_str().to_owned();
+    name.push(format!(".corrupt.{}", ts));
+    let target = PathBuf::from(name);
+    fs::rename(config_path, &target).map_err(|e| {
+        format!(
+            "隔离损坏的配置文件失败: {} -> {}: {}",
+            config_path.display(),
+            target.display(),
+            e
+        )
+    })?;
+    Ok(target)
+}
+
+/// config.json 解析失败时调用：隔离损坏文件并从最近可用的备份恢复
+pub fn recover(config_path: &Path, error: &str) -> Result<MultiAppConfig, String> {
+    log::error!("config.json 已损坏: {}，尝试从备份恢复", error);
+
+    let quarantined = quarantine(config_path)?;
+    log::warn!("已隔离损坏的配置文件: {}", quarantined.display());
+
+    let mut health = StoreHealth::new(StoreStatus::Reset);
+    health.quarantined_path = Some(quarantined.to_string_lossy().to_string());
+    health.error = Some(error.to_string());
+
+    let mut recovered = None;
+    for candidate in recovery_candidates() {
+        let Ok(content) = fs::read_to_string(&candidate) else {
+            continue;
+        };
+        match MultiAppConfig::parse_with_migration(&content) {
+            Ok((config, _)) => {
+                log::info!("已从备份恢复配置: {}", candidate.display());
+                health.status = StoreStatus::Recovered;
+                health.recovered_from = Some(candidate.to_string_lossy().to_string());
+                recovered = Some(config);
+                break;
+            }
+            Err(e) => log::warn!("备份不可用: {}: {}", candidate.display(), e),
+        }
+    }
+
+    let config = recovered.unwrap_or_else(|| {
+        log::warn!("没有可用的备份，使用空配置");
+        MultiAppConfig::default()
+    });
+    config.save()?;
+    record(health);
+    Ok(config)
+}
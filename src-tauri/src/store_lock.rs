@@ -0,0 +1,150 @@
+use fs4::fs_std::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::get_app_config_dir;
+
+/// 等待其他进程释放锁的最长时间
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 锁持有者信息，写入锁文件供等待方诊断
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockOwner {
+    pub pid: u32,
+    pub instance_id: String,
+    pub host: String,
+    /// 持锁目的，例如 "保存配置"、"导入配置"
+    pub purpose: String,
+    /// 获取时间（Unix 毫秒）
+    pub acquired_at: i64,
+}
+
+impl LockOwner {
+    fn current(purpose: &str) -> Self {
+        Self {
+            pid: std::process::id(),
+            instance_id: crate::app_store::get_instance_id(),
+            host: hostname(),
+            purpose: purpose.to_string(),
+            acquired_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{}（PID {}，主机 {}，实例 {}）",
+            self.purpose, self.pid, self.host, self.instance_id
+        )
+    }
+}
+
+/// config.json 写入的跨进程咨询锁（`~/.cc-switch/config.json.lock`），drop 时清除持有者信息并释放
+pub struct StoreLock {
+    file: File,
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        // 清空持有者信息：下一个持有者据此区分正常释放与进程异常退出
+        if let Err(e) = self.file.set_len(0) {
+            log::warn!("清除锁文件持有者信息失败: {}", e);
+        }
+    }
+}
+
+fn lock_path() -> PathBuf {
+    get_app_config_dir().join("config.json.lock")
+}
+
+fn open_lock_file(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("打开锁文件失败: {}: {}", path.display(), e))
+}
+
+fn read_owner(mut file: &File) -> Option<LockOwner> {
+    let mut content = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut content).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_owner(mut file: &File, owner: &LockOwner) -> Result<(), String> {
+    let content =
+        serde_json::to_vec_pretty(owner).map_err(|e| format!("序列化锁持有者信息失败: {}", e))?;
+    file.set_len(0)
+        .and_then(|_| file.seek(SeekFrom::Start(0)))
+        .and_then(|_| file.write_all(&content))
+        .and_then(|_| file.flush())
+        .map_err(|e| format!("写入锁持有者信息失败: {}", e))
+}
+
+fn hostname() -> String {
+    #[cfg(unix)]
+    if let Ok(name) = std::fs::read_to_string("/etc/hostname") {
+        let name = name.trim();
+        if !name.is_empty() {
+            return name.to_string();
+        }
+    }
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_default()
+}
+
+/// 获取写锁；其他 cc-switch 进程（托盘、CLI 等）持有锁时等待，超时返回错误并说明持有者。
+/// 持有进程退出时由系统自动释放锁，无需回收
+pub fn acquire(purpose: &str) -> Result<StoreLock, String> {
+    let dir = get_app_config_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("创建配置目录失败: {}: {}", dir.display(), e))?;
+    let path = lock_path();
+    let file = open_lock_file(&path)?;
+
+    let started = Instant::now();
+    loop {
+        match FileExt::try_lock_exclusive(&file) {
+            Ok(true) => {
+                if let Some(previous) = read_owner(&file) {
+                    log::warn!(
+                        "上次持有配置文件锁的进程未正常释放: {}",
+                        previous.describe()
+                    );
+                }
+                let lock = StoreLock { file };
+                write_owner(&lock.file, &LockOwner::current(purpose))?;
+                return Ok(lock);
+            }
+            Ok(false) => {}
+            Err(e) => return Err(format!("获取配置文件锁失败: {}", e)),
+        }
+
+        if started.elapsed() >= LOCK_TIMEOUT {
+            return Err(match read_owner(&file) {
+                Some(owner) => format!("等待配置文件锁超时，当前持有者: {}", owner.describe()),
+                None => "等待配置文件锁超时，可能有其他 cc-switch 进程正在写入".to_string(),
+            });
+        }
+        std::thread::sleep(RETRY_INTERVAL);
+    }
+}
+
+/// 当前持有配置文件锁的进程信息（无人持有时返回 None）
+pub fn current_owner() -> Option<LockOwner> {
+    let file = File::open(lock_path()).ok()?;
+    // 能立即获取共享锁说明无人持有，残留内容来自异常退出的进程
+    if matches!(FileExt::try_lock_shared(&file), Ok(true)) {
+        let _ = FileExt::unlock(&file);
+        return None;
+    }
+    read_owner(&file)
+}
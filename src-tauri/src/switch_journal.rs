@@ -0,0 +1,257 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::config::{get_app_config_dir, read_json_file, write_json_file};
+use crate::error::{AppError, ResultExt};
+
+/// 切换日志中记录的单个 live 文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalFile {
+    pub path: String,
+    /// 写入前内容的 SHA-256（文件不存在时为空）
+    pub pre_hash: Option<String>,
+}
+
+/// 切换意图日志：切换开始前写入，成功后删除；启动时仍存在说明上次切换被中断
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchJournal {
+    pub app_type: String,
+    pub previous_current: String,
+    pub target_id: String,
+    pub started_at: i64,
+    /// 切换前快照 ID，用于回滚 live 文件
+    pub snapshot_id: Option<String>,
+    pub files: Vec<JournalFile>,
+}
+
+/// 中断切换的检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterruptedSwitch {
+    #[serde(flatten)]
+    pub journal: SwitchJournal,
+    /// 已被改写的 live 文件
+    pub modified_files: Vec<String>,
+    /// 是否存在可用于回滚的快照
+    pub can_rollback: bool,
+    /// 目标供应商是否仍存在（可完成切换）
+    pub can_complete: bool,
+}
+
+/// 中断切换的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecoveryAction {
+    /// 用切换前快照恢复 live 文件，当前供应商保持切换前的值
+    Rollback,
+    /// 保留已写入的 live 文件，并将当前供应商设为切换目标
+    Complete,
+    /// 仅清除日志，不做任何修改
+    Dismiss,
+}
+
+fn journal_path(app: &AppType) -> PathBuf {
+    get_app_config_dir().join(format!("switch-journal-{}.json", app.as_str()))
+}
+
+fn file_hash(path: &Path) -> Option<String> {
+    fs::read(path)
+        .ok()
+        .map(|bytes| crate::crypto::sha256_hex(&bytes))
+}
+
+/// 进行中的切换；`finish` 后删除日志。未完成即被丢弃（切换出错）时，
+/// 若 live 文件均未改写则删除日志，否则保留以便稍后回滚或完成
+pub struct JournalGuard {
+    app: AppType,
+    finished: bool,
+}
+
+impl JournalGuard {
+    /// 切换成功后调用
+    pub fn finish(mut self) {
+        self.finished = true;
+        clear(&self.app);
+    }
+}
+
+impl Drop for JournalGuard {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        match read(&self.app) {
+            Some(journal) if modified_files(&journal).is_empty() => clear(&self.app),
+            Some(_) => log::warn!(
+                "{} 切换失败且 live 配置已部分写入，保留切换日志",
+                self.app.as_str()
+            ),
+            None => {}
+        }
+    }
+}
+
+/// 切换开始前写入意图日志，记录目标与各 live 文件写入前的摘要
+pub fn begin(
+    app: &AppType,
+    previous_current: &str,
+    target_id: &str,
+    snapshot_id: Option<String>,
+) -> Result<JournalGuard, AppError> {
+    let files = crate::backup::live_files(app)
+        .into_iter()
+        .map(|path| JournalFile {
+            pre_hash: file_hash(&path),
+            path: path.to_string_lossy().to_string(),
+        })
+        .collect();
+    let journal = SwitchJournal {
+        app_type: app.as_str().to_string(),
+        previous_current: previous_current.to_string(),
+        target_id: target_id.to_string(),
+        started_at: Utc::now().timestamp_millis(),
+        snapshot_id,
+        files,
+    };
+    write_json_file(&journal_path(app), &journal).context("写入切换日志失败")?;
+    Ok(JournalGuard {
+        app: app.clone(),
+        finished: false,
+    })
+}
+
+/// 切换成功（或已处理完中断）后删除日志
+pub fn clear(app: &AppType) {
+    let path = journal_path(app);
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!("删除切换日志失败: {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn read(app: &AppType) -> Option<SwitchJournal> {
+    let path = journal_path(app);
+    if !path.exists() {
+        return None;
+    }
+    match read_json_file::<SwitchJournal>(&path) {
+        Ok(journal) => Some(journal),
+        Err(e) => {
+            log::warn!("切换日志已损坏，已忽略: {}", e);
+            None
+        }
+    }
+}
+
+/// 与写入前摘要不一致的 live 文件
+fn modified_files(journal: &SwitchJournal) -> Vec<String> {
+    journal
+        .files
+        .iter()
+        .filter(|f| file_hash(Path::new(&f.path)) != f.pre_hash)
+        .map(|f| f.path.clone())
+        .collect()
+}
+
+fn inspect(config: &MultiAppConfig, app: &AppType, journal: SwitchJournal) -> InterruptedSwitch {
+    let modified_files = modified_files(&journal);
+    let can_rollback = journal
+        .snapshot_id
+        .as_deref()
+        .and_then(|id| crate::snapshot::find_snapshot(app, id))
+        .is_some();
+    let can_complete = config
+        .get_manager(app)
+        .is_some_and(|m| m.providers.contains_key(&journal.target_id));
+    InterruptedSwitch {
+        journal,
+        modified_files,
+        can_rollback,
+        can_complete,
+    }
+}
+
+/// 列出所有应用中被中断的切换
+pub fn pending(config: &MultiAppConfig) -> Vec<InterruptedSwitch> {
+    [AppType::Claude, AppType::Codex]
+        .iter()
+        .filter_map(|app| read(app).map(|j| inspect(config, app, j)))
+        .collect()
+}
+
+/// 启动时检查：live 文件均未被改写的日志直接清除，其余保留并提醒用户处理
+pub fn check_on_startup(config: &MultiAppConfig) {
+    for interrupted in pending(config) {
+        let app: AppType = interrupted.journal.app_type.as_str().into();
+        if interrupted.modified_files.is_empty() {
+            log::info!(
+                "{} 上次切换在写入 live 配置前中断，已清除切换日志",
+                app.as_str()
+            );
+            clear(&app);
+            continue;
+        }
+        log::warn!(
+            "{} 上次切换到「{}」未完成，已改写 {} 个 live 文件",
+            app.as_str(),
+            interrupted.journal.target_id,
+            interrupted.modified_files.len()
+        );
+        crate::notify::send(
+            "上次切换未完成",
+            "检测到供应商切换被中断，请打开主窗口选择回滚或完成切换",
+        );
+    }
+}
+
+/// 处理中断切换的文件部分（在配置锁外执行）：回滚时恢复切换前快照中的 live 文件。
+/// 返回切换日志，供 [`apply`] 更新当前供应商
+pub fn restore_files(app: &AppType, action: RecoveryAction) -> Result<SwitchJournal, AppError> {
+    let journal = read(app).ok_or_else(|| format!("{} 没有未完成的切换", app.as_str()))?;
+    if let RecoveryAction::Rollback = action {
+        let snapshot = journal
+            .snapshot_id
+            .as_deref()
+            .and_then(|id| crate::snapshot::find_snapshot(app, id))
+            .ok_or_else(|| "缺少切换前快照，无法回滚".to_string())?;
+        crate::snapshot::restore_live_files(app, &snapshot)?;
+        crate::snapshot::remove_snapshot(app, &snapshot.id)?;
+    }
+    Ok(journal)
+}
+
+/// 在内存配置中应用处理结果（仅修改当前供应商指针）；返回处理后的当前供应商 ID
+pub fn apply(
+    config: &mut MultiAppConfig,
+    app: &AppType,
+    journal: &SwitchJournal,
+    action: RecoveryAction,
+) -> Result<String, AppError> {
+    let manager = config
+        .get_manager_mut(app)
+        .ok_or_else(|| AppError::app_type_not_found(app))?;
+    match action {
+        RecoveryAction::Rollback => {
+            if journal.previous_current.is_empty()
+                || manager.providers.contains_key(&journal.previous_current)
+            {
+                manager.current = journal.previous_current.clone();
+            }
+        }
+        RecoveryAction::Complete => {
+            if !manager.providers.contains_key(&journal.target_id) {
+                return Err(AppError::ProviderNotFound(journal.target_id.clone()));
+            }
+            manager.record_recent(&journal.target_id);
+            manager.current = journal.target_id.clone();
+        }
+        RecoveryAction::Dismiss => {}
+    }
+    Ok(manager.current.clone())
+}
@@ -0,0 +1,245 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::Emitter;
+use tokio::task::AbortHandle;
+
+use crate::error::AppError;
+
+/// 保留的已结束任务数量（更早的记录被丢弃）
+const FINISHED_LIMIT: usize = 50;
+
+/// 后台任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+/// 后台任务信息；每次变化通过 `task-updated` 事件推送
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskInfo {
+    pub id: String,
+    /// 任务类型，例如 "benchmark"、"webdav_sync"
+    pub kind: String,
+    pub label: String,
+    pub status: TaskStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// 最近一条进度说明
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub started_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<i64>,
+}
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancelled: Arc<AtomicBool>,
+    abort: Option<AbortHandle>,
+}
+
+fn registry() -> &'static Mutex<HashMap<String, TaskEntry>> {
+    static TASKS: OnceLock<Mutex<HashMap<String, TaskEntry>>> = OnceLock::new();
+    TASKS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn emit(info: &TaskInfo) {
+    let Some(handle) = crate::app_store::get_app_handle() else {
+        return;
+    };
+    if let Err(e) = handle.emit("task-updated", info) {
+        log::warn!("发送任务进度事件失败: {}", e);
+    }
+    crate::events::publish(crate::events::StateEvent::TaskUpdated { task: info.clone() });
+}
+
+/// 修改任务信息并推送事件；任务不存在时忽略
+fn update(id: &str, f: impl FnOnce(&mut TaskInfo)) {
+    let info = {
+        let Ok(mut tasks) = registry().lock() else {
+            return;
+        };
+        let Some(entry) = tasks.get_mut(id) else {
+            return;
+        };
+        f(&mut entry.info);
+        entry.info.clone()
+    };
+    emit(&info);
+}
+
+/// 结束任务：运行中的任务改为最终状态（已取消的保持取消），并清理过多的历史记录
+fn finish(id: &str, status: TaskStatus, error: Option<String>) {
+    update(id, |info| {
+        if info.status == TaskStatus::Running {
+            info.status = status;
+            info.error = error;
+            info.finished_at = Some(chrono::Utc::now().timestamp_millis());
+        }
+    });
+    if let Ok(mut tasks) = registry().lock() {
+        if let Some(entry) = tasks.get_mut(id) {
+            entry.abort = None;
+        }
+        let mut finished: Vec<(i64, String)> = tasks
+            .values()
+            .filter_map(|e| e.info.finished_at.map(|t| (t, e.info.id.clone())))
+            .collect();
+        if finished.len() > FINISHED_LIMIT {
+            finished.sort();
+            for (_, id) in finished.iter().take(finished.len() - FINISHED_LIMIT) {
+                tasks.remove(id);
+            }
+        }
+    }
+}
+
+/// 任务内使用的上下文：上报进度、检查是否已被取消
+#[derive(Clone)]
+pub struct TaskContext {
+    id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TaskContext {
+    /// 上报进度（completed / total，可附带说明）
+    pub fn progress(&self, completed: usize, total: usize, message: Option<String>) {
+        update(&self.id, |info| {
+            info.completed = Some(completed);
+            info.total = Some(total);
+            if message.is_some() {
+                info.message = message;
+            }
+        });
+    }
+
+    /// 是否已请求取消；长循环中应定期检查并尽早返回
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// 在后台运行任务并等待结果；任务登记在任务列表中，可通过 `cancel_task` 取消
+///
+/// 任务在异步运行时中独立执行，不占用调用方所在的命令线程；被取消时返回错误
+pub async fn run<T, F, Fut>(kind: &str, label: impl Into<String>, f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(TaskContext) -> Fut,
+    Fut: Future<Output = Result<T, AppError>> + Send + 'static,
+{
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    let id = format!("task-{}", NEXT_ID.fetch_add(1, Ordering::SeqCst));
+    let ctx = TaskContext {
+        id: id.clone(),
+        cancelled: Arc::new(AtomicBool::new(false)),
+    };
+    let info = TaskInfo {
+        id: id.clone(),
+        kind: kind.to_string(),
+        label: label.into(),
+        status: TaskStatus::Running,
+        completed: None,
+        total: None,
+        message: None,
+        error: None,
+        started_at: chrono::Utc::now().timestamp_millis(),
+        finished_at: None,
+    };
+
+    let future = f(ctx.clone());
+    // 先登记再启动，保证取消请求总能找到任务
+    if let Ok(mut tasks) = registry().lock() {
+        tasks.insert(
+            id.clone(),
+            TaskEntry {
+                info: info.clone(),
+                cancelled: ctx.cancelled.clone(),
+                abort: None,
+            },
+        );
+    }
+    emit(&info);
+
+    let join = tauri::async_runtime::spawn(future);
+    if let Ok(mut tasks) = registry().lock() {
+        if let Some(entry) = tasks.get_mut(&id) {
+            entry.abort = Some(join.inner().abort_handle());
+        }
+    }
+    if ctx.is_cancelled() {
+        join.abort();
+    }
+
+    match join.await {
+        Ok(Ok(value)) => {
+            finish(&id, TaskStatus::Succeeded, None);
+            Ok(value)
+        }
+        Ok(Err(e)) => {
+            let status = if ctx.is_cancelled() {
+                TaskStatus::Cancelled
+            } else {
+                TaskStatus::Failed
+            };
+            finish(&id, status, Some(e.detail()));
+            Err(e)
+        }
+        Err(_) if ctx.is_cancelled() => {
+            finish(&id, TaskStatus::Cancelled, None);
+            Err("任务已取消".into())
+        }
+        Err(e) => {
+            let message = format!("后台任务异常结束: {}", e);
+            finish(&id, TaskStatus::Failed, Some(message.clone()));
+            Err(message.into())
+        }
+    }
+}
+
+/// 所有任务（运行中在前，其余按开始时间倒序）
+pub fn list() -> Vec<TaskInfo> {
+    let mut tasks: Vec<TaskInfo> = registry()
+        .lock()
+        .map(|tasks| tasks.values().map(|e| e.info.clone()).collect())
+        .unwrap_or_default();
+    tasks.sort_by_key(|t| (t.status != TaskStatus::Running, -t.started_at));
+    tasks
+}
+
+/// 取消运行中的任务；返回任务是否仍在运行
+pub fn cancel(id: &str) -> Result<bool, AppError> {
+    let info = {
+        let mut tasks = registry()
+            .lock()
+            .map_err(|e| format!("获取任务列表锁失败: {}", e))?;
+        let entry = tasks
+            .get_mut(id)
+            .ok_or_else(|| format!("任务不存在: {}", id))?;
+        if entry.info.status != TaskStatus::Running {
+            return Ok(false);
+        }
+        entry.cancelled.store(true, Ordering::SeqCst);
+        if let Some(abort) = entry.abort.take() {
+            abort.abort();
+        }
+        entry.info.status = TaskStatus::Cancelled;
+        entry.info.finished_at = Some(chrono::Utc::now().timestamp_millis());
+        entry.info.clone()
+    };
+    log::info!("已取消任务 {}（{}）", info.id, info.label);
+    emit(&info);
+    Ok(true)
+}
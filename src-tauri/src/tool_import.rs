@@ -0,0 +1,365 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::provider::Provider;
+
+/// 支持导入的第三方工具格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ToolFormat {
+    /// claude-code-router：`Providers: [{ name, api_base_url, api_key, models }]`
+    ClaudeCodeRouter,
+    /// 以名称为键、值为 Claude settings（含 env）的配置集合
+    ClaudeProfiles,
+    /// 通用供应商列表：`[{ name, baseUrl, apiKey, model }]` 或 `{ providers: [...] }`
+    GenericList,
+}
+
+/// 从第三方配置中解析出的供应商条目
+#[derive(Debug, Clone)]
+pub struct ImportedEntry {
+    pub app_type: AppType,
+    pub name: String,
+    pub settings_config: Value,
+    pub website_url: Option<String>,
+}
+
+/// 导入结果中的单个供应商
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedProvider {
+    pub app_type: String,
+    pub id: String,
+    pub name: String,
+}
+
+/// 跳过的条目及原因
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedEntry {
+    pub name: String,
+    pub reason: String,
+}
+
+/// 导入报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolImportReport {
+    pub format: ToolFormat,
+    pub imported: Vec<ImportedProvider>,
+    pub skipped: Vec<SkippedEntry>,
+}
+
+/// 各工具的默认配置文件位置
+pub fn default_path(format: ToolFormat) -> Option<std::path::PathBuf> {
+    match format {
+        ToolFormat::ClaudeCodeRouter => {
+            crate::config::home_dir().map(|h| h.join(".claude-code-router").join("config.json"))
+        }
+        _ => None,
+    }
+}
+
+fn str_field(obj: &Map<String, Value>, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+        .map(|s| s.trim().to_string())
+        .find(|s| !s.is_empty())
+}
+
+fn first_model(obj: &Map<String, Value>) -> Option<String> {
+    str_field(obj, &["model", "defaultModel", "default_model"]).or_else(|| {
+        obj.get("models")
+            .and_then(|v| v.as_array())
+            .and_then(|arr| arr.iter().find_map(|m| m.as_str()))
+            .map(|s| s.to_string())
+    })
+}
+
+/// 识别配置格式
+pub fn detect_format(value: &Value) -> Option<ToolFormat> {
+    if let Some(obj) = value.as_object() {
+        let ccr = obj
+            .get("Providers")
+            .or_else(|| obj.get("providers"))
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .any(|p| p.get("api_base_url").is_some() || p.get("api_key").is_some())
+            })
+            .unwrap_or(false);
+        if ccr {
+            return Some(ToolFormat::ClaudeCodeRouter);
+        }
+        if obj.get("providers").map(|v| v.is_array()).unwrap_or(false) {
+            return Some(ToolFormat::GenericList);
+        }
+        let profiles = obj.get("profiles").unwrap_or(value);
+        if profiles
+            .as_object()
+            .map(|m| !m.is_empty() && m.values().all(|v| v.get("env").is_some()))
+            .unwrap_or(false)
+        {
+            return Some(ToolFormat::ClaudeProfiles);
+        }
+    }
+    if value.is_array() {
+        return Some(ToolFormat::GenericList);
+    }
+    None
+}
+
+/// 根据端点地址构造 Claude 或 Codex 供应商配置
+///
+/// 以 `/chat/completions` 或 `/responses` 结尾的 OpenAI 兼容端点导入为 Codex，
+/// 其余按 Anthropic 兼容端点导入为 Claude
+fn entry_from_endpoint(
+    name: &str,
+    base_url: &str,
+    api_key: &str,
+    model: Option<&str>,
+) -> ImportedEntry {
+    let url = base_url.trim().trim_end_matches('/');
+    let openai_base = url
+        .strip_suffix("/chat/completions")
+        .or_else(|| url.strip_suffix("/responses"));
+
+    if let Some(base) = openai_base {
+        let config = crate::codex_config::generate_third_party_config(
+            name,
+            base,
+            model.unwrap_or("gpt-5-codex"),
+        );
+        return ImportedEntry {
+            app_type: AppType::Codex,
+            name: name.to_string(),
+            settings_config: json!({
+                "auth": { "OPENAI_API_KEY": api_key },
+                "config": config,
+            }),
+            website_url: None,
+        };
+    }
+
+    let base = url.strip_suffix("/v1/messages").unwrap_or(url);
+    let mut env = Map::new();
+    env.insert("ANTHROPIC_BASE_URL".into(), json!(base));
+    env.insert("ANTHROPIC_AUTH_TOKEN".into(), json!(api_key));
+    if let Some(model) = model {
+        env.insert("ANTHROPIC_MODEL".into(), json!(model));
+    }
+    ImportedEntry {
+        app_type: AppType::Claude,
+        name: name.to_string(),
+        settings_config: json!({ "env": env }),
+        website_url: None,
+    }
+}
+
+fn parse_ccr(value: &Value) -> Vec<Result<ImportedEntry, SkippedEntry>> {
+    let providers = value
+        .get("Providers")
+        .or_else(|| value.get("providers"))
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    providers
+        .iter()
+        .enumerate()
+        .map(|(idx, p)| {
+            let obj = p.as_object().cloned().unwrap_or_default();
+            let name = str_field(&obj, &["name"]).unwrap_or_else(|| format!("ccr-{}", idx + 1));
+            let base_url = str_field(&obj, &["api_base_url"]).ok_or_else(|| SkippedEntry {
+                name: name.clone(),
+                reason: "缺少 api_base_url".to_string(),
+            })?;
+            let api_key = str_field(&obj, &["api_key"]).unwrap_or_default();
+            Ok(entry_from_endpoint(
+                &name,
+                &base_url,
+                &api_key,
+                first_model(&obj).as_deref(),
+            ))
+        })
+        .collect()
+}
+
+fn parse_profiles(value: &Value) -> Vec<Result<ImportedEntry, SkippedEntry>> {
+    let profiles = value
+        .get("profiles")
+        .unwrap_or(value)
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+    profiles
+        .into_iter()
+        .map(|(name, settings)| {
+            if !settings.get("env").map(|v| v.is_object()).unwrap_or(false) {
+                return Err(SkippedEntry {
+                    name,
+                    reason: "env 字段必须是对象".to_string(),
+                });
+            }
+            Ok(ImportedEntry {
+                app_type: AppType::Claude,
+                name,
+                settings_config: settings,
+                website_url: None,
+            })
+        })
+        .collect()
+}
+
+fn parse_generic(value: &Value) -> Vec<Result<ImportedEntry, SkippedEntry>> {
+    let items = value
+        .as_array()
+        .or_else(|| value.get("providers").and_then(|v| v.as_array()))
+        .cloned()
+        .unwrap_or_default();
+    items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            let obj = item.as_object().cloned().unwrap_or_default();
+            let name = str_field(&obj, &["name", "label", "title"])
+                .unwrap_or_else(|| format!("imported-{}", idx + 1));
+            let base_url = str_field(
+                &obj,
+                &[
+                    "baseUrl",
+                    "base_url",
+                    "baseURL",
+                    "url",
+                    "endpoint",
+                    "api_base_url",
+                ],
+            )
+            .ok_or_else(|| SkippedEntry {
+                name: name.clone(),
+                reason: "缺少端点地址".to_string(),
+            })?;
+            let api_key = str_field(&obj, &["apiKey", "api_key", "key", "token", "authToken"])
+                .unwrap_or_default();
+            let mut entry =
+                entry_from_endpoint(&name, &base_url, &api_key, first_model(&obj).as_deref());
+            entry.website_url = str_field(&obj, &["websiteUrl", "website_url", "homepage"]);
+            Ok(entry)
+        })
+        .collect()
+}
+
+/// 按格式解析第三方配置
+pub fn parse(value: &Value, format: ToolFormat) -> Vec<Result<ImportedEntry, SkippedEntry>> {
+    match format {
+        ToolFormat::ClaudeCodeRouter => parse_ccr(value),
+        ToolFormat::ClaudeProfiles => parse_profiles(value),
+        ToolFormat::GenericList => parse_generic(value),
+    }
+}
+
+/// 供应商去重指纹（端点 + 密钥）
+fn fingerprint(app: &AppType, settings: &Value) -> Option<(String, String)> {
+    match app {
+        AppType::Claude => {
+            let env = settings.get("env")?;
+            let url = env
+                .get("ANTHROPIC_BASE_URL")?
+                .as_str()?
+                .trim_end_matches('/');
+            let key = crate::migration::extract_claude_api_key(settings).unwrap_or_default();
+            Some((url.to_string(), key))
+        }
+        AppType::Codex => {
+            let cfg = settings.get("config")?.as_str()?;
+            let url = crate::codex_config::extract_base_url(cfg)?;
+            let key = crate::migration::extract_codex_api_key(settings).unwrap_or_default();
+            Some((url.trim_end_matches('/').to_string(), key))
+        }
+    }
+}
+
+/// 将解析出的条目写入配置（跳过端点与密钥均相同的已有供应商）
+pub fn apply(
+    config: &mut MultiAppConfig,
+    format: ToolFormat,
+    entries: Vec<Result<ImportedEntry, SkippedEntry>>,
+) -> ToolImportReport {
+    let mut report = ToolImportReport {
+        format,
+        imported: vec![],
+        skipped: vec![],
+    };
+    let now = chrono::Utc::now().timestamp_millis();
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(skipped) => {
+                report.skipped.push(skipped);
+                continue;
+            }
+        };
+        let mut entry = entry;
+        match crate::config::normalize_provider_name(&entry.name) {
+            Ok(name) => entry.name = name,
+            Err(reason) => {
+                report.skipped.push(SkippedEntry {
+                    name: entry.name,
+                    reason: reason.to_string(),
+                });
+                continue;
+            }
+        }
+        config.ensure_app(&entry.app_type);
+        let Some(manager) = config.get_manager_mut(&entry.app_type) else {
+            continue;
+        };
+
+        let fp = fingerprint(&entry.app_type, &entry.settings_config);
+        let duplicate = fp.is_some()
+            && manager
+                .providers
+                .values()
+                .any(|p| fingerprint(&entry.app_type, &p.settings_config) == fp);
+        if duplicate {
+            report.skipped.push(SkippedEntry {
+                name: entry.name,
+                reason: "已存在相同端点与密钥的供应商".to_string(),
+            });
+            continue;
+        }
+
+        let existing: HashSet<String> = manager.providers.keys().cloned().collect();
+        let id = crate::migration::next_unique_id(&existing, &entry.name);
+        // 启用钥匙串时导入的密钥同样只在 config.json 中保存引用
+        let settings_config =
+            match crate::keychain::protect(&entry.app_type, &id, entry.settings_config) {
+                Ok(settings) => settings,
+                Err(reason) => {
+                    report.skipped.push(SkippedEntry {
+                        name: entry.name,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+        let mut provider = Provider::with_id(
+            id.clone(),
+            entry.name.clone(),
+            settings_config,
+            entry.website_url,
+        );
+        provider.category = Some("custom".to_string());
+        provider.created_at = Some(now);
+        manager.providers.insert(id.clone(), provider);
+
+        report.imported.push(ImportedProvider {
+            app_type: entry.app_type.as_str().to_string(),
+            id,
+            name: entry.name,
+        });
+    }
+    report
+}
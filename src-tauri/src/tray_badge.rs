@@ -0,0 +1,115 @@
+use tauri::image::Image;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::provider::Provider;
+
+/// 官方供应商徽标颜色（绿色）
+const OFFICIAL_COLOR: [u8; 3] = [0x22, 0xc5, 0x5e];
+/// 第三方 / 中转供应商按 id 取色，避开官方的绿色
+const RELAY_PALETTE: [[u8; 3]; 6] = [
+    [0xf9, 0x73, 0x16],
+    [0x3b, 0x82, 0xf6],
+    [0xa8, 0x55, 0xf7],
+    [0xef, 0x44, 0x44],
+    [0xec, 0x48, 0x99],
+    [0xea, 0xb3, 0x08],
+];
+
+fn badge_color(provider: &Provider) -> [u8; 3] {
+    if provider.category.as_deref() == Some("official") {
+        return OFFICIAL_COLOR;
+    }
+    // FNV-1a：同一供应商在不同启动间颜色保持稳定
+    let hash = provider.id.bytes().fold(0x811c_9dc5u32, |h, b| {
+        (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    });
+    RELAY_PALETTE[hash as usize % RELAY_PALETTE.len()]
+}
+
+fn current_provider<'a>(config: &'a MultiAppConfig, app: &AppType) -> Option<&'a Provider> {
+    let manager = config.get_manager(app)?;
+    manager.providers.get(&manager.current)
+}
+
+/// 在图标上绘制带白色描边的圆形徽标，中心为 (cx, cy)
+fn draw_badge(rgba: &mut [u8], width: u32, height: u32, cx: f32, cy: f32, r: f32, color: [u8; 3]) {
+    let border = (r * 0.25).max(1.0);
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist > r {
+                continue;
+            }
+            let fill = if dist > r - border {
+                [0xff, 0xff, 0xff]
+            } else {
+                color
+            };
+            let i = ((y * width + x) * 4) as usize;
+            rgba[i..i + 4].copy_from_slice(&[fill[0], fill[1], fill[2], 0xff]);
+        }
+    }
+}
+
+/// 以默认图标为底，为 Claude（右下）与 Codex（左下）当前供应商叠加颜色徽标
+fn render(base: &Image<'_>, config: &MultiAppConfig) -> Image<'static> {
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    let r = width.min(height) as f32 * 0.2;
+    let cy = height as f32 - r;
+    let slots = [(AppType::Claude, width as f32 - r), (AppType::Codex, r)];
+    for (app, cx) in slots {
+        if let Some(provider) = current_provider(config, &app) {
+            draw_badge(&mut rgba, width, height, cx, cy, r, badge_color(provider));
+        }
+    }
+    Image::new_owned(rgba, width, height)
+}
+
+/// 当前供应商摘要，如 `Claude: work-relay | Codex: official`
+fn summary(config: &MultiAppConfig) -> String {
+    let parts: Vec<String> = [("Claude", AppType::Claude), ("Codex", AppType::Codex)]
+        .into_iter()
+        .filter_map(|(label, app)| {
+            current_provider(config, &app).map(|p| format!("{}: {}", label, p.name))
+        })
+        .collect();
+    if parts.is_empty() {
+        "CC Switch".to_string()
+    } else {
+        parts.join(" | ")
+    }
+}
+
+/// 按当前供应商重新生成托盘图标、提示文字与 macOS 菜单栏标题（关闭徽标时恢复默认图标）
+pub fn refresh(app: &tauri::AppHandle, config: &MultiAppConfig) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    let Some(base) = app.default_window_icon() else {
+        return;
+    };
+    let settings = crate::settings::get_settings();
+    let icon = if settings.show_tray_badge {
+        render(base, config)
+    } else {
+        base.clone().to_owned()
+    };
+    if let Err(e) = tray.set_icon(Some(icon)) {
+        log::warn!("更新托盘图标失败: {}", e);
+    }
+    // Linux 托盘不支持提示文字，调用为空操作
+    let text = summary(config);
+    if let Err(e) = tray.set_tooltip(Some(&text)) {
+        log::warn!("更新托盘提示失败: {}", e);
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let title = settings.show_tray_title.then_some(text);
+        if let Err(e) = tray.set_title(title) {
+            log::warn!("更新菜单栏标题失败: {}", e);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use tauri_plugin_opener::OpenerExt;
+
+use crate::settings::TrayLink;
+
+/// 保存设置前校验自定义托盘项：需有名称，且网址与命令二选一
+pub fn validate(links: &[TrayLink]) -> Result<(), String> {
+    for link in links {
+        let label = link.label.trim();
+        if label.is_empty() {
+            return Err("自定义托盘项名称不能为空".to_string());
+        }
+        let url = link.url.as_deref().map(str::trim).filter(|u| !u.is_empty());
+        let command = link
+            .command
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty());
+        match (url, command) {
+            (Some(url), None) => {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(format!(
+                        "自定义托盘项「{}」的网址需以 http:// 或 https:// 开头",
+                        label
+                    ));
+                }
+            }
+            (None, Some(_)) => {}
+            _ => {
+                return Err(format!(
+                    "自定义托盘项「{}」需填写网址或命令（二选一）",
+                    label
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn shell_command(command: &str) -> std::process::Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", command]).creation_flags(0x0800_0000);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// 执行自定义托盘项：网址交由系统浏览器打开，命令在后台执行
+pub fn open(app: &tauri::AppHandle, link: &TrayLink) -> Result<(), String> {
+    if let Some(url) = link.url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        return app
+            .opener()
+            .open_url(url, None::<String>)
+            .map_err(|e| format!("打开链接失败: {}", e));
+    }
+    if let Some(command) = link
+        .command
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+    {
+        let mut cmd = shell_command(command);
+        if let Some(home) = crate::config::home_dir() {
+            cmd.current_dir(home);
+        }
+        cmd.spawn().map_err(|e| format!("执行命令失败: {}", e))?;
+    }
+    Ok(())
+}
@@ -1,4 +1,3 @@
-use reqwest::Client;
 use rquickjs::{Context, Runtime, Function};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -109,7 +108,7 @@ struct RequestConfig {
 
 /// 发送 HTTP 请求
 async fn send_http_request(config: &RequestConfig, timeout_secs: u64) -> Result<String, String> {
-    let client = Client::builder()
+    let client = crate::http_client::builder()?
         .timeout(Duration::from_secs(timeout_secs))
         .build()
         .map_err(|e| format!("创建客户端失败: {}", e))?;
@@ -0,0 +1,362 @@
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::app_config::MultiAppConfig;
+use crate::config::{get_app_config_dir, get_app_config_path, read_json_file, write_json_file};
+use crate::settings::{SyncConflictStrategy, WebDavSyncSettings};
+use crate::store::AppState;
+
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// 同步进行中标记，避免自动同步与手动同步并发
+static SYNCING: AtomicBool = AtomicBool::new(false);
+
+/// 同步结果状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncStatus {
+    Disabled,
+    UpToDate,
+    Pushed,
+    Pulled,
+    Conflict,
+    Busy,
+}
+
+impl SyncStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SyncStatus::Disabled => "disabled",
+            SyncStatus::UpToDate => "upToDate",
+            SyncStatus::Pushed => "pushed",
+            SyncStatus::Pulled => "pulled",
+            SyncStatus::Conflict => "conflict",
+            SyncStatus::Busy => "busy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    pub status: SyncStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_modified_at: Option<i64>,
+}
+
+impl SyncResult {
+    fn new(status: SyncStatus) -> Self {
+        Self {
+            status,
+            synced_at: None,
+            remote_modified_at: None,
+        }
+    }
+}
+
+/// 冲突时由用户选择保留的一方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictChoice {
+    Local,
+    Remote,
+}
+
+/// 本地同步状态（记录上次双方一致时的内容摘要）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncState {
+    #[serde(default)]
+    last_synced_hash: Option<String>,
+    #[serde(default)]
+    last_synced_at: Option<i64>,
+}
+
+struct RemoteFile {
+    plain: Vec<u8>,
+    modified_at: Option<i64>,
+}
+
+fn sync_state_path() -> PathBuf {
+    get_app_config_dir().join("sync").join("webdav-state.json")
+}
+
+fn load_sync_state() -> SyncState {
+    let path = sync_state_path();
+    if !path.exists() {
+        return SyncState::default();
+    }
+    read_json_file(&path).unwrap_or_default()
+}
+
+fn save_sync_state(state: &SyncState) -> Result<(), String> {
+    write_json_file(&sync_state_path(), state).map_err(Into::into)
+}
+
+fn remote_url(settings: &WebDavSyncSettings, path: &str) -> String {
+    format!(
+        "{}/{}",
+        settings.url.trim().trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+fn build_client() -> Result<Client, String> {
+    crate::http_client::builder()?
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+fn validate_settings(settings: &WebDavSyncSettings) -> Result<(), String> {
+    if settings.url.trim().is_empty() {
+        return Err("WebDAV 地址不能为空".to_string());
+    }
+    if settings.passphrase.is_empty() {
+        return Err("WebDAV 同步需要设置加密口令".to_string());
+    }
+    if settings.remote_path.trim().trim_matches('/').is_empty() {
+        return Err("WebDAV 远端路径不能为空".to_string());
+    }
+    Ok(())
+}
+
+/// 以规范化 JSON（键有序）序列化配置，保证相同内容得到相同摘要
+fn canonical_config_bytes(config: &MultiAppConfig) -> Result<Vec<u8>, String> {
+    let value = serde_json::to_value(config).map_err(|e| format!("序列化配置失败: {}", e))?;
+    serde_json::to_vec_pretty(&value).map_err(|e| format!("序列化配置失败: {}", e))
+}
+
+async fn snapshot_local(state: &AppState) -> Result<Vec<u8>, String> {
+    canonical_config_bytes(&*state.read().await)
+}
+
+fn local_modified_at() -> Option<i64> {
+    let modified = std::fs::metadata(get_app_config_path())
+        .and_then(|m| m.modified())
+        .ok()?;
+    Some(DateTime::<Utc>::from(modified).timestamp_millis())
+}
+
+async fn fetch_remote(
+    client: &Client,
+    settings: &WebDavSyncSettings,
+) -> Result<Option<RemoteFile>, String> {
+    let url = remote_url(settings, &settings.remote_path);
+    let resp = client
+        .get(&url)
+        .basic_auth(&settings.username, Some(&settings.password))
+        .send()
+        .await
+        .map_err(|e| format!("WebDAV 下载失败: {}", e))?;
+
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        return Err(format!("WebDAV 下载失败: HTTP {}", resp.status()));
+    }
+
+    let modified_at = resp
+        .headers()
+        .get(reqwest::header::LAST_MODIFIED)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| DateTime::parse_from_rfc2822(s).ok())
+        .map(|dt| dt.timestamp_millis());
+    let bytes = resp
+        .bytes()
+        .await
+        .map_err(|e| format!("读取 WebDAV 响应失败: {}", e))?;
+    let plain = crate::crypto::decrypt_with_passphrase(&bytes, &settings.passphrase)?;
+
+    Ok(Some(RemoteFile { plain, modified_at }))
+}
+
+/// 逐级创建远端目录（已存在时服务端返回 405，忽略即可）
+async fn ensure_remote_dirs(client: &Client, settings: &WebDavSyncSettings) -> Result<(), String> {
+    let mkcol = Method::from_bytes(b"MKCOL").map_err(|e| format!("构造 MKCOL 请求失败: {}", e))?;
+    let segments: Vec<&str> = settings
+        .remote_path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let mut current = String::new();
+    for dir in segments.iter().take(segments.len().saturating_sub(1)) {
+        current.push_str(dir);
+        current.push('/');
+        let resp = client
+            .request(mkcol.clone(), remote_url(settings, &current))
+            .basic_auth(&settings.username, Some(&settings.password))
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV 创建目录失败: {}", e))?;
+        let status = resp.status();
+        if !(status.is_success() || status == StatusCode::METHOD_NOT_ALLOWED) {
+            return Err(format!("WebDAV 创建目录失败: HTTP {}", status));
+        }
+    }
+    Ok(())
+}
+
+async fn push_remote(
+    client: &Client,
+    settings: &WebDavSyncSettings,
+    plain: &[u8],
+) -> Result<(), String> {
+    ensure_remote_dirs(client, settings).await?;
+    let body = crate::crypto::encrypt_with_passphrase(plain, &settings.passphrase)?;
+    let resp = client
+        .put(remote_url(settings, &settings.remote_path))
+        .basic_auth(&settings.username, Some(&settings.password))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("WebDAV 上传失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("WebDAV 上传失败: HTTP {}", resp.status()));
+    }
+    Ok(())
+}
+
+async fn apply_remote(state: &AppState, plain: &[u8]) -> Result<(), String> {
+    let remote: MultiAppConfig =
+        serde_json::from_slice(plain).map_err(|e| format!("远端配置格式无效: {}", e))?;
+    state.replace_config(remote).await?;
+    // 当前供应商可能已随远端配置改变，重新写入 live 使其与存储一致
+    crate::live_write::apply_current_providers(state)
+        .await
+        .map_err(Into::into)
+}
+
+fn mark_synced(hash: String) -> Result<i64, String> {
+    let now = Utc::now().timestamp_millis();
+    save_sync_state(&SyncState {
+        last_synced_hash: Some(hash),
+        last_synced_at: Some(now),
+    })?;
+    Ok(now)
+}
+
+async fn sync_inner(
+    state: &AppState,
+    settings: &WebDavSyncSettings,
+    choice: Option<ConflictChoice>,
+) -> Result<SyncResult, String> {
+    validate_settings(settings)?;
+    let client = build_client()?;
+
+    let local_plain = snapshot_local(state).await?;
+    let local_hash = crate::crypto::sha256_hex(&local_plain);
+    let sync_state = load_sync_state();
+
+    let Some(remote) = fetch_remote(&client, settings).await? else {
+        push_remote(&client, settings, &local_plain).await?;
+        let mut result = SyncResult::new(SyncStatus::Pushed);
+        result.synced_at = Some(mark_synced(local_hash)?);
+        return Ok(result);
+    };
+
+    // 远端内容先规范化再计算摘要，避免格式差异被误判为修改
+    let remote_hash = match serde_json::from_slice::<MultiAppConfig>(&remote.plain) {
+        Ok(cfg) => crate::crypto::sha256_hex(&canonical_config_bytes(&cfg)?),
+        Err(e) => return Err(format!("远端配置格式无效: {}", e)),
+    };
+
+    let mut result = SyncResult::new(SyncStatus::UpToDate);
+    result.remote_modified_at = remote.modified_at;
+
+    if remote_hash == local_hash {
+        result.synced_at = Some(mark_synced(local_hash)?);
+        return Ok(result);
+    }
+
+    let last = sync_state.last_synced_hash.as_deref();
+    let local_changed = last != Some(local_hash.as_str());
+    let remote_changed = last != Some(remote_hash.as_str());
+
+    let keep_local = match (local_changed, remote_changed, choice) {
+        (_, _, Some(ConflictChoice::Local)) => true,
+        (_, _, Some(ConflictChoice::Remote)) => false,
+        (true, false, None) => true,
+        (false, true, None) => false,
+        _ => match settings.conflict_strategy {
+            SyncConflictStrategy::Prompt => {
+                result.status = SyncStatus::Conflict;
+                return Ok(result);
+            }
+            SyncConflictStrategy::LastWriterWins => {
+                local_modified_at().unwrap_or(0) >= remote.modified_at.unwrap_or(0)
+            }
+        },
+    };
+
+    if keep_local {
+        push_remote(&client, settings, &local_plain).await?;
+        result.status = SyncStatus::Pushed;
+        result.synced_at = Some(mark_synced(local_hash)?);
+    } else {
+        apply_remote(state, &remote.plain).await?;
+        result.status = SyncStatus::Pulled;
+        result.synced_at = Some(mark_synced(remote_hash)?);
+    }
+    Ok(result)
+}
+
+/// 执行一次同步；`choice` 用于在冲突时指定保留哪一方
+pub async fn sync_now(
+    state: &AppState,
+    choice: Option<ConflictChoice>,
+) -> Result<SyncResult, String> {
+    let settings = crate::settings::get_settings().webdav_sync;
+    if !settings.enabled {
+        return Ok(SyncResult::new(SyncStatus::Disabled));
+    }
+    let settings = crate::keychain::resolve_settings(crate::settings::get_settings())?.webdav_sync;
+    if SYNCING.swap(true, Ordering::SeqCst) {
+        return Ok(SyncResult::new(SyncStatus::Busy));
+    }
+    crate::events::publish(crate::events::StateEvent::sync_status(
+        "webdav", "started", None,
+    ));
+    let result = sync_inner(state, &settings, choice).await;
+    SYNCING.store(false, Ordering::SeqCst);
+    crate::events::publish(match &result {
+        Ok(r) => crate::events::StateEvent::sync_status("webdav", r.status.as_str(), None),
+        Err(e) => crate::events::StateEvent::sync_status("webdav", "failed", Some(e.clone())),
+    });
+    result
+}
+
+/// 配置保存后触发的自动同步（后台执行，不阻塞调用方）
+pub fn schedule_auto_sync() {
+    let settings = crate::settings::get_settings().webdav_sync;
+    if !(settings.enabled && settings.auto_sync) {
+        return;
+    }
+    let Some(handle) = crate::app_store::get_app_handle() else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = handle.try_state::<AppState>() else {
+            return;
+        };
+        match sync_now(state.inner(), None).await {
+            Ok(result) if result.status == SyncStatus::Conflict => {
+                log::warn!("WebDAV 自动同步检测到冲突，等待用户处理");
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("WebDAV 自动同步失败: {}", e);
+                crate::notify::send("WebDAV 同步失败", &e);
+            }
+        }
+    });
+}
@@ -0,0 +1,836 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::app_config::AppType;
+use crate::settings::{AppSettings, NamedEnvironment, TargetEnv};
+
+/// 内置本机环境的名称，无需在设置中定义
+pub const NATIVE_ENV: &str = "native";
+
+/// 访问 WSL 发行版文件系统的 UNC 前缀
+const UNC_LOCALHOST: &str = r"\\wsl.localhost";
+const UNC_LEGACY: &str = r"\\wsl$";
+
+/// WSL 环境仅在 Windows 下可用；macOS / Linux 上隐藏相关环境
+const WSL_SUPPORTED: bool = cfg!(windows);
+
+/// 当前平台信息，供前端隐藏不可用的环境类型
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformInfo {
+    /// windows / macos / linux
+    pub os: String,
+    pub supports_wsl: bool,
+    /// 当前平台可选的环境类型
+    pub target_envs: Vec<TargetEnv>,
+}
+
+pub fn platform_info() -> PlatformInfo {
+    let mut target_envs = vec![TargetEnv::Native];
+    if WSL_SUPPORTED {
+        target_envs.push(TargetEnv::Wsl);
+    }
+    target_envs.push(TargetEnv::Ssh);
+    PlatformInfo {
+        os: std::env::consts::OS.to_string(),
+        supports_wsl: WSL_SUPPORTED,
+        target_envs,
+    }
+}
+
+/// 单个环境中应用配置的存在情况
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentStatus {
+    pub name: String,
+    pub target_env: TargetEnv,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wsl_distro: Option<String>,
+    /// 是否为该应用当前选中的环境
+    pub active: bool,
+    /// 配置目录（主目录无法解析时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub exists: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// WSL 版本（1 / 2），仅 WSL 环境
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wsl_version: Option<u8>,
+    /// 访问发行版文件使用的 UNC 前缀（`\\wsl.localhost` 或 `\\wsl$`），仅经 UNC 访问的 WSL 环境
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unc_scheme: Option<String>,
+}
+
+fn native_env() -> NamedEnvironment {
+    NamedEnvironment {
+        name: NATIVE_ENV.to_string(),
+        target_env: TargetEnv::Native,
+        wsl_distro: None,
+        wsl_write_mode: Default::default(),
+        wsl_auto_start: false,
+        wsl_fix_permissions: false,
+        ssh: None,
+    }
+}
+
+/// 环境注册表：内置本机环境在前，其后为设置中定义的命名环境（不支持 WSL 的平台上略过 WSL 环境）
+pub fn registry(settings: &AppSettings) -> Vec<NamedEnvironment> {
+    std::iter::once(native_env())
+        .chain(
+            settings
+                .environments
+                .iter()
+                .filter(|env| WSL_SUPPORTED || env.target_env != TargetEnv::Wsl)
+                .cloned(),
+        )
+        .collect()
+}
+
+fn selected_name<'a>(settings: &'a AppSettings, app: &AppType) -> Option<&'a str> {
+    match app {
+        AppType::Claude => settings.claude_environment.as_deref(),
+        AppType::Codex => settings.codex_environment.as_deref(),
+    }
+}
+
+fn mirror_name<'a>(settings: &'a AppSettings, app: &AppType) -> Option<&'a str> {
+    match app {
+        AppType::Claude => settings.claude_mirror_environment.as_deref(),
+        AppType::Codex => settings.codex_mirror_environment.as_deref(),
+    }
+}
+
+/// 指定应用选中的环境；未选择或名称不存在时回落到本机环境
+pub fn active_environment(settings: &AppSettings, app: &AppType) -> NamedEnvironment {
+    let Some(name) = selected_name(settings, app) else {
+        return native_env();
+    };
+    registry(settings)
+        .into_iter()
+        .find(|env| env.name == name)
+        .unwrap_or_else(|| {
+            log::warn!("{} 选择的环境 {} 不存在，使用本机环境", app.as_str(), name);
+            native_env()
+        })
+}
+
+/// 校验环境定义：名称非空且唯一、不与内置环境重名，WSL 环境需指定发行版，且各应用选择的环境存在
+pub fn validate(settings: &AppSettings) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for env in settings.environments.iter() {
+        let name = env.name.trim();
+        if name.is_empty() {
+            return Err("环境名称不能为空".to_string());
+        }
+        if name.eq_ignore_ascii_case(NATIVE_ENV) {
+            return Err(format!("环境名称 {} 为内置保留名称", NATIVE_ENV));
+        }
+        if !names.insert(name) {
+            return Err(format!("环境名称重复: {}", name));
+        }
+        let has_host = env.ssh.as_ref().is_some_and(|t| !t.host.trim().is_empty());
+        if env.target_env == TargetEnv::Ssh && !has_host {
+            return Err(format!("SSH 环境 {} 未配置主机", name));
+        }
+        if let Some(target) = env
+            .ssh
+            .as_ref()
+            .filter(|_| env.target_env == TargetEnv::Ssh)
+        {
+            let user = target.user.as_deref().unwrap_or_default();
+            for (field, value) in [("主机", target.host.trim()), ("用户名", user.trim())] {
+                // 以 `-` 开头会被 ssh 当作选项，空白会拆分目标地址
+                if value.starts_with('-') || value.chars().any(char::is_whitespace) {
+                    return Err(format!(
+                        "SSH 环境 {} 的{}不能以 - 开头或包含空白: {}",
+                        name, field, value
+                    ));
+                }
+            }
+        }
+    }
+    let selections = [AppType::Claude, AppType::Codex]
+        .into_iter()
+        .flat_map(|app| {
+            [selected_name(settings, &app), mirror_name(settings, &app)]
+                .into_iter()
+                .flatten()
+                .map(move |name| (app.clone(), name))
+        });
+    for (app, name) in selections {
+        if name == NATIVE_ENV {
+            continue;
+        }
+        let Some(env) = settings.environments.iter().find(|e| e.name.trim() == name) else {
+            return Err(format!("{} 选择的环境不存在: {}", app.as_str(), name));
+        };
+        if env.target_env == TargetEnv::Wsl && !WSL_SUPPORTED {
+            return Err(format!(
+                "WSL 环境仅在 Windows 下可用，{} 不能选择 {}",
+                app.as_str(),
+                name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// WSL 环境使用的发行版：未指定时使用系统默认发行版
+pub fn resolve_distro(env: &NamedEnvironment) -> Result<String, String> {
+    if let Some(distro) = env
+        .wsl_distro
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+    {
+        return Ok(distro.to_string());
+    }
+    // 默认发行版在运行期间基本不变，缓存以免每次解析路径都启动 wsl.exe
+    static DEFAULT: OnceLock<Result<String, String>> = OnceLock::new();
+    DEFAULT.get_or_init(default_distro).clone().map_err(|e| {
+        format!(
+            "WSL 环境 {} 未指定发行版，且无法获取默认发行版: {}",
+            env.name, e
+        )
+    })
+}
+
+/// 选中 WSL 环境时检查发行版是否在运行；`start` 为 true 且环境允许自动启动时尝试启动
+pub fn ensure_ready(app: &AppType, start: bool) -> Result<(), String> {
+    ensure_env_ready(
+        &active_environment(&crate::settings::get_settings(), app),
+        start,
+    )
+}
+
+fn ensure_env_ready(env: &NamedEnvironment, start: bool) -> Result<(), String> {
+    if env.target_env != TargetEnv::Wsl {
+        return Ok(());
+    }
+    let distro = resolve_distro(env)?;
+    if !is_running(&distro)? {
+        if !(start && env.wsl_auto_start) {
+            return Err(format!("WSL 发行版 {} 未运行，请先启动后再试", distro));
+        }
+        start_distro(&distro)?;
+    }
+    // 发行版未运行时的主目录解析失败不再有效，清除以便重新解析
+    if let Ok(mut cache) = home_cache().lock() {
+        if matches!(cache.get(&distro), Some(CachedHome { home: Err(_), .. })) {
+            cache.remove(&distro);
+        }
+    }
+    Ok(())
+}
+
+/// 发行版是否在运行（`wsl.exe -l --running -q`，输出与系统语言无关）
+#[cfg(windows)]
+fn is_running(distro: &str) -> Result<bool, String> {
+    let output = wsl_exe(&["-l", "--running", "-q"])?;
+    // 没有运行中的发行版时 wsl.exe 返回非零退出码
+    Ok(output.status.success()
+        && decode_wsl_output(&output.stdout)
+            .lines()
+            .any(|line| line.trim().eq_ignore_ascii_case(distro)))
+}
+
+#[cfg(windows)]
+fn start_distro(distro: &str) -> Result<(), String> {
+    let output = wsl_exe(&["-d", distro, "-e", "true"])?;
+    if !output.status.success() {
+        return Err(format!(
+            "启动 WSL 发行版 {} 失败: {}",
+            distro,
+            decode_wsl_output(&output.stderr).trim()
+        ));
+    }
+    log::info!("已启动 WSL 发行版 {}", distro);
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn is_running(_distro: &str) -> Result<bool, String> {
+    Err("WSL 环境仅在 Windows 下可用".to_string())
+}
+
+#[cfg(not(windows))]
+fn start_distro(_distro: &str) -> Result<(), String> {
+    Err("WSL 环境仅在 Windows 下可用".to_string())
+}
+
+/// 以无窗口方式运行 wsl.exe
+#[cfg(windows)]
+fn wsl_exe(args: &[&str]) -> Result<std::process::Output, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    std::process::Command::new("wsl.exe")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("启动 wsl.exe 失败: {}", e))
+}
+
+/// 查询系统默认 WSL 发行版（`wsl.exe -l -q` 的第一项）
+#[cfg(windows)]
+pub fn default_distro() -> Result<String, String> {
+    let output = wsl_exe(&["-l", "-q"])?;
+    if !output.status.success() {
+        return Err(format!("wsl.exe 退出码: {:?}", output.status.code()));
+    }
+    decode_wsl_output(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .ok_or_else(|| "未安装任何 WSL 发行版".to_string())
+}
+
+#[cfg(not(windows))]
+pub fn default_distro() -> Result<String, String> {
+    Err("WSL 环境仅在 Windows 下可用".to_string())
+}
+
+/// 解码 wsl.exe 自身的输出：默认为 UTF-16LE（可能带 BOM），设置 WSL_UTF8=1 时为 UTF-8
+#[cfg(windows)]
+fn decode_wsl_output(bytes: &[u8]) -> String {
+    fn utf16le(bytes: &[u8]) -> String {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    }
+    // UTF-8 文本不会包含 NUL；UTF-16LE 的 ASCII 字符高字节为 0
+    let looks_utf16 = bytes.len() % 2 == 0
+        && (bytes.iter().skip(1).step_by(2).any(|b| *b == 0)
+            || std::str::from_utf8(bytes).is_err());
+    let text = match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16le(rest),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ if looks_utf16 => utf16le(bytes),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    };
+    text.replace('\0', "")
+}
+
+/// 已安装的 WSL 发行版
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WslDistro {
+    pub name: String,
+    /// WSL 版本（1 / 2）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u8>,
+    pub running: bool,
+    pub is_default: bool,
+}
+
+/// 列出已安装的发行版：名称、版本与默认标记来自 `wsl.exe -l -v`（表头随系统语言变化，跳过首行），
+/// 运行状态来自与语言无关的 `wsl.exe -l --running -q`
+#[cfg(windows)]
+pub fn list_distros() -> Result<Vec<WslDistro>, String> {
+    let output = wsl_exe(&["-l", "-v"])?;
+    if !output.status.success() {
+        return Err(format!("wsl.exe 退出码: {:?}", output.status.code()));
+    }
+    let running: HashSet<String> = wsl_exe(&["-l", "--running", "-q"])
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| {
+            decode_wsl_output(&o.stdout)
+                .lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(decode_wsl_output(&output.stdout)
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let line = line.trim();
+            let is_default = line.starts_with('*');
+            let mut cols = line.trim_start_matches('*').split_whitespace();
+            let name = cols.next()?.to_string();
+            let version = cols.last().and_then(|v| v.parse().ok());
+            Some(WslDistro {
+                running: running.contains(&name.to_lowercase()),
+                name,
+                version,
+                is_default,
+            })
+        })
+        .collect())
+}
+
+#[cfg(not(windows))]
+pub fn list_distros() -> Result<Vec<WslDistro>, String> {
+    Ok(Vec::new())
+}
+
+/// 主目录解析成功后的缓存时长（发行版内修改 $HOME 或重装后可在到期后自动生效）
+const HOME_CACHE_TTL: Duration = Duration::from_secs(600);
+/// 解析失败的缓存时长：避免反复启动 wsl.exe，又能在发行版启动后较快重试
+const HOME_ERROR_TTL: Duration = Duration::from_secs(30);
+
+struct CachedHome {
+    home: Result<PathBuf, String>,
+    resolved_at: Instant,
+}
+
+impl CachedHome {
+    fn is_fresh(&self) -> bool {
+        let ttl = if self.home.is_ok() {
+            HOME_CACHE_TTL
+        } else {
+            HOME_ERROR_TTL
+        };
+        self.resolved_at.elapsed() < ttl
+    }
+}
+
+/// 已解析的 WSL 发行版主目录（按发行版缓存，失败结果也短暂缓存）
+fn home_cache() -> &'static Mutex<HashMap<String, CachedHome>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CachedHome>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 清除主目录缓存（指定发行版或全部），下次访问时重新通过 wsl.exe 解析
+pub fn refresh_home(distro: Option<&str>) -> Result<(), String> {
+    let mut cache = home_cache()
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    match distro.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(distro) => {
+            cache.retain(|name, _| !name.eq_ignore_ascii_case(distro));
+        }
+        None => cache.clear(),
+    }
+    Ok(())
+}
+
+fn native_home() -> PathBuf {
+    crate::config::home_dir().expect("无法获取用户主目录")
+}
+
+/// 环境中的用户主目录：WSL 环境为 `\\wsl.localhost\<发行版>\home\<用户>`（旧系统为 `\\wsl$`），
+/// SSH 与 WSL exec 环境为本地镜像目录
+pub fn env_home(env: &NamedEnvironment) -> Result<PathBuf, String> {
+    match env.target_env {
+        TargetEnv::Native => return Ok(native_home()),
+        TargetEnv::Ssh => return Ok(crate::remote_env::mirror_home(env)),
+        TargetEnv::Wsl if crate::remote_env::uses_mirror(env) => {
+            return Ok(crate::remote_env::mirror_home(env))
+        }
+        TargetEnv::Wsl => {}
+    }
+    let distro = resolve_distro(env)?;
+    let distro = distro.as_str();
+
+    let mut cache = home_cache()
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    if let Some(cached) = cache.get(distro).filter(|c| c.is_fresh()) {
+        return cached.home.clone();
+    }
+    let home = wsl_home(distro);
+    cache.insert(
+        distro.to_string(),
+        CachedHome {
+            home: home.clone(),
+            resolved_at: Instant::now(),
+        },
+    );
+    home
+}
+
+/// 指定应用所在环境的用户主目录，解析失败时回落到本机主目录
+pub fn home_dir(app: &AppType) -> PathBuf {
+    let env = active_environment(&crate::settings::get_settings(), app);
+    env_home(&env).unwrap_or_else(|e| {
+        log::warn!("解析环境 {} 的主目录失败，使用本机目录: {}", env.name, e);
+        native_home()
+    })
+}
+
+/// 环境连通性测试结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentTest {
+    pub ok: bool,
+    /// 解析出的主目录（远程环境为本地镜像目录）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 新增或按名称替换环境定义
+pub fn upsert_environment(settings: &mut AppSettings, mut env: NamedEnvironment) {
+    env.name = env.name.trim().to_string();
+    match settings
+        .environments
+        .iter_mut()
+        .find(|e| e.name == env.name)
+    {
+        Some(existing) => *existing = env,
+        None => settings.environments.push(env),
+    }
+}
+
+/// 删除环境定义，并清除引用它的应用选择与镜像设置；返回是否存在该环境
+pub fn remove_environment(settings: &mut AppSettings, name: &str) -> bool {
+    let before = settings.environments.len();
+    settings.environments.retain(|e| e.name != name);
+    for selected in [
+        &mut settings.claude_environment,
+        &mut settings.codex_environment,
+        &mut settings.claude_mirror_environment,
+        &mut settings.codex_mirror_environment,
+    ] {
+        if selected.as_deref() == Some(name) {
+            *selected = None;
+        }
+    }
+    settings.environments.len() != before
+}
+
+/// 测试环境是否可用：WSL 检查发行版运行状态与主目录，SSH / exec 环境在远程执行一次探测
+pub fn test_environment(env: &NamedEnvironment) -> EnvironmentTest {
+    let result = ensure_env_ready(env, false).and_then(|_| {
+        if crate::remote_env::uses_mirror(env) && !crate::remote_env::remote_exists(env, ".")? {
+            return Err("远程主目录不存在".to_string());
+        }
+        env_home(env)
+    });
+    match result {
+        Ok(home) => EnvironmentTest {
+            ok: true,
+            home: Some(home.to_string_lossy().to_string()),
+            error: None,
+        },
+        Err(e) => EnvironmentTest {
+            ok: false,
+            home: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// 应用配置目录在主目录下的默认名称
+fn default_dir_name(app: &AppType) -> &'static str {
+    match app {
+        AppType::Claude => ".claude",
+        AppType::Codex => ".codex",
+    }
+}
+
+/// Linux 沙盒安装（Snap / Flatpak）中的配置目录
+#[derive(Debug, Clone)]
+struct SandboxDir {
+    /// 如 `snap:claude-code`、`flatpak:com.example.App`
+    label: String,
+    path: PathBuf,
+}
+
+/// 扫描沙盒安装的配置目录：Snap 应用的 HOME 为 ~/snap/<名称>/current，
+/// Flatpak 应用的数据位于 ~/.var/app/<应用 ID>
+#[cfg(target_os = "linux")]
+fn detect_sandbox_dir(dir_name: &str) -> Option<SandboxDir> {
+    let home = native_home();
+    let roots = [
+        ("snap", home.join("snap"), Some("current")),
+        ("flatpak", home.join(".var").join("app"), None),
+    ];
+    roots.into_iter().find_map(|(kind, root, sub)| {
+        let mut entries: Vec<_> = std::fs::read_dir(&root).ok()?.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+        entries.into_iter().find_map(|entry| {
+            let mut path = entry.path();
+            if let Some(sub) = sub {
+                path.push(sub);
+            }
+            path.push(dir_name);
+            path.is_dir().then(|| SandboxDir {
+                label: format!("{}:{}", kind, entry.file_name().to_string_lossy()),
+                path,
+            })
+        })
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_sandbox_dir(_dir_name: &str) -> Option<SandboxDir> {
+    None
+}
+
+/// 本机环境下默认目录不存在时使用的沙盒配置目录（进程内只扫描一次）
+fn sandbox_dir(app: &AppType) -> Option<SandboxDir> {
+    static DIRS: OnceLock<(Option<SandboxDir>, Option<SandboxDir>)> = OnceLock::new();
+    let (claude, codex) = DIRS.get_or_init(|| {
+        (
+            detect_sandbox_dir(default_dir_name(&AppType::Claude)),
+            detect_sandbox_dir(default_dir_name(&AppType::Codex)),
+        )
+    });
+    match app {
+        AppType::Claude => claude.clone(),
+        AppType::Codex => codex.clone(),
+    }
+}
+
+/// 解析应用配置目录，同时返回命中的沙盒安装标签
+fn resolve_config_dir(app: &AppType) -> (PathBuf, Option<String>) {
+    let custom = match app {
+        AppType::Claude => crate::settings::get_claude_override_dir(),
+        AppType::Codex => crate::settings::get_codex_override_dir(),
+    };
+    if let Some(custom) = custom {
+        return (custom, None);
+    }
+    let env = active_environment(&crate::settings::get_settings(), app);
+    let default = home_dir(app).join(default_dir_name(app));
+    if env.target_env == TargetEnv::Native && !default.exists() {
+        if let Some(sandbox) = sandbox_dir(app) {
+            return (sandbox.path, Some(sandbox.label));
+        }
+    }
+    (default, None)
+}
+
+/// 应用配置目录：设置中的绝对路径覆盖优先，其次为所选环境主目录下的默认目录；
+/// 本机默认目录不存在时使用检测到的 Snap / Flatpak 安装目录
+pub fn config_dir(app: &AppType) -> PathBuf {
+    resolve_config_dir(app).0
+}
+
+/// 当前配置目录来自沙盒安装时返回其标签
+pub fn sandbox_label(app: &AppType) -> Option<String> {
+    resolve_config_dir(app).1
+}
+
+/// 各环境下应用配置目录的存在情况；选中环境遵循设置中的目录覆盖
+pub fn environment_status(app: &AppType) -> Vec<EnvironmentStatus> {
+    let settings = crate::settings::get_settings();
+    let active = active_environment(&settings, app).name;
+    registry(&settings)
+        .into_iter()
+        .map(|env| {
+            let is_active = env.name == active;
+            if crate::remote_env::uses_mirror(&env) {
+                return remote_status(app, env, is_active);
+            }
+            let dir = if is_active {
+                Ok(config_dir(app))
+            } else {
+                env_home(&env).map(|home| home.join(default_dir_name(app)))
+            };
+            let (path, exists, error) = match dir {
+                Ok(dir) => (Some(dir.to_string_lossy().to_string()), dir.exists(), None),
+                Err(e) => (None, false, Some(e)),
+            };
+            let (wsl_version, unc_scheme) = if env.target_env == TargetEnv::Wsl {
+                let version = resolve_distro(&env).ok().and_then(|d| wsl_version(&d));
+                let scheme = path.as_deref().and_then(|p| {
+                    [UNC_LOCALHOST, UNC_LEGACY]
+                        .into_iter()
+                        .find(|prefix| p.starts_with(prefix))
+                        .map(|prefix| prefix.to_string())
+                });
+                (version, scheme)
+            } else {
+                (None, None)
+            };
+            EnvironmentStatus {
+                name: env.name,
+                target_env: env.target_env,
+                wsl_distro: env.wsl_distro,
+                active: is_active,
+                path,
+                exists,
+                error,
+                wsl_version,
+                unc_scheme,
+            }
+        })
+        .collect()
+}
+
+/// 将选中环境中的 live 文件同步写入镜像环境（未配置镜像或与选中环境相同时不做任何事）
+pub fn mirror_live(app: &AppType) -> Result<(), String> {
+    let settings = crate::settings::get_settings();
+    let Some(name) = mirror_name(&settings, app) else {
+        return Ok(());
+    };
+    if name == active_environment(&settings, app).name {
+        return Ok(());
+    }
+    let target = registry(&settings)
+        .into_iter()
+        .find(|env| env.name == name)
+        .ok_or_else(|| format!("镜像环境不存在: {}", name))?;
+    ensure_env_ready(&target, true)?;
+
+    let source = config_dir(app);
+    let file_names: Vec<String> = match app {
+        AppType::Claude => crate::config::get_claude_settings_path()
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .into_iter()
+            .collect(),
+        AppType::Codex => vec!["auth.json".to_string(), "config.toml".to_string()],
+    };
+    let dir_name = default_dir_name(app);
+    for file_name in file_names {
+        let path = source.join(&file_name);
+        if !path.exists() {
+            continue;
+        }
+        let data =
+            std::fs::read(&path).map_err(|e| format!("读取文件失败: {}: {}", path.display(), e))?;
+        if crate::remote_env::uses_mirror(&target) {
+            crate::remote_env::push_file(&target, &format!("{}/{}", dir_name, file_name), &data)?;
+        } else {
+            let dest = env_home(&target)?.join(dir_name).join(&file_name);
+            crate::config::atomic_write(&dest, &data)?;
+        }
+    }
+    Ok(())
+}
+
+/// 经远程 shell 访问的环境直接检查环境内主目录下的配置目录
+fn remote_status(app: &AppType, env: NamedEnvironment, active: bool) -> EnvironmentStatus {
+    let rel = default_dir_name(app);
+    let (exists, error) = match crate::remote_env::remote_exists(&env, rel) {
+        Ok(exists) => (exists, None),
+        Err(e) => (false, Some(e)),
+    };
+    let path = match env.target_env {
+        TargetEnv::Ssh => env
+            .ssh
+            .as_ref()
+            .map(|t| format!("{}:~/{}", t.host.trim(), rel)),
+        _ => env
+            .wsl_distro
+            .as_deref()
+            .map(|d| format!("{}:~/{}", d.trim(), rel)),
+    };
+    let wsl_version = match env.target_env {
+        TargetEnv::Wsl => resolve_distro(&env).ok().and_then(|d| wsl_version(&d)),
+        _ => None,
+    };
+    EnvironmentStatus {
+        name: env.name,
+        target_env: env.target_env,
+        wsl_distro: env.wsl_distro,
+        active,
+        path,
+        exists,
+        error,
+        wsl_version,
+        unc_scheme: None,
+    }
+}
+
+#[cfg(windows)]
+fn wsl_home(distro: &str) -> Result<PathBuf, String> {
+    let output = wsl_exe(&["-d", distro, "-e", "sh", "-c", "printf %s \"$HOME\""])?;
+    if !output.status.success() {
+        return Err(format!("wsl.exe 退出码: {:?}", output.status.code()));
+    }
+    let home = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !home.starts_with('/') {
+        return Err(format!("无法识别的主目录: {}", home));
+    }
+    // 较新的 Windows 提供 \\wsl.localhost，旧版本仅支持 \\wsl$
+    let prefix = [UNC_LOCALHOST, UNC_LEGACY]
+        .into_iter()
+        .find(|prefix| PathBuf::from(format!(r"{}\{}", prefix, distro)).is_dir())
+        .unwrap_or(UNC_LEGACY);
+    Ok(PathBuf::from(format!(
+        r"{}\{}{}",
+        prefix,
+        distro,
+        home.replace('/', "\\")
+    )))
+}
+
+/// 修正选中 WSL 环境（UNC 写入方式）中 live 文件的所有者与权限，返回处理的 Linux 路径；
+/// `force` 为 false 时遵循环境的 wslFixPermissions 设置
+#[cfg(windows)]
+pub fn fix_live_permissions(app: &AppType, force: bool) -> Result<Vec<String>, String> {
+    let env = active_environment(&crate::settings::get_settings(), app);
+    if env.target_env != TargetEnv::Wsl
+        || crate::remote_env::uses_mirror(&env)
+        || !(force || env.wsl_fix_permissions)
+    {
+        return Ok(Vec::new());
+    }
+    let distro = resolve_distro(&env)?;
+    let home = env_home(&env)?;
+    let to_linux = |path: &std::path::Path| -> Option<String> {
+        let text = path.to_string_lossy();
+        [UNC_LOCALHOST, UNC_LEGACY].into_iter().find_map(|prefix| {
+            text.strip_prefix(&format!(r"{}\{}", prefix, distro))
+                .map(|rest| rest.replace('\\', "/"))
+        })
+    };
+    let linux_home =
+        to_linux(home.as_path()).ok_or_else(|| "无法解析发行版内的主目录".to_string())?;
+    let files: Vec<String> = match app {
+        AppType::Claude => vec![crate::config::get_claude_settings_path()],
+        AppType::Codex => vec![
+            crate::codex_config::get_codex_auth_path(),
+            crate::codex_config::get_codex_config_path(),
+        ],
+    }
+    .iter()
+    .filter(|p| p.exists())
+    .filter_map(|p| to_linux(p.as_path()))
+    .collect();
+    if files.is_empty() {
+        return Ok(files);
+    }
+
+    let quote = |p: &str| format!("'{}'", p.replace('\'', "'\\''"));
+    let quoted = files.iter().map(|f| quote(f)).collect::<Vec<_>>().join(" ");
+    // 以 root 执行 chown，所有者取自用户主目录
+    let script = format!(
+        "chown --reference={} {files} && chmod 600 {files}",
+        quote(&linux_home),
+        files = quoted
+    );
+    let output = wsl_exe(&["-d", &distro, "-u", "root", "-e", "sh", "-c", &script])?;
+    if !output.status.success() {
+        return Err(format!(
+            "修正 WSL 文件权限失败: {}",
+            decode_wsl_output(&output.stderr).trim()
+        ));
+    }
+    Ok(files)
+}
+
+#[cfg(not(windows))]
+pub fn fix_live_permissions(_app: &AppType, _force: bool) -> Result<Vec<String>, String> {
+    Ok(Vec::new())
+}
+
+/// 发行版的 WSL 版本
+fn wsl_version(distro: &str) -> Option<u8> {
+    list_distros()
+        .ok()?
+        .into_iter()
+        .find(|d| d.name.eq_ignore_ascii_case(distro))
+        .and_then(|d| d.version)
+}
+
+#[cfg(not(windows))]
+fn wsl_home(_distro: &str) -> Result<PathBuf, String> {
+    Err("WSL 环境仅在 Windows 下可用".to_string())
+}
@@ -1,11 +1,17 @@
 import { useState, useEffect, useRef } from "react";
 import { useTranslation } from "react-i18next";
-import { Provider } from "./types";
+import {
+  Provider,
+  InterruptedSwitch,
+  SwitchRecoveryAction,
+  DriftPolicy,
+} from "./types";
 import { AppType } from "./lib/tauri-api";
 import ProviderList from "./components/ProviderList";
 import AddProviderModal from "./components/AddProviderModal";
 import EditProviderModal from "./components/EditProviderModal";
 import { ConfirmDialog } from "./components/ConfirmDialog";
+import { InterruptedSwitchDialog } from "./components/InterruptedSwitchDialog";
 import { AppSwitcher } from "./components/AppSwitcher";
 import SettingsModal from "./components/SettingsModal";
 import { UpdateBadge } from "./components/UpdateBadge";
@@ -13,10 +19,13 @@ import { Plus, Settings, Moon, Sun } from "lucide-react";
 import McpPanel from "./components/mcp/McpPanel";
 import { buttonStyles } from "./lib/styles";
 import { useDarkMode } from "./hooks/useDarkMode";
-import { extractErrorMessage } from "./utils/errorUtils";
+import {
+  translateCommandError,
+  getLiveDriftReport,
+} from "./utils/errorUtils";
 
 function App() {
-  const { t } = useTranslation();
+  const { t, i18n } = useTranslation();
   const { isDarkMode, toggleDarkMode } = useDarkMode();
   const [activeApp, setActiveApp] = useState<AppType>("claude");
   const [providers, setProviders] = useState<Record<string, Provider>>({});
@@ -36,6 +45,10 @@ function App() {
     message: string;
     onConfirm: () => void;
   } | null>(null);
+  const [interruptedSwitches, setInterruptedSwitches] = useState<
+    InterruptedSwitch[]
+  >([]);
+  const [isRecovering, setIsRecovering] = useState(false);
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
   const [isMcpOpen, setIsMcpOpen] = useState(false);
   const timeoutRef = useRef<ReturnType<typeof setTimeout> | null>(null);
@@ -71,6 +84,42 @@ function App() {
     loadProviders();
   }, [activeApp]); // 当切换应用时重新加载
 
+  // 启动时检查上次被中断的切换，逐个提示用户回滚或完成
+  useEffect(() => {
+    window.api
+      .getInterruptedSwitches()
+      .then(setInterruptedSwitches)
+      .catch((error) => {
+        console.error(t("console.loadInterruptedSwitchesFailed"), error);
+      });
+  }, []);
+
+  const handleRecoverSwitch = async (action: SwitchRecoveryAction) => {
+    const [interrupted] = interruptedSwitches;
+    if (!interrupted) return;
+    setIsRecovering(true);
+    try {
+      await window.api.recoverSwitch(interrupted.appType as AppType, action);
+      setInterruptedSwitches((items) => items.slice(1));
+      if (interrupted.appType === activeApp) {
+        await loadProviders();
+      }
+      await window.api.updateTrayMenu();
+      if (action !== "dismiss") {
+        showNotification(t("interruptedSwitch.resolved"), "success");
+      }
+    } catch (error) {
+      const errorMessage = translateCommandError(error, t);
+      showNotification(
+        t("interruptedSwitch.failed", { error: errorMessage }),
+        "error",
+        6000,
+      );
+    } finally {
+      setIsRecovering(false);
+    }
+  };
+
   // 清理定时器
   useEffect(() => {
     return () => {
@@ -116,6 +165,130 @@ function App() {
     };
   }, [activeApp]);
 
+  // 监听 ccswitch://provider 分享链接：确认后导入为新供应商
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+
+    const setupListener = async () => {
+      try {
+        unlisten = await window.api.onShareLinkReceived((share) => {
+          setConfirmDialog({
+            isOpen: true,
+            title: t("confirm.importShare"),
+            message: t("confirm.importShareMessage", {
+              name: share.name,
+              appName: t(`apps.${share.app}`),
+            }),
+            onConfirm: async () => {
+              setConfirmDialog(null);
+              try {
+                await window.api.importProviderShare(share.payload);
+                if (share.app === activeApp) {
+                  await loadProviders();
+                } else {
+                  setActiveApp(share.app);
+                }
+                await window.api.updateTrayMenu();
+                showNotification(
+                  t("notifications.shareImported", { name: share.name }),
+                  "success",
+                );
+              } catch (error) {
+                const errorMessage = translateCommandError(error, t);
+                showNotification(
+                  t("notifications.shareImportFailed", { error: errorMessage }),
+                  "error",
+                  6000,
+                );
+              }
+            },
+          });
+        });
+      } catch (error) {
+        console.error(t("console.setupListenerFailed"), error);
+      }
+    };
+
+    setupListener();
+
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, [activeApp]);
+
+  // 监听后台保存失败（编辑会延迟落盘，失败时不会体现在命令返回值中）
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+
+    const setupListener = async () => {
+      try {
+        unlisten = await window.api.onConfigSaveFailed((error) => {
+          const errorMessage = translateCommandError(error, t);
+          showNotification(
+            t("notifications.saveFailed", { error: errorMessage }),
+            "error",
+            6000,
+          );
+        });
+      } catch (error) {
+        console.error(t("console.setupListenerFailed"), error);
+      }
+    };
+
+    setupListener();
+
+    return () => {
+      if (unlisten) {
+        unlisten();
+      }
+    };
+  }, []);
+
+  // 监听 config.json 的外部修改（手动编辑、同步客户端或其他实例）
+  useEffect(() => {
+    const unlisteners: Array<() => void> = [];
+
+    const setupListeners = async () => {
+      try {
+        unlisteners.push(
+          await window.api.onConfigReloaded(async () => {
+            await loadProviders();
+            showNotification(t("notifications.configReloaded"), "success", 3000);
+          }),
+        );
+        unlisteners.push(
+          await window.api.onSettingsChanged((settings) => {
+            // 导入、恢复备份等流程也会改写设置，界面语言随之同步
+            const language = settings.language === "en" ? "en" : "zh";
+            if (i18n.language !== language) {
+              void i18n.changeLanguage(language);
+            }
+          }),
+        );
+        unlisteners.push(
+          await window.api.onConfigExternalConflict((error) => {
+            const errorMessage = translateCommandError(error, t);
+            showNotification(
+              t("notifications.configExternalConflict", { error: errorMessage }),
+              "error",
+              6000,
+            );
+          }),
+        );
+      } catch (error) {
+        console.error(t("console.setupListenerFailed"), error);
+      }
+    };
+
+    setupListeners();
+
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten());
+    };
+  }, [activeApp]);
+
   const loadProviders = async () => {
     const loadedProviders = await window.api.getProviders(activeApp);
     const currentId = await window.api.getCurrentProvider(activeApp);
@@ -158,7 +331,7 @@ function App() {
     } catch (error) {
       console.error(t("console.updateProviderFailed"), error);
       setEditingProviderId(null);
-      const errorMessage = extractErrorMessage(error);
+      const errorMessage = translateCommandError(error, t);
       const message = errorMessage
         ? t("notifications.saveFailed", { error: errorMessage })
         : t("notifications.saveFailedGeneric");
@@ -214,9 +387,16 @@ function App() {
     }
   };
 
-  const handleSwitchProvider = async (id: string) => {
+  const handleSwitchProvider = async (
+    id: string,
+    driftPolicy?: DriftPolicy,
+  ) => {
     try {
-      const success = await window.api.switchProvider(id, activeApp);
+      const success = await window.api.switchProvider(
+        id,
+        activeApp,
+        driftPolicy,
+      );
       if (success) {
         setCurrentProviderId(id);
         // 显示重启提示
@@ -236,7 +416,24 @@ function App() {
         showNotification(t("notifications.switchFailed"), "error");
       }
     } catch (error) {
-      const detail = extractErrorMessage(error);
+      // live 被外部修改（如重新登录）：确认后将其回填到当前供应商再切换
+      const drift = getLiveDriftReport(error);
+      if (drift && !driftPolicy) {
+        setConfirmDialog({
+          isOpen: true,
+          title: t("confirm.liveDrift"),
+          message: t("confirm.liveDriftMessage", {
+            name: drift.currentName,
+            count: drift.changes.length,
+          }),
+          onConfirm: () => {
+            setConfirmDialog(null);
+            void handleSwitchProvider(id, "backfill");
+          },
+        });
+        return;
+      }
+      const detail = translateCommandError(error, t);
       const msg = detail
         ? `${t("notifications.switchFailed")}: ${detail}`
         : t("notifications.switchFailed");
@@ -389,6 +586,14 @@ function App() {
         />
       )}
 
+      {interruptedSwitches.length > 0 && (
+        <InterruptedSwitchDialog
+          interrupted={interruptedSwitches[0]}
+          busy={isRecovering}
+          onResolve={handleRecoverSwitch}
+        />
+      )}
+
       {isSettingsOpen && (
         <SettingsModal
           onClose={() => setIsSettingsOpen(false)}
@@ -0,0 +1,89 @@
+import React from "react";
+import { useTranslation } from "react-i18next";
+import { AlertTriangle } from "lucide-react";
+import { isLinux } from "../lib/platform";
+import { InterruptedSwitch, SwitchRecoveryAction } from "../types";
+
+interface InterruptedSwitchDialogProps {
+  interrupted: InterruptedSwitch;
+  busy: boolean;
+  onResolve: (action: SwitchRecoveryAction) => void;
+}
+
+// 上次切换在写入 live 后中断：由用户选择回滚、完成切换或仅清除记录
+export const InterruptedSwitchDialog: React.FC<InterruptedSwitchDialogProps> = ({
+  interrupted,
+  busy,
+  onResolve,
+}) => {
+  const { t } = useTranslation();
+  const appName = t(`apps.${interrupted.appType}`, interrupted.appType);
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center">
+      {/* Backdrop */}
+      <div
+        className={`absolute inset-0 bg-black/50${isLinux() ? "" : " backdrop-blur-sm"}`}
+      />
+
+      {/* Dialog */}
+      <div className="relative bg-white dark:bg-gray-900 rounded-xl shadow-lg max-w-md w-full mx-4 overflow-hidden">
+        {/* Header */}
+        <div className="flex items-center gap-3 p-6 border-b border-gray-200 dark:border-gray-800">
+          <div className="w-10 h-10 bg-amber-100 dark:bg-amber-500/10 rounded-full flex items-center justify-center">
+            <AlertTriangle size={20} className="text-amber-500" />
+          </div>
+          <h3 className="text-lg font-semibold text-gray-900 dark:text-gray-100">
+            {t("interruptedSwitch.title")}
+          </h3>
+        </div>
+
+        {/* Content */}
+        <div className="p-6 space-y-3">
+          <p className="text-gray-500 dark:text-gray-400 leading-relaxed">
+            {t("interruptedSwitch.message", {
+              appName,
+              target: interrupted.targetId,
+              count: interrupted.modifiedFiles.length,
+            })}
+          </p>
+          <ul className="text-xs font-mono text-gray-500 dark:text-gray-400 space-y-1 break-all">
+            {interrupted.modifiedFiles.map((path) => (
+              <li key={path}>{path}</li>
+            ))}
+          </ul>
+        </div>
+
+        {/* Actions */}
+        <div className="flex items-center justify-end gap-3 p-6 border-t border-gray-200 dark:border-gray-800 bg-gray-100 dark:bg-gray-900">
+          <button
+            onClick={() => onResolve("dismiss")}
+            disabled={busy}
+            className="px-4 py-2 text-sm font-medium text-gray-500 hover:text-gray-900 hover:bg-white dark:text-gray-400 dark:hover:text-gray-100 dark:hover:bg-gray-800 rounded-md transition-colors disabled:opacity-50"
+          >
+            {t("interruptedSwitch.dismiss")}
+          </button>
+          {interrupted.canComplete && (
+            <button
+              onClick={() => onResolve("complete")}
+              disabled={busy}
+              className="px-4 py-2 text-sm font-medium text-gray-700 bg-white hover:bg-gray-50 dark:text-gray-200 dark:bg-gray-800 dark:hover:bg-gray-700 rounded-md transition-colors disabled:opacity-50"
+            >
+              {t("interruptedSwitch.complete")}
+            </button>
+          )}
+          {interrupted.canRollback && (
+            <button
+              onClick={() => onResolve("rollback")}
+              disabled={busy}
+              className="px-4 py-2 text-sm font-medium bg-blue-500 text-white hover:bg-blue-500/90 rounded-md transition-colors disabled:opacity-50"
+              autoFocus
+            >
+              {t("interruptedSwitch.rollback")}
+            </button>
+          )}
+        </div>
+      </div>
+    </div>
+  );
+};
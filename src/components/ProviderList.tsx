@@ -1,6 +1,6 @@
 import React, { useState } from "react";
 import { useTranslation } from "react-i18next";
-import { Provider, UsageScript } from "../types";
+import { CodexAuthMode, Provider, UsageScript } from "../types";
 import { AppType } from "../lib/tauri-api";
 import { Play, Edit3, Trash2, CheckCircle2, Users, Check, BarChart3, GripVertical } from "lucide-react";
 import { buttonStyles, badgeStyles, cn } from "../lib/styles";
@@ -40,6 +40,17 @@ interface ProviderListProps {
   onProvidersUpdated?: () => Promise<void>;
 }
 
+// Codex 认证方式：优先使用显式设置，否则按 auth.json 内容推断
+const getCodexAuthMode = (provider: Provider): CodexAuthMode | undefined => {
+  if (provider.authMode) return provider.authMode;
+  const auth = provider.settingsConfig?.auth;
+  if (auth?.tokens && typeof auth.tokens === "object") return "chatgpt";
+  if (typeof auth?.OPENAI_API_KEY === "string" && auth.OPENAI_API_KEY.trim()) {
+    return "apiKey";
+  }
+  return undefined;
+};
+
 // Sortable Provider Item Component
 interface SortableProviderItemProps {
   provider: Provider;
@@ -128,6 +139,13 @@ const SortableProviderItem: React.FC<SortableProviderItemProps> = ({
               <CheckCircle2 size={12} />
               {t("provider.currentlyUsing")}
             </div>
+            {appType === "codex" && getCodexAuthMode(provider) && (
+              <div className={badgeStyles.info}>
+                {getCodexAuthMode(provider) === "chatgpt"
+                  ? t("provider.authModeChatgpt")
+                  : t("provider.authModeApiKey")}
+              </div>
+            )}
           </div>
 
           <div className="flex items-center gap-2 text-sm">
@@ -148,7 +148,9 @@ export default function SettingsModal({
           : persistedLanguage,
       );
 
+      // 保留后端返回的完整设置，保存时整体回写，避免丢失本界面未展示的字段
       setSettings({
+        ...loadedSettings,
         showInTray,
         minimizeToTrayOnClose,
         enableClaudePluginIntegration:
@@ -322,7 +324,7 @@ export default function SettingsModal({
   const handleCheckUpdate = async () => {
     if (hasUpdate && updateHandle) {
       if (isPortable) {
-        await window.api.checkForUpdates();
+        await window.api.openReleasesPage();
         return;
       }
       // 已检测到更新：直接复用 updateHandle 下载并安装，避免重复检查
@@ -334,7 +336,7 @@ export default function SettingsModal({
       } catch (error) {
         console.error(t("console.updateFailed"), error);
         // 更新失败时回退到打开 Releases 页面
-        await window.api.checkForUpdates();
+        await window.api.openReleasesPage();
       } finally {
         setIsDownloading(false);
       }
@@ -362,7 +364,7 @@ export default function SettingsModal({
           }, 3000);
         } else {
           // 生产环境下如果更新插件不可用，回退到打开 Releases 页面
-          await window.api.checkForUpdates();
+          await window.api.openReleasesPage();
         }
       } finally {
         setIsCheckingUpdate(false);
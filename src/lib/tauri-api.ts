@@ -1,14 +1,37 @@
-import { invoke } from "@tauri-apps/api/core";
+import { invoke as tauriInvoke, InvokeArgs } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import {
   Provider,
+  ProviderQuery,
+  ProviderSummary,
   Settings,
+  TaskInfo,
+  StateChange,
+  BackendInfo,
+  AllStatus,
+  InterruptedSwitch,
+  SwitchRecoveryAction,
+  DriftPolicy,
+  ReceivedShare,
+  ConfigFragment,
   CustomEndpoint,
   McpStatus,
   McpServer,
   McpServerSpec,
   McpConfigResponse,
+  UpdateCheck,
+  LatestRelease,
 } from "../types";
+import { toCommandError } from "../utils/errorUtils";
+
+// 后端错误为 { code, message, params }，统一包装为 CommandError 抛出
+const invoke = async <T>(cmd: string, args?: InvokeArgs): Promise<T> => {
+  try {
+    return await tauriInvoke<T>(cmd, args);
+  } catch (error) {
+    throw toCommandError(error);
+  }
+};
 
 // 应用类型
 export type AppType = "claude" | "codex";
@@ -45,6 +68,11 @@ export const tauriAPI = {
     }
   },
 
+  // 按条件查询供应商摘要（不含配置与密钥）
+  queryProviders: async (query?: ProviderQuery): Promise<ProviderSummary[]> => {
+    return await invoke("query_providers", { query });
+  },
+
   // 获取当前供应商ID
   getCurrentProvider: async (app?: AppType): Promise<string> => {
     try {
@@ -78,6 +106,25 @@ export const tauriAPI = {
     }
   },
 
+  // 设置供应商模型（空字符串表示不指定）
+  setProviderModel: async (
+    id: string,
+    model: string | null,
+    app?: AppType,
+  ): Promise<boolean> => {
+    try {
+      return await invoke("set_provider_model", {
+        id,
+        model,
+        app_type: app,
+        app,
+      });
+    } catch (error) {
+      console.error("设置供应商模型失败:", error);
+      throw error;
+    }
+  },
+
   // 删除供应商
   deleteProvider: async (id: string, app?: AppType): Promise<boolean> => {
     try {
@@ -92,12 +139,14 @@ export const tauriAPI = {
   switchProvider: async (
     providerId: string,
     app?: AppType,
+    driftPolicy?: DriftPolicy,
   ): Promise<boolean> => {
     try {
       return await invoke("switch_provider", {
         id: providerId,
         app_type: app,
         app,
+        driftPolicy,
       });
     } catch (error) {
       // 让调用方拿到后端的详细错误信息
@@ -138,6 +187,16 @@ export const tauriAPI = {
     }
   },
 
+  // 获取后端版本与支持的功能
+  getBackendInfo: async (): Promise<BackendInfo> => {
+    return await invoke("get_backend_info");
+  },
+
+  // 一次获取所有应用的配置状态、当前供应商、漂移与环境信息
+  getAllStatus: async (): Promise<AllStatus> => {
+    return await invoke("get_all_status");
+  },
+
   // 获取当前生效的配置目录
   getConfigDir: async (app?: AppType): Promise<string> => {
     try {
@@ -209,6 +268,21 @@ export const tauriAPI = {
     }
   },
 
+  // 重新生成本机 HTTP 接口令牌，返回新令牌
+  regenerateHttpApiToken: async (): Promise<string> => {
+    return await invoke("regenerate_http_api_token");
+  },
+
+  // 退出应用程序
+  quitApp: async (): Promise<boolean> => {
+    try {
+      return await invoke("quit_app");
+    } catch (error) {
+      console.error("退出应用失败:", error);
+      return false;
+    }
+  },
+
   // 重启应用程序
   restartApp: async (): Promise<boolean> => {
     try {
@@ -219,12 +293,42 @@ export const tauriAPI = {
     }
   },
 
-  // 检查更新
-  checkForUpdates: async (): Promise<void> => {
+  // 检查更新（由后端 updater 查询最新版本）
+  checkForUpdates: async (): Promise<UpdateCheck | null> => {
     try {
-      await invoke("check_for_updates");
+      return await invoke<UpdateCheck>("check_for_updates");
     } catch (error) {
       console.error("检查更新失败:", error);
+      return null;
+    }
+  },
+
+  // 查询 GitHub 最新发布（用于展示更新提示）
+  getLatestRelease: async (): Promise<LatestRelease | null> => {
+    try {
+      return await invoke<LatestRelease>("get_latest_release");
+    } catch (error) {
+      console.error("查询最新版本失败:", error);
+      return null;
+    }
+  },
+
+  // 下载并安装更新（完成后应用自动重启）
+  installUpdate: async (): Promise<boolean> => {
+    try {
+      return await invoke<boolean>("install_update");
+    } catch (error) {
+      console.error("安装更新失败:", error);
+      return false;
+    }
+  },
+
+  // 打开 GitHub Releases 页面
+  openReleasesPage: async (): Promise<void> => {
+    try {
+      await invoke("open_releases_page");
+    } catch (error) {
+      console.error("打开更新页面失败:", error);
     }
   },
 
@@ -664,6 +768,116 @@ export const tauriAPI = {
     return unlisten;
   },
 
+  // 监听后台保存配置失败事件（修改会延迟落盘，失败时由后端推送错误）
+  onConfigSaveFailed: async (
+    callback: (error: unknown) => void,
+  ): Promise<UnlistenFn> => {
+    const unlisten = await listen("config-save-failed", (event) => {
+      callback(toCommandError(event.payload));
+    });
+    return unlisten;
+  },
+
+  // 监听 config.json 被外部修改后重新加载的事件
+  onConfigReloaded: async (callback: () => void): Promise<UnlistenFn> => {
+    return await listen("config-reloaded", () => callback());
+  },
+
+  // 监听 ccswitch://provider 分享链接
+  onShareLinkReceived: async (
+    callback: (share: ReceivedShare) => void,
+  ): Promise<UnlistenFn> => {
+    return await listen<ReceivedShare>("share-link-received", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  // 从分享链接 / 二维码载荷创建新供应商
+  importProviderShare: async (
+    payload: string,
+    name?: string,
+  ): Promise<Provider> => {
+    return await invoke("import_provider_share", { payload, name });
+  },
+
+  // 列出后台任务（运行中与最近结束的任务）
+  listTasks: async (): Promise<TaskInfo[]> => {
+    return await invoke("list_tasks");
+  },
+
+  // 取消运行中的后台任务
+  cancelTask: async (id: string): Promise<boolean> => {
+    return await invoke("cancel_task", { id });
+  },
+
+  // 获取上次未完成的切换
+  getInterruptedSwitches: async (): Promise<InterruptedSwitch[]> => {
+    return await invoke("get_interrupted_switches");
+  },
+
+  // 处理未完成的切换，返回处理后的当前供应商 ID
+  recoverSwitch: async (
+    app: AppType,
+    action: SwitchRecoveryAction,
+  ): Promise<string> => {
+    return await invoke("recover_switch", {
+      app_type: app,
+      app,
+      appType: app,
+      action,
+    });
+  },
+
+  // Codex 配置片段
+  getCodexFragments: async (): Promise<ConfigFragment[]> => {
+    return await invoke("get_codex_fragments");
+  },
+
+  saveCodexFragment: async (fragment: ConfigFragment): Promise<boolean> => {
+    return await invoke("save_codex_fragment", { fragment });
+  },
+
+  // 仍被供应商引用的片段无法删除
+  deleteCodexFragment: async (id: string): Promise<boolean> => {
+    return await invoke("delete_codex_fragment", { id });
+  },
+
+  // 监听后台任务状态与进度变化
+  onTaskUpdated: async (
+    callback: (task: TaskInfo) => void,
+  ): Promise<UnlistenFn> => {
+    return await listen<TaskInfo>("task-updated", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  // 监听统一状态事件（供应商增删改与切换、设置、同步、任务等）
+  onStateChanged: async (
+    callback: (change: StateChange) => void,
+  ): Promise<UnlistenFn> => {
+    return await listen<StateChange>("state-changed", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  // 监听设置变更事件（任意命令保存设置后触发，携带最新设置）
+  onSettingsChanged: async (
+    callback: (settings: Settings) => void,
+  ): Promise<UnlistenFn> => {
+    return await listen<Settings>("settings-changed", (event) => {
+      callback(event.payload);
+    });
+  },
+
+  // 监听 config.json 外部修改与未保存更改冲突的事件
+  onConfigExternalConflict: async (
+    callback: (error: unknown) => void,
+  ): Promise<UnlistenFn> => {
+    return await listen("config-external-conflict", (event) => {
+      callback(toCommandError(event.payload));
+    });
+  },
+
   // 获取 app_config_dir 覆盖配置(从 Store)
   getAppConfigDirOverride: async (): Promise<string | null> => {
     try {
@@ -16,6 +16,48 @@ export interface Provider {
   sortIndex?: number; // 排序索引（用于自定义拖拽排序）
   // 可选：供应商元数据（仅存于 ~/.cc-switch/config.json，不写入 live 配置）
   meta?: ProviderMeta;
+  // 仅 Codex：认证方式，未设置时按 auth.json 内容推断
+  authMode?: CodexAuthMode;
+  // 仅 Codex：引用的配置片段 ID（按顺序合并，供应商自身的 config 最后合并）
+  configFragments?: string[];
+  // 切换时写入的模型（Claude：env.ANTHROPIC_MODEL；Codex：config.toml 的 model）
+  model?: string;
+}
+
+// Codex 配置片段：多个供应商共用的 config.toml 内容
+export interface ConfigFragment {
+  id: string;
+  name: string;
+  content: string; // TOML 文本
+}
+
+// Codex 认证方式：API Key 或 ChatGPT 登录
+export type CodexAuthMode = "apiKey" | "chatgpt";
+
+// 供应商列表查询条件（query_providers）
+export interface ProviderQuery {
+  app?: "claude" | "codex";
+  category?: ProviderCategory;
+  current?: boolean; // true 仅当前供应商，false 排除当前供应商
+  keyword?: string; // 匹配名称或请求地址
+  sort?: "manual" | "name" | "lastUsed";
+  limit?: number;
+}
+
+// 供应商摘要：不含 settingsConfig 与密钥
+export interface ProviderSummary {
+  appType: "claude" | "codex";
+  id: string;
+  name: string;
+  category?: ProviderCategory;
+  websiteUrl?: string;
+  baseUrl: string;
+  hasApiKey: boolean;
+  authMode?: CodexAuthMode; // 仅 Codex
+  isCurrent: boolean;
+  sortIndex?: number;
+  createdAt?: number;
+  recentRank?: number; // 在最近切换列表中的位置（0 为最近一次）
 }
 
 export interface AppConfig {
@@ -83,6 +125,147 @@ export interface Settings {
   customEndpointsClaude?: Record<string, CustomEndpoint>;
   // Codex 自定义端点列表
   customEndpointsCodex?: Record<string, CustomEndpoint>;
+  // 托盘图标叠加当前供应商颜色徽标
+  showTrayBadge?: boolean;
+  // macOS 菜单栏显示当前供应商名称
+  showTrayTitle?: boolean;
+  // 后台定期检查更新及间隔（小时）
+  autoCheckUpdates?: boolean;
+  updateCheckIntervalHours?: number;
+  // 登录系统时自动启动
+  launchAtLogin?: boolean;
+  // 系统通知
+  notificationsEnabled?: boolean;
+  // live 备份保留策略（0 表示不限制）
+  backupMaxCount?: number;
+  backupMaxAgeDays?: number;
+  // config.json 历史版本保留份数
+  historyMaxVersions?: number;
+  // config.json 位于网盘同步目录时启用写入围栏
+  sharedFolderSync?: boolean;
+  // 回收站保留天数
+  trashRetentionDays?: number;
+  // API Key 存入系统钥匙串
+  useKeychain?: boolean;
+  // 加密配置空闲自动锁定（分钟）
+  storeAutoLockMinutes?: number;
+  // live 配置读写失败时的尝试次数
+  ioRetryAttempts?: number;
+  // 全局快捷键
+  shortcuts?: ShortcutSettings;
+  // 托盘自定义链接与命令
+  trayLinks?: TrayLink[];
+  // 日志级别
+  logLevel?: "error" | "warn" | "info" | "debug" | "trace";
+  proxy?: ProxySettings;
+  claudeLiveMode?: LiveWriteMode;
+  codexLiveMode?: LiveWriteMode;
+  // 目标环境（本机 / WSL / SSH）
+  environments?: NamedEnvironment[];
+  claudeEnvironment?: string;
+  codexEnvironment?: string;
+  claudeMirrorEnvironment?: string;
+  codexMirrorEnvironment?: string;
+  webdavSync?: WebDavSyncSettings;
+  s3Backup?: S3BackupSettings;
+  httpApi?: HttpApiSettings;
+}
+
+// profiles 仅适用于 Codex
+export type LiveWriteMode = "replace" | "merge" | "profiles";
+
+export interface TrayLink {
+  label: string;
+  url?: string;
+  command?: string;
+}
+
+export interface PinnedShortcut {
+  appType: "claude" | "codex";
+  providerId: string;
+  accelerator?: string;
+}
+
+export interface ShortcutSettings {
+  toggleWindow?: string;
+  quickSwitcher?: string;
+  pinned?: PinnedShortcut[];
+}
+
+export interface ProxySettings {
+  mode: "system" | "none" | "custom";
+  url: string;
+}
+
+export interface SshTarget {
+  host: string;
+  user?: string;
+  port?: number;
+  keyPath?: string;
+}
+
+export interface NamedEnvironment {
+  name: string;
+  targetEnv: "native" | "wsl" | "ssh";
+  wslDistro?: string;
+  wslWriteMode?: "unc" | "exec";
+  wslAutoStart?: boolean;
+  wslFixPermissions?: boolean;
+  ssh?: SshTarget;
+}
+
+export interface WebDavSyncSettings {
+  enabled: boolean;
+  url: string;
+  username: string;
+  password: string;
+  passphrase: string;
+  remotePath: string;
+  autoSync: boolean;
+  conflictStrategy: "lastWriterWins" | "prompt";
+}
+
+export interface S3BackupSettings {
+  endpoint: string;
+  region: string;
+  bucket: string;
+  accessKeyId: string;
+  secretAccessKey: string;
+  prefix: string;
+  passphrase: string;
+}
+
+// 本机 HTTP 接口（仅监听 127.0.0.1，需携带 Bearer 令牌）
+export interface HttpApiSettings {
+  enabled: boolean;
+  port: number;
+  token: string;
+}
+
+// 后端更新检查结果（check_for_updates）
+export interface UpdateCheck {
+  available: boolean;
+  currentVersion: string;
+  version?: string;
+  notes?: string;
+  date?: string;
+}
+
+// GitHub 最新发布信息（get_latest_release）
+export interface ReleaseAsset {
+  name: string;
+  url: string;
+  size: number;
+}
+
+export interface LatestRelease {
+  version: string;
+  currentVersion: string;
+  updateAvailable: boolean;
+  notes?: string;
+  publishedAt?: string;
+  htmlUrl: string;
+  assets: ReleaseAsset[];
 }
 
 // MCP 服务器连接参数（宽松：允许扩展字段）
@@ -127,3 +310,139 @@ export interface McpConfigResponse {
   configPath: string;
   servers: Record<string, McpServer>;
 }
+
+// 后台任务（测速、余额查询、同步、备份等）
+export interface TaskInfo {
+  id: string;
+  kind: string;
+  label: string;
+  status: "running" | "succeeded" | "failed" | "cancelled";
+  completed?: number;
+  total?: number;
+  message?: string;
+  error?: string;
+  startedAt: number;
+  finishedAt?: number;
+}
+
+// 统一状态事件（state-changed 通道），按 type 区分；需要完整数据时应重新查询
+export type StateEvent =
+  | { type: "providerAdded"; appType: string; providerId: string }
+  | { type: "providerUpdated"; appType: string; providerId: string }
+  | { type: "providerDeleted"; appType: string; providerId: string }
+  | { type: "providerRestored"; appType: string; providerId: string }
+  | {
+      type: "providerSwitched";
+      appType: string;
+      providerId: string;
+      previousId: string;
+    }
+  | { type: "settingsChanged" }
+  | { type: "configReloaded" }
+  | { type: "syncStatus"; target: string; status: string; error?: string }
+  | { type: "taskUpdated"; task: TaskInfo };
+
+export type StateChange = StateEvent & {
+  version: number;
+  seq: number;
+  timestamp: number;
+};
+
+// 配置差异条目（path 以 `.` 连接字段，数组下标为 `[i]`）
+export interface ConfigDiffEntry {
+  path: string;
+  kind: "added" | "removed" | "changed";
+  before?: unknown;
+  after?: unknown;
+}
+
+// live 配置被外部修改后与当前供应商存储的差异
+export interface DriftReport {
+  appType: string;
+  currentId: string;
+  currentName: string;
+  storedFingerprint: string;
+  liveFingerprint: string;
+  changes: ConfigDiffEntry[];
+}
+
+// 切换时 live 已被外部修改的处理方式：丢弃外部修改或回填到当前供应商
+export type DriftPolicy = "force" | "backfill";
+
+// 上次未完成（进程中途退出）的供应商切换
+export interface InterruptedSwitch {
+  appType: string;
+  previousCurrent: string;
+  targetId: string;
+  startedAt: number;
+  snapshotId?: string | null;
+  files: { path: string; preHash?: string | null }[];
+  modifiedFiles: string[];
+  canRollback: boolean;
+  canComplete: boolean;
+}
+
+export type SwitchRecoveryAction = "rollback" | "complete" | "dismiss";
+
+// 通过 ccswitch://provider 链接收到、待确认导入的供应商分享
+export interface ReceivedShare {
+  payload: string;
+  app: "claude" | "codex";
+  name: string;
+  url?: string;
+}
+
+// 单个应用的状态汇总（get_all_status）
+export interface AppStatus {
+  appType: string;
+  config: {
+    exists: boolean;
+    path: string;
+    codexLogin?: unknown;
+    codexAuthMode?: CodexAuthMode;
+    envError?: string;
+    sandbox?: string;
+  };
+  configDir: string;
+  liveFiles: string[];
+  currentId: string;
+  currentName?: string;
+  drifted: boolean;
+  driftChanges?: number;
+  driftError?: string;
+  environments: {
+    name: string;
+    targetEnv: string;
+    wslDistro?: string;
+    active: boolean;
+    path?: string;
+    exists: boolean;
+    error?: string;
+    wslVersion?: number;
+    uncScheme?: string;
+  }[];
+}
+
+export interface AllStatus {
+  platform: { os: string; supportsWsl: boolean; targetEnvs: string[] };
+  apps: AppStatus[];
+}
+
+// 后端版本与功能探测（get_backend_info）
+export interface BackendInfo {
+  version: string;
+  schemaVersion: number;
+  os: string;
+  appTypes: string[];
+  features: {
+    wsl: boolean;
+    remoteEnv: boolean;
+    keychain: boolean;
+    encryption: boolean;
+    webdavSync: boolean;
+    s3Backup: boolean;
+    autoUpdate: boolean;
+    tasks: boolean;
+    switchRecovery: boolean;
+  };
+}
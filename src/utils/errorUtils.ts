@@ -1,3 +1,5 @@
+import type { DriftReport } from "../types";
+
 /**
  * 从各种错误对象中提取错误信息
  * @param error 错误对象
@@ -37,6 +39,75 @@ export const extractErrorMessage = (error: unknown): string => {
   return "";
 };
 
+/**
+ * 后端命令返回的结构化错误：code 供本地化与匹配，message 为中文兜底文案
+ */
+export interface CommandErrorPayload {
+  code: string;
+  message: string;
+  params?: Record<string, unknown>;
+}
+
+export class CommandError extends Error {
+  readonly code: string;
+  readonly params: Record<string, unknown>;
+
+  constructor(payload: CommandErrorPayload) {
+    super(payload.message);
+    this.name = "CommandError";
+    this.code = payload.code;
+    this.params = payload.params ?? {};
+  }
+
+  // 兼容 String(error) 的既有用法
+  toString(): string {
+    return this.message;
+  }
+}
+
+const isCommandErrorPayload = (
+  error: unknown,
+): error is CommandErrorPayload => {
+  if (!error || typeof error !== "object") return false;
+  const errObject = error as Record<string, unknown>;
+  return (
+    typeof errObject.code === "string" && typeof errObject.message === "string"
+  );
+};
+
+/**
+ * 将 invoke 抛出的结构化错误包装为 CommandError，其余错误原样返回
+ */
+export const toCommandError = (error: unknown): unknown =>
+  isCommandErrorPayload(error) ? new CommandError(error) : error;
+
+/**
+ * 按错误码本地化后端错误；未收录的错误码回退到后端返回的 message
+ */
+export const translateCommandError = (
+  error: unknown,
+  t: (key: string, opts?: any) => string,
+): string => {
+  if (error instanceof CommandError && error.code !== "internal") {
+    const key = `errors.${error.code}`;
+    const translated = t(key, error.params);
+    if (translated && translated !== key) {
+      return translated;
+    }
+  }
+  return extractErrorMessage(error);
+};
+
+/**
+ * 切换供应商时 live 配置已被外部修改（live_drift），返回差异报告；其他错误返回 null
+ */
+export const getLiveDriftReport = (error: unknown): DriftReport | null => {
+  if (error instanceof CommandError && error.code === "live_drift") {
+    return (error.params.report as DriftReport | undefined) ?? null;
+  }
+  return null;
+};
+
 /**
  * 将已知的 MCP 相关后端错误（通常为中文硬编码）映射为 i18n 文案
  * 采用包含式匹配，尽量稳健地覆盖不同上下文的相似消息。
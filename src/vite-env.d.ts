@@ -2,12 +2,25 @@
 
 import {
   Provider,
+  ProviderQuery,
+  ProviderSummary,
   Settings,
+  TaskInfo,
+  StateChange,
+  BackendInfo,
+  AllStatus,
+  InterruptedSwitch,
+  SwitchRecoveryAction,
+  DriftPolicy,
+  ReceivedShare,
+  ConfigFragment,
   CustomEndpoint,
   McpStatus,
   McpConfigResponse,
   McpServer,
   McpServerSpec,
+  UpdateCheck,
+  LatestRelease,
 } from "./types";
 import { AppType } from "./lib/tauri-api";
 import type { UnlistenFn } from "@tauri-apps/api/event";
@@ -27,15 +40,27 @@ declare global {
   interface Window {
     api: {
       getProviders: (app?: AppType) => Promise<Record<string, Provider>>;
+      queryProviders: (query?: ProviderQuery) => Promise<ProviderSummary[]>;
       getCurrentProvider: (app?: AppType) => Promise<string>;
       addProvider: (provider: Provider, app?: AppType) => Promise<boolean>;
       deleteProvider: (id: string, app?: AppType) => Promise<boolean>;
       updateProvider: (provider: Provider, app?: AppType) => Promise<boolean>;
-      switchProvider: (providerId: string, app?: AppType) => Promise<boolean>;
+      setProviderModel: (
+        id: string,
+        model: string | null,
+        app?: AppType,
+      ) => Promise<boolean>;
+      switchProvider: (
+        providerId: string,
+        app?: AppType,
+        driftPolicy?: DriftPolicy,
+      ) => Promise<boolean>;
       importCurrentConfigAsDefault: (app?: AppType) => Promise<ImportResult>;
       getClaudeCodeConfigPath: () => Promise<string>;
       getClaudeConfigStatus: () => Promise<ConfigStatus>;
       getConfigStatus: (app?: AppType) => Promise<ConfigStatus>;
+      getBackendInfo: () => Promise<BackendInfo>;
+      getAllStatus: () => Promise<AllStatus>;
       getConfigDir: (app?: AppType) => Promise<string>;
       saveFileDialog: (defaultName: string) => Promise<string | null>;
       openFileDialog: () => Promise<string | null>;
@@ -56,10 +81,45 @@ declare global {
       onProviderSwitched: (
         callback: (data: { appType: string; providerId: string }) => void,
       ) => Promise<UnlistenFn>;
+      onConfigSaveFailed: (
+        callback: (error: unknown) => void,
+      ) => Promise<UnlistenFn>;
+      onConfigReloaded: (callback: () => void) => Promise<UnlistenFn>;
+      onShareLinkReceived: (
+        callback: (share: ReceivedShare) => void,
+      ) => Promise<UnlistenFn>;
+      importProviderShare: (payload: string, name?: string) => Promise<Provider>;
+      listTasks: () => Promise<TaskInfo[]>;
+      cancelTask: (id: string) => Promise<boolean>;
+      getInterruptedSwitches: () => Promise<InterruptedSwitch[]>;
+      recoverSwitch: (
+        app: AppType,
+        action: SwitchRecoveryAction,
+      ) => Promise<string>;
+      getCodexFragments: () => Promise<ConfigFragment[]>;
+      saveCodexFragment: (fragment: ConfigFragment) => Promise<boolean>;
+      deleteCodexFragment: (id: string) => Promise<boolean>;
+      onTaskUpdated: (
+        callback: (task: TaskInfo) => void,
+      ) => Promise<UnlistenFn>;
+      onStateChanged: (
+        callback: (change: StateChange) => void,
+      ) => Promise<UnlistenFn>;
+      onSettingsChanged: (
+        callback: (settings: Settings) => void,
+      ) => Promise<UnlistenFn>;
+      onConfigExternalConflict: (
+        callback: (error: unknown) => void,
+      ) => Promise<UnlistenFn>;
       getSettings: () => Promise<Settings>;
       saveSettings: (settings: Settings) => Promise<boolean>;
+      regenerateHttpApiToken: () => Promise<string>;
       restartApp: () => Promise<boolean>;
-      checkForUpdates: () => Promise<void>;
+      quitApp: () => Promise<boolean>;
+      checkForUpdates: () => Promise<UpdateCheck | null>;
+      getLatestRelease: () => Promise<LatestRelease | null>;
+      installUpdate: () => Promise<boolean>;
+      openReleasesPage: () => Promise<void>;
       isPortable: () => Promise<boolean>;
       getAppConfigPath: () => Promise<string>;
       openAppConfigFolder: () => Promise<void>;